@@ -1367,6 +1367,7 @@ def external_crates_repository(name, cargo_lockfile, lockfile, sanitizers_enable
                     "request-id",
                     "util",
                     "compression-full",
+                    "decompression-gzip",
                     "tracing",
                 ],
             ),
@@ -5,6 +5,7 @@ use candid::Principal;
 use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue as Value;
 use icrc_ledger_types::icrc1::account::Account;
 use icrc_ledger_types::icrc1::transfer::{BlockIndex, TransferArg, TransferError};
+use icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArgs};
 use icrc_ledger_types::icrc2::approve::{ApproveArgs, ApproveError};
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 
@@ -125,6 +126,13 @@ impl<R: Runtime> ICRC1Client<R> {
             .map(untuple)?;
         Ok(result)
     }
+
+    pub async fn allowance(&self, args: AllowanceArgs) -> Result<Allowance, (i32, String)> {
+        self.runtime
+            .call(self.ledger_canister_id, "icrc2_allowance", (args,))
+            .await
+            .map(untuple)
+    }
 }
 
 // extract the element from an unary tuple
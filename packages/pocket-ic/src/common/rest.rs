@@ -2,15 +2,17 @@
 //! The types in this module are used to serialize and deserialize data
 //! from and to JSON, and are used by both crates.
 
-use crate::UserError;
-use candid::Principal;
+use crate::{IngressStatus, UserError, WasmResult};
+use candid::{CandidType, Principal};
 use hex;
 use reqwest::Response;
 use schemars::JsonSchema;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 
 pub type InstanceId = usize;
 
@@ -28,10 +30,64 @@ pub struct HttpsConfig {
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct HttpGatewayConfig {
+    /// The IP address the HTTP gateway binds to.
+    /// Defaults to `[::]` (all interfaces, dual-stack) if not specified.
+    pub listen_addr: Option<IpAddr>,
     pub listen_at: Option<u16>,
     pub forward_to: HttpGatewayBackend,
     pub domains: Option<Vec<String>>,
     pub https_config: Option<HttpsConfig>,
+    /// Grace period for in-flight requests to complete when the HTTP gateway is stopped
+    /// (connections still open afterwards are closed forcibly).
+    /// Defaults to 5 seconds if not specified.
+    pub shutdown_grace_period: Option<Duration>,
+    /// Names of request headers forwarded to the backend on API requests
+    /// (hop-by-hop headers are never forwarded).
+    /// Defaults to common tracing headers, `Idempotency-Key`, and `User-Agent` if not specified.
+    pub forward_headers: Option<Vec<String>>,
+}
+
+/// Configuration of automatic progress of an instance.
+#[derive(Debug, Clone, Copy, Default, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct AutoProgressConfig {
+    /// The minimum wall-clock time between consecutive ticks.
+    /// Defaults to the minimum operation delay of the PocketIC server if not specified.
+    pub tick_interval: Option<Duration>,
+    /// The time by which the time of the instance is advanced on every tick
+    /// (regardless of how much wall-clock time has elapsed).
+    /// Defaults to the wall-clock time elapsed since the previous tick if not specified.
+    pub advance_by: Option<Duration>,
+}
+
+/// The kind of operation executed by the thread making an instance progress automatically.
+#[derive(Debug, Clone, Copy, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum ProgressOperationKind {
+    AdvanceTimeAndTick,
+    /// Retrieving pending canister HTTP outcalls, making them, and mocking their responses.
+    ProcessCanisterHttpRequests,
+    /// Waiting for the next tick.
+    Sleep,
+    /// Waiting for automatic progress to be resumed.
+    Paused,
+}
+
+/// The status of the thread making an instance progress automatically.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ProgressStatus {
+    /// The operation executed last (possibly still in progress).
+    pub last_operation: ProgressOperationKind,
+    /// The wall-clock time at which the last operation started.
+    pub last_operation_started_at: SystemTime,
+    /// The number of canister HTTP outcalls processed in the most recent iteration.
+    pub canister_http_requests: usize,
+}
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum GatewayStatus {
+    Running,
+    Stopped,
+    /// The HTTP gateway failed to start or stopped serving due to the given error.
+    Failed(String),
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -40,6 +96,20 @@ pub struct HttpGatewayInfo {
     pub port: u16,
 }
 
+/// An HTTP gateway as listed by the PocketIC server.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HttpGatewayDetails {
+    /// The id of the HTTP gateway (used to stop it and to query its status).
+    pub gateway_id: InstanceId,
+    pub port: u16,
+    pub forward_to: HttpGatewayBackend,
+    /// The domains used to resolve canister IDs from the host of incoming requests.
+    pub domains: Vec<String>,
+    /// Whether the HTTP gateway serves HTTPS (i.e., it was created with an `HttpsConfig`).
+    pub https: bool,
+    pub status: GatewayStatus,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum CreateHttpGatewayResponse {
     Created(HttpGatewayInfo),
@@ -130,6 +200,62 @@ pub enum RawWasmResult {
     Reject(String),
 }
 
+impl From<RawWasmResult> for WasmResult {
+    fn from(raw_wasm_result: RawWasmResult) -> Self {
+        match raw_wasm_result {
+            RawWasmResult::Reply(data) => WasmResult::Reply(data),
+            RawWasmResult::Reject(message) => WasmResult::Reject(message),
+        }
+    }
+}
+
+impl From<WasmResult> for RawWasmResult {
+    fn from(wasm_result: WasmResult) -> Self {
+        match wasm_result {
+            WasmResult::Reply(data) => RawWasmResult::Reply(data),
+            WasmResult::Reject(message) => RawWasmResult::Reject(message),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub enum RawIngressStatus {
+    Received,
+    Processing,
+    Replied(RawWasmResult),
+    Rejected(UserError),
+    Done,
+    Unknown,
+}
+
+impl From<RawIngressStatus> for IngressStatus {
+    fn from(raw_ingress_status: RawIngressStatus) -> Self {
+        match raw_ingress_status {
+            RawIngressStatus::Received => IngressStatus::Received,
+            RawIngressStatus::Processing => IngressStatus::Processing,
+            RawIngressStatus::Replied(raw_wasm_result) => {
+                IngressStatus::Replied(raw_wasm_result.into())
+            }
+            RawIngressStatus::Rejected(user_error) => IngressStatus::Rejected(user_error),
+            RawIngressStatus::Done => IngressStatus::Done,
+            RawIngressStatus::Unknown => IngressStatus::Unknown,
+        }
+    }
+}
+
+impl From<IngressStatus> for RawIngressStatus {
+    fn from(ingress_status: IngressStatus) -> Self {
+        match ingress_status {
+            IngressStatus::Received => RawIngressStatus::Received,
+            IngressStatus::Processing => RawIngressStatus::Processing,
+            IngressStatus::Replied(wasm_result) => RawIngressStatus::Replied(wasm_result.into()),
+            IngressStatus::Rejected(user_error) => RawIngressStatus::Rejected(user_error),
+            IngressStatus::Done => RawIngressStatus::Done,
+            IngressStatus::Unknown => RawIngressStatus::Unknown,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RawSetStableMemory {
     #[serde(deserialize_with = "base64::deserialize")]
@@ -145,6 +271,27 @@ pub struct RawStableMemory {
     pub blob: Vec<u8>,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawStableMemoryRangeRequest {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    pub offset: u64,
+    pub length: u64,
+}
+
+/// A range of the stable memory of a canister.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct StableMemoryRange {
+    /// The total size of the stable memory in bytes.
+    pub total_size: u64,
+    /// The bytes of the stable memory in the requested range
+    /// (empty if the requested range starts beyond the end of the stable memory).
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub blob: Vec<u8>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct ApiError {
     message: String,
@@ -188,17 +335,12 @@ impl<T: DeserializeOwned> ApiResponse<T> {
                     },
                 }
             }
-            reqwest::StatusCode::CONFLICT => {
-                let result = resp.json::<StartedOrBusyResponse>().await;
-                match result {
-                    Ok(StartedOrBusyResponse { state_label, op_id }) => {
-                        ApiResponse::Busy { state_label, op_id }
-                    }
-                    Err(e) => ApiResponse::Error {
-                        message: format!("Could not parse response: {}", e),
-                    },
-                }
-            }
+            reqwest::StatusCode::CONFLICT => match resp.bytes().await {
+                Ok(body) => Self::busy_or_error(&body),
+                Err(e) => ApiResponse::Error {
+                    message: format!("Could not parse response: {}", e),
+                },
+            },
             _ => {
                 let result = resp.json::<ApiError>().await;
                 match result {
@@ -210,6 +352,20 @@ impl<T: DeserializeOwned> ApiResponse<T> {
             }
         }
     }
+
+    /// A `409 Conflict` response either describes the operation the instance is busy with
+    /// or an error of an operation that cannot be performed while the instance is busy.
+    fn busy_or_error(body: &[u8]) -> Self {
+        if let Ok(StartedOrBusyResponse { state_label, op_id }) = serde_json::from_slice(body) {
+            return ApiResponse::Busy { state_label, op_id };
+        }
+        match serde_json::from_slice::<ApiError>(body) {
+            Ok(e) => ApiResponse::Error { message: e.message },
+            Err(e) => ApiResponse::Error {
+                message: format!("Could not parse response: {}", e),
+            },
+        }
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
@@ -220,12 +376,73 @@ pub struct RawAddCycles {
     pub amount: u128,
 }
 
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetControllers {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    pub controllers: Vec<Principal>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct RawCycles {
     pub cycles: u128,
 }
 
-#[derive(Clone, Serialize, Eq, PartialEq, Ord, PartialOrd, Deserialize, Debug, JsonSchema)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetFreezingThreshold {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    pub threshold_secs: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawFreezingThreshold {
+    pub threshold_secs: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawFaultInjection {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    pub method: String,
+    /// The error with which matching calls fail.
+    pub error: UserError,
+    /// Whether the fault applies to all matching calls rather than only to the next one.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+/// A label of a path in the state tree.
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawLabel(
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub Vec<u8>,
+);
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawStateCertificateRequest {
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub canister_id: Vec<u8>,
+    /// The paths to certify, each given as the sequence of its labels.
+    pub paths: Vec<Vec<RawLabel>>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawCertificate {
+    /// The CBOR-encoded certificate.
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub certificate: Vec<u8>,
+}
+
+#[derive(
+    Clone, Serialize, Eq, PartialEq, Ord, PartialOrd, Deserialize, Debug, JsonSchema, CandidType,
+)]
 pub struct RawCanisterId {
     // raw bytes of the principal
     #[serde(deserialize_with = "base64::deserialize")]
@@ -265,7 +482,17 @@ impl From<RawSubnetId> for Principal {
 }
 
 #[derive(
-    Clone, Serialize, Deserialize, Debug, JsonSchema, PartialEq, Eq, PartialOrd, Ord, Hash,
+    Clone,
+    Serialize,
+    Deserialize,
+    Debug,
+    JsonSchema,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    CandidType,
 )]
 pub struct RawNodeId {
     #[serde(deserialize_with = "base64::deserialize")]
@@ -332,24 +559,68 @@ pub enum BlobCompression {
 // This enables serializing Vec<u8> to a compact base64 representation.
 #[allow(deprecated)]
 pub mod base64 {
-    use serde::{Deserialize, Serialize};
+    use serde::de::{Error, SeqAccess, Visitor};
+    use serde::Serialize;
     use serde::{Deserializer, Serializer};
+    use std::fmt;
 
     pub fn serialize<S: Serializer>(v: &Vec<u8>, s: S) -> Result<S::Ok, S::Error> {
         let base64 = base64::encode(v);
         String::serialize(&base64, s)
     }
 
+    /// Deserializes a base64-encoded string (e.g., from JSON) or, for self-describing
+    /// binary formats such as Candid, the raw bytes.
     pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
-        let base64 = String::deserialize(d)?;
-        base64::decode(base64.as_bytes()).map_err(serde::de::Error::custom)
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a base64-encoded string or bytes")
+            }
+
+            fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+                base64::decode(v.as_bytes()).map_err(E::custom)
+            }
+
+            fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(bytes)
+            }
+        }
+
+        d.deserialize_any(BytesVisitor)
     }
 }
 
 // ================================================================================================================= //
 
 #[derive(
-    Debug, Clone, Copy, Eq, Hash, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    CandidType,
 )]
 pub enum SubnetKind {
     Application,
@@ -522,7 +793,17 @@ impl Default for SubnetSpec {
 
 /// Specifies instruction limits for canister execution on this subnet.
 #[derive(
-    Debug, Clone, Eq, Hash, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema,
+    Debug,
+    Clone,
+    Eq,
+    Hash,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    CandidType,
 )]
 pub enum SubnetInstructionConfig {
     /// Use default instruction limits as in production.
@@ -641,7 +922,9 @@ impl ExtendedSubnetConfigSet {
 }
 
 /// Configuration details for a subnet, returned by PocketIc server
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema)]
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema, CandidType,
+)]
 pub struct SubnetConfig {
     pub subnet_kind: SubnetKind,
     pub subnet_seed: [u8; 32],
@@ -653,13 +936,17 @@ pub struct SubnetConfig {
     pub canister_ranges: Vec<CanisterIdRange>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema)]
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema, CandidType,
+)]
 pub struct CanisterIdRange {
     pub start: RawCanisterId,
     pub end: RawCanisterId,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema)]
+#[derive(
+    Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, JsonSchema, CandidType,
+)]
 pub struct Topology(pub BTreeMap<SubnetId, SubnetConfig>);
 
 impl Topology {
@@ -724,8 +1011,50 @@ impl Topology {
     }
 }
 
+/// The changes to the topology of a PocketIC instance since a previous topology.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema, CandidType)]
+pub struct TopologyDelta {
+    /// The (hex-encoded) hash of the current topology
+    /// to be used as the previous topology of the next topology delta.
+    pub topology_hash: String,
+    /// Subnets that are not part of the previous topology.
+    pub added_subnets: BTreeMap<SubnetId, SubnetConfig>,
+    /// Subnets of the previous topology that are no longer part of the topology.
+    pub removed_subnets: Vec<SubnetId>,
+    /// Nodes added to subnets that are part of both topologies.
+    pub added_nodes: BTreeMap<SubnetId, Vec<RawNodeId>>,
+    /// Nodes removed from subnets that are part of both topologies.
+    pub removed_nodes: BTreeMap<SubnetId, Vec<RawNodeId>>,
+}
+
+impl TopologyDelta {
+    pub fn is_empty(&self) -> bool {
+        self.added_subnets.is_empty()
+            && self.removed_subnets.is_empty()
+            && self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawTopologyDeltaRequest {
+    /// The (hex-encoded) hash of the previous topology
+    /// (the entire topology is returned as added subnets if not provided).
+    pub since: Option<String>,
+}
+
 #[derive(
-    Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+    Clone,
+    Serialize,
+    Deserialize,
+    Debug,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    JsonSchema,
+    CandidType,
 )]
 pub enum CanisterHttpMethod {
     GET,
@@ -734,7 +1063,17 @@ pub enum CanisterHttpMethod {
 }
 
 #[derive(
-    Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
+    Clone,
+    Serialize,
+    Deserialize,
+    Debug,
+    Hash,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    JsonSchema,
+    CandidType,
 )]
 pub struct CanisterHttpHeader {
     pub name: String,
@@ -754,7 +1093,9 @@ pub struct RawCanisterHttpRequest {
     pub max_response_bytes: Option<u64>,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(
+    Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, CandidType,
+)]
 pub struct CanisterHttpRequest {
     pub subnet_id: Principal,
     pub request_id: u64,
@@ -797,6 +1138,122 @@ impl From<CanisterHttpRequest> for RawCanisterHttpRequest {
     }
 }
 
+#[derive(
+    Clone, Copy, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, JsonSchema, CandidType,
+)]
+pub enum ScheduledMessageKind {
+    Ingress,
+    Request,
+    Response,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawScheduledMessage {
+    pub canister_id: RawCanisterId,
+    pub kind: ScheduledMessageKind,
+}
+
+/// A message that is pending execution on a subnet.
+#[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, CandidType)]
+pub struct ScheduledMessage {
+    pub canister_id: Principal,
+    pub kind: ScheduledMessageKind,
+}
+
+impl From<RawScheduledMessage> for ScheduledMessage {
+    fn from(raw_scheduled_message: RawScheduledMessage) -> Self {
+        Self {
+            canister_id: candid::Principal::from_slice(
+                &raw_scheduled_message.canister_id.canister_id,
+            ),
+            kind: raw_scheduled_message.kind,
+        }
+    }
+}
+
+impl From<ScheduledMessage> for RawScheduledMessage {
+    fn from(scheduled_message: ScheduledMessage) -> Self {
+        Self {
+            canister_id: scheduled_message.canister_id.into(),
+            kind: scheduled_message.kind,
+        }
+    }
+}
+
+/// Aggregate statistics of the canisters on a subnet.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema, CandidType)]
+pub struct SubnetMetrics {
+    /// The number of canisters on the subnet.
+    pub num_canisters: u64,
+    /// The total size of the heap (Wasm) and stable memory of all canisters in bytes.
+    pub canister_memory_bytes: u64,
+    /// The total cycles balance of all canisters.
+    pub total_cycles: u128,
+    /// The number of ingress messages pending execution on the subnet,
+    /// i.e., in the ingress pool or in the ingress queues of the canisters.
+    pub ingress_queue_length: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawInstanceMetadataEntry {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawOpId {
+    pub op_id: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawInstanceMetadataKey {
+    pub key: String,
+}
+
+/// The cycles balance of a canister recorded after a tick.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema, CandidType)]
+pub struct CycleConsumptionRecord {
+    /// The tick (counted from 1 once the recording is enabled) after which the balance was recorded.
+    pub tick: u64,
+    /// The cycles balance of the canister after the tick.
+    pub balance: u128,
+    /// The cycles consumed by the canister since the previous tick.
+    pub consumed: u128,
+}
+
+/// The wall-clock time it took to compute an operation on an instance.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OpTiming {
+    /// The id of the operation, e.g., `execute_ingress_message(...)`.
+    pub op_id: String,
+    pub duration: Duration,
+}
+
+/// A log record of a canister.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CanisterLogRecord {
+    /// Monotonically increasing index of the log record.
+    pub idx: u64,
+    /// The IC time at which the log record was recorded.
+    pub timestamp_nanos: u64,
+    #[serde(deserialize_with = "base64::deserialize")]
+    #[serde(serialize_with = "base64::serialize")]
+    pub content: Vec<u8>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSubnetNode {
+    pub subnet_id: RawSubnetId,
+    pub node_id: RawNodeId,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetNodeClockSkew {
+    pub subnet_id: RawSubnetId,
+    pub node_id: RawNodeId,
+    pub skew_nanos: i64,
+}
+
 #[derive(
     Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, JsonSchema,
 )]
@@ -829,6 +1286,59 @@ pub struct RawMockCanisterHttpResponse {
     pub subnet_id: RawSubnetId,
     pub request_id: u64,
     pub response: CanisterHttpResponse,
+    #[serde(default)]
+    pub apply_after_nanos: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetCanisterHttpResponseLimit {
+    pub subnet_id: RawSubnetId,
+    pub max_bytes: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetCanisterHttpSocksProxy {
+    pub proxy_url: Option<String>,
+}
+
+/// A TLS client certificate (chain) and the corresponding private key (both PEM-encoded)
+/// presented by canister HTTP outcalls to servers requiring mutual TLS.
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CanisterHttpClientIdentity {
+    pub cert_chain_pem: String,
+    pub private_key_pem: String,
+}
+
+impl std::fmt::Debug for CanisterHttpClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        // The private key is never printed.
+        f.debug_struct("CanisterHttpClientIdentity")
+            .field("cert_chain_pem", &self.cert_chain_pem)
+            .field("private_key_pem", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawSetCanisterHttpClientIdentity {
+    pub client_identity: Option<CanisterHttpClientIdentity>,
+}
+
+/// Policy applied to XNet messages from one subnet to another.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub enum XNetPolicy {
+    /// Messages are only delivered after being withheld for the given number of ticks.
+    Delay { ticks: u64 },
+    /// Every attempt to deliver messages fails with the given probability (in percent)
+    /// drawn from a PRNG with the given seed. Failed deliveries are retried on later ticks.
+    Drop { probability_percent: u8, seed: u64 },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
+pub struct RawConfigureXNet {
+    pub from_subnet: RawSubnetId,
+    pub to_subnet: RawSubnetId,
+    pub policy: Option<XNetPolicy>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
@@ -836,6 +1346,9 @@ pub struct MockCanisterHttpResponse {
     pub subnet_id: Principal,
     pub request_id: u64,
     pub response: CanisterHttpResponse,
+    /// If set, the response is held back by the server and only applied
+    /// once the IC time has advanced by at least this duration.
+    pub apply_after: Option<Duration>,
 }
 
 impl From<RawMockCanisterHttpResponse> for MockCanisterHttpResponse {
@@ -846,6 +1359,9 @@ impl From<RawMockCanisterHttpResponse> for MockCanisterHttpResponse {
             ),
             request_id: raw_mock_canister_http_response.request_id,
             response: raw_mock_canister_http_response.response,
+            apply_after: raw_mock_canister_http_response
+                .apply_after_nanos
+                .map(Duration::from_nanos),
         }
     }
 }
@@ -858,6 +1374,43 @@ impl From<MockCanisterHttpResponse> for RawMockCanisterHttpResponse {
             },
             request_id: mock_canister_http_response.request_id,
             response: mock_canister_http_response.response,
+            apply_after_nanos: mock_canister_http_response
+                .apply_after
+                .map(|apply_after| apply_after.as_nanos() as u64),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conflict_response_is_busy_or_error() {
+        let busy = serde_json::to_vec(&StartedOrBusyResponse {
+            state_label: "state_label".to_string(),
+            op_id: "op_id".to_string(),
+        })
+        .unwrap();
+        let ApiResponse::<()>::Busy { state_label, op_id } = ApiResponse::busy_or_error(&busy)
+        else {
+            panic!("expected a busy response");
+        };
+        assert_eq!(state_label, "state_label");
+        assert_eq!(op_id, "op_id");
+
+        let error = serde_json::to_vec(&ApiError {
+            message: "Instance is busy".to_string(),
+        })
+        .unwrap();
+        let ApiResponse::<()>::Error { message } = ApiResponse::busy_or_error(&error) else {
+            panic!("expected an error response");
+        };
+        assert_eq!(message, "Instance is busy");
+
+        let ApiResponse::<()>::Error { message } = ApiResponse::busy_or_error(b"garbage") else {
+            panic!("expected an error response");
+        };
+        assert!(message.starts_with("Could not parse response"));
+    }
+}
@@ -34,15 +34,17 @@
 //! For more information, see the [README](https://crates.io/crates/pocket-ic).
 //!
 use crate::common::rest::{
-    BlobCompression, BlobId, CanisterHttpRequest, DtsFlag, ExtendedSubnetConfigSet, HttpsConfig,
-    InstanceId, MockCanisterHttpResponse, RawEffectivePrincipal, RawMessageId, SubnetId,
-    SubnetSpec, Topology,
+    AutoProgressConfig, BlobCompression, BlobId, CanisterHttpClientIdentity, CanisterHttpRequest,
+    CanisterLogRecord, CycleConsumptionRecord, DtsFlag, ExtendedSubnetConfigSet,
+    HttpGatewayDetails, HttpsConfig, InstanceId, MockCanisterHttpResponse, OpTiming,
+    RawEffectivePrincipal, RawMessageId, ScheduledMessage, StableMemoryRange, SubnetId,
+    SubnetMetrics, SubnetSpec, Topology, TopologyDelta, XNetPolicy,
 };
 use crate::nonblocking::PocketIc as PocketIcAsync;
 use candid::{
     decode_args, encode_args,
     utils::{ArgumentDecoder, ArgumentEncoder},
-    Principal,
+    CandidType, Principal,
 };
 pub use ic_cdk::api::management_canister::main::CanisterSettings;
 use ic_cdk::api::management_canister::main::{CanisterId, CanisterStatusResponse};
@@ -53,6 +55,7 @@ use std::sync::mpsc::channel;
 use std::thread;
 use std::thread::JoinHandle;
 use std::{
+    collections::BTreeMap,
     fs::File,
     path::{Path, PathBuf},
     process::Command,
@@ -368,6 +371,14 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.topology().await })
     }
 
+    /// Returns the subnets and nodes added and removed since the topology with the given hash
+    /// (as returned in a previous topology delta) or the entire topology if no hash is given.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn topology_delta(&self, since: Option<String>) -> TopologyDelta {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.topology_delta(since).await })
+    }
+
     /// Upload and store a binary blob to the PocketIC server.
     #[instrument(ret(Display), skip(self, blob), fields(instance_id=self.pocket_ic.instance_id, blob_len = %blob.len(), compression = ?compression))]
     pub fn upload_blob(&self, blob: Vec<u8>, compression: BlobCompression) -> BlobId {
@@ -399,6 +410,48 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.get_stable_memory(canister_id).await })
     }
 
+    /// Get (at most) `length` bytes of the stable memory of a canister starting at `offset`
+    /// together with the total size of the stable memory.
+    /// No bytes are returned if `offset` is beyond the end of the stable memory.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), offset = %offset, length = %length))]
+    pub fn get_stable_memory_range(
+        &self,
+        canister_id: CanisterId,
+        offset: u64,
+        length: u64,
+    ) -> StableMemoryRange {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .get_stable_memory_range(canister_id, offset, length)
+                .await
+        })
+    }
+
+    /// Get the log records of a canister (e.g., recorded via `ic0.debug_print` or on traps)
+    /// in the order in which they were recorded.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn get_canister_logs(&self, canister_id: CanisterId) -> Vec<CanisterLogRecord> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_canister_logs(canister_id).await })
+    }
+
+    /// Get the cycles balances of a canister recorded after every tick starting at tick `since_tick`.
+    /// The PocketIC server only records cycles balances if started with `--record-cycle-history`.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn get_cycle_consumption(
+        &self,
+        canister_id: CanisterId,
+        since_tick: u64,
+    ) -> Vec<CycleConsumptionRecord> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .get_cycle_consumption(canister_id, since_tick)
+                .await
+        })
+    }
+
     /// List all instances and their status.
     #[instrument(ret)]
     pub fn list_instances() -> Vec<String> {
@@ -412,6 +465,19 @@ impl PocketIc {
         instances
     }
 
+    /// List all HTTP gateways of the PocketIC server (including stopped and failed ones)
+    /// with their ports, backends, and domains.
+    #[instrument(ret)]
+    pub fn list_http_gateways() -> Vec<HttpGatewayDetails> {
+        let url = crate::start_or_reuse_server().join("http_gateway").unwrap();
+        reqwest::blocking::Client::new()
+            .get(url)
+            .send()
+            .expect("Failed to get result")
+            .json()
+            .expect("Failed to get json")
+    }
+
     /// Verify a canister signature.
     #[instrument(skip_all, fields(instance_id=self.pocket_ic.instance_id))]
     pub fn verify_canister_signature(
@@ -450,6 +516,20 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.auto_progress().await })
     }
 
+    /// Configures the IC to make progress automatically as in `auto_progress`,
+    /// but with a (possibly) fixed tick interval and a (possibly) fixed time
+    /// by which the time of the IC is advanced on every tick
+    /// (independently of the wall-clock time elapsed).
+    /// If the IC already makes progress automatically with a different config,
+    /// it continues with the given config.
+    /// Returns the URL at which `/api/v2` requests
+    /// for this instance can be made.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn auto_progress_with_config(&self, config: AutoProgressConfig) -> Url {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.auto_progress_with_config(config).await })
+    }
+
     /// Stops automatic progress (see `auto_progress`) on the IC.
     #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
     pub fn stop_progress(&self) {
@@ -457,6 +537,38 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.stop_progress().await })
     }
 
+    /// Pauses automatic progress (see `auto_progress`) on the IC without stopping it:
+    /// the IC stays frozen until automatic progress is resumed by `resume_progress`.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn pause_progress(&self) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.pause_progress().await })
+    }
+
+    /// Resumes automatic progress paused by `pause_progress` on the IC.
+    /// The time of the IC does not advance by the wall-clock time elapsed while paused.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn resume_progress(&self) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.resume_progress().await })
+    }
+
+    /// Resets the wall-clock time spent computing operations on the IC
+    /// if the PocketIC server was started with a CPU budget per instance.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn reset_cpu_budget(&self) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.reset_cpu_budget().await })
+    }
+
+    /// Returns the slowest operations (e.g., ingress messages) computed on the IC
+    /// together with the wall-clock time it took to compute them, ordered by decreasing duration.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn op_timings(&self) -> Vec<OpTiming> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.op_timings().await })
+    }
+
     /// Returns the URL at which `/api/v2` requests
     /// for this instance can be made if the HTTP
     /// gateway has been started.
@@ -557,6 +669,56 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.add_cycles(canister_id, amount).await })
     }
 
+    /// Get the freezing threshold (in seconds) of a canister.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn freezing_threshold(&self, canister_id: CanisterId) -> u64 {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.freezing_threshold(canister_id).await })
+    }
+
+    /// Set the freezing threshold (in seconds) of a canister directly,
+    /// i.e., without calling the management canister. Returns the new freezing threshold.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), threshold_secs = %threshold_secs))]
+    pub fn set_freezing_threshold(&self, canister_id: CanisterId, threshold_secs: u64) -> u64 {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .set_freezing_threshold(canister_id, threshold_secs)
+                .await
+        })
+    }
+
+    /// Get a certificate of the state tree of the subnet of a canister for the given paths
+    /// (each given as a sequence of labels), e.g., to verify certified data of the canister.
+    /// The certificate is CBOR-encoded as in a response to `read_state` and
+    /// it always includes the path `/time`.
+    #[instrument(skip(self, paths), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string()))]
+    pub fn state_certificate(&self, canister_id: CanisterId, paths: Vec<Vec<Vec<u8>>>) -> Vec<u8> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.state_certificate(canister_id, paths).await })
+    }
+
+    /// Inject a fault into calls of the given method of the given canister:
+    /// the next such update call fails with the given error
+    /// without being executed by the canister.
+    /// If `repeat` is set, then all subsequent such update and query calls fail with the given error.
+    /// Query calls are read-only and thus never consume a fault that is not repeated.
+    #[instrument(skip(self, error), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), method = %method))]
+    pub fn inject_fault(
+        &self,
+        canister_id: CanisterId,
+        method: &str,
+        error: UserError,
+        repeat: bool,
+    ) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .inject_fault(canister_id, method, error, repeat)
+                .await
+        })
+    }
+
     /// Submit an update call (without executing it immediately).
     pub fn submit_call(
         &self,
@@ -602,6 +764,13 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.await_call(message_id).await })
     }
 
+    /// Get the status of an update call submitted previously by `submit_call_with_effective_principal`
+    /// without executing any rounds.
+    pub fn ingress_status(&self, message_id: RawMessageId) -> IngressStatus {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.ingress_status(message_id).await })
+    }
+
     /// Execute an update call on a canister.
     #[instrument(skip(self, payload), fields(instance_id=self.pocket_ic.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
     pub fn update_call(
@@ -848,6 +1017,68 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.get_subnet(canister_id).await })
     }
 
+    /// Returns the messages pending execution on the given subnet in the order
+    /// in which the scheduler considers them in the next round.
+    /// Messages to the management canister are not included.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub fn get_scheduled_messages(&self, subnet_id: SubnetId) -> Vec<ScheduledMessage> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_scheduled_messages(subnet_id).await })
+    }
+
+    /// Returns aggregate statistics of the canisters on the given subnet:
+    /// their number, memory usage, cycles balance, and pending ingress messages.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub fn get_subnet_metrics(&self, subnet_id: SubnetId) -> SubnetMetrics {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_subnet_metrics(subnet_id).await })
+    }
+
+    /// Sets the clock skew of the given node on the given subnet in nanoseconds, i.e., the amount
+    /// by which the local clock of the node is ahead of (if positive) or behind (if negative)
+    /// the IC time. The time used for executing rounds on the subnet is not affected,
+    /// but ingress messages submitted to a subnet are rejected if their expiry is not within
+    /// the range accepted by the local clock of the first node of the subnet.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, subnet_id = %subnet_id.to_string(), node_id = %node_id.to_string(), skew_nanos = %skew_nanos))]
+    pub fn set_node_clock_skew(&self, subnet_id: SubnetId, node_id: Principal, skew_nanos: i64) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .set_node_clock_skew(subnet_id, node_id, skew_nanos)
+                .await
+        })
+    }
+
+    /// Get the time reported by the local clock of the given node on the given subnet.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, subnet_id = %subnet_id.to_string(), node_id = %node_id.to_string()))]
+    pub fn get_node_time(&self, subnet_id: SubnetId, node_id: Principal) -> SystemTime {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_node_time(subnet_id, node_id).await })
+    }
+
+    /// Attaches a key-value pair of metadata (e.g., the test name) to the instance,
+    /// overwriting any previous value for the key.
+    /// The number and size of key-value pairs is bounded.
+    #[instrument(skip(self), fields(instance_id=self.pocket_ic.instance_id, key = %key, value = %value))]
+    pub fn set_instance_metadata(&self, key: &str, value: &str) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.set_instance_metadata(key, value).await })
+    }
+
+    /// Returns the value of the given key of the instance metadata, if any.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, key = %key))]
+    pub fn get_instance_metadata(&self, key: &str) -> Option<String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_instance_metadata(key).await })
+    }
+
+    /// Returns all key-value pairs of the instance metadata.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn get_all_instance_metadata(&self) -> BTreeMap<String, String> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.get_all_instance_metadata().await })
+    }
+
     fn update_call_with_effective_principal(
         &self,
         canister_id: CanisterId,
@@ -887,6 +1118,16 @@ impl PocketIc {
         runtime.block_on(async { self.pocket_ic.get_canister_http().await })
     }
 
+    /// List all pending canister HTTP outcalls (including those with a response
+    /// mocked to be applied after a delay) without processing them.
+    /// This is a pure read that does not change the state of the PocketIC instance,
+    /// e.g., to inspect the outcalls before mocking their responses.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn peek_canister_http(&self) -> Vec<CanisterHttpRequest> {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async { self.pocket_ic.peek_canister_http().await })
+    }
+
     /// Mock a response to a pending canister HTTP outcall.
     #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
     pub fn mock_canister_http_response(
@@ -900,6 +1141,65 @@ impl PocketIc {
                 .await
         })
     }
+
+    /// Limit the size of canister HTTP outcall responses on the given subnet.
+    /// Responses to canister HTTP outcalls on that subnet exceeding the limit are rejected.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub fn set_canister_http_response_limit(&self, subnet_id: SubnetId, max_bytes: u64) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .set_canister_http_response_limit(subnet_id, max_bytes)
+                .await
+        })
+    }
+
+    /// Configure the policy applied to XNet messages from one subnet to another,
+    /// e.g., to delay or drop them. XNet messages are delivered as soon as possible
+    /// if `None` is provided (default).
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id, from_subnet = %from_subnet.to_string(), to_subnet = %to_subnet.to_string()))]
+    pub fn configure_xnet(
+        &self,
+        from_subnet: SubnetId,
+        to_subnet: SubnetId,
+        policy: Option<XNetPolicy>,
+    ) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .configure_xnet(from_subnet, to_subnet, policy)
+                .await
+        })
+    }
+
+    /// Route canister HTTP outcalls through the given SOCKS proxy (e.g., `socks5://127.0.0.1:1080`)
+    /// if a direct connection fails. Outcalls are only made directly if `None` is provided (default).
+    /// This only applies to outcalls made by the PocketIC server in auto progress mode.
+    #[instrument(ret, skip(self), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn set_canister_http_socks_proxy(&self, proxy_url: Option<String>) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .set_canister_http_socks_proxy(proxy_url)
+                .await
+        })
+    }
+
+    /// Present the given TLS client certificate (chain) and private key in canister HTTP outcalls
+    /// to servers requiring mutual TLS. No client certificate is presented if `None` is provided (default).
+    /// This only applies to outcalls made by the PocketIC server in auto progress mode.
+    #[instrument(ret, skip(self, client_identity), fields(instance_id=self.pocket_ic.instance_id))]
+    pub fn set_canister_http_client_identity(
+        &self,
+        client_identity: Option<CanisterHttpClientIdentity>,
+    ) {
+        let runtime = self.runtime.clone();
+        runtime.block_on(async {
+            self.pocket_ic
+                .set_canister_http_client_identity(client_identity)
+                .await
+        })
+    }
 }
 
 impl Default for PocketIc {
@@ -1071,7 +1371,18 @@ pub enum TryFromError {
 /// code and the rest is just a sequentially assigned two-digit
 /// number.
 #[derive(
-    PartialOrd, Ord, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    CandidType,
 )]
 pub enum ErrorCode {
     // 1xx -- `RejectCode::SysFatal`
@@ -1230,7 +1541,17 @@ impl std::fmt::Display for ErrorCode {
 /// wrong. It's designed to be copyable and serializable so that we
 /// can persist it in the ingress history.
 #[derive(
-    PartialOrd, Ord, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema,
+    PartialOrd,
+    Ord,
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    JsonSchema,
+    CandidType,
 )]
 pub struct UserError {
     /// The error code.
@@ -1255,7 +1576,9 @@ pub enum CallError {
 
 /// This struct describes the different types that executing a WASM function in
 /// a canister can produce.
-#[derive(PartialOrd, Ord, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(
+    PartialOrd, Ord, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, CandidType,
+)]
 pub enum WasmResult {
     /// Raw response, returned in a successful case.
     Reply(#[serde(with = "serde_bytes")] Vec<u8>),
@@ -1264,6 +1587,23 @@ pub enum WasmResult {
     Reject(String),
 }
 
+/// The status of an ingress message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, CandidType)]
+pub enum IngressStatus {
+    /// The message has been inducted into the input queue of the receiver.
+    Received,
+    /// The message is currently being executed.
+    Processing,
+    /// The message was executed and the canister replied or rejected it.
+    Replied(WasmResult),
+    /// The execution of the message failed.
+    Rejected(UserError),
+    /// The result of the message has been pruned.
+    Done,
+    /// The message is unknown (e.g., it has not been submitted yet or it expired).
+    Unknown,
+}
+
 /// Attempt to start a new PocketIC server if it's not already running.
 pub fn start_or_reuse_server() -> Url {
     let bin_path = match std::env::var_os("POCKET_IC_BIN") {
@@ -1,13 +1,22 @@
 use crate::common::rest::{
-    ApiResponse, BlobCompression, BlobId, CanisterHttpRequest, CreateHttpGatewayResponse,
-    CreateInstanceResponse, ExtendedSubnetConfigSet, HttpGatewayBackend, HttpGatewayConfig,
-    HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId, MockCanisterHttpResponse,
-    RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId, RawCanisterResult,
-    RawCycles, RawEffectivePrincipal, RawMessageId, RawMockCanisterHttpResponse,
-    RawSetStableMemory, RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime,
-    RawVerifyCanisterSigArg, RawWasmResult, SubnetId, Topology,
+    ApiResponse, AutoProgressConfig, BlobCompression, BlobId, CanisterHttpClientIdentity,
+    CanisterHttpRequest, CanisterLogRecord, CreateHttpGatewayResponse, CreateInstanceResponse,
+    CycleConsumptionRecord, ExtendedSubnetConfigSet, HttpGatewayBackend, HttpGatewayConfig,
+    HttpGatewayDetails, HttpGatewayInfo, HttpsConfig, InstanceConfig, InstanceId,
+    MockCanisterHttpResponse, OpTiming, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest,
+    RawCanisterId, RawCanisterResult, RawCertificate, RawConfigureXNet, RawCycles,
+    RawEffectivePrincipal, RawFaultInjection, RawFreezingThreshold, RawIngressStatus,
+    RawInstanceMetadataEntry, RawInstanceMetadataKey, RawLabel, RawMessageId,
+    RawMockCanisterHttpResponse, RawScheduledMessage, RawSetCanisterHttpClientIdentity,
+    RawSetCanisterHttpResponseLimit, RawSetCanisterHttpSocksProxy, RawSetFreezingThreshold,
+    RawSetNodeClockSkew, RawSetStableMemory, RawStableMemory, RawStableMemoryRangeRequest,
+    RawStateCertificateRequest, RawSubmitIngressResult, RawSubnetId, RawSubnetNode, RawTime,
+    RawTopologyDeltaRequest, RawVerifyCanisterSigArg, RawWasmResult, ScheduledMessage,
+    StableMemoryRange, SubnetId, SubnetMetrics, Topology, TopologyDelta, XNetPolicy,
+};
+use crate::{
+    CallError, IngressStatus, PocketIcBuilder, UserError, WasmResult, DEFAULT_MAX_REQUEST_TIME_MS,
 };
-use crate::{CallError, PocketIcBuilder, UserError, WasmResult, DEFAULT_MAX_REQUEST_TIME_MS};
 use candid::{
     decode_args, encode_args,
     utils::{ArgumentDecoder, ArgumentEncoder},
@@ -21,6 +30,7 @@ use ic_cdk::api::management_canister::main::{
 use reqwest::Url;
 use serde::{de::DeserializeOwned, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs::File;
 use std::future::Future;
 use std::path::PathBuf;
@@ -171,6 +181,14 @@ impl PocketIc {
         self.get(endpoint).await
     }
 
+    /// Returns the subnets and nodes added and removed since the topology with the given hash
+    /// (as returned in a previous topology delta) or the entire topology if no hash is given.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn topology_delta(&self, since: Option<String>) -> TopologyDelta {
+        let endpoint = "read/topology_delta";
+        self.post(endpoint, RawTopologyDeltaRequest { since }).await
+    }
+
     /// Upload and store a binary blob to the PocketIC server.
     #[instrument(ret(Display), skip(self, blob), fields(instance_id=self.instance_id, blob_len = %blob.len(), compression = ?compression))]
     pub async fn upload_blob(&self, blob: Vec<u8>, compression: BlobCompression) -> BlobId {
@@ -229,6 +247,52 @@ impl PocketIc {
         blob
     }
 
+    /// Get (at most) `length` bytes of the stable memory of a canister starting at `offset`
+    /// together with the total size of the stable memory.
+    /// No bytes are returned if `offset` is beyond the end of the stable memory.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), offset = %offset, length = %length))]
+    pub async fn get_stable_memory_range(
+        &self,
+        canister_id: CanisterId,
+        offset: u64,
+        length: u64,
+    ) -> StableMemoryRange {
+        let endpoint = "read/get_stable_memory_range";
+        self.post(
+            endpoint,
+            RawStableMemoryRangeRequest {
+                canister_id: canister_id.as_slice().to_vec(),
+                offset,
+                length,
+            },
+        )
+        .await
+    }
+
+    /// Get the log records of a canister (e.g., recorded via `ic0.debug_print` or on traps)
+    /// in the order in which they were recorded.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn get_canister_logs(&self, canister_id: CanisterId) -> Vec<CanisterLogRecord> {
+        let endpoint = format!("read/canister_logs/{}", canister_id.to_text());
+        self.get(&endpoint).await
+    }
+
+    /// Get the cycles balances of a canister recorded after every tick starting at tick `since_tick`.
+    /// The PocketIC server only records cycles balances if started with `--record-cycle-history`.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn get_cycle_consumption(
+        &self,
+        canister_id: CanisterId,
+        since_tick: u64,
+    ) -> Vec<CycleConsumptionRecord> {
+        let endpoint = format!(
+            "read/cycle_consumption/{}/{}",
+            canister_id.to_text(),
+            since_tick
+        );
+        self.get(&endpoint).await
+    }
+
     /// List all instances and their status.
     #[instrument(ret)]
     pub async fn list_instances() -> Vec<String> {
@@ -244,6 +308,21 @@ impl PocketIc {
         instances
     }
 
+    /// List all HTTP gateways of the PocketIC server (including stopped and failed ones)
+    /// with their ports, backends, and domains.
+    #[instrument(ret)]
+    pub async fn list_http_gateways() -> Vec<HttpGatewayDetails> {
+        let url = crate::start_or_reuse_server().join("http_gateway").unwrap();
+        reqwest::Client::new()
+            .get(url)
+            .send()
+            .await
+            .expect("Failed to get result")
+            .json()
+            .await
+            .expect("Failed to get json")
+    }
+
     /// Verify a canister signature.
     #[instrument(skip_all, fields(instance_id=self.instance_id))]
     pub async fn verify_canister_signature(
@@ -287,10 +366,24 @@ impl PocketIc {
     /// for this instance can be made.
     #[instrument(skip(self), fields(instance_id=self.instance_id))]
     pub async fn auto_progress(&self) -> Url {
+        self.auto_progress_with_config(AutoProgressConfig::default())
+            .await
+    }
+
+    /// Configures the IC to make progress automatically as in `auto_progress`,
+    /// but with a (possibly) fixed tick interval and a (possibly) fixed time
+    /// by which the time of the IC is advanced on every tick
+    /// (independently of the wall-clock time elapsed).
+    /// If the IC already makes progress automatically with a different config,
+    /// it continues with the given config.
+    /// Returns the URL at which `/api/v2` requests
+    /// for this instance can be made.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn auto_progress_with_config(&self, config: AutoProgressConfig) -> Url {
         let now = std::time::SystemTime::now();
         self.set_time(now).await;
         let endpoint = "auto_progress";
-        self.post::<(), _>(endpoint, "").await;
+        self.post::<(), _>(endpoint, config).await;
         self.instance_url()
     }
 
@@ -309,6 +402,38 @@ impl PocketIc {
         self.post::<(), _>(endpoint, "").await;
     }
 
+    /// Pauses automatic progress (see `auto_progress`) on the IC without stopping it:
+    /// the IC stays frozen until automatic progress is resumed by `resume_progress`.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn pause_progress(&self) {
+        let endpoint = "pause_progress";
+        self.post::<(), _>(endpoint, "").await;
+    }
+
+    /// Resumes automatic progress paused by `pause_progress` on the IC.
+    /// The time of the IC does not advance by the wall-clock time elapsed while paused.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn resume_progress(&self) {
+        let endpoint = "resume_progress";
+        self.post::<(), _>(endpoint, "").await;
+    }
+
+    /// Resets the wall-clock time spent computing operations on the IC
+    /// if the PocketIC server was started with a CPU budget per instance.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn reset_cpu_budget(&self) {
+        let endpoint = "reset_cpu_budget";
+        self.post::<(), _>(endpoint, "").await;
+    }
+
+    /// Returns the slowest operations (e.g., ingress messages) computed on this instance
+    /// together with the wall-clock time it took to compute them, ordered by decreasing duration.
+    #[instrument(skip(self), fields(instance_id=self.instance_id))]
+    pub async fn op_timings(&self) -> Vec<OpTiming> {
+        let endpoint = "op_timings";
+        self.get(endpoint).await
+    }
+
     /// Returns the URL at which `/api/v2` requests
     /// for this instance can be made if the HTTP
     /// gateway has been started.
@@ -362,10 +487,13 @@ impl PocketIc {
         }
         let endpoint = self.server_url.join("http_gateway").unwrap();
         let http_gateway_config = HttpGatewayConfig {
+            listen_addr: None,
             listen_at,
             forward_to: HttpGatewayBackend::PocketIcInstance(self.instance_id),
             domains: domains.clone(),
             https_config: https_config.clone(),
+            shutdown_grace_period: None,
+            forward_headers: None,
         };
         let res = self
             .reqwest_client
@@ -507,6 +635,94 @@ impl PocketIc {
         result.cycles
     }
 
+    /// Get the freezing threshold (in seconds) of a canister.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn freezing_threshold(&self, canister_id: CanisterId) -> u64 {
+        let endpoint = "read/get_freezing_threshold";
+        let result: RawFreezingThreshold = self
+            .post(
+                endpoint,
+                RawCanisterId {
+                    canister_id: canister_id.as_slice().to_vec(),
+                },
+            )
+            .await;
+        result.threshold_secs
+    }
+
+    /// Set the freezing threshold (in seconds) of a canister directly,
+    /// i.e., without calling the management canister. Returns the new freezing threshold.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), threshold_secs = %threshold_secs))]
+    pub async fn set_freezing_threshold(
+        &self,
+        canister_id: CanisterId,
+        threshold_secs: u64,
+    ) -> u64 {
+        let endpoint = "update/set_freezing_threshold";
+        let result: RawFreezingThreshold = self
+            .post(
+                endpoint,
+                RawSetFreezingThreshold {
+                    canister_id: canister_id.as_slice().to_vec(),
+                    threshold_secs,
+                },
+            )
+            .await;
+        result.threshold_secs
+    }
+
+    /// Get a certificate of the state tree of the subnet of a canister for the given paths
+    /// (each given as a sequence of labels), e.g., to verify certified data of the canister.
+    /// The certificate is CBOR-encoded as in a response to `read_state` and
+    /// it always includes the path `/time`.
+    #[instrument(skip(self, paths), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string()))]
+    pub async fn state_certificate(
+        &self,
+        canister_id: CanisterId,
+        paths: Vec<Vec<Vec<u8>>>,
+    ) -> Vec<u8> {
+        let endpoint = "read/get_state_certificate";
+        let result: RawCertificate = self
+            .post(
+                endpoint,
+                RawStateCertificateRequest {
+                    canister_id: canister_id.as_slice().to_vec(),
+                    paths: paths
+                        .into_iter()
+                        .map(|path| path.into_iter().map(RawLabel).collect())
+                        .collect(),
+                },
+            )
+            .await;
+        result.certificate
+    }
+
+    /// Inject a fault into calls of the given method of the given canister:
+    /// the next such update call fails with the given error
+    /// without being executed by the canister.
+    /// If `repeat` is set, then all subsequent such update and query calls fail with the given error.
+    /// Query calls are read-only and thus never consume a fault that is not repeated.
+    #[instrument(skip(self, error), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), method = %method))]
+    pub async fn inject_fault(
+        &self,
+        canister_id: CanisterId,
+        method: &str,
+        error: UserError,
+        repeat: bool,
+    ) {
+        let endpoint = "update/inject_fault";
+        self.post::<(), _>(
+            endpoint,
+            RawFaultInjection {
+                canister_id: canister_id.as_slice().to_vec(),
+                method: method.to_string(),
+                error,
+                repeat,
+            },
+        )
+        .await;
+    }
+
     /// Submit an update call (without executing it immediately).
     pub async fn submit_call(
         &self,
@@ -562,6 +778,14 @@ impl PocketIc {
         }
     }
 
+    /// Get the status of an update call submitted previously by `submit_call_with_effective_principal`
+    /// without executing any rounds.
+    pub async fn ingress_status(&self, message_id: RawMessageId) -> IngressStatus {
+        let endpoint = "read/ingress_status";
+        let result: RawIngressStatus = self.post(endpoint, message_id).await;
+        result.into()
+    }
+
     /// Execute an update call on a canister.
     #[instrument(skip(self, payload), fields(instance_id=self.instance_id, canister_id = %canister_id.to_string(), sender = %sender.to_string(), method = %method, payload_len = %payload.len()))]
     pub async fn update_call(
@@ -1013,6 +1237,102 @@ impl PocketIc {
         result.map(|RawSubnetId { subnet_id }| SubnetId::from_slice(&subnet_id))
     }
 
+    /// Returns the messages pending execution on the given subnet in the order
+    /// in which the scheduler considers them in the next round.
+    /// Messages to the management canister are not included.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub async fn get_scheduled_messages(&self, subnet_id: SubnetId) -> Vec<ScheduledMessage> {
+        let endpoint = "read/get_scheduled_messages";
+        let subnet_id: RawSubnetId = subnet_id.into();
+        let res: Vec<RawScheduledMessage> = self.post(endpoint, subnet_id).await;
+        res.into_iter().map(|m| m.into()).collect()
+    }
+
+    /// Returns aggregate statistics of the canisters on the given subnet:
+    /// their number, memory usage, cycles balance, and pending ingress messages.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub async fn get_subnet_metrics(&self, subnet_id: SubnetId) -> SubnetMetrics {
+        let endpoint = "read/get_subnet_metrics";
+        let subnet_id: RawSubnetId = subnet_id.into();
+        self.post(endpoint, subnet_id).await
+    }
+
+    /// Sets the clock skew of the given node on the given subnet in nanoseconds, i.e., the amount
+    /// by which the local clock of the node is ahead of (if positive) or behind (if negative)
+    /// the IC time. The time used for executing rounds on the subnet is not affected,
+    /// but ingress messages submitted to a subnet are rejected if their expiry is not within
+    /// the range accepted by the local clock of the first node of the subnet.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, subnet_id = %subnet_id.to_string(), node_id = %node_id.to_string(), skew_nanos = %skew_nanos))]
+    pub async fn set_node_clock_skew(
+        &self,
+        subnet_id: SubnetId,
+        node_id: Principal,
+        skew_nanos: i64,
+    ) {
+        let endpoint = "update/set_node_clock_skew";
+        self.post::<(), _>(
+            endpoint,
+            RawSetNodeClockSkew {
+                subnet_id: subnet_id.into(),
+                node_id: node_id.into(),
+                skew_nanos,
+            },
+        )
+        .await;
+    }
+
+    /// Get the time reported by the local clock of the given node on the given subnet.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, subnet_id = %subnet_id.to_string(), node_id = %node_id.to_string()))]
+    pub async fn get_node_time(&self, subnet_id: SubnetId, node_id: Principal) -> SystemTime {
+        let endpoint = "read/get_node_time";
+        let result: RawTime = self
+            .post(
+                endpoint,
+                RawSubnetNode {
+                    subnet_id: subnet_id.into(),
+                    node_id: node_id.into(),
+                },
+            )
+            .await;
+        SystemTime::UNIX_EPOCH + Duration::from_nanos(result.nanos_since_epoch)
+    }
+
+    /// Attaches a key-value pair of metadata (e.g., the test name) to the instance,
+    /// overwriting any previous value for the key.
+    /// The number and size of key-value pairs is bounded.
+    #[instrument(skip(self), fields(instance_id=self.instance_id, key = %key, value = %value))]
+    pub async fn set_instance_metadata(&self, key: &str, value: &str) {
+        let endpoint = "update/set_instance_metadata";
+        self.post::<(), _>(
+            endpoint,
+            RawInstanceMetadataEntry {
+                key: key.to_string(),
+                value: value.to_string(),
+            },
+        )
+        .await;
+    }
+
+    /// Returns the value of the given key of the instance metadata, if any.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, key = %key))]
+    pub async fn get_instance_metadata(&self, key: &str) -> Option<String> {
+        let endpoint = "read/get_instance_metadata";
+        self.post(
+            endpoint,
+            RawInstanceMetadataKey {
+                key: key.to_string(),
+            },
+        )
+        .await
+    }
+
+    /// Returns all key-value pairs of the instance metadata.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn get_all_instance_metadata(&self) -> BTreeMap<String, String> {
+        let endpoint = "metadata/kv";
+        self.get(endpoint).await
+    }
+
     /// This (asynchronous) drop function must be called to drop the PocketIc instance.
     /// It must be called manually as Rust doesn't support asynchronous drop.
     pub async fn drop(mut self) {
@@ -1102,6 +1422,12 @@ impl PocketIc {
                             .await
                             .expect("HTTP failure");
                         match ApiResponse::<_>::from_response(result).await {
+                            ApiResponse::Error { message } if message == "Cancelled" => {
+                                panic!("The operation {} was cancelled.", op_id)
+                            }
+                            ApiResponse::Error { message } if message == "Expired" => {
+                                panic!("The result of the operation {} has expired.", op_id)
+                            }
                             ApiResponse::Error { message } => {
                                 debug!("Polling has not succeeded yet: {}", message)
                             }
@@ -1204,6 +1530,17 @@ impl PocketIc {
         res.into_iter().map(|r| r.into()).collect()
     }
 
+    /// List all pending canister HTTP outcalls (including those with a response
+    /// mocked to be applied after a delay) without processing them.
+    /// This is a pure read that does not change the state of the PocketIC instance,
+    /// e.g., to inspect the outcalls before mocking their responses.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn peek_canister_http(&self) -> Vec<CanisterHttpRequest> {
+        let endpoint = "read/peek_canister_http";
+        let res: Vec<RawCanisterHttpRequest> = self.get(endpoint).await;
+        res.into_iter().map(|r| r.into()).collect()
+    }
+
     /// Mock a response to a pending canister HTTP outcall.
     #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
     pub async fn mock_canister_http_response(
@@ -1215,6 +1552,75 @@ impl PocketIc {
             mock_canister_http_response.into();
         self.post(endpoint, raw_mock_canister_http_response).await
     }
+
+    /// Limit the size of canister HTTP outcall responses on the given subnet.
+    /// Responses to canister HTTP outcalls on that subnet exceeding the limit are rejected.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, subnet_id = %subnet_id.to_string()))]
+    pub async fn set_canister_http_response_limit(&self, subnet_id: SubnetId, max_bytes: u64) {
+        let endpoint = "update/set_canister_http_response_limit";
+        self.post::<(), _>(
+            endpoint,
+            RawSetCanisterHttpResponseLimit {
+                subnet_id: RawSubnetId {
+                    subnet_id: subnet_id.as_slice().to_vec(),
+                },
+                max_bytes,
+            },
+        )
+        .await
+    }
+
+    /// Configure the policy applied to XNet messages from one subnet to another,
+    /// e.g., to delay or drop them. XNet messages are delivered as soon as possible
+    /// if `None` is provided (default).
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id, from_subnet = %from_subnet.to_string(), to_subnet = %to_subnet.to_string()))]
+    pub async fn configure_xnet(
+        &self,
+        from_subnet: SubnetId,
+        to_subnet: SubnetId,
+        policy: Option<XNetPolicy>,
+    ) {
+        let endpoint = "update/configure_xnet";
+        self.post::<(), _>(
+            endpoint,
+            RawConfigureXNet {
+                from_subnet: RawSubnetId {
+                    subnet_id: from_subnet.as_slice().to_vec(),
+                },
+                to_subnet: RawSubnetId {
+                    subnet_id: to_subnet.as_slice().to_vec(),
+                },
+                policy,
+            },
+        )
+        .await
+    }
+
+    /// Route canister HTTP outcalls through the given SOCKS proxy (e.g., `socks5://127.0.0.1:1080`)
+    /// if a direct connection fails. Outcalls are only made directly if `None` is provided (default).
+    /// This only applies to outcalls made by the PocketIC server in auto progress mode.
+    #[instrument(ret, skip(self), fields(instance_id=self.instance_id))]
+    pub async fn set_canister_http_socks_proxy(&self, proxy_url: Option<String>) {
+        let endpoint = "update/set_canister_http_socks_proxy";
+        self.post::<(), _>(endpoint, RawSetCanisterHttpSocksProxy { proxy_url })
+            .await
+    }
+
+    /// Present the given TLS client certificate (chain) and private key in canister HTTP outcalls
+    /// to servers requiring mutual TLS. No client certificate is presented if `None` is provided (default).
+    /// This only applies to outcalls made by the PocketIC server in auto progress mode.
+    #[instrument(ret, skip(self, client_identity), fields(instance_id=self.instance_id))]
+    pub async fn set_canister_http_client_identity(
+        &self,
+        client_identity: Option<CanisterHttpClientIdentity>,
+    ) {
+        let endpoint = "update/set_canister_http_client_identity";
+        self.post::<(), _>(
+            endpoint,
+            RawSetCanisterHttpClientIdentity { client_identity },
+        )
+        .await
+    }
 }
 
 /// Call a canister candid method, authenticated. The sender can be impersonated (i.e., the
@@ -10,13 +10,18 @@ use icp_ledger::{
 };
 use pocket_ic::{
     common::rest::{
-        BlobCompression, CanisterHttpReply, CanisterHttpResponse, MockCanisterHttpResponse,
-        SubnetConfigSet, SubnetKind,
+        AutoProgressConfig, BlobCompression, CanisterHttpReply, CanisterHttpRequest,
+        CanisterHttpResponse, MockCanisterHttpResponse, ScheduledMessageKind, SubnetConfigSet,
+        SubnetId, SubnetKind, XNetPolicy,
     },
-    update_candid, PocketIc, PocketIcBuilder, WasmResult,
+    update_candid, ErrorCode, IngressStatus, PocketIc, PocketIcBuilder, UserError, WasmResult,
 };
 use sha2::{Digest, Sha256};
-use std::{collections::HashMap, io::Read, time::SystemTime};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Read,
+    time::{Duration, SystemTime},
+};
 
 // 2T cycles
 const INIT_CYCLES: u128 = 2_000_000_000_000;
@@ -550,6 +555,161 @@ fn test_multiple_large_xnet_payloads() {
     }
 }
 
+#[test]
+fn test_auto_progress_with_config() {
+    let pic = PocketIc::new();
+    let advance_by = std::time::Duration::from_secs(3600);
+
+    let start = SystemTime::now();
+    pic.auto_progress_with_config(AutoProgressConfig {
+        tick_interval: Some(std::time::Duration::from_millis(100)),
+        advance_by: Some(advance_by),
+    });
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    pic.stop_progress();
+
+    // The time is advanced by the configured amount on every tick
+    // which is much more than the elapsed wall-clock time.
+    let elapsed = pic.get_time().duration_since(start).unwrap();
+    assert!(elapsed >= advance_by);
+    assert!(elapsed > SystemTime::now().duration_since(start).unwrap());
+}
+
+#[test]
+fn test_freezing_threshold() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let update = || {
+        pic.update_call(
+            canister_id,
+            Principal::anonymous(),
+            "update",
+            wasm().reply_data(b"hello").build(),
+        )
+    };
+    update().unwrap();
+
+    // A freezing threshold (of about 30k years) for which the cycles balance
+    // of the canister does not suffice freezes the canister.
+    let threshold_secs = 1_000_000_000_000;
+    assert_eq!(
+        pic.set_freezing_threshold(canister_id, threshold_secs),
+        threshold_secs
+    );
+    assert_eq!(pic.freezing_threshold(canister_id), threshold_secs);
+    let err = update().unwrap_err();
+    assert_eq!(err.code, ErrorCode::CanisterOutOfCycles);
+
+    // Lowering the freezing threshold unfreezes the canister.
+    pic.set_freezing_threshold(canister_id, 0);
+    update().unwrap();
+}
+
+#[test]
+fn test_state_certificate() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let certified_data = b"certified data of the canister";
+    pic.update_call(
+        canister_id,
+        Principal::anonymous(),
+        "update",
+        wasm()
+            .certified_data_set(certified_data)
+            .reply_data(b"")
+            .build(),
+    )
+    .unwrap();
+
+    let certificate = pic.state_certificate(
+        canister_id,
+        vec![vec![
+            b"canister".to_vec(),
+            canister_id.as_slice().to_vec(),
+            b"certified_data".to_vec(),
+        ]],
+    );
+    // The certificate is CBOR-encoded with the self-describe tag.
+    assert_eq!(certificate[..3], [0xd9, 0xd9, 0xf7]);
+    // The certified data is a leaf of the certified tree.
+    assert!(certificate
+        .windows(certified_data.len())
+        .any(|window| window == certified_data));
+}
+
+#[test]
+fn test_inject_fault() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let error = UserError {
+        code: ErrorCode::CanisterRejectedMessage,
+        description: "injected fault".to_string(),
+    };
+    pic.inject_fault(canister_id, "query", error.clone(), true);
+    assert!(
+        PocketIc::list_instances()[pic.instance_id()].starts_with("Available(injected faults: [")
+    );
+
+    // A repeated fault applies to all matching calls.
+    for _ in 0..2 {
+        let user_error = pic
+            .query_call(
+                canister_id,
+                Principal::anonymous(),
+                "query",
+                wasm().reply_data(b"hello").build(),
+            )
+            .unwrap_err();
+        assert_eq!(user_error, error);
+    }
+
+    // Calls of other methods are not affected.
+    let reply = pic
+        .update_call(
+            canister_id,
+            Principal::anonymous(),
+            "update",
+            wasm().reply_data(b"hello").build(),
+        )
+        .unwrap();
+    assert_eq!(reply, WasmResult::Reply(b"hello".to_vec()));
+}
+
+#[test]
+fn test_pause_and_resume_progress() {
+    let pic = PocketIc::new();
+    let advance_by = std::time::Duration::from_secs(3600);
+
+    pic.auto_progress_with_config(AutoProgressConfig {
+        tick_interval: Some(std::time::Duration::from_millis(100)),
+        advance_by: Some(advance_by),
+    });
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    pic.pause_progress();
+    // The pause takes effect once the current iteration completes.
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    // The time does not advance while paused.
+    let paused_time = pic.get_time();
+    std::thread::sleep(std::time::Duration::from_secs(1));
+    assert_eq!(pic.get_time(), paused_time);
+
+    pic.resume_progress();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    pic.stop_progress();
+
+    assert!(pic.get_time() >= paused_time + advance_by);
+}
+
 #[test]
 fn test_get_and_set_and_advance_time() {
     let pic = PocketIc::new();
@@ -568,6 +728,93 @@ fn test_get_and_set_and_advance_time() {
     );
 }
 
+fn app_subnet_node_ids(pic: &PocketIc) -> (SubnetId, Vec<Principal>) {
+    let subnet_id = pic.topology().get_app_subnets()[0];
+    let node_ids: Vec<Principal> = pic.topology().0[&subnet_id]
+        .node_ids
+        .iter()
+        .cloned()
+        .map(Principal::from)
+        .collect();
+    assert!(node_ids.len() > 2);
+    (subnet_id, node_ids)
+}
+
+#[test]
+fn test_node_clock_skew() {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+    let (subnet_id, node_ids) = app_subnet_node_ids(&pic);
+
+    // The clock of the first node (receiving ingress messages) is slightly behind
+    // and the clock of another node is far ahead of the IC time.
+    let behind = Duration::from_secs(10);
+    let ahead = Duration::from_secs(600);
+    pic.set_node_clock_skew(subnet_id, node_ids[0], -(behind.as_nanos() as i64));
+    pic.set_node_clock_skew(subnet_id, node_ids[1], ahead.as_nanos() as i64);
+
+    // The subnet still makes progress.
+    let can_id = pic.create_canister_on_subnet(None, None, subnet_id);
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+    let reply = call_counter_can(&pic, can_id, "write");
+    assert_eq!(reply, WasmResult::Reply(vec![1, 0, 0, 0]));
+
+    // Only the skewed nodes report a different time.
+    let time = pic.get_time();
+    assert_eq!(pic.get_node_time(subnet_id, node_ids[0]), time - behind);
+    assert_eq!(pic.get_node_time(subnet_id, node_ids[1]), time + ahead);
+    assert_eq!(pic.get_node_time(subnet_id, node_ids[2]), time);
+}
+
+#[test]
+#[should_panic(expected = "Specified ingress_expiry not within expected range")]
+fn test_node_clock_skew_rejects_ingress_messages() {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+    let (subnet_id, node_ids) = app_subnet_node_ids(&pic);
+    let can_id = pic.create_canister_on_subnet(None, None, subnet_id);
+    pic.add_cycles(can_id, INIT_CYCLES);
+    pic.install_canister(can_id, counter_wasm(), vec![], None);
+
+    // The clock of the first node (receiving ingress messages) is so far behind
+    // that the expiry of new ingress messages is too far in the future for that node.
+    let behind = Duration::from_secs(60);
+    pic.set_node_clock_skew(subnet_id, node_ids[0], -(behind.as_nanos() as i64));
+    call_counter_can(&pic, can_id, "write");
+}
+
+#[test]
+fn test_instance_metadata() {
+    let pic = PocketIc::new();
+
+    pic.set_instance_metadata("test_name", "test_instance_metadata");
+    pic.set_instance_metadata("git_sha", "0123456789abcdef");
+    pic.set_instance_metadata("test_name", "overwritten");
+
+    assert_eq!(
+        pic.get_instance_metadata("test_name"),
+        Some("overwritten".to_string())
+    );
+    assert_eq!(
+        pic.get_instance_metadata("git_sha"),
+        Some("0123456789abcdef".to_string())
+    );
+    assert_eq!(pic.get_instance_metadata("ticket_id"), None);
+    assert_eq!(
+        pic.get_all_instance_metadata(),
+        BTreeMap::from([
+            ("git_sha".to_string(), "0123456789abcdef".to_string()),
+            ("test_name".to_string(), "overwritten".to_string()),
+        ])
+    );
+}
+
+#[test]
+#[should_panic(expected = "InstanceMetadataLimitExceeded")]
+fn test_instance_metadata_value_too_large() {
+    let pic = PocketIc::new();
+    pic.set_instance_metadata("key", &"x".repeat(4097));
+}
+
 #[test]
 fn test_get_set_cycle_balance() {
     let pic = PocketIc::new();
@@ -688,6 +935,28 @@ fn test_set_and_get_stable_memory_compressed() {
     assert_eq!(data, read_data[..8]);
 }
 
+#[test]
+fn test_get_stable_memory_range() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    let counter_wasm = counter_wasm();
+    pic.install_canister(canister_id, counter_wasm, vec![], None);
+
+    let data = "deadbeef".as_bytes().to_vec();
+    pic.set_stable_memory(canister_id, data, BlobCompression::NoCompression);
+
+    let range = pic.get_stable_memory_range(canister_id, 4, 4);
+    assert_eq!(range.blob, "beef".as_bytes().to_vec());
+    let total_size = range.total_size;
+    assert_eq!(total_size, pic.get_stable_memory(canister_id).len() as u64);
+
+    // An offset beyond the end of the stable memory yields no bytes.
+    let range = pic.get_stable_memory_range(canister_id, total_size + 1, 4);
+    assert!(range.blob.is_empty());
+    assert_eq!(range.total_size, total_size);
+}
+
 #[test]
 fn test_parallel_calls() {
     let wat = r#"
@@ -750,6 +1019,44 @@ fn test_parallel_calls() {
     assert!(time1 != time3);
 }
 
+#[test]
+fn test_ingress_status() {
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+
+    let reply_id = pic
+        .submit_call(
+            canister_id,
+            Principal::anonymous(),
+            "update",
+            wasm().reply_data(b"hello").build(),
+        )
+        .unwrap();
+    // the message has not been inducted yet
+    assert_eq!(pic.ingress_status(reply_id.clone()), IngressStatus::Unknown);
+    pic.await_call(reply_id.clone()).unwrap();
+    assert_eq!(
+        pic.ingress_status(reply_id),
+        IngressStatus::Replied(WasmResult::Reply(b"hello".to_vec()))
+    );
+
+    let trap_id = pic
+        .submit_call(
+            canister_id,
+            Principal::anonymous(),
+            "update",
+            wasm().trap().build(),
+        )
+        .unwrap();
+    pic.await_call(trap_id.clone()).unwrap_err();
+    assert!(matches!(
+        pic.ingress_status(trap_id),
+        IngressStatus::Rejected(_)
+    ));
+}
+
 #[test]
 fn test_inspect_message() {
     let wat = r#"
@@ -966,6 +1273,30 @@ fn test_update_canister_settings() {
     assert_eq!(status.settings.compute_allocation, new_compute_allocation);
 }
 
+#[test]
+fn test_topology_delta() {
+    let pic = PocketIcBuilder::new().with_application_subnet().build();
+
+    // Initially, all subnets are reported as added.
+    let delta = pic.topology_delta(None);
+    assert_eq!(delta.added_subnets, pic.topology().0);
+
+    // Creating a canister with a specified canister ID on the IC mainnet
+    // that belongs to no subnet on the PocketIC instance creates exactly one new subnet.
+    let specified_id = Principal::from_text("rimrc-piaaa-aaaao-aaljq-cai").unwrap();
+    pic.create_canister_with_id(None, None, specified_id)
+        .unwrap();
+    let delta = pic.topology_delta(Some(delta.topology_hash));
+    assert_eq!(delta.added_subnets.len(), 1);
+    let new_subnet = pic.get_subnet(specified_id).unwrap();
+    assert!(delta.added_subnets.contains_key(&new_subnet));
+    assert!(delta.removed_subnets.is_empty());
+
+    // Nothing has changed since.
+    let delta = pic.topology_delta(Some(delta.topology_hash));
+    assert!(delta.is_empty());
+}
+
 #[test]
 fn test_xnet_call_and_create_canister_with_specified_id() {
     // We start with a PocketIC instance consisting of two application subnets.
@@ -1035,6 +1366,62 @@ fn test_xnet_call_and_create_canister_with_specified_id() {
     }
 }
 
+#[test]
+fn test_xnet_delay() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_application_subnet()
+        .build();
+    let subnet_id_1 = pic.topology().get_app_subnets()[0];
+    let subnet_id_2 = pic.topology().get_app_subnets()[1];
+    let canister_1 = pic.create_canister_on_subnet(None, None, subnet_id_1);
+    let canister_2 = pic.create_canister_on_subnet(None, None, subnet_id_2);
+    for canister in [canister_1, canister_2] {
+        pic.add_cycles(canister, INIT_CYCLES);
+        pic.install_canister(canister, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+    }
+
+    // XNet messages from the first to the second subnet are delayed by 20 ticks.
+    pic.configure_xnet(
+        subnet_id_1,
+        subnet_id_2,
+        Some(XNetPolicy::Delay { ticks: 20 }),
+    );
+
+    let call_id = pic
+        .submit_call(
+            canister_1,
+            Principal::anonymous(),
+            "update",
+            wasm()
+                .inter_update(
+                    canister_2,
+                    CallArgs::default().other_side(wasm().set_global_data(b"called").reply()),
+                )
+                .build(),
+        )
+        .unwrap();
+    let global_data = || {
+        pic.query_call(
+            canister_2,
+            Principal::anonymous(),
+            "query",
+            wasm().get_global_data().append_and_reply().build(),
+        )
+        .unwrap()
+    };
+
+    // The call has not reached the second canister yet.
+    for _ in 0..10 {
+        pic.tick();
+    }
+    assert_eq!(global_data(), WasmResult::Reply(vec![]));
+
+    // The call eventually reaches the second canister.
+    pic.await_call(call_id).unwrap();
+    assert_eq!(global_data(), WasmResult::Reply(b"called".to_vec()));
+}
+
 #[test]
 fn test_query_call_on_new_pocket_ic() {
     let pic = PocketIc::new();
@@ -1216,6 +1603,7 @@ fn test_canister_http() {
             headers: vec![],
             body: body.clone(),
         }),
+        apply_after: None,
     };
     pic.mock_canister_http_response(mock_canister_http_response);
 
@@ -1262,6 +1650,7 @@ fn test_canister_http() {
             headers: vec![],
             body: body.clone(),
         }),
+        apply_after: None,
     };
     pic.mock_canister_http_response(mock_canister_http_response);
 
@@ -1284,3 +1673,296 @@ fn test_canister_http() {
     let canister_http_requests = pic.get_canister_http();
     assert_eq!(canister_http_requests.len(), 0);
 }
+
+#[test]
+fn test_canister_http_delayed_response() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the test canister wasm file on the canister.
+    let test_wasm = test_canister_wasm();
+    pic.install_canister(can_id, test_wasm, vec![], None);
+
+    let call_id = pic
+        .submit_call(
+            can_id,
+            Principal::anonymous(),
+            "canister_http",
+            encode_one(()).unwrap(),
+        )
+        .unwrap();
+    pic.tick();
+    pic.tick();
+    let canister_http_requests = pic.get_canister_http();
+    assert_eq!(canister_http_requests.len(), 1);
+    let canister_http_request = &canister_http_requests[0];
+
+    // Peeking at the pending canister http outcalls does not change the state.
+    assert_eq!(pic.peek_canister_http(), canister_http_requests);
+    assert_eq!(pic.get_canister_http(), canister_http_requests);
+
+    // Mock a canister http outcall response to be applied after 10 seconds.
+    let body = b"hello".to_vec();
+    pic.mock_canister_http_response(MockCanisterHttpResponse {
+        subnet_id: canister_http_request.subnet_id,
+        request_id: canister_http_request.request_id,
+        response: CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+            status: 200,
+            headers: vec![],
+            body: body.clone(),
+        }),
+        apply_after: Some(Duration::from_secs(10)),
+    });
+
+    // The held response is not applied yet and the request is no longer pending,
+    // but it can still be inspected.
+    assert!(pic.get_canister_http().is_empty());
+    assert_eq!(pic.peek_canister_http(), canister_http_requests);
+    assert!(PocketIc::list_instances()[pic.instance_id()]
+        .starts_with("Available(held canister HTTP responses: ["));
+    pic.tick();
+    assert!(PocketIc::list_instances()[pic.instance_id()]
+        .starts_with("Available(held canister HTTP responses: ["));
+
+    // Once the time advanced sufficiently, the response is applied.
+    pic.advance_time(Duration::from_secs(10));
+    let reply = pic.await_call(call_id).unwrap();
+    match reply {
+        WasmResult::Reply(data) => {
+            let http_response: HttpResponse = decode_one(&data).unwrap();
+            assert_eq!(http_response.body, body);
+        }
+        WasmResult::Reject(msg) => panic!("Unexpected reject {}", msg),
+    };
+    assert_eq!(
+        PocketIc::list_instances()[pic.instance_id()],
+        "Available".to_string()
+    );
+}
+
+#[test]
+fn test_canister_http_response_limit() {
+    let pic = PocketIc::new();
+
+    // Create a canister and charge it with 2T cycles.
+    let can_id = pic.create_canister();
+    pic.add_cycles(can_id, INIT_CYCLES);
+
+    // Install the test canister wasm file on the canister.
+    let test_wasm = test_canister_wasm();
+    pic.install_canister(can_id, test_wasm, vec![], None);
+
+    let make_canister_http_outcall = || {
+        let call_id = pic
+            .submit_call(
+                can_id,
+                Principal::anonymous(),
+                "canister_http",
+                encode_one(()).unwrap(),
+            )
+            .unwrap();
+        // We need a pair of ticks for the test canister method to make the http outcall
+        // and for the management canister to start processing the http outcall.
+        pic.tick();
+        pic.tick();
+        let canister_http_requests = pic.get_canister_http();
+        assert_eq!(canister_http_requests.len(), 1);
+        (call_id, canister_http_requests[0].clone())
+    };
+
+    let mock_response = |canister_http_request: &CanisterHttpRequest, body: Vec<u8>| {
+        pic.mock_canister_http_response(MockCanisterHttpResponse {
+            subnet_id: canister_http_request.subnet_id,
+            request_id: canister_http_request.request_id,
+            response: CanisterHttpResponse::CanisterHttpReply(CanisterHttpReply {
+                status: 200,
+                headers: vec![],
+                body,
+            }),
+            apply_after: None,
+        });
+    };
+
+    let (call_id, canister_http_request) = make_canister_http_outcall();
+    pic.set_canister_http_response_limit(canister_http_request.subnet_id, 10);
+
+    // A response exceeding the limit is rejected.
+    mock_response(&canister_http_request, vec![42; 11]);
+    let err = pic.await_call(call_id).unwrap_err();
+    assert!(err.description.contains("exceeds size limit of 10 bytes"));
+
+    // A response within the limit is delivered to the canister.
+    let (call_id, canister_http_request) = make_canister_http_outcall();
+    let body = b"hello".to_vec();
+    mock_response(&canister_http_request, body.clone());
+    let reply = pic.await_call(call_id).unwrap();
+    match reply {
+        WasmResult::Reply(data) => {
+            let http_response: HttpResponse = decode_one(&data).unwrap();
+            assert_eq!(http_response.body, body);
+        }
+        WasmResult::Reject(msg) => panic!("Unexpected reject {}", msg),
+    };
+}
+
+#[test]
+fn test_get_subnet_metrics() {
+    let pic = PocketIc::new();
+    let subnet_id = pic.topology().get_app_subnets()[0];
+
+    let metrics = pic.get_subnet_metrics(subnet_id);
+    assert_eq!(metrics.num_canisters, 0);
+    assert_eq!(metrics.canister_memory_bytes, 0);
+    assert_eq!(metrics.total_cycles, 0);
+    assert_eq!(metrics.ingress_queue_length, 0);
+
+    let canister_id = pic.create_canister_on_subnet(None, None, subnet_id);
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+    let metrics = pic.get_subnet_metrics(subnet_id);
+    assert_eq!(metrics.num_canisters, 1);
+    assert!(metrics.canister_memory_bytes > 0);
+    assert_eq!(metrics.total_cycles, pic.cycle_balance(canister_id));
+    assert_eq!(metrics.ingress_queue_length, 0);
+
+    // A submitted ingress message is pending until the next round.
+    pic.submit_call(
+        canister_id,
+        Principal::anonymous(),
+        "update",
+        wasm().reply().build(),
+    )
+    .unwrap();
+    assert_eq!(pic.get_subnet_metrics(subnet_id).ingress_queue_length, 1);
+    pic.tick();
+    assert_eq!(pic.get_subnet_metrics(subnet_id).ingress_queue_length, 0);
+}
+
+#[test]
+fn test_get_scheduled_messages() {
+    let pic = PocketIcBuilder::new()
+        .with_application_subnet()
+        .with_application_subnet()
+        .build();
+
+    // Subnets are ticked in the order of their subnet IDs. Hence a request from
+    // a canister on the second subnet to a canister on the first subnet is pending
+    // in between two ticks.
+    let mut subnet_ids = pic.topology().get_app_subnets();
+    subnet_ids.sort();
+    let callee = pic.create_canister_on_subnet(None, None, subnet_ids[0]);
+    let caller = pic.create_canister_on_subnet(None, None, subnet_ids[1]);
+    for canister_id in [callee, caller] {
+        pic.add_cycles(canister_id, INIT_CYCLES);
+        pic.install_canister(canister_id, UNIVERSAL_CANISTER_WASM.to_vec(), vec![], None);
+    }
+
+    // The caller makes an inter-canister call to the callee.
+    pic.submit_call(
+        caller,
+        Principal::anonymous(),
+        "update",
+        wasm()
+            .inter_update(
+                callee,
+                CallArgs::default().other_side(
+                    wasm()
+                        .push_bytes(b"R")
+                        .append_to_global_data()
+                        .reply()
+                        .build(),
+                ),
+            )
+            .build(),
+    )
+    .unwrap();
+    pic.tick();
+
+    // An ingress message to the callee is submitted, too.
+    pic.submit_call(
+        callee,
+        Principal::anonymous(),
+        "update",
+        wasm()
+            .push_bytes(b"I")
+            .append_to_global_data()
+            .reply()
+            .build(),
+    )
+    .unwrap();
+
+    let scheduled_messages = pic.get_scheduled_messages(subnet_ids[0]);
+    assert_eq!(scheduled_messages.len(), 2);
+    assert!(scheduled_messages.iter().all(|m| m.canister_id == callee));
+    let expected_execution_order: Vec<u8> = scheduled_messages
+        .iter()
+        .map(|m| match m.kind {
+            ScheduledMessageKind::Ingress => b'I',
+            ScheduledMessageKind::Request => b'R',
+            ScheduledMessageKind::Response => panic!("Unexpected response"),
+        })
+        .collect();
+    assert!(expected_execution_order.contains(&b'I'));
+    assert!(expected_execution_order.contains(&b'R'));
+
+    // The messages are executed in the reported order.
+    pic.tick();
+    let global_data = pic
+        .query_call(
+            callee,
+            Principal::anonymous(),
+            "query",
+            wasm().get_global_data().append_and_reply().build(),
+        )
+        .unwrap();
+    assert_eq!(global_data, WasmResult::Reply(expected_execution_order));
+}
+
+#[test]
+fn test_canister_logs() {
+    let wat = r#"
+    (module
+        (import "ic0" "debug_print" (func $debug_print (param i32 i32)))
+        (import "ic0" "trap" (func $trap (param i32 i32)))
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (func $print
+            (call $debug_print (i32.const 0) (i32.const 5))
+            (call $msg_reply))
+        (func $fail
+            (call $trap (i32.const 5) (i32.const 4)))
+        (memory $memory 1)
+        (data (i32.const 0) "helloboom")
+        (export "canister_update print" (func $print))
+        (export "canister_update fail" (func $fail))
+    )
+"#;
+
+    let pic = PocketIc::new();
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, INIT_CYCLES);
+    pic.install_canister(canister_id, wat::parse_str(wat).unwrap(), vec![], None);
+
+    assert!(pic.get_canister_logs(canister_id).is_empty());
+
+    pic.update_call(canister_id, Principal::anonymous(), "print", vec![])
+        .unwrap();
+    pic.update_call(canister_id, Principal::anonymous(), "fail", vec![])
+        .unwrap_err();
+
+    let logs = pic.get_canister_logs(canister_id);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].content, b"hello".to_vec());
+    assert!(String::from_utf8_lossy(&logs[1].content).contains("boom"));
+    assert!(logs[0].idx < logs[1].idx);
+    assert!(logs[0].timestamp_nanos <= logs[1].timestamp_nanos);
+}
+
+#[test]
+#[should_panic(expected = "CanisterNotFound")]
+fn test_canister_logs_nonexistent_canister() {
+    let pic = PocketIc::new();
+    pic.get_canister_logs(Principal::from_slice(&[0xff; 10]));
+}
@@ -213,7 +213,7 @@ use ic_types::{
 
 use std::cell::RefCell;
 use std::collections::{BTreeSet, HashSet};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 pub(crate) mod complaints;
@@ -223,6 +223,7 @@ pub(crate) mod metrics;
 pub(crate) mod payload_builder;
 pub(crate) mod payload_verifier;
 pub(crate) mod pre_signer;
+pub(crate) mod progress;
 pub(crate) mod signer;
 pub mod stats;
 #[cfg(test)]
@@ -235,15 +236,34 @@ pub(crate) use payload_builder::{
 pub(crate) use payload_verifier::{
     validate_payload, IDkgPayloadValidationFailure, InvalidIDkgPayloadReason,
 };
+pub use progress::{IDkgProgressReport, IDkgTranscriptProgress};
+pub use signer::ThresholdSignerMode;
 pub use stats::IDkgStatsImpl;
 
 use self::utils::get_context_request_id;
 
-/// Similar to consensus, we don't fetch artifacts too far ahead in future.
-const LOOK_AHEAD: u64 = 10;
+/// Similar to consensus, we don't fetch artifacts too far ahead in future. This is
+/// the default number of heights to look ahead, unless a subnet specifies another one.
+/// Artifacts at exactly the finalized (or certified) height plus the look-ahead are
+/// still fetched, artifacts beyond that are stashed.
+pub const DEFAULT_LOOK_AHEAD: u64 = 10;
 
-/// Frequency for clearing the inactive key transcripts.
-pub(crate) const INACTIVE_TRANSCRIPT_PURGE_SECS: Duration = Duration::from_secs(60);
+/// Default frequency for clearing the inactive key transcripts.
+pub const INACTIVE_TRANSCRIPT_PURGE_SECS: Duration = Duration::from_secs(60);
+
+/// After each consecutive aborted purge, the purge interval is doubled, up to
+/// `2^MAX_TRANSCRIPT_PURGE_BACKOFF_EXPONENT` times the configured interval.
+const MAX_TRANSCRIPT_PURGE_BACKOFF_EXPONENT: u32 = 5;
+
+/// The subcomponents of [`IDkgImpl`], in the order they are scheduled by
+/// `on_state_change`.
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum IDkgSubcomponent {
+    PreSigner = 0,
+    Signer = 1,
+    ComplaintHandler = 2,
+}
 
 /// `IDkgImpl` is the consensus component responsible for processing threshold
 /// IDKG payloads.
@@ -255,7 +275,15 @@ pub struct IDkgImpl {
     consensus_block_cache: Arc<dyn ConsensusBlockCache>,
     crypto: Arc<dyn ConsensusCrypto>,
     schedule: RoundRobin,
+    /// If set, the subcomponents are called in this order on every
+    /// `on_state_change`, instead of following the round robin schedule.
+    #[cfg(test)]
+    fixed_schedule: Option<Vec<IDkgSubcomponent>>,
+    transcript_purge_interval: Duration,
     last_transcript_purge_ts: RefCell<Instant>,
+    aborted_transcript_purges: RefCell<u32>,
+    /// The height of the last finalized block reported in the progress metrics.
+    last_reported_height: RefCell<Option<Height>>,
     metrics: IDkgClientMetrics,
     logger: ReplicaLogger,
     #[cfg_attr(not(feature = "malicious_code"), allow(dead_code))]
@@ -263,12 +291,20 @@ pub struct IDkgImpl {
 }
 
 impl IDkgImpl {
-    /// Builds a new IDKG component
+    /// Builds a new IDKG component. With [`ThresholdSignerMode::ValidateOnly`],
+    /// the node validates signature shares of its peers but never creates its own.
+    /// Inactive transcripts are purged every `transcript_purge_interval` (see
+    /// [`INACTIVE_TRANSCRIPT_PURGE_SECS`]), backing off exponentially while
+    /// purges are aborted. If `max_transcript_loads_per_round` is set, the
+    /// pre-signer loads at most that many transcripts each time it sends dealings.
     pub fn new(
         node_id: NodeId,
         consensus_block_cache: Arc<dyn ConsensusBlockCache>,
         crypto: Arc<dyn ConsensusCrypto>,
         state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        signer_mode: ThresholdSignerMode,
+        transcript_purge_interval: Duration,
+        max_transcript_loads_per_round: Option<usize>,
         metrics_registry: MetricsRegistry,
         logger: ReplicaLogger,
         malicious_flags: MaliciousFlags,
@@ -277,6 +313,7 @@ impl IDkgImpl {
             node_id,
             consensus_block_cache.clone(),
             crypto.clone(),
+            max_transcript_loads_per_round,
             metrics_registry.clone(),
             logger.clone(),
         ));
@@ -285,6 +322,7 @@ impl IDkgImpl {
             consensus_block_cache.clone(),
             crypto.clone(),
             state_reader,
+            signer_mode,
             metrics_registry.clone(),
             logger.clone(),
         ));
@@ -302,14 +340,125 @@ impl IDkgImpl {
             crypto,
             consensus_block_cache,
             schedule: RoundRobin::default(),
+            #[cfg(test)]
+            fixed_schedule: None,
+            transcript_purge_interval,
             last_transcript_purge_ts: RefCell::new(Instant::now()),
+            aborted_transcript_purges: RefCell::new(0),
+            last_reported_height: RefCell::new(None),
             metrics: IDkgClientMetrics::new(metrics_registry),
             logger,
             malicious_flags,
         }
     }
 
+    /// Pins the order in which `on_state_change` calls the subcomponents. Like
+    /// the round robin schedule, the first non-empty change set is returned, but
+    /// every call starts again from the beginning of `order`.
+    #[cfg(test)]
+    pub(crate) fn set_fixed_schedule(&mut self, order: Vec<IDkgSubcomponent>) {
+        self.fixed_schedule = Some(order);
+    }
+
+    /// Calls the next subcomponent according to the schedule.
+    fn call_next(&self, calls: &[&dyn Fn() -> IDkgChangeSet; 3]) -> IDkgChangeSet {
+        #[cfg(test)]
+        if let Some(order) = &self.fixed_schedule {
+            return order
+                .iter()
+                .map(|subcomponent| calls[*subcomponent as usize]())
+                .find(|changeset| !changeset.is_empty())
+                .unwrap_or_default();
+        }
+        self.schedule.call_next(calls)
+    }
+
+    /// Returns the ids of the transcripts that are considered active as of the
+    /// finalized tip. Each id identifies the subnet and height the transcript
+    /// originates from, so the result can be compared against the source subnet
+    /// when debugging xnet key transfers.
+    pub fn active_transcript_ids(&self) -> BTreeSet<IDkgTranscriptId> {
+        let block_reader = IDkgBlockReaderImpl::new(self.consensus_block_cache.finalized_chain());
+        active_transcript_ids(&block_reader)
+    }
+
+    /// Returns, for every transcript requested in the finalized tip, how many
+    /// validated dealings and support shares are in the given pool compared to
+    /// how many are needed, so that stalled transcripts can be diagnosed. As
+    /// `IDkgImpl` is not `Sync`, a summary of this report is also published as
+    /// the `idkg_client_transcript_progress` gauges whenever the finalized tip
+    /// advances, which can be scraped from any thread.
+    pub fn idkg_progress(&self, idkg_pool: &dyn IDkgPool) -> IDkgProgressReport {
+        let block_reader = IDkgBlockReaderImpl::new(self.consensus_block_cache.finalized_chain());
+        progress::idkg_progress(&block_reader, idkg_pool)
+    }
+
+    /// Immediately purges the transcripts that are no longer active as of the
+    /// finalized tip (e.g., after a key rotation), instead of waiting for the
+    /// purge interval to elapse. The next periodic purge is rescheduled
+    /// accordingly.
+    pub fn purge_inactive_transcripts_now(&self) {
+        let block_reader = IDkgBlockReaderImpl::new(self.consensus_block_cache.finalized_chain());
+        timed_call(
+            "purge_inactive_transcripts",
+            || self.purge_inactive_transcripts(&block_reader),
+            &self.metrics.on_state_change_duration,
+        );
+        *self.last_transcript_purge_ts.borrow_mut() = Instant::now();
+    }
+
+    /// Returns the time to wait between two purges, which is doubled after every
+    /// consecutive aborted purge (up to a limit) and reset by a completed one.
+    fn transcript_purge_backoff(&self) -> Duration {
+        let exponent =
+            (*self.aborted_transcript_purges.borrow()).min(MAX_TRANSCRIPT_PURGE_BACKOFF_EXPONENT);
+        self.transcript_purge_interval.saturating_mul(1 << exponent)
+    }
+
+    /// Reports the transcripts created and the pre-signatures completed, by signature
+    /// scheme, in the blocks finalized since the last report. Blocks finalized before
+    /// the first report (e.g., before a restart) are not counted, except for the tip.
+    /// Also publishes the progress of the transcripts requested in the new tip.
+    fn report_finalized_progress(&self, idkg_pool: &dyn IDkgPool) {
+        let chain = self.consensus_block_cache.finalized_chain();
+        let tip_height = chain.tip().height();
+        let mut last_reported_height = self.last_reported_height.borrow_mut();
+        let from_height = match *last_reported_height {
+            Some(height) if height >= tip_height => return,
+            Some(height) => height.increment(),
+            None => tip_height,
+        };
+        let idkg_payload = |height: Height| {
+            chain
+                .get_block_by_height(height)
+                .ok()
+                .and_then(|block| block.payload.as_ref().as_idkg())
+        };
+        for height in from_height.get()..=tip_height.get() {
+            let height = Height::from(height);
+            if let Some(payload) = idkg_payload(height) {
+                let parent_payload = height
+                    .get()
+                    .checked_sub(1)
+                    .and_then(|parent_height| idkg_payload(Height::from(parent_height)));
+                self.metrics
+                    .report_finalized_payload(payload, parent_payload);
+            }
+        }
+        *last_reported_height = Some(tip_height);
+
+        let block_reader = IDkgBlockReaderImpl::new(chain);
+        self.metrics
+            .report_transcript_progress(&progress::idkg_progress(&block_reader, idkg_pool));
+    }
+
     /// Purges the transcripts that are no longer active.
+    ///
+    /// The purge is aborted if any active transcript ref can't be resolved: the
+    /// crypto component deletes the key material of every transcript it is not
+    /// given, so purging with a partial set could delete the keys of a transcript
+    /// that is still in use. Consecutive aborts delay the next purge according to
+    /// [`Self::transcript_purge_backoff`].
     fn purge_inactive_transcripts(&self, block_reader: &dyn IDkgBlockReader) {
         let mut active_transcripts = HashSet::new();
         let mut error_count = 0;
@@ -340,12 +489,16 @@ impl IDkgImpl {
         }
 
         if error_count > 0 {
+            *self.aborted_transcript_purges.borrow_mut() += 1;
             warn!(
                 self.logger,
-                "purge_inactive_transcripts(): abort due to {} errors", error_count,
+                "purge_inactive_transcripts(): abort due to {} errors, retrying in {:?}",
+                error_count,
+                self.transcript_purge_backoff(),
             );
             return;
         }
+        *self.aborted_transcript_purges.borrow_mut() = 0;
 
         match IDkgProtocol::retain_active_transcripts(&*self.crypto, &active_transcripts) {
             Err(IDkgRetainKeysError::TransientInternalError { internal_error }) => {
@@ -423,17 +576,12 @@ impl<T: IDkgPool> ChangeSetProducer<T> for IDkgImpl {
         };
 
         let calls: [&'_ dyn Fn() -> IDkgChangeSet; 3] = [&pre_signer, &signer, &complaint_handler];
-        let ret = self.schedule.call_next(&calls);
+        let ret = self.call_next(&calls);
 
-        if self.last_transcript_purge_ts.borrow().elapsed() >= INACTIVE_TRANSCRIPT_PURGE_SECS {
-            let block_reader =
-                IDkgBlockReaderImpl::new(self.consensus_block_cache.finalized_chain());
-            timed_call(
-                "purge_inactive_transcripts",
-                || self.purge_inactive_transcripts(&block_reader),
-                &metrics.on_state_change_duration,
-            );
-            *self.last_transcript_purge_ts.borrow_mut() = Instant::now();
+        self.report_finalized_progress(idkg_pool);
+
+        if self.last_transcript_purge_ts.borrow().elapsed() >= self.transcript_purge_backoff() {
+            self.purge_inactive_transcripts_now();
         }
         ret
     }
@@ -445,30 +593,65 @@ pub struct IDkgGossipImpl {
     subnet_id: SubnetId,
     consensus_block_cache: Arc<dyn ConsensusBlockCache>,
     state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+    look_ahead: u64,
+    /// The arguments of the last priority function, which are reused as long as
+    /// neither the finalized nor the certified height advances.
+    cached_priority_fn_args: Mutex<Option<Arc<IDkgPriorityFnArgs>>>,
     metrics: IDkgGossipMetrics,
 }
 
 impl IDkgGossipImpl {
-    /// Builds a new IDkgGossipImpl component
+    /// Builds a new IDkgGossipImpl component. Artifacts are fetched up to and
+    /// including `look_ahead` heights ahead (see [`DEFAULT_LOOK_AHEAD`]), and
+    /// stashed beyond that.
     pub fn new(
         subnet_id: SubnetId,
         consensus_block_cache: Arc<dyn ConsensusBlockCache>,
         state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        look_ahead: u64,
         metrics_registry: MetricsRegistry,
     ) -> Self {
         Self {
             subnet_id,
             consensus_block_cache,
             state_reader,
+            look_ahead,
+            cached_priority_fn_args: Mutex::new(None),
             metrics: IDkgGossipMetrics::new(metrics_registry),
         }
     }
+
+    /// Returns the arguments of the priority function, which are only rebuilt if
+    /// the finalized or the certified height changed since they were last built.
+    fn priority_fn_args(&self, block_reader: &dyn IDkgBlockReader) -> Arc<IDkgPriorityFnArgs> {
+        let finalized_height = block_reader.tip_height();
+        let certified_height = self.state_reader.latest_certified_height();
+        let mut cached_args = self.cached_priority_fn_args.lock().unwrap();
+        if let Some(args) = cached_args.as_ref() {
+            if args.finalized_height == finalized_height
+                && args.certified_height == certified_height
+            {
+                return Arc::clone(args);
+            }
+        }
+
+        let args = Arc::new(IDkgPriorityFnArgs::new(
+            block_reader,
+            self.state_reader.as_ref(),
+            &self.metrics,
+        ));
+        *cached_args = Some(Arc::clone(&args));
+        args
+    }
 }
 
 struct IDkgPriorityFnArgs {
     finalized_height: Height,
-    #[allow(dead_code)]
     certified_height: Height,
+    /// Whether a certified state snapshot was available. If not, the certified
+    /// height and the requested signatures are unknown, and signature shares
+    /// are fetched unconditionally.
+    certified_state_available: bool,
     requested_transcripts: BTreeSet<IDkgTranscriptId>,
     requested_signatures: BTreeSet<RequestId>,
     active_transcripts: BTreeSet<IDkgTranscriptId>,
@@ -478,20 +661,25 @@ impl IDkgPriorityFnArgs {
     fn new(
         block_reader: &dyn IDkgBlockReader,
         state_reader: &dyn StateReader<State = ReplicatedState>,
+        metrics: &IDkgGossipMetrics,
     ) -> Self {
         let mut requested_transcripts = BTreeSet::new();
         for params in block_reader.requested_transcripts() {
             requested_transcripts.insert(params.transcript_id);
         }
 
-        let mut active_transcripts = BTreeSet::new();
-        for transcript_ref in block_reader.active_transcripts() {
-            active_transcripts.insert(transcript_ref.transcript_id);
-        }
+        let active_transcripts = active_transcript_ids(block_reader);
 
-        let (certified_height, requested_signatures) = state_reader
-            .get_certified_state_snapshot()
-            .map_or(Default::default(), |snapshot| {
+        // A node that is still catching up may not have a certified state yet, in
+        // which case no signature requests are known. Record this, so that such a
+        // node can be told apart from one without any outstanding requests.
+        let snapshot = state_reader.get_certified_state_snapshot();
+        let certified_state_available = snapshot.is_some();
+        metrics
+            .certified_state_unavailable
+            .set(!certified_state_available as i64);
+        let (certified_height, requested_signatures) =
+            snapshot.map_or(Default::default(), |snapshot| {
                 let request_contexts = snapshot
                     .get_state()
                     .signature_request_contexts()
@@ -505,6 +693,7 @@ impl IDkgPriorityFnArgs {
         Self {
             finalized_height: block_reader.tip_height(),
             certified_height,
+            certified_state_available,
             requested_transcripts,
             requested_signatures,
             active_transcripts,
@@ -512,6 +701,14 @@ impl IDkgPriorityFnArgs {
     }
 }
 
+fn active_transcript_ids(block_reader: &dyn IDkgBlockReader) -> BTreeSet<IDkgTranscriptId> {
+    block_reader
+        .active_transcripts()
+        .into_iter()
+        .map(|transcript_ref| transcript_ref.transcript_id)
+        .collect()
+}
+
 impl<Pool: IDkgPool> PriorityFnFactory<IDkgMessage, Pool> for IDkgGossipImpl {
     fn get_priority_function(
         &self,
@@ -519,10 +716,11 @@ impl<Pool: IDkgPool> PriorityFnFactory<IDkgMessage, Pool> for IDkgGossipImpl {
     ) -> PriorityFn<IDkgMessageId, IDkgMessageAttribute> {
         let block_reader = IDkgBlockReaderImpl::new(self.consensus_block_cache.finalized_chain());
         let subnet_id = self.subnet_id;
-        let args = IDkgPriorityFnArgs::new(&block_reader, self.state_reader.as_ref());
+        let look_ahead = self.look_ahead;
+        let args = self.priority_fn_args(&block_reader);
         let metrics = self.metrics.clone();
         Box::new(move |_, attr: &'_ IDkgMessageAttribute| {
-            compute_priority(attr, subnet_id, &args, &metrics)
+            compute_priority(attr, subnet_id, look_ahead, &args, &metrics)
         })
     }
 }
@@ -530,9 +728,38 @@ impl<Pool: IDkgPool> PriorityFnFactory<IDkgMessage, Pool> for IDkgGossipImpl {
 fn compute_priority(
     attr: &IDkgMessageAttribute,
     subnet_id: SubnetId,
+    look_ahead: u64,
     args: &IDkgPriorityFnArgs,
     metrics: &IDkgGossipMetrics,
 ) -> Priority {
+    let priority = compute_priority_unrecorded(attr, subnet_id, look_ahead, args);
+    if !args.certified_state_available
+        && matches!(
+            attr,
+            IDkgMessageAttribute::EcdsaSigShare(_) | IDkgMessageAttribute::SchnorrSigShare(_)
+        )
+    {
+        metrics
+            .adverts_without_certified_state
+            .with_label_values(&[attr.as_str()])
+            .inc();
+    }
+    let adverts = match priority {
+        Priority::Drop => &metrics.dropped_adverts,
+        Priority::Stash => &metrics.stashed_adverts,
+        Priority::FetchNow => &metrics.fetched_adverts,
+    };
+    adverts.with_label_values(&[attr.as_str()]).inc();
+    priority
+}
+
+fn compute_priority_unrecorded(
+    attr: &IDkgMessageAttribute,
+    subnet_id: SubnetId,
+    look_ahead: u64,
+    args: &IDkgPriorityFnArgs,
+) -> Priority {
+    let look_ahead = Height::from(look_ahead);
     match attr {
         IDkgMessageAttribute::Dealing(transcript_id)
         | IDkgMessageAttribute::DealingSupport(transcript_id) => {
@@ -548,13 +775,9 @@ fn compute_priority(
                 if args.requested_transcripts.contains(transcript_id) {
                     Priority::FetchNow
                 } else {
-                    metrics
-                        .dropped_adverts
-                        .with_label_values(&[attr.as_str()])
-                        .inc();
                     Priority::Drop
                 }
-            } else if height < args.finalized_height + Height::from(LOOK_AHEAD) {
+            } else if height <= args.finalized_height + look_ahead {
                 Priority::FetchNow
             } else {
                 Priority::Stash
@@ -562,21 +785,7 @@ fn compute_priority(
         }
         IDkgMessageAttribute::EcdsaSigShare(request_id)
         | IDkgMessageAttribute::SchnorrSigShare(request_id) => {
-            if request_id.height <= args.certified_height {
-                if args.requested_signatures.contains(request_id) {
-                    Priority::FetchNow
-                } else {
-                    metrics
-                        .dropped_adverts
-                        .with_label_values(&[attr.as_str()])
-                        .inc();
-                    Priority::Drop
-                }
-            } else if request_id.height < args.certified_height + Height::from(LOOK_AHEAD) {
-                Priority::FetchNow
-            } else {
-                Priority::Stash
-            }
+            compute_sig_share_priority(request_id, look_ahead, args)
         }
         IDkgMessageAttribute::Complaint(transcript_id)
         | IDkgMessageAttribute::Opening(transcript_id) => {
@@ -587,13 +796,9 @@ fn compute_priority(
                 {
                     Priority::FetchNow
                 } else {
-                    metrics
-                        .dropped_adverts
-                        .with_label_values(&[attr.as_str()])
-                        .inc();
                     Priority::Drop
                 }
-            } else if height < args.finalized_height + Height::from(LOOK_AHEAD) {
+            } else if height <= args.finalized_height + look_ahead {
                 Priority::FetchNow
             } else {
                 Priority::Stash
@@ -602,17 +807,47 @@ fn compute_priority(
     }
 }
 
+/// Computes the priority of a signature share, independently of its signature
+/// scheme. Signature shares for a new scheme should be handled by delegating
+/// to this function, so they share the same fetch/stash/drop behavior.
+///
+/// Without a certified state, the height of a signature share cannot be compared
+/// against the certified height, so the share is conservatively fetched.
+fn compute_sig_share_priority(
+    request_id: &RequestId,
+    look_ahead: Height,
+    args: &IDkgPriorityFnArgs,
+) -> Priority {
+    if !args.certified_state_available {
+        Priority::FetchNow
+    } else if request_id.height <= args.certified_height {
+        if args.requested_signatures.contains(request_id) {
+            Priority::FetchNow
+        } else {
+            Priority::Drop
+        }
+    } else if request_id.height <= args.certified_height + look_ahead {
+        Priority::FetchNow
+    } else {
+        Priority::Stash
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use self::test_utils::{
-        fake_completed_signature_request_context, fake_signature_request_context_with_pre_sig,
-        fake_state_with_signature_requests, TestIDkgBlockReader,
+        create_available_pre_signature, empty_idkg_payload,
+        fake_completed_signature_request_context, fake_schnorr_master_public_key_id,
+        fake_signature_request_context_with_pre_sig, fake_state_with_signature_requests,
+        TestIDkgBlockReader,
     };
 
     use super::*;
+    use crate::idkg::complaints::{IDkgTranscriptLoader, TranscriptLoadStatus};
+    use ic_management_canister_types::SchnorrAlgorithm;
     use ic_test_utilities::state_manager::RefMockStateManager;
-    use ic_types::consensus::idkg::{IDkgUIDGenerator, PreSigId};
-    use ic_types::crypto::canister_threshold_sig::idkg::IDkgTranscriptId;
+    use ic_types::consensus::idkg::{IDkgUIDGenerator, PreSigId, TranscriptRef};
+    use ic_types::crypto::canister_threshold_sig::idkg::{IDkgTranscript, IDkgTranscriptId};
     use ic_types::{consensus::idkg::RequestId, PrincipalId, SubnetId};
     use test_utils::fake_ecdsa_master_public_key_id;
     use tests::test_utils::create_sig_inputs;
@@ -650,7 +885,10 @@ mod tests {
         );
 
         // Only the context with matched quadruple should be in "requested"
-        let args = IDkgPriorityFnArgs::new(&block_reader, state_manager.as_ref());
+        let metrics = IDkgGossipMetrics::new(MetricsRegistry::new());
+        let args = IDkgPriorityFnArgs::new(&block_reader, state_manager.as_ref(), &metrics);
+        assert_eq!(metrics.certified_state_unavailable.get(), 0);
+        assert!(args.certified_state_available);
         assert_eq!(args.certified_height, height);
         assert_eq!(args.requested_signatures.len(), 1);
         assert_eq!(
@@ -659,6 +897,462 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_idkg_priority_fn_args_without_certified_state() {
+        let state_manager = Arc::new(RefMockStateManager::default());
+        state_manager
+            .get_mut()
+            .expect_get_certified_state_snapshot()
+            .returning(|| None);
+        let height = Height::from(100);
+        let key_id = fake_ecdsa_master_public_key_id();
+        let request_id = RequestId {
+            pre_signature_id: PreSigId(0),
+            pseudo_random_id: [0; 32],
+            height,
+        };
+        let block_reader = TestIDkgBlockReader::for_signer_test(
+            height,
+            vec![(request_id, create_sig_inputs(0, &key_id))],
+        );
+
+        let metrics = IDkgGossipMetrics::new(MetricsRegistry::new());
+        let args = IDkgPriorityFnArgs::new(&block_reader, state_manager.as_ref(), &metrics);
+        assert_eq!(metrics.certified_state_unavailable.get(), 1);
+        assert!(!args.certified_state_available);
+        assert_eq!(args.certified_height, Height::from(0));
+        assert!(args.requested_signatures.is_empty());
+
+        // Signature shares are fetched regardless of their height, while other
+        // artifacts are still prioritized by the finalized height.
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+        for request_height in [10, 100, 200] {
+            let request_id = RequestId {
+                pre_signature_id: PreSigId(1),
+                pseudo_random_id: [1; 32],
+                height: Height::from(request_height),
+            };
+            for attr in [
+                IDkgMessageAttribute::EcdsaSigShare(request_id.clone()),
+                IDkgMessageAttribute::SchnorrSigShare(request_id),
+            ] {
+                assert_eq!(
+                    compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
+                    Priority::FetchNow
+                );
+            }
+        }
+        let transcript_id = IDkgTranscriptId::new(subnet_id, 1, Height::from(200));
+        assert_eq!(
+            compute_priority(
+                &IDkgMessageAttribute::Dealing(transcript_id),
+                subnet_id,
+                DEFAULT_LOOK_AHEAD,
+                &args,
+                &metrics
+            ),
+            Priority::Stash
+        );
+        for label in ["ecdsa_sig_share", "schnorr_sig_share"] {
+            assert_eq!(
+                metrics
+                    .adverts_without_certified_state
+                    .with_label_values(&[label])
+                    .get(),
+                3
+            );
+        }
+        assert_eq!(
+            metrics
+                .adverts_without_certified_state
+                .with_label_values(&["signed_dealing"])
+                .get(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_active_transcript_ids() {
+        let xnet_subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(2));
+        let key_id = fake_ecdsa_master_public_key_id();
+        let local_ids = [
+            IDkgTranscriptId::new(subnet_id, 1, Height::from(10)),
+            IDkgTranscriptId::new(subnet_id, 2, Height::from(20)),
+        ];
+        let xnet_ids = [
+            IDkgTranscriptId::new(xnet_subnet_id, 1, Height::from(10)),
+            IDkgTranscriptId::new(xnet_subnet_id, 3, Height::from(30)),
+        ];
+        let active_refs = local_ids
+            .iter()
+            .chain(xnet_ids.iter())
+            .map(|id| TranscriptRef::new(id.source_height(), *id))
+            .collect();
+        let block_reader =
+            TestIDkgBlockReader::for_complainer_test(&key_id, Height::from(100), active_refs);
+
+        let ids = active_transcript_ids(&block_reader);
+        assert_eq!(ids.len(), 4);
+        for id in local_ids.iter().chain(xnet_ids.iter()) {
+            assert!(ids.contains(id));
+        }
+        assert_eq!(
+            ids.iter()
+                .filter(|id| *id.source_subnet() == xnet_subnet_id)
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_report_finalized_payload_by_scheme() {
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+        let metrics = IDkgClientMetrics::new(MetricsRegistry::new());
+        let pre_signatures_completed = |scheme| {
+            metrics
+                .pre_signatures_completed
+                .with_label_values(&[scheme])
+                .get()
+        };
+        let transcripts_created = |scheme| {
+            metrics
+                .transcripts_created
+                .with_label_values(&[scheme])
+                .get()
+        };
+
+        // An ECDSA pre-signature consists of four transcripts (plus the key transcript).
+        let mut parent_payload = empty_idkg_payload(subnet_id);
+        create_available_pre_signature(&mut parent_payload, fake_ecdsa_master_public_key_id(), 1);
+        metrics.report_finalized_payload(&parent_payload, None);
+        assert_eq!(pre_signatures_completed("ecdsa"), 1);
+        assert_eq!(transcripts_created("ecdsa"), 5);
+        assert_eq!(pre_signatures_completed("schnorr"), 0);
+        assert_eq!(transcripts_created("schnorr"), 0);
+
+        // A Schnorr pre-signature consists of one blinder (plus the key transcript).
+        // The ECDSA pre-signature that was already available is not counted again.
+        let mut payload = parent_payload.clone();
+        payload.idkg_transcripts.clear();
+        create_available_pre_signature(
+            &mut payload,
+            fake_schnorr_master_public_key_id(SchnorrAlgorithm::Bip340Secp256k1),
+            2,
+        );
+        metrics.report_finalized_payload(&payload, Some(&parent_payload));
+        assert_eq!(pre_signatures_completed("ecdsa"), 1);
+        assert_eq!(transcripts_created("ecdsa"), 5);
+        assert_eq!(pre_signatures_completed("schnorr"), 1);
+        assert_eq!(transcripts_created("schnorr"), 2);
+        assert_eq!(transcripts_created(""), 0);
+    }
+
+    #[test]
+    fn test_report_transcript_progress() {
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+        let metrics = IDkgClientMetrics::new(MetricsRegistry::new());
+        let transcript_progress = |id, dealings, supported_dealings| IDkgTranscriptProgress {
+            transcript_id: IDkgTranscriptId::new(subnet_id, id, Height::from(100)),
+            dealings,
+            dealings_needed: 2,
+            support_shares: 3 * supported_dealings,
+            support_shares_needed_per_dealing: 3,
+            supported_dealings,
+        };
+        let report = IDkgProgressReport {
+            finalized_height: Height::from(100),
+            transcripts: vec![
+                transcript_progress(1, 1, 1),
+                transcript_progress(2, 2, 1),
+                transcript_progress(3, 3, 1),
+                transcript_progress(4, 2, 2),
+            ],
+            unresolved_transcripts: 1,
+        };
+
+        metrics.report_transcript_progress(&report);
+        let transcripts = |state| {
+            metrics
+                .transcript_progress
+                .with_label_values(&[state])
+                .get()
+        };
+        assert_eq!(transcripts("blocked_on_dealings"), 1);
+        assert_eq!(transcripts("blocked_on_support"), 2);
+        assert_eq!(transcripts("supported"), 1);
+        assert_eq!(transcripts("unresolved"), 1);
+
+        // The gauges reflect the latest report only.
+        metrics.report_transcript_progress(&IDkgProgressReport::default());
+        assert_eq!(transcripts("blocked_on_support"), 0);
+        assert_eq!(transcripts("unresolved"), 0);
+    }
+
+    #[test]
+    fn test_purge_inactive_transcripts_retains_active_transcripts() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            let ic_consensus_mocks::Dependencies {
+                pool,
+                crypto,
+                state_manager,
+                ..
+            } = ic_consensus_mocks::dependencies(pool_config, 1);
+            let idkg = IDkgImpl::new(
+                ic_test_utilities_types::ids::NODE_1,
+                pool.get_block_cache(),
+                crypto.clone(),
+                state_manager,
+                ThresholdSignerMode::Full,
+                INACTIVE_TRANSCRIPT_PURGE_SECS,
+                None,
+                MetricsRegistry::new(),
+                ic_logger::replica_logger::no_op_logger(),
+                MaliciousFlags::default(),
+            );
+
+            // There are no active transcripts in the finalized chain yet.
+            idkg.purge_inactive_transcripts_now();
+            assert_eq!(
+                *crypto.retained_idkg_transcripts.read().unwrap(),
+                vec![HashSet::new()]
+            );
+
+            // Once a transcript becomes active, exactly that transcript is retained.
+            let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+            let transcript_id = IDkgTranscriptId::new(subnet_id, 1, Height::from(10));
+            let block_reader = TestIDkgBlockReader::for_complainer_test(
+                &fake_ecdsa_master_public_key_id(),
+                Height::from(100),
+                vec![TranscriptRef::new(Height::from(10), transcript_id)],
+            );
+            idkg.purge_inactive_transcripts(&block_reader);
+            assert_eq!(
+                *crypto.retained_idkg_transcripts.read().unwrap(),
+                vec![HashSet::new(), HashSet::from([transcript_id])]
+            );
+        })
+    }
+
+    #[test]
+    fn test_purge_inactive_transcripts_backs_off_while_aborted() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            let ic_consensus_mocks::Dependencies {
+                pool,
+                crypto,
+                state_manager,
+                ..
+            } = ic_consensus_mocks::dependencies(pool_config, 1);
+            let idkg = IDkgImpl::new(
+                ic_test_utilities_types::ids::NODE_1,
+                pool.get_block_cache(),
+                crypto.clone(),
+                state_manager,
+                ThresholdSignerMode::Full,
+                INACTIVE_TRANSCRIPT_PURGE_SECS,
+                None,
+                MetricsRegistry::new(),
+                ic_logger::replica_logger::no_op_logger(),
+                MaliciousFlags::default(),
+            );
+            assert_eq!(
+                idkg.transcript_purge_backoff(),
+                INACTIVE_TRANSCRIPT_PURGE_SECS
+            );
+
+            let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(1));
+            let transcript_id = IDkgTranscriptId::new(subnet_id, 1, Height::from(10));
+            let active_refs = vec![TranscriptRef::new(Height::from(10), transcript_id)];
+            let failing_block_reader = TestIDkgBlockReader::for_complainer_test(
+                &fake_ecdsa_master_public_key_id(),
+                Height::from(100),
+                active_refs.clone(),
+            )
+            .with_fail_to_resolve();
+
+            // Aborted purges don't retain anything and double the backoff, up to a limit.
+            for aborts in 1..=MAX_TRANSCRIPT_PURGE_BACKOFF_EXPONENT + 2 {
+                idkg.purge_inactive_transcripts(&failing_block_reader);
+                let exponent = aborts.min(MAX_TRANSCRIPT_PURGE_BACKOFF_EXPONENT);
+                assert_eq!(
+                    idkg.transcript_purge_backoff(),
+                    INACTIVE_TRANSCRIPT_PURGE_SECS * (1 << exponent)
+                );
+            }
+            assert!(crypto.retained_idkg_transcripts.read().unwrap().is_empty());
+
+            // A completed purge resets the backoff.
+            let block_reader = TestIDkgBlockReader::for_complainer_test(
+                &fake_ecdsa_master_public_key_id(),
+                Height::from(100),
+                active_refs,
+            );
+            idkg.purge_inactive_transcripts(&block_reader);
+            assert_eq!(
+                *crypto.retained_idkg_transcripts.read().unwrap(),
+                vec![HashSet::from([transcript_id])]
+            );
+            assert_eq!(
+                idkg.transcript_purge_backoff(),
+                INACTIVE_TRANSCRIPT_PURGE_SECS
+            );
+        })
+    }
+
+    /// Records the subcomponents called by `on_state_change`.
+    #[derive(Clone, Default)]
+    struct CallLog(Arc<Mutex<Vec<IDkgSubcomponent>>>);
+
+    impl CallLog {
+        fn record(&self, subcomponent: IDkgSubcomponent) -> IDkgChangeSet {
+            self.0.lock().unwrap().push(subcomponent);
+            vec![]
+        }
+
+        fn take(&self) -> Vec<IDkgSubcomponent> {
+            std::mem::take(&mut self.0.lock().unwrap())
+        }
+    }
+
+    impl ThresholdSigner for CallLog {
+        fn on_state_change(
+            &self,
+            _idkg_pool: &dyn IDkgPool,
+            _transcript_loader: &dyn IDkgTranscriptLoader,
+        ) -> IDkgChangeSet {
+            self.record(IDkgSubcomponent::Signer)
+        }
+    }
+
+    impl IDkgComplaintHandler for CallLog {
+        fn on_state_change(&self, _idkg_pool: &dyn IDkgPool) -> IDkgChangeSet {
+            self.record(IDkgSubcomponent::ComplaintHandler)
+        }
+
+        fn as_transcript_loader(&self) -> &dyn IDkgTranscriptLoader {
+            self
+        }
+    }
+
+    impl IDkgTranscriptLoader for CallLog {
+        fn load_transcript(
+            &self,
+            _idkg_pool: &dyn IDkgPool,
+            _transcript: &IDkgTranscript,
+        ) -> TranscriptLoadStatus {
+            TranscriptLoadStatus::Success
+        }
+    }
+
+    #[test]
+    fn test_on_state_change_with_fixed_schedule() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            let ic_consensus_mocks::Dependencies {
+                pool,
+                crypto,
+                state_manager,
+                ..
+            } = ic_consensus_mocks::dependencies(pool_config.clone(), 1);
+            let mut idkg = IDkgImpl::new(
+                ic_test_utilities_types::ids::NODE_1,
+                pool.get_block_cache(),
+                crypto,
+                state_manager,
+                ThresholdSignerMode::Full,
+                INACTIVE_TRANSCRIPT_PURGE_SECS,
+                None,
+                MetricsRegistry::new(),
+                ic_logger::replica_logger::no_op_logger(),
+                MaliciousFlags::default(),
+            );
+            let call_log = CallLog::default();
+            idkg.signer = Box::new(call_log.clone());
+            idkg.complaint_handler = Box::new(call_log.clone());
+            let idkg_pool = test_utils::create_idkg_pool(
+                pool_config,
+                ic_logger::replica_logger::no_op_logger(),
+                MetricsRegistry::new(),
+            );
+
+            // By default, the round robin schedule starts with the pre-signer.
+            assert!(idkg.on_state_change(&idkg_pool).is_empty());
+            assert_eq!(
+                call_log.take(),
+                vec![IDkgSubcomponent::Signer, IDkgSubcomponent::ComplaintHandler]
+            );
+
+            // With a fixed schedule, every call follows the same order.
+            idkg.set_fixed_schedule(vec![
+                IDkgSubcomponent::ComplaintHandler,
+                IDkgSubcomponent::PreSigner,
+                IDkgSubcomponent::Signer,
+            ]);
+            for _ in 0..3 {
+                assert!(idkg.on_state_change(&idkg_pool).is_empty());
+                assert_eq!(
+                    call_log.take(),
+                    vec![IDkgSubcomponent::ComplaintHandler, IDkgSubcomponent::Signer]
+                );
+            }
+
+            // Subcomponents missing from the schedule are never called.
+            idkg.set_fixed_schedule(vec![IDkgSubcomponent::Signer]);
+            assert!(idkg.on_state_change(&idkg_pool).is_empty());
+            assert_eq!(call_log.take(), vec![IDkgSubcomponent::Signer]);
+        })
+    }
+
+    #[test]
+    fn test_idkg_priority_fn_args_are_cached_until_heights_advance() {
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            let ic_consensus_mocks::Dependencies { pool, .. } =
+                ic_consensus_mocks::dependencies(pool_config, 1);
+            let state_manager = Arc::new(RefMockStateManager::default());
+            let certified_height = Arc::new(Mutex::new(Height::from(100)));
+            let latest_certified_height = Arc::clone(&certified_height);
+            state_manager
+                .get_mut()
+                .expect_latest_certified_height()
+                .returning(move || *latest_certified_height.lock().unwrap());
+            let snapshot_height = Arc::clone(&certified_height);
+            state_manager
+                .get_mut()
+                .expect_get_certified_state_snapshot()
+                // Once initially, once for each advanced height.
+                .times(3)
+                .returning(move || {
+                    let snapshot =
+                        fake_state_with_signature_requests(*snapshot_height.lock().unwrap(), []);
+                    Some(Box::new(snapshot) as Box<_>)
+                });
+            let gossip = IDkgGossipImpl::new(
+                SubnetId::from(PrincipalId::new_subnet_test_id(1)),
+                pool.get_block_cache(),
+                state_manager,
+                DEFAULT_LOOK_AHEAD,
+                MetricsRegistry::new(),
+            );
+
+            let block_reader = TestIDkgBlockReader::for_signer_test(Height::from(100), vec![]);
+            let args = gossip.priority_fn_args(&block_reader);
+            assert_eq!(args.finalized_height, Height::from(100));
+            assert_eq!(args.certified_height, Height::from(100));
+            assert!(Arc::ptr_eq(&args, &gossip.priority_fn_args(&block_reader)));
+
+            // The certified height advances.
+            *certified_height.lock().unwrap() = Height::from(101);
+            let args = gossip.priority_fn_args(&block_reader);
+            assert_eq!(args.certified_height, Height::from(101));
+            assert!(Arc::ptr_eq(&args, &gossip.priority_fn_args(&block_reader)));
+
+            // The finalized height advances.
+            let block_reader = TestIDkgBlockReader::for_signer_test(Height::from(101), vec![]);
+            let args = gossip.priority_fn_args(&block_reader);
+            assert_eq!(args.finalized_height, Height::from(101));
+            assert!(Arc::ptr_eq(&args, &gossip.priority_fn_args(&block_reader)));
+        })
+    }
+
     // Tests the priority computation for dealings/support.
     #[test]
     fn test_idkg_priority_fn_dealing_support() {
@@ -678,6 +1372,7 @@ mod tests {
         let args = IDkgPriorityFnArgs {
             finalized_height: Height::from(100),
             certified_height: Height::from(100),
+            certified_state_available: true,
             requested_transcripts,
             requested_signatures: BTreeSet::new(),
             active_transcripts: BTreeSet::new(),
@@ -730,7 +1425,141 @@ mod tests {
 
         for (attr, expected) in tests {
             assert_eq!(
-                compute_priority(&attr, subnet_id, &args, &metrics),
+                compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
+                expected
+            );
+        }
+    }
+
+    // Tests that a larger look-ahead fetches artifacts that would be stashed otherwise.
+    #[test]
+    fn test_idkg_priority_fn_look_ahead() {
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(2));
+        let transcript_id = IDkgTranscriptId::new(subnet_id, 1, Height::from(115));
+
+        let metrics_registry = MetricsRegistry::new();
+        let metrics = IDkgGossipMetrics::new(metrics_registry);
+
+        let args = IDkgPriorityFnArgs {
+            finalized_height: Height::from(100),
+            certified_height: Height::from(100),
+            certified_state_available: true,
+            requested_transcripts: BTreeSet::new(),
+            requested_signatures: BTreeSet::new(),
+            active_transcripts: BTreeSet::new(),
+        };
+
+        for attr in [
+            IDkgMessageAttribute::Dealing(transcript_id),
+            IDkgMessageAttribute::DealingSupport(transcript_id),
+        ] {
+            assert_eq!(
+                compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
+                Priority::Stash
+            );
+            assert_eq!(
+                compute_priority(&attr, subnet_id, 20, &args, &metrics),
+                Priority::FetchNow
+            );
+            for adverts in [&metrics.stashed_adverts, &metrics.fetched_adverts] {
+                assert_eq!(adverts.with_label_values(&[attr.as_str()]).get(), 1);
+            }
+            assert_eq!(
+                metrics
+                    .dropped_adverts
+                    .with_label_values(&[attr.as_str()])
+                    .get(),
+                0
+            );
+        }
+    }
+
+    // Tests that artifacts at exactly the look-ahead boundary are fetched, and
+    // artifacts one height beyond it are stashed.
+    #[test]
+    fn test_idkg_priority_fn_look_ahead_boundary() {
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(2));
+        let boundary = Height::from(100 + DEFAULT_LOOK_AHEAD);
+        let transcript_id_fetch = IDkgTranscriptId::new(subnet_id, 1, boundary);
+        let transcript_id_stash = IDkgTranscriptId::new(subnet_id, 2, boundary.increment());
+        let mut uid_generator = IDkgUIDGenerator::new(subnet_id, Height::new(0));
+        let request_id_fetch = RequestId {
+            pre_signature_id: uid_generator.next_pre_signature_id(),
+            pseudo_random_id: [1; 32],
+            height: boundary,
+        };
+        let request_id_stash = RequestId {
+            pre_signature_id: uid_generator.next_pre_signature_id(),
+            pseudo_random_id: [2; 32],
+            height: boundary.increment(),
+        };
+
+        let metrics_registry = MetricsRegistry::new();
+        let metrics = IDkgGossipMetrics::new(metrics_registry);
+
+        let args = IDkgPriorityFnArgs {
+            finalized_height: Height::from(100),
+            certified_height: Height::from(100),
+            certified_state_available: true,
+            requested_transcripts: BTreeSet::new(),
+            requested_signatures: BTreeSet::new(),
+            active_transcripts: BTreeSet::new(),
+        };
+
+        let tests = vec![
+            (
+                IDkgMessageAttribute::Dealing(transcript_id_fetch),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::Dealing(transcript_id_stash),
+                Priority::Stash,
+            ),
+            (
+                IDkgMessageAttribute::DealingSupport(transcript_id_fetch),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::DealingSupport(transcript_id_stash),
+                Priority::Stash,
+            ),
+            (
+                IDkgMessageAttribute::EcdsaSigShare(request_id_fetch.clone()),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::EcdsaSigShare(request_id_stash.clone()),
+                Priority::Stash,
+            ),
+            (
+                IDkgMessageAttribute::SchnorrSigShare(request_id_fetch),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::SchnorrSigShare(request_id_stash),
+                Priority::Stash,
+            ),
+            (
+                IDkgMessageAttribute::Complaint(transcript_id_fetch),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::Complaint(transcript_id_stash),
+                Priority::Stash,
+            ),
+            (
+                IDkgMessageAttribute::Opening(transcript_id_fetch),
+                Priority::FetchNow,
+            ),
+            (
+                IDkgMessageAttribute::Opening(transcript_id_stash),
+                Priority::Stash,
+            ),
+        ];
+
+        for (attr, expected) in tests {
+            assert_eq!(
+                compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
                 expected
             );
         }
@@ -770,6 +1599,7 @@ mod tests {
         let args = IDkgPriorityFnArgs {
             finalized_height: Height::from(100),
             certified_height: Height::from(100),
+            certified_state_available: true,
             requested_transcripts: BTreeSet::new(),
             requested_signatures,
             active_transcripts: BTreeSet::new(),
@@ -812,12 +1642,52 @@ mod tests {
 
         for (attr, expected) in tests {
             assert_eq!(
-                compute_priority(&attr, subnet_id, &args, &metrics),
+                compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
                 expected
             );
         }
     }
 
+    // Tests that sig shares of all schemes share the same priority computation.
+    #[test]
+    fn test_idkg_priority_fn_sig_shares_agree_across_schemes() {
+        let subnet_id = SubnetId::from(PrincipalId::new_subnet_test_id(2));
+        let mut uid_generator = IDkgUIDGenerator::new(subnet_id, Height::new(0));
+        let metrics_registry = MetricsRegistry::new();
+        let metrics = IDkgGossipMetrics::new(metrics_registry);
+
+        let request_ids: Vec<_> = [80, 100, 110, 111, 200]
+            .into_iter()
+            .map(|height| RequestId {
+                pre_signature_id: uid_generator.next_pre_signature_id(),
+                pseudo_random_id: [height as u8; 32],
+                height: Height::from(height),
+            })
+            .collect();
+        let args = IDkgPriorityFnArgs {
+            finalized_height: Height::from(100),
+            certified_height: Height::from(100),
+            certified_state_available: true,
+            requested_transcripts: BTreeSet::new(),
+            requested_signatures: BTreeSet::from([request_ids[0].clone()]),
+            active_transcripts: BTreeSet::new(),
+        };
+
+        for request_id in request_ids {
+            let expected =
+                compute_sig_share_priority(&request_id, Height::from(DEFAULT_LOOK_AHEAD), &args);
+            for attr in [
+                IDkgMessageAttribute::EcdsaSigShare(request_id.clone()),
+                IDkgMessageAttribute::SchnorrSigShare(request_id.clone()),
+            ] {
+                assert_eq!(
+                    compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
+                    expected
+                );
+            }
+        }
+    }
+
     // Tests the priority computation for complaints/openings.
     #[test]
     fn test_idkg_priority_fn_complaint_opening() {
@@ -838,6 +1708,7 @@ mod tests {
         let args = IDkgPriorityFnArgs {
             finalized_height: Height::from(100),
             certified_height: Height::from(100),
+            certified_state_available: true,
             requested_transcripts,
             requested_signatures: BTreeSet::new(),
             active_transcripts,
@@ -890,7 +1761,7 @@ mod tests {
 
         for (attr, expected) in tests {
             assert_eq!(
-                compute_priority(&attr, subnet_id, &args, &metrics),
+                compute_priority(&attr, subnet_id, DEFAULT_LOOK_AHEAD, &args, &metrics),
                 expected
             );
         }
@@ -1,6 +1,6 @@
 //! The complaint handling
 
-use crate::idkg::metrics::{timed_call, IDkgComplaintMetrics};
+use crate::idkg::metrics::{timed_call, ComplaintAuthor, IDkgComplaintMetrics};
 use crate::idkg::utils::IDkgBlockReaderImpl;
 
 use ic_consensus_utils::crypto::ConsensusCrypto;
@@ -8,7 +8,7 @@ use ic_consensus_utils::RoundRobin;
 use ic_interfaces::consensus_pool::ConsensusBlockCache;
 use ic_interfaces::crypto::{ErrorReproducibility, IDkgProtocol};
 use ic_interfaces::idkg::{IDkgChangeAction, IDkgChangeSet, IDkgPool};
-use ic_logger::{debug, warn, ReplicaLogger};
+use ic_logger::{debug, info, warn, ReplicaLogger};
 use ic_metrics::MetricsRegistry;
 use ic_types::artifact::IDkgMessageId;
 use ic_types::consensus::idkg::{
@@ -405,6 +405,14 @@ impl IDkgComplaintHandlerImpl {
             Ok(signature) => {
                 let signed_complaint = SignedIDkgComplaint { content, signature };
                 self.metrics.complaint_metrics_inc("complaints_sent");
+                self.metrics
+                    .complaints_by_author_inc(ComplaintAuthor::Local);
+                info!(
+                    self.log,
+                    "Issued complaint: transcript_id: {:?}, dealer_id: {:?}",
+                    signed_complaint.content.idkg_complaint.transcript_id,
+                    signed_complaint.content.idkg_complaint.dealer_id,
+                );
                 Some(signed_complaint)
             }
             Err(err) => {
@@ -488,6 +496,17 @@ impl IDkgComplaintHandlerImpl {
             }
             Ok(()) => {
                 self.metrics.complaint_metrics_inc("complaint_received");
+                // Complaints issued by this node were already counted when they were created.
+                if signed_complaint.signature.signer != self.node_id {
+                    self.metrics.complaints_by_author_inc(ComplaintAuthor::Peer);
+                }
+                info!(
+                    self.log,
+                    "Validated complaint: transcript_id: {:?}, dealer_id: {:?}, complainer_id: {:?}",
+                    complaint.idkg_complaint.transcript_id,
+                    complaint.idkg_complaint.dealer_id,
+                    signed_complaint.signature.signer,
+                );
                 Some(IDkgChangeAction::MoveToValidated(IDkgMessage::Complaint(
                     signed_complaint,
                 )))
@@ -1067,6 +1086,64 @@ mod tests {
         })
     }
 
+    // Tests that complaints are counted by author
+    #[test]
+    fn test_complaints_by_author_metrics() {
+        let key_id = fake_ecdsa_master_public_key_id();
+        let id = create_transcript_id_with_height(1, Height::from(30));
+        let block_reader = TestIDkgBlockReader::for_complainer_test(
+            &key_id,
+            Height::new(100),
+            vec![TranscriptRef::new(Height::new(10), id)],
+        );
+
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|logger| {
+                let (mut idkg_pool, complaint_handler) =
+                    create_complaint_dependencies(pool_config, logger);
+                let count = |author: ComplaintAuthor| {
+                    complaint_handler
+                        .metrics
+                        .complaints_by_author
+                        .with_label_values(&[author.as_str()])
+                        .get()
+                };
+
+                // Create a local complaint
+                let local_complaint = create_complaint(id, NODE_2, NODE_1);
+                assert!(complaint_handler
+                    .crypto_create_complaint(
+                        local_complaint.content.idkg_complaint.clone(),
+                        RegistryVersion::from(1),
+                    )
+                    .is_some());
+                assert_eq!(count(ComplaintAuthor::Local), 1);
+                assert_eq!(count(ComplaintAuthor::Peer), 0);
+
+                // Validate a complaint from a peer, and the local complaint received back
+                // from a peer, which must not be counted again
+                let peer_complaint = create_complaint(id, NODE_2, NODE_3);
+                let msg_id = peer_complaint.message_id();
+                idkg_pool.insert(UnvalidatedArtifact {
+                    message: IDkgMessage::Complaint(peer_complaint),
+                    peer_id: NODE_3,
+                    timestamp: UNIX_EPOCH,
+                });
+                let local_msg_id = local_complaint.message_id();
+                idkg_pool.insert(UnvalidatedArtifact {
+                    message: IDkgMessage::Complaint(local_complaint),
+                    peer_id: NODE_3,
+                    timestamp: UNIX_EPOCH,
+                });
+                let change_set = complaint_handler.validate_complaints(&idkg_pool, &block_reader);
+                assert!(is_moved_to_validated(&change_set, &msg_id));
+                assert!(is_moved_to_validated(&change_set, &local_msg_id));
+                assert_eq!(count(ComplaintAuthor::Local), 1);
+                assert_eq!(count(ComplaintAuthor::Peer), 1);
+            })
+        })
+    }
+
     // Tests validation of the received complaints
     #[test]
     fn test_validate_complaints_all_algorithms() {
@@ -1,15 +1,18 @@
 //! Metrics for the ecdsa feature
 
+use crate::idkg::IDkgProgressReport;
 use ic_management_canister_types::MasterPublicKeyId;
 use ic_metrics::{
     buckets::{decimal_buckets, linear_buckets},
     MetricsRegistry,
 };
 use ic_types::consensus::idkg::{HasMasterPublicKeyId, IDkgPayload};
+use ic_types::crypto::canister_threshold_sig::idkg::IDkgTranscriptId;
 use prometheus::{Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
 use std::collections::BTreeMap;
 
 pub const KEY_ID_LABEL: &str = "key_id";
+pub const SCHEME_LABEL: &str = "scheme";
 
 pub(crate) const CRITICAL_ERROR_ECDSA_KEY_TRANSCRIPT_MISSING: &str = "ecdsa_key_transcript_missing";
 pub(crate) const CRITICAL_ERROR_ECDSA_RETAIN_ACTIVE_TRANSCRIPTS: &str =
@@ -22,6 +25,12 @@ pub struct IDkgClientMetrics {
     pub client_errors: IntCounterVec,
     /// critical error when retain_active_transcripts fails
     pub critical_error_ecdsa_retain_active_transcripts: IntCounter,
+    /// transcripts created in finalized blocks, by signature scheme
+    pub transcripts_created: IntCounterVec,
+    /// pre-signatures completed in finalized blocks, by signature scheme
+    pub pre_signatures_completed: IntCounterVec,
+    /// transcripts requested in the finalized tip, by how far they got
+    pub transcript_progress: IntGaugeVec,
 }
 
 impl IDkgClientMetrics {
@@ -47,13 +56,126 @@ impl IDkgClientMetrics {
             ),
             critical_error_ecdsa_retain_active_transcripts: metrics_registry
                 .error_counter(CRITICAL_ERROR_ECDSA_RETAIN_ACTIVE_TRANSCRIPTS),
+            transcripts_created: metrics_registry.int_counter_vec(
+                "idkg_client_transcripts_created",
+                "Number of IDKG transcripts created in finalized blocks",
+                &[SCHEME_LABEL],
+            ),
+            pre_signatures_completed: metrics_registry.int_counter_vec(
+                "idkg_client_pre_signatures_completed",
+                "Number of pre-signatures completed in finalized blocks",
+                &[SCHEME_LABEL],
+            ),
+            transcript_progress: metrics_registry.int_gauge_vec(
+                "idkg_client_transcript_progress",
+                "Number of IDKG transcripts requested in the finalized tip, by progress state",
+                &["state"],
+            ),
+        }
+    }
+
+    /// Publishes how many of the requested transcripts in the given report are
+    /// blocked on dealings, blocked on support, or have enough supported dealings,
+    /// and how many could not be resolved.
+    pub(crate) fn report_transcript_progress(&self, report: &IDkgProgressReport) {
+        let mut blocked_on_dealings = 0;
+        let mut blocked_on_support = 0;
+        let mut supported = 0;
+        for transcript in &report.transcripts {
+            if transcript.is_blocked_on_dealings() {
+                blocked_on_dealings += 1;
+            } else if transcript.is_blocked_on_support() {
+                blocked_on_support += 1;
+            } else {
+                supported += 1;
+            }
+        }
+        for (state, count) in [
+            ("blocked_on_dealings", blocked_on_dealings),
+            ("blocked_on_support", blocked_on_support),
+            ("supported", supported),
+            ("unresolved", report.unresolved_transcripts),
+        ] {
+            self.transcript_progress
+                .with_label_values(&[state])
+                .set(count as i64);
+        }
+    }
+
+    /// Counts the transcripts created and the pre-signatures completed in a finalized
+    /// block, given its IDKG payload and the IDKG payload of its parent (if any).
+    /// Transcripts that can't be attributed to a key (e.g., the target side of an xnet
+    /// reshare) are counted with an empty scheme label.
+    pub(crate) fn report_finalized_payload(
+        &self,
+        payload: &IDkgPayload,
+        parent_payload: Option<&IDkgPayload>,
+    ) {
+        let key_ids = transcript_key_ids(payload, parent_payload);
+        for transcript_id in payload.idkg_transcripts.keys() {
+            self.transcripts_created
+                .with_label_values(&[scheme_label(key_ids.get(transcript_id))])
+                .inc();
+        }
+
+        for (pre_sig_id, pre_sig) in &payload.available_pre_signatures {
+            let was_available = parent_payload.is_some_and(|parent_payload| {
+                parent_payload
+                    .available_pre_signatures
+                    .contains_key(pre_sig_id)
+            });
+            if !was_available {
+                self.pre_signatures_completed
+                    .with_label_values(&[scheme_label(Some(&pre_sig.key_id()))])
+                    .inc();
+            }
         }
     }
 }
 
+/// Returns the key id of the configs referencing the transcripts of the given payload,
+/// including the xnet reshares of the parent payload that may have completed since.
+fn transcript_key_ids(
+    payload: &IDkgPayload,
+    parent_payload: Option<&IDkgPayload>,
+) -> BTreeMap<IDkgTranscriptId, MasterPublicKeyId> {
+    let mut key_ids = BTreeMap::new();
+    let pre_signatures = payload
+        .available_pre_signatures
+        .values()
+        .map(|pre_sig| (pre_sig.key_id(), pre_sig.get_refs()));
+    let pre_signatures_in_creation = payload
+        .pre_signatures_in_creation
+        .values()
+        .map(|pre_sig| (pre_sig.key_id(), pre_sig.get_refs()));
+    let key_transcripts = payload
+        .key_transcripts
+        .values()
+        .map(|key_transcript| (key_transcript.key_id(), key_transcript.get_refs()));
+    for (key_id, refs) in pre_signatures
+        .chain(pre_signatures_in_creation)
+        .chain(key_transcripts)
+    {
+        for transcript_ref in refs {
+            key_ids.insert(transcript_ref.transcript_id, key_id.clone());
+        }
+    }
+    for (request, params) in parent_payload
+        .into_iter()
+        .flat_map(|parent_payload| &parent_payload.ongoing_xnet_reshares)
+    {
+        key_ids.insert(params.as_ref().transcript_id, request.key_id());
+    }
+    key_ids
+}
+
 #[derive(Clone)]
 pub struct IDkgGossipMetrics {
     pub dropped_adverts: IntCounterVec,
+    pub stashed_adverts: IntCounterVec,
+    pub fetched_adverts: IntCounterVec,
+    pub certified_state_unavailable: IntGauge,
+    pub adverts_without_certified_state: IntCounterVec,
 }
 
 impl IDkgGossipMetrics {
@@ -64,6 +186,25 @@ impl IDkgGossipMetrics {
                 "IDKG adverts dropped by priority fn",
                 &["type"],
             ),
+            stashed_adverts: metrics_registry.int_counter_vec(
+                "idkg_priority_fn_stashed_adverts",
+                "IDKG adverts stashed by priority fn",
+                &["type"],
+            ),
+            fetched_adverts: metrics_registry.int_counter_vec(
+                "idkg_priority_fn_fetched_adverts",
+                "IDKG adverts fetched immediately by priority fn",
+                &["type"],
+            ),
+            certified_state_unavailable: metrics_registry.int_gauge(
+                "idkg_priority_fn_certified_state_unavailable",
+                "1 if no certified state snapshot was available when the priority fn was last built, 0 otherwise",
+            ),
+            adverts_without_certified_state: metrics_registry.int_counter_vec(
+                "idkg_priority_fn_adverts_without_certified_state",
+                "IDKG signature share adverts fetched because no certified state snapshot was available",
+                &["type"],
+            ),
         }
     }
 }
@@ -284,6 +425,9 @@ pub struct IDkgComplaintMetrics {
     pub on_state_change_duration: HistogramVec,
     pub complaint_metrics: IntCounterVec,
     pub complaint_errors: IntCounterVec,
+    /// Complaints issued by this node ("local") versus validated
+    /// complaints issued by other nodes ("peer").
+    pub complaints_by_author: IntCounterVec,
 }
 
 impl IDkgComplaintMetrics {
@@ -307,6 +451,11 @@ impl IDkgComplaintMetrics {
                 "Complaint related errors",
                 &["type"],
             ),
+            complaints_by_author: metrics_registry.int_counter_vec(
+                "idkg_complaints_by_author",
+                "Complaints issued by this node versus validated complaints issued by peers",
+                &["author"],
+            ),
         }
     }
 
@@ -317,6 +466,28 @@ impl IDkgComplaintMetrics {
     pub fn complaint_errors_inc(&self, label: &str) {
         self.complaint_errors.with_label_values(&[label]).inc();
     }
+
+    pub fn complaints_by_author_inc(&self, author: ComplaintAuthor) {
+        self.complaints_by_author
+            .with_label_values(&[author.as_str()])
+            .inc();
+    }
+}
+
+/// The author of a complaint, relative to this node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ComplaintAuthor {
+    Local,
+    Peer,
+}
+
+impl ComplaintAuthor {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ComplaintAuthor::Local => "local",
+            ComplaintAuthor::Peer => "peer",
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -449,6 +620,15 @@ pub fn key_id_label(key_id: Option<&MasterPublicKeyId>) -> String {
     key_id.map(ToString::to_string).unwrap_or_default()
 }
 
+/// Returns the signature scheme corresponding to the [`MasterPublicKeyId`]
+pub fn scheme_label(key_id: Option<&MasterPublicKeyId>) -> &'static str {
+    match key_id {
+        Some(MasterPublicKeyId::Ecdsa(_)) => "ecdsa",
+        Some(MasterPublicKeyId::Schnorr(_)) => "schnorr",
+        None => "",
+    }
+}
+
 pub fn expected_keys(payload: &IDkgPayload) -> Vec<MasterPublicKeyId> {
     payload.key_transcripts.keys().cloned().collect()
 }
@@ -45,16 +45,25 @@ pub struct IDkgPreSignerImpl {
     pub(crate) consensus_block_cache: Arc<dyn ConsensusBlockCache>,
     pub(crate) crypto: Arc<dyn ConsensusCrypto>,
     schedule: RoundRobin,
+    /// The maximum number of transcripts loaded by a single `send_dealings`
+    /// call, or `None` if transcript loading is unbounded.
+    max_transcript_loads_per_round: Option<usize>,
     pub(crate) metrics: IDkgPreSignerMetrics,
     pub(crate) log: ReplicaLogger,
     prev_finalized_height: RefCell<Height>,
 }
 
 impl IDkgPreSignerImpl {
+    /// Builds a new pre-signer. If `max_transcript_loads_per_round` is set, at most
+    /// that many transcripts are loaded each time dealings are sent. Dealings whose
+    /// dependencies exceed the remaining budget are deferred to the next time
+    /// `send_dealings` is scheduled. The first dealing of each call is always
+    /// created, so a transcript with more dependencies than the bound still makes progress.
     pub(crate) fn new(
         node_id: NodeId,
         consensus_block_cache: Arc<dyn ConsensusBlockCache>,
         crypto: Arc<dyn ConsensusCrypto>,
+        max_transcript_loads_per_round: Option<usize>,
         metrics_registry: MetricsRegistry,
         log: ReplicaLogger,
     ) -> Self {
@@ -63,6 +72,7 @@ impl IDkgPreSignerImpl {
             consensus_block_cache,
             crypto,
             schedule: RoundRobin::default(),
+            max_transcript_loads_per_round,
             metrics: IDkgPreSignerMetrics::new(metrics_registry),
             log,
             prev_finalized_height: RefCell::new(Height::from(0)),
@@ -83,6 +93,8 @@ impl IDkgPreSignerImpl {
             target_subnet_xnet_transcripts.insert(transcript_params_ref.transcript_id);
         }
 
+        let mut remaining_transcript_loads = self.max_transcript_loads_per_round;
+        let mut dealing_issued = false;
         block_reader
             .requested_transcripts()
             .filter_map(|transcript_params_ref| {
@@ -115,6 +127,23 @@ impl IDkgPreSignerImpl {
                     );
                 }
 
+                if let Some(remaining) = remaining_transcript_loads.as_mut() {
+                    let loads = Self::dependencies(&transcript_params).len();
+                    if loads > *remaining && dealing_issued {
+                        self.metrics
+                            .pre_sign_metrics_inc("transcript_load_deferred");
+                        debug!(
+                            self.log,
+                            "Dealing creation: deferred loading {} transcripts for {:?}",
+                            loads,
+                            transcript_params.transcript_id(),
+                        );
+                        return Default::default();
+                    }
+                    *remaining = remaining.saturating_sub(loads);
+                }
+                dealing_issued = true;
+
                 self.crypto_create_dealing(idkg_pool, transcript_loader, &transcript_params)
             })
             .collect()
@@ -796,18 +825,22 @@ impl IDkgPreSignerImpl {
         transcript_loader: &dyn IDkgTranscriptLoader,
         transcript_params: &IDkgTranscriptParams,
     ) -> Option<IDkgChangeSet> {
-        match &transcript_params.operation_type() {
-            IDkgTranscriptOperation::Random => None,
-            IDkgTranscriptOperation::RandomUnmasked => None,
-            IDkgTranscriptOperation::ReshareOfMasked(t) => {
-                load_transcripts(idkg_pool, transcript_loader, &[t])
-            }
-            IDkgTranscriptOperation::ReshareOfUnmasked(t) => {
-                load_transcripts(idkg_pool, transcript_loader, &[t])
-            }
-            IDkgTranscriptOperation::UnmaskedTimesMasked(t1, t2) => {
-                load_transcripts(idkg_pool, transcript_loader, &[t1, t2])
-            }
+        load_transcripts(
+            idkg_pool,
+            transcript_loader,
+            &Self::dependencies(transcript_params),
+        )
+    }
+
+    /// Returns the transcripts that need to be loaded before creating a
+    /// dealing for the given transcript params
+    fn dependencies(transcript_params: &IDkgTranscriptParams) -> Vec<&IDkgTranscript> {
+        match transcript_params.operation_type() {
+            IDkgTranscriptOperation::Random => vec![],
+            IDkgTranscriptOperation::RandomUnmasked => vec![],
+            IDkgTranscriptOperation::ReshareOfMasked(t) => vec![t],
+            IDkgTranscriptOperation::ReshareOfUnmasked(t) => vec![t],
+            IDkgTranscriptOperation::UnmaskedTimesMasked(t1, t2) => vec![t1, t2],
         }
     }
 
@@ -1478,6 +1511,101 @@ mod tests {
         })
     }
 
+    // Tests that dealings whose dependencies exceed the transcript load bound
+    // are deferred to the next call.
+    #[test]
+    fn test_send_dealings_defers_transcript_loads_over_bound() {
+        let key_id = fake_ecdsa_master_public_key_id();
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|logger| {
+                let (mut idkg_pool, mut pre_signer) =
+                    create_pre_signer_dependencies(pool_config, logger);
+                pre_signer.max_transcript_loads_per_round = Some(2);
+                let (id_1, id_2, id_3) = (
+                    create_transcript_id(1),
+                    create_transcript_id(2),
+                    create_transcript_id(3),
+                );
+
+                // Each requested transcript reshares one masked transcript
+                let t1 = create_transcript_param(&key_id, id_1, &[NODE_1], &[NODE_2]);
+                let t2 = create_transcript_param(&key_id, id_2, &[NODE_1], &[NODE_3]);
+                let t3 = create_transcript_param(&key_id, id_3, &[NODE_1], &[NODE_4]);
+                let block_reader =
+                    TestIDkgBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2, t3]);
+                let transcript_loader: TestIDkgTranscriptLoader = Default::default();
+                let deferred = || {
+                    pre_signer
+                        .metrics
+                        .pre_sign_metrics
+                        .with_label_values(&["transcript_load_deferred"])
+                        .get()
+                };
+
+                // Only two transcripts may be loaded, so the third dealing is deferred
+                let change_set =
+                    pre_signer.send_dealings(&idkg_pool, &transcript_loader, &block_reader);
+                assert_eq!(change_set.len(), 2);
+                assert!(is_dealing_added_to_validated(&change_set, &id_1));
+                assert!(is_dealing_added_to_validated(&change_set, &id_2));
+                assert_eq!(deferred(), 1);
+                idkg_pool.apply_changes(change_set);
+
+                // The deferred dealing is sent on the next call
+                let change_set =
+                    pre_signer.send_dealings(&idkg_pool, &transcript_loader, &block_reader);
+                assert_eq!(change_set.len(), 1);
+                assert!(is_dealing_added_to_validated(&change_set, &id_3));
+                assert_eq!(deferred(), 1);
+            })
+        })
+    }
+
+    // Tests that a dealing whose dependencies exceed the transcript load bound
+    // is not deferred forever if it is the first one of the call.
+    #[test]
+    fn test_send_dealings_loads_first_transcript_over_bound() {
+        let key_id = fake_ecdsa_master_public_key_id();
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|logger| {
+                let (mut idkg_pool, mut pre_signer) =
+                    create_pre_signer_dependencies(pool_config, logger);
+                pre_signer.max_transcript_loads_per_round = Some(1);
+                let (id_1, id_2) = (create_transcript_id(1), create_transcript_id(2));
+
+                // Each requested transcript multiplies two transcripts
+                let t1 = create_unmasked_times_masked_transcript_param(
+                    &key_id,
+                    id_1,
+                    &[NODE_1, NODE_2],
+                    &[NODE_3],
+                );
+                let t2 = create_unmasked_times_masked_transcript_param(
+                    &key_id,
+                    id_2,
+                    &[NODE_1, NODE_2],
+                    &[NODE_3],
+                );
+                let block_reader =
+                    TestIDkgBlockReader::for_pre_signer_test(Height::from(100), vec![t1, t2]);
+                let transcript_loader: TestIDkgTranscriptLoader = Default::default();
+
+                // The first dealing is sent although it needs two transcript loads
+                let change_set =
+                    pre_signer.send_dealings(&idkg_pool, &transcript_loader, &block_reader);
+                assert_eq!(change_set.len(), 1);
+                assert!(is_dealing_added_to_validated(&change_set, &id_1));
+                idkg_pool.apply_changes(change_set);
+
+                // The second one follows on the next call
+                let change_set =
+                    pre_signer.send_dealings(&idkg_pool, &transcript_loader, &block_reader);
+                assert_eq!(change_set.len(), 1);
+                assert!(is_dealing_added_to_validated(&change_set, &id_2));
+            })
+        })
+    }
+
     // Tests that dealings are purged once the finalized height increases
     #[test]
     fn test_ecdsa_dealings_purging() {
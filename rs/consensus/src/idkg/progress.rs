@@ -0,0 +1,116 @@
+//! Reports how far this node got in building the transcripts requested in the
+//! finalized tip, i.e., how many validated dealings and support shares it holds
+//! per transcript compared to how many are needed (see "validate IDKG dealings"
+//! and "Support DKG dealings" in the module documentation of [`crate::idkg`]).
+
+use ic_interfaces::idkg::IDkgPool;
+use ic_types::{
+    consensus::idkg::IDkgBlockReader,
+    crypto::{canister_threshold_sig::idkg::IDkgTranscriptId, CryptoHashOf},
+    Height,
+};
+use std::collections::BTreeMap;
+
+/// The progress of building all transcripts requested in the finalized tip.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IDkgProgressReport {
+    /// The height of the finalized tip the report refers to.
+    pub finalized_height: Height,
+    /// The progress per requested transcript, ordered by transcript id.
+    pub transcripts: Vec<IDkgTranscriptProgress>,
+    /// The number of requested transcripts whose params could not be resolved,
+    /// and which are therefore missing from `transcripts`.
+    pub unresolved_transcripts: usize,
+}
+
+/// The progress of building a single transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IDkgTranscriptProgress {
+    pub transcript_id: IDkgTranscriptId,
+    /// The number of validated dealings for the transcript.
+    pub dealings: usize,
+    /// The number of verified dealings needed to build the transcript.
+    pub dealings_needed: usize,
+    /// The number of validated support shares for the dealings of the transcript.
+    pub support_shares: usize,
+    /// The number of support shares needed to verify a single dealing.
+    pub support_shares_needed_per_dealing: usize,
+    /// The number of dealings with enough support shares to be verified.
+    pub supported_dealings: usize,
+}
+
+impl IDkgTranscriptProgress {
+    /// Returns true if not enough dealings were received yet.
+    pub fn is_blocked_on_dealings(&self) -> bool {
+        self.dealings < self.dealings_needed
+    }
+
+    /// Returns true if enough dealings were received, but not enough of them have
+    /// enough support shares yet.
+    pub fn is_blocked_on_support(&self) -> bool {
+        !self.is_blocked_on_dealings() && self.supported_dealings < self.dealings_needed
+    }
+}
+
+/// Builds the progress report from a single pass over the validated dealings
+/// and support shares in the pool.
+pub(crate) fn idkg_progress(
+    block_reader: &dyn IDkgBlockReader,
+    idkg_pool: &dyn IDkgPool,
+) -> IDkgProgressReport {
+    let mut report = IDkgProgressReport {
+        finalized_height: block_reader.tip_height(),
+        ..Default::default()
+    };
+
+    let mut progress = BTreeMap::new();
+    for transcript_params_ref in block_reader.requested_transcripts() {
+        match transcript_params_ref.translate(block_reader) {
+            Ok(transcript_params) => {
+                progress.insert(
+                    transcript_params_ref.transcript_id,
+                    IDkgTranscriptProgress {
+                        transcript_id: transcript_params_ref.transcript_id,
+                        dealings: 0,
+                        dealings_needed: transcript_params.collection_threshold().get() as usize,
+                        support_shares: 0,
+                        support_shares_needed_per_dealing: transcript_params
+                            .verification_threshold()
+                            .get()
+                            as usize,
+                        supported_dealings: 0,
+                    },
+                );
+            }
+            Err(_) => report.unresolved_transcripts += 1,
+        }
+    }
+
+    let validated = idkg_pool.validated();
+    let mut support_shares_per_dealing = BTreeMap::<CryptoHashOf<_>, usize>::new();
+    for (_, support) in validated.dealing_support() {
+        if let Some(transcript_progress) = progress.get_mut(&support.transcript_id) {
+            transcript_progress.support_shares += 1;
+            *support_shares_per_dealing
+                .entry(support.dealing_hash)
+                .or_default() += 1;
+        }
+    }
+    for (id, signed_dealing) in validated.signed_dealings() {
+        let transcript_id = signed_dealing.idkg_dealing().transcript_id;
+        if let Some(transcript_progress) = progress.get_mut(&transcript_id) {
+            transcript_progress.dealings += 1;
+            let support_shares = id
+                .dealing_hash()
+                .and_then(|dealing_hash| support_shares_per_dealing.get(&dealing_hash))
+                .copied()
+                .unwrap_or_default();
+            if support_shares >= transcript_progress.support_shares_needed_per_dealing {
+                transcript_progress.supported_dealings += 1;
+            }
+        }
+    }
+
+    report.transcripts = progress.into_values().collect();
+    report
+}
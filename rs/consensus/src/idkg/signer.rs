@@ -81,6 +81,18 @@ impl CombineSigSharesError {
     }
 }
 
+/// Determines whether the signer participates in signing or only observes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ThresholdSignerMode {
+    /// Create signature shares for requests this node is a signer of, and
+    /// validate the shares received from peers.
+    #[default]
+    Full,
+    /// Only validate the shares received from peers, never create our own.
+    /// Intended for read-only observer replicas.
+    ValidateOnly,
+}
+
 pub(crate) trait ThresholdSigner: Send {
     /// The on_state_change() called from the main IDKG path.
     fn on_state_change(
@@ -95,6 +107,7 @@ pub(crate) struct ThresholdSignerImpl {
     consensus_block_cache: Arc<dyn ConsensusBlockCache>,
     crypto: Arc<dyn ConsensusCrypto>,
     state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+    mode: ThresholdSignerMode,
     schedule: RoundRobin,
     metrics: ThresholdSignerMetrics,
     log: ReplicaLogger,
@@ -107,6 +120,7 @@ impl ThresholdSignerImpl {
         consensus_block_cache: Arc<dyn ConsensusBlockCache>,
         crypto: Arc<dyn ConsensusCrypto>,
         state_reader: Arc<dyn StateReader<State = ReplicatedState>>,
+        mode: ThresholdSignerMode,
         metrics_registry: MetricsRegistry,
         log: ReplicaLogger,
     ) -> Self {
@@ -115,6 +129,7 @@ impl ThresholdSignerImpl {
             consensus_block_cache,
             crypto,
             state_reader,
+            mode,
             schedule: RoundRobin::default(),
             metrics: ThresholdSignerMetrics::new(metrics_registry),
             log,
@@ -124,6 +139,7 @@ impl ThresholdSignerImpl {
 
     /// Generates signature shares for the newly added signature requests.
     /// The requests for new signatures come from the latest finalized block.
+    /// No shares are created in [`ThresholdSignerMode::ValidateOnly`].
     fn send_signature_shares(
         &self,
         idkg_pool: &dyn IDkgPool,
@@ -131,6 +147,10 @@ impl ThresholdSignerImpl {
         block_reader: &dyn IDkgBlockReader,
         state_snapshot: &dyn CertifiedStateSnapshot<State = ReplicatedState>,
     ) -> IDkgChangeSet {
+        if self.mode == ThresholdSignerMode::ValidateOnly {
+            return IDkgChangeSet::new();
+        }
+
         state_snapshot
             .get_state()
             .signature_request_contexts()
@@ -1077,6 +1097,73 @@ mod tests {
         });
     }
 
+    // Tests that a signer in validate-only mode never issues signature shares,
+    // even though this node is in the signer set of the requests.
+    #[test]
+    fn test_send_signature_shares_validate_only_all_algorithms() {
+        for key_id in fake_master_public_key_ids_for_all_algorithms() {
+            println!("Running test for key ID {key_id}");
+            test_send_signature_shares_validate_only(key_id);
+        }
+    }
+
+    fn test_send_signature_shares_validate_only(key_id: MasterPublicKeyId) {
+        let mut uid_generator = IDkgUIDGenerator::new(subnet_test_id(1), Height::new(0));
+        let height = Height::from(100);
+        let (id_1, id_2) = (
+            create_request_id(&mut uid_generator, height),
+            create_request_id(&mut uid_generator, height),
+        );
+
+        let block_reader = TestIDkgBlockReader::for_signer_test(
+            height,
+            vec![
+                (id_1.clone(), create_sig_inputs(1, &key_id)),
+                (id_2.clone(), create_sig_inputs(2, &key_id)),
+            ],
+        );
+        let transcript_loader: TestIDkgTranscriptLoader = Default::default();
+
+        let state = fake_state_with_signature_requests(
+            height,
+            block_reader.requested_signatures().map(|(request_id, _)| {
+                fake_signature_request_context_from_id(key_id.clone(), request_id)
+            }),
+        );
+
+        ic_test_utilities::artifact_pool_config::with_test_pool_config(|pool_config| {
+            with_test_replica_logger(|logger| {
+                let (idkg_pool, signer, state_manager) =
+                    create_signer_dependencies_with_mode_and_state_manager(
+                        pool_config,
+                        logger,
+                        ThresholdSignerMode::ValidateOnly,
+                    );
+                let snapshot = state.clone();
+                state_manager
+                    .get_mut()
+                    .expect_get_certified_state_snapshot()
+                    .returning(move || Some(Box::new(snapshot.clone())));
+
+                let change_set = signer.send_signature_shares(
+                    &idkg_pool,
+                    &transcript_loader,
+                    &block_reader,
+                    &state,
+                );
+                assert!(change_set.is_empty());
+
+                // Both subcomponents are scheduled once within two calls
+                for _ in 0..2 {
+                    let change_set = signer.on_state_change(&idkg_pool, &transcript_loader);
+                    assert!(!change_set
+                        .iter()
+                        .any(|action| matches!(action, IDkgChangeAction::AddToValidated(_))));
+                }
+            })
+        });
+    }
+
     // Tests that no signature shares for incomplete contexts are created
     #[test]
     fn test_send_signature_shares_incomplete_contexts_all_algorithms() {
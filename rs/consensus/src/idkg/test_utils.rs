@@ -2,7 +2,7 @@ use crate::idkg::complaints::{
     IDkgComplaintHandlerImpl, IDkgTranscriptLoader, TranscriptLoadStatus,
 };
 use crate::idkg::pre_signer::{IDkgPreSignerImpl, IDkgTranscriptBuilder};
-use crate::idkg::signer::{ThresholdSignatureBuilder, ThresholdSignerImpl};
+use crate::idkg::signer::{ThresholdSignatureBuilder, ThresholdSignerImpl, ThresholdSignerMode};
 use ic_artifact_pool::idkg_pool::IDkgPoolImpl;
 use ic_config::artifact_pool::ArtifactPoolConfig;
 use ic_consensus_mocks::{dependencies, Dependencies};
@@ -713,6 +713,7 @@ pub(crate) fn create_pre_signer_dependencies_with_crypto(
         NODE_1,
         pool.get_block_cache(),
         consensus_crypto.unwrap_or(crypto),
+        None,
         metrics_registry.clone(),
         logger.clone(),
     );
@@ -733,6 +734,7 @@ pub(crate) fn create_pre_signer_dependencies_and_pool(
         NODE_1,
         pool.get_block_cache(),
         crypto,
+        None,
         metrics_registry.clone(),
         logger.clone(),
     );
@@ -768,6 +770,7 @@ pub(crate) fn create_signer_dependencies_with_crypto(
         pool.get_block_cache(),
         consensus_crypto.unwrap_or(crypto),
         state_manager as Arc<_>,
+        ThresholdSignerMode::Full,
         metrics_registry.clone(),
         logger.clone(),
     );
@@ -787,6 +790,18 @@ pub(crate) fn create_signer_dependencies(
 pub(crate) fn create_signer_dependencies_and_state_manager(
     pool_config: ArtifactPoolConfig,
     logger: ReplicaLogger,
+) -> (IDkgPoolImpl, ThresholdSignerImpl, Arc<RefMockStateManager>) {
+    create_signer_dependencies_with_mode_and_state_manager(
+        pool_config,
+        logger,
+        ThresholdSignerMode::Full,
+    )
+}
+
+pub(crate) fn create_signer_dependencies_with_mode_and_state_manager(
+    pool_config: ArtifactPoolConfig,
+    logger: ReplicaLogger,
+    mode: ThresholdSignerMode,
 ) -> (IDkgPoolImpl, ThresholdSignerImpl, Arc<RefMockStateManager>) {
     let metrics_registry = MetricsRegistry::new();
     let Dependencies {
@@ -801,6 +816,7 @@ pub(crate) fn create_signer_dependencies_and_state_manager(
         pool.get_block_cache(),
         crypto,
         state_manager.clone(),
+        mode,
         metrics_registry.clone(),
         logger.clone(),
     );
@@ -823,6 +839,7 @@ pub(crate) fn create_complaint_dependencies_with_crypto_and_node_id(
         node_id,
         pool.get_block_cache(),
         consensus_crypto.unwrap_or(crypto),
+        None,
         metrics_registry.clone(),
         logger.clone(),
     );
@@ -915,6 +932,48 @@ pub(crate) fn create_transcript_param(
     )
 }
 
+/// Creates a test transcript param that multiplies an unmasked and a masked
+/// transcript, both dealt to the given dealers
+pub(crate) fn create_unmasked_times_masked_transcript_param(
+    key_id: &MasterPublicKeyId,
+    transcript_id: IDkgTranscriptId,
+    dealer_list: &[NodeId],
+    receiver_list: &[NodeId],
+) -> TestTranscriptParams {
+    let height = Height::new(0);
+    let mut unmasked_transcript = create_transcript(
+        key_id,
+        create_transcript_id(transcript_id.id() * 214365 + 1),
+        dealer_list,
+    );
+    unmasked_transcript.transcript_type =
+        IDkgTranscriptType::Unmasked(IDkgUnmaskedTranscriptOrigin::Random);
+    let masked_transcript = create_transcript(
+        key_id,
+        create_transcript_id(transcript_id.id() * 214365 + 2),
+        dealer_list,
+    );
+    let unmasked = UnmaskedTranscript::try_from((height, &unmasked_transcript)).unwrap();
+    let masked = MaskedTranscript::try_from((height, &masked_transcript)).unwrap();
+    let mut idkg_transcripts = BTreeMap::new();
+    idkg_transcripts.insert(*unmasked.as_ref(), unmasked_transcript);
+    idkg_transcripts.insert(*masked.as_ref(), masked_transcript);
+
+    let transcript_params_ref = IDkgTranscriptParamsRef::new(
+        transcript_id,
+        dealer_list.iter().cloned().collect(),
+        receiver_list.iter().cloned().collect(),
+        RegistryVersion::from(0),
+        algorithm_for_key_id(key_id),
+        IDkgTranscriptOperationRef::UnmaskedTimesMasked(unmasked, masked),
+    );
+
+    TestTranscriptParams {
+        idkg_transcripts,
+        transcript_params_ref,
+    }
+}
+
 /// Creates a test transcript param for a specific registry version
 pub(crate) fn create_transcript_param_with_registry_version(
     key_id: &MasterPublicKeyId,
@@ -183,6 +183,9 @@ impl<'a> ConsensusRunner<'a> {
             deps.consensus_pool.read().unwrap().get_block_cache(),
             consensus_crypto,
             deps.state_manager.clone(),
+            idkg::ThresholdSignerMode::Full,
+            idkg::INACTIVE_TRANSCRIPT_PURGE_SECS,
+            None,
             deps.metrics_registry.clone(),
             replica_logger.clone(),
             malicious_flags,
@@ -44,9 +44,12 @@ impl Display for AddressValidationError {
 }
 
 /// Validate whether the given address can be used as the destination of an Ethereum transaction.
+///
+/// Mixed-case addresses must carry a valid EIP-55 checksum, while all-lowercase
+/// and all-uppercase addresses are accepted as is.
 pub fn validate_address_as_destination(address: &str) -> Result<Address, AddressValidationError> {
     let address =
-        Address::from_str(address).map_err(|e| AddressValidationError::Invalid { error: e })?;
+        parse_address(address).map_err(|e| AddressValidationError::Invalid { error: e })?;
     if address == Address::ZERO {
         return Err(AddressValidationError::NotSupported(address));
     }
@@ -55,3 +58,25 @@ pub fn validate_address_as_destination(address: &str) -> Result<Address, Address
     }
     Ok(address)
 }
+
+fn parse_address(address: &str) -> Result<Address, String> {
+    let hex_address = address
+        .strip_prefix("0x")
+        .ok_or_else(|| "address doesn't start with '0x'".to_string())?;
+    if hex_address.len() != 40 {
+        return Err(format!(
+            "address has wrong length: expected 40 hexadecimal characters after '0x', got {}",
+            hex_address.len()
+        ));
+    }
+    let parsed = Address::from_str(address)?;
+    let is_mixed_case = hex_address.chars().any(|c| c.is_ascii_lowercase())
+        && hex_address.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case && parsed.to_string() != address {
+        return Err(format!(
+            "address has an invalid EIP-55 checksum: expected {}",
+            parsed
+        ));
+    }
+    Ok(parsed)
+}
@@ -28,13 +28,38 @@ mod validate_address_as_destination {
     // 20 bytes represent 40 hexadecimal characters
     proptest! {
         #[test]
-        fn should_validate_non_zero_addresses(valid_address in "0x[0-9a-fA-F]{40}") {
+        fn should_validate_non_zero_addresses(valid_address in "0x[0-9a-f]{40}") {
             prop_assume!(valid_address != "0x0000000000000000000000000000000000000000");
             let address = Address::from_str(&valid_address).unwrap();
-            prop_assert_eq!(validate_address_as_destination(&valid_address), Ok(address));
+            let uppercase_address = format!("0x{}", valid_address[2..].to_ascii_uppercase());
+            let checksummed_address = address.to_string();
+            for valid_address in [valid_address, uppercase_address, checksummed_address] {
+                prop_assert_eq!(validate_address_as_destination(&valid_address), Ok(address));
+            }
         }
     }
 
+    #[test]
+    fn should_fail_when_checksum_is_invalid() {
+        // EIP-55 checksum is 0x01e2919679362dFBC9ee1644Ba9C6da6D6245BB1
+        assert_eq!(
+            validate_address_as_destination("0x01e2919679362dfBC9ee1644Ba9C6da6D6245BB1"),
+            Err(AddressValidationError::Invalid {
+                error: "address has an invalid EIP-55 checksum: expected 0x01e2919679362dFBC9ee1644Ba9C6da6D6245BB1".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn should_fail_when_length_is_wrong() {
+        assert_eq!(
+            validate_address_as_destination("0x01e2919679362dFBC9ee1644Ba9C6da6D6245B"),
+            Err(AddressValidationError::Invalid {
+                error: "address has wrong length: expected 40 hexadecimal characters after '0x', got 38".to_string()
+            })
+        );
+    }
+
     proptest! {
         #[test]
         fn should_fail_when_address_too_short(invalid_address in "0x[0-9a-fA-F]{0, 39}") {
@@ -380,9 +380,10 @@ pub async fn update_last_observed_block_number() -> Option<BlockNumber> {
         .await
     {
         Ok(latest_block) => {
-            let block_number = Some(latest_block.number);
-            mutate_state(|s| s.last_observed_block_number = block_number);
-            block_number
+            let block_number = latest_block.number;
+            let now = ic_cdk::api::time();
+            mutate_state(|s| s.update_last_observed_block_number(block_number, now));
+            Some(block_number)
         }
         Err(e) => {
             log!(
@@ -1,7 +1,7 @@
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::ledger_client::LedgerBurnError;
 use crate::numeric::LedgerBurnIndex;
-use crate::state::{transactions, transactions::EthWithdrawalRequest};
+use crate::state::transactions;
 use crate::tx::{SignedEip1559TransactionRequest, TransactionPrice};
 use candid::{CandidType, Deserialize, Nat, Principal};
 use icrc_ledger_types::icrc1::account::Account;
@@ -60,6 +60,23 @@ pub struct Erc20Balance {
     pub balance: Nat,
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, Eq, PartialEq)]
+pub struct ReorgStats {
+    pub total_reorgs: u64,
+    pub max_depth_blocks: u64,
+    pub last_reorg_timestamp: Option<u64>,
+}
+
+impl From<crate::state::ReorgStats> for ReorgStats {
+    fn from(stats: crate::state::ReorgStats) -> Self {
+        Self {
+            total_reorgs: stats.total_reorgs,
+            max_depth_blocks: stats.max_depth_blocks,
+            last_reorg_timestamp: stats.last_reorg_timestamp,
+        }
+    }
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug, Eq, PartialEq)]
 pub struct MinterInfo {
     pub minter_address: Option<String>,
@@ -73,6 +90,12 @@ pub struct MinterInfo {
     pub last_observed_block_number: Option<Nat>,
     pub eth_balance: Option<Nat>,
     pub last_gas_fee_estimate: Option<GasFeeEstimate>,
+    /// The most recent gas fee estimates, oldest first.
+    pub recent_gas_fee_estimates: Option<Vec<GasFeeEstimate>>,
+    /// The nonce of the last transaction created by the minter.
+    pub last_transaction_nonce: Option<Nat>,
+    /// The number of distinct nonces of transactions created or sent but not yet finalized.
+    pub pending_transaction_count: Option<Nat>,
     pub erc20_balances: Option<Vec<Erc20Balance>>,
     pub last_eth_scraped_block_number: Option<Nat>,
     pub last_erc20_scraped_block_number: Option<Nat>,
@@ -110,6 +133,8 @@ impl From<&TransactionReceipt> for EthTransaction {
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub struct RetrieveEthRequest {
     pub block_index: Nat,
+    /// The Ethereum address of the minter that will send the withdrawn ETH.
+    pub minter_address: String,
 }
 
 #[derive(CandidType, Debug, Default, Deserialize, Clone, Encode, Decode, PartialEq, Eq)]
@@ -129,14 +154,11 @@ pub enum CandidBlockTag {
     /// <https://www.alchemy.com/overviews/ethereum-commitment-levels#what-are-ethereum-commitment-levels>
     #[cbor(n(2))]
     Finalized,
-}
-
-impl From<EthWithdrawalRequest> for RetrieveEthRequest {
-    fn from(value: EthWithdrawalRequest) -> Self {
-        Self {
-            block_index: Nat::from(value.ledger_burn_index.get()),
-        }
-    }
+    /// The pending block, i.e. the block currently being built on top of the latest mined block.
+    /// This trades safety for latency: data read from the pending block is the most recent
+    /// but is the most likely to be reorganized away.
+    #[cbor(n(3))]
+    Pending,
 }
 
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
@@ -145,9 +167,22 @@ pub enum RetrieveEthStatus {
     Pending,
     TxCreated,
     TxSent(EthTransaction),
+    TxReplaced {
+        old_tx: EthTransaction,
+        new_tx: EthTransaction,
+    },
     TxFinalized(TxFinalizedStatus),
 }
 
+/// Maximum number of withdrawal ids that can be looked up in a single call to
+/// `retrieve_eth_status_batch`.
+pub const MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE: usize = 100;
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum RetrieveEthStatusBatchError {
+    TooManyWithdrawalIds { max_withdrawal_ids: u64 },
+}
+
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub enum TxFinalizedStatus {
     Success {
@@ -162,6 +197,17 @@ pub enum TxFinalizedStatus {
     },
 }
 
+/// Details of the reimbursement of a failed withdrawal.
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct ReimbursementInfo {
+    /// The amount reimbursed in the smallest denomination of the reimbursed token.
+    pub reimbursed_amount: Nat,
+    /// The index of the mint transaction on the ledger of the reimbursed token.
+    pub reimbursed_in_block: Nat,
+    /// The hash of the failed Ethereum transaction, if the withdrawal got that far.
+    pub transaction_hash: Option<String>,
+}
+
 impl Display for RetrieveEthStatus {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -169,6 +215,11 @@ impl Display for RetrieveEthStatus {
             RetrieveEthStatus::Pending => write!(f, "Pending"),
             RetrieveEthStatus::TxCreated => write!(f, "Created"),
             RetrieveEthStatus::TxSent(tx) => write!(f, "Sent({})", tx.transaction_hash),
+            RetrieveEthStatus::TxReplaced { old_tx, new_tx } => write!(
+                f,
+                "Replaced({} by {})",
+                old_tx.transaction_hash, new_tx.transaction_hash
+            ),
             RetrieveEthStatus::TxFinalized(tx_status) => match tx_status {
                 TxFinalizedStatus::Success {
                     transaction_hash, ..
@@ -194,6 +245,11 @@ impl Display for RetrieveEthStatus {
 pub struct WithdrawalArg {
     pub amount: Nat,
     pub recipient: String,
+    /// ENS name (e.g. `vitalik.eth`) to resolve to the recipient's address.
+    /// When set, `recipient` must be empty.
+    pub recipient_ens: Option<String>,
+    /// Client-chosen key identifying the withdrawal, so that it can be safely retried.
+    pub idempotency_key: Option<[u8; 32]>,
 }
 
 #[derive(CandidType, Deserialize, Debug, PartialEq)]
@@ -203,6 +259,10 @@ pub enum WithdrawalError {
     InsufficientAllowance { allowance: Nat },
     RecipientAddressBlocked { address: String },
     TemporarilyUnavailable(String),
+    EnsResolutionFailed { name: String, reason: String },
+    InvalidDestination { reason: String },
+    DuplicateRequest { original_block_index: Nat },
+    MinterPaused { reason: String },
 }
 
 impl From<LedgerBurnError> for WithdrawalError {
@@ -278,6 +338,22 @@ pub struct AddCkErc20Token {
     pub ckerc20_ledger_id: Principal,
 }
 
+/// A dangling or orphaned reference found while scanning the event log.
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ConsistencyViolation {
+    /// A mint event references a deposit that was never accepted.
+    MintWithoutDeposit { event_source: events::EventSource },
+    /// A deposit was minted more than once.
+    DuplicateMint { event_source: events::EventSource },
+    /// A transaction event references a withdrawal that was never accepted.
+    TransactionWithoutWithdrawalRequest { withdrawal_id: Nat },
+    /// A signed, replaced or finalized transaction event references a withdrawal
+    /// for which no transaction was created.
+    TransactionEventWithoutCreatedTransaction { withdrawal_id: Nat },
+    /// A reimbursement references a withdrawal that was never accepted.
+    ReimbursementWithoutWithdrawalRequest { withdrawal_id: Nat },
+}
+
 pub mod events {
     use crate::lifecycle::init::InitArg;
     use crate::lifecycle::upgrade::UpgradeArg;
@@ -288,12 +364,21 @@ pub mod events {
     pub struct GetEventsArg {
         pub start: u64,
         pub length: u64,
+        /// Opaque cursor returned as `next_cursor` by a previous call.
+        /// When set, `start` is ignored and events are returned from the cursor on.
+        pub cursor: Option<Vec<u8>>,
+        /// When set, only events whose payload is of one of the given kinds are returned.
+        /// The filter is applied to the selected range of events, so that fewer than
+        /// `length` events may be returned even if more matching events exist.
+        pub kinds: Option<Vec<EventKind>>,
     }
 
     #[derive(CandidType, Deserialize, Debug, Clone)]
     pub struct GetEventsResult {
         pub events: Vec<Event>,
         pub total_event_count: u64,
+        /// Opaque cursor to pass in the next call to continue after the returned events.
+        pub next_cursor: Option<Vec<u8>>,
     }
 
     #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -308,6 +393,15 @@ pub mod events {
         pub log_index: Nat,
     }
 
+    impl From<crate::eth_logs::EventSource> for EventSource {
+        fn from(source: crate::eth_logs::EventSource) -> Self {
+            Self {
+                transaction_hash: source.transaction_hash.to_string(),
+                log_index: source.log_index.into(),
+            }
+        }
+    }
+
     #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
     pub enum ReimbursementIndex {
         CkEth {
@@ -337,6 +431,16 @@ pub mod events {
         pub value: Nat,
         pub data: ByteBuf,
         pub access_list: Vec<AccessListItem>,
+        /// Only set for EIP-4844 (type-3) blob-carrying transactions.
+        pub blob: Option<BlobTransactionFields>,
+    }
+
+    /// The fields specific to EIP-4844 (type-3) blob-carrying transactions.
+    /// See <https://eips.ethereum.org/EIPS/eip-4844>.
+    #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
+    pub struct BlobTransactionFields {
+        pub max_fee_per_blob_gas: Nat,
+        pub blob_versioned_hashes: Vec<String>,
     }
 
     #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -353,6 +457,8 @@ pub mod events {
         pub gas_used: Nat,
         pub status: TransactionStatus,
         pub transaction_hash: String,
+        /// Only set for EIP-4844 (type-3) blob-carrying transactions.
+        pub blob_gas_used: Option<Nat>,
     }
 
     #[derive(CandidType, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -468,5 +574,81 @@ pub mod events {
         QuarantinedReimbursement {
             index: ReimbursementIndex,
         },
+        AcceptedWithdrawalIdempotencyKey {
+            caller: Principal,
+            idempotency_key: [u8; 32],
+            ledger_burn_index: Nat,
+            accepted_at: u64,
+        },
+    }
+
+    /// The kind of an [`EventPayload`], used to filter events.
+    #[derive(CandidType, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum EventKind {
+        Init,
+        Upgrade,
+        AcceptedDeposit,
+        AcceptedErc20Deposit,
+        InvalidDeposit,
+        MintedCkEth,
+        SyncedToBlock,
+        SyncedErc20ToBlock,
+        AcceptedEthWithdrawalRequest,
+        CreatedTransaction,
+        SignedTransaction,
+        ReplacedTransaction,
+        FinalizedTransaction,
+        ReimbursedEthWithdrawal,
+        ReimbursedErc20Withdrawal,
+        SkippedBlock,
+        AddedCkErc20Token,
+        AcceptedErc20WithdrawalRequest,
+        FailedErc20WithdrawalRequest,
+        MintedCkErc20,
+        QuarantinedDeposit,
+        QuarantinedReimbursement,
+        AcceptedWithdrawalIdempotencyKey,
+    }
+
+    impl EventPayload {
+        pub fn kind(&self) -> EventKind {
+            match self {
+                EventPayload::Init(_) => EventKind::Init,
+                EventPayload::Upgrade(_) => EventKind::Upgrade,
+                EventPayload::AcceptedDeposit { .. } => EventKind::AcceptedDeposit,
+                EventPayload::AcceptedErc20Deposit { .. } => EventKind::AcceptedErc20Deposit,
+                EventPayload::InvalidDeposit { .. } => EventKind::InvalidDeposit,
+                EventPayload::MintedCkEth { .. } => EventKind::MintedCkEth,
+                EventPayload::SyncedToBlock { .. } => EventKind::SyncedToBlock,
+                EventPayload::SyncedErc20ToBlock { .. } => EventKind::SyncedErc20ToBlock,
+                EventPayload::AcceptedEthWithdrawalRequest { .. } => {
+                    EventKind::AcceptedEthWithdrawalRequest
+                }
+                EventPayload::CreatedTransaction { .. } => EventKind::CreatedTransaction,
+                EventPayload::SignedTransaction { .. } => EventKind::SignedTransaction,
+                EventPayload::ReplacedTransaction { .. } => EventKind::ReplacedTransaction,
+                EventPayload::FinalizedTransaction { .. } => EventKind::FinalizedTransaction,
+                EventPayload::ReimbursedEthWithdrawal { .. } => EventKind::ReimbursedEthWithdrawal,
+                EventPayload::ReimbursedErc20Withdrawal { .. } => {
+                    EventKind::ReimbursedErc20Withdrawal
+                }
+                EventPayload::SkippedBlock { .. } => EventKind::SkippedBlock,
+                EventPayload::AddedCkErc20Token { .. } => EventKind::AddedCkErc20Token,
+                EventPayload::AcceptedErc20WithdrawalRequest { .. } => {
+                    EventKind::AcceptedErc20WithdrawalRequest
+                }
+                EventPayload::FailedErc20WithdrawalRequest { .. } => {
+                    EventKind::FailedErc20WithdrawalRequest
+                }
+                EventPayload::MintedCkErc20 { .. } => EventKind::MintedCkErc20,
+                EventPayload::QuarantinedDeposit { .. } => EventKind::QuarantinedDeposit,
+                EventPayload::QuarantinedReimbursement { .. } => {
+                    EventKind::QuarantinedReimbursement
+                }
+                EventPayload::AcceptedWithdrawalIdempotencyKey { .. } => {
+                    EventKind::AcceptedWithdrawalIdempotencyKey
+                }
+            }
+        }
     }
 }
@@ -24,6 +24,18 @@ impl From<Erc20WithdrawalRequest> for RetrieveErc20Request {
     }
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub struct TokenStats {
+    pub ckerc20_token_symbol: String,
+    pub erc20_contract_address: String,
+    /// Total amount of ERC-20 tokens sent by successfully finalized withdrawals.
+    pub total_withdrawn: Nat,
+    /// Number of withdrawals whose transaction is not finalized yet.
+    pub pending_withdrawal_count: u64,
+    /// Block number of the most recently finalized withdrawal transaction, if any.
+    pub last_finalized_block_number: Option<Nat>,
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
 pub enum WithdrawErc20Error {
     TokenNotSupported {
@@ -40,6 +52,9 @@ pub enum WithdrawErc20Error {
         error: LedgerError,
     },
     TemporarilyUnavailable(String),
+    MinterPaused {
+        reason: String,
+    },
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
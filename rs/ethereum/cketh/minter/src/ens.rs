@@ -0,0 +1,150 @@
+//! Resolution of [ENS](https://docs.ens.domains/) names to Ethereum addresses.
+use crate::eth_rpc::{BlockSpec, BlockTag, Data};
+use crate::eth_rpc_client::requests::EthCallParams;
+use crate::eth_rpc_client::EthRpcClient;
+use ic_ethereum_types::Address;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[cfg(test)]
+mod tests;
+
+/// Address of the ENS registry, which is the same on Ethereum mainnet and Sepolia.
+pub const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+
+/// Selector of `resolver(bytes32)` of the ENS registry.
+const RESOLVER_SELECTOR: [u8; 4] = hex_literal::hex!("0178b8bf");
+
+/// Selector of `addr(bytes32)` of an ENS resolver.
+const ADDR_SELECTOR: [u8; 4] = hex_literal::hex!("3b3b57de");
+
+const MAX_NAME_LENGTH: usize = 255;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EnsResolutionError {
+    InvalidName(String),
+    NoResolver,
+    NoAddress,
+    RpcError(String),
+    UnexpectedResponse(String),
+}
+
+impl Display for EnsResolutionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EnsResolutionError::InvalidName(error) => write!(f, "invalid ENS name: {}", error),
+            EnsResolutionError::NoResolver => write!(f, "no resolver is set for the name"),
+            EnsResolutionError::NoAddress => write!(f, "no address is set for the name"),
+            EnsResolutionError::RpcError(error) => {
+                write!(f, "failed to query Ethereum: {}", error)
+            }
+            EnsResolutionError::UnexpectedResponse(response) => {
+                write!(f, "unexpected response: {}", response)
+            }
+        }
+    }
+}
+
+/// Validates the given ENS name and returns its normalized (lowercase) form.
+///
+/// Only names consisting of ASCII letters, digits and hyphens are supported,
+/// since the full ENS normalization (ENSIP-15) is not implemented.
+pub fn validate_ens_name(name: &str) -> Result<String, EnsResolutionError> {
+    if name.len() > MAX_NAME_LENGTH {
+        return Err(EnsResolutionError::InvalidName(format!(
+            "name is longer than {} bytes",
+            MAX_NAME_LENGTH
+        )));
+    }
+    if !name.contains('.') {
+        return Err(EnsResolutionError::InvalidName(
+            "name must have at least two labels".to_string(),
+        ));
+    }
+    for label in name.split('.') {
+        if label.is_empty() {
+            return Err(EnsResolutionError::InvalidName(
+                "name contains an empty label".to_string(),
+            ));
+        }
+        if let Some(c) = label
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && *c != '-')
+        {
+            return Err(EnsResolutionError::InvalidName(format!(
+                "unsupported character '{}'",
+                c
+            )));
+        }
+    }
+    Ok(name.to_ascii_lowercase())
+}
+
+/// Computes the [namehash](https://docs.ens.domains/resolution/names#namehash) of a normalized name.
+pub fn namehash(name: &str) -> [u8; 32] {
+    name.rsplit('.').fold([0_u8; 32], |node, label| {
+        let mut buf = [0_u8; 64];
+        buf[..32].copy_from_slice(&node);
+        buf[32..].copy_from_slice(&ic_crypto_sha3::Keccak256::hash(label.as_bytes()));
+        ic_crypto_sha3::Keccak256::hash(buf)
+    })
+}
+
+/// Resolves the given ENS name to an Ethereum address by querying the ENS registry
+/// for the resolver of the name and then the resolver for the address.
+///
+/// Both calls are made against the latest finalized block, so that a recent change
+/// of the ENS records cannot be reverted by a reorganization.
+pub async fn resolve_ens_name(
+    client: &EthRpcClient,
+    name: &str,
+) -> Result<Address, EnsResolutionError> {
+    let node = namehash(&validate_ens_name(name)?);
+    let registry =
+        Address::from_str(ENS_REGISTRY_ADDRESS).expect("BUG: invalid ENS registry address");
+    let resolver = call_returning_address(client, registry, RESOLVER_SELECTOR, node).await?;
+    if resolver == Address::ZERO {
+        return Err(EnsResolutionError::NoResolver);
+    }
+    let address = call_returning_address(client, resolver, ADDR_SELECTOR, node).await?;
+    if address == Address::ZERO {
+        return Err(EnsResolutionError::NoAddress);
+    }
+    Ok(address)
+}
+
+async fn call_returning_address(
+    client: &EthRpcClient,
+    to: Address,
+    selector: [u8; 4],
+    node: [u8; 32],
+) -> Result<Address, EnsResolutionError> {
+    let mut data = Vec::with_capacity(36);
+    data.extend_from_slice(&selector);
+    data.extend_from_slice(&node);
+    let result = client
+        .eth_call(EthCallParams {
+            to,
+            data: Data(data),
+            block: BlockSpec::Tag(BlockTag::Finalized),
+        })
+        .await
+        .map_err(|e| EnsResolutionError::RpcError(format!("{:?}", e)))?;
+    decode_address(&result.0)
+}
+
+/// Decodes an ABI-encoded address, i.e., a 32-byte word whose first 12 bytes are zero.
+fn decode_address(word: &[u8]) -> Result<Address, EnsResolutionError> {
+    match <[u8; 32]>::try_from(word) {
+        Ok(word) if word[..12].iter().all(|b| *b == 0) => {
+            let mut address = [0_u8; 20];
+            address.copy_from_slice(&word[12..]);
+            Ok(Address::new(address))
+        }
+        _ => Err(EnsResolutionError::UnexpectedResponse(format!(
+            "0x{}",
+            hex::encode(word)
+        ))),
+    }
+}
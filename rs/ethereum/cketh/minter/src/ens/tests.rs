@@ -0,0 +1,88 @@
+mod validate_ens_name {
+    use crate::ens::{validate_ens_name, EnsResolutionError};
+    use assert_matches::assert_matches;
+
+    #[test]
+    fn should_normalize_valid_name() {
+        assert_eq!(
+            validate_ens_name("Vitalik.eth"),
+            Ok("vitalik.eth".to_string())
+        );
+        assert_eq!(
+            validate_ens_name("my-wallet.sub.eth"),
+            Ok("my-wallet.sub.eth".to_string())
+        );
+    }
+
+    #[test]
+    fn should_fail_on_invalid_name() {
+        for name in [
+            "",
+            "eth",
+            ".eth",
+            "vitalik..eth",
+            "vitalik.eth.",
+            "vita lik.eth",
+        ] {
+            assert_matches!(
+                validate_ens_name(name),
+                Err(EnsResolutionError::InvalidName(_)),
+                "unexpected result for {name}"
+            );
+        }
+        assert_matches!(
+            validate_ens_name(&format!("{}.eth", "a".repeat(256))),
+            Err(EnsResolutionError::InvalidName(_))
+        );
+    }
+}
+
+mod namehash {
+    use crate::ens::namehash;
+    use hex_literal::hex;
+
+    // Test vectors from EIP-137.
+    #[test]
+    fn should_compute_namehash() {
+        assert_eq!(
+            namehash("eth"),
+            hex!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("foo.eth"),
+            hex!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+    }
+}
+
+mod decode_address {
+    use crate::ens::{decode_address, EnsResolutionError};
+    use assert_matches::assert_matches;
+    use hex_literal::hex;
+    use ic_ethereum_types::Address;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_decode_abi_encoded_address() {
+        assert_eq!(
+            decode_address(&hex!(
+                "000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"
+            )),
+            Ok(Address::from_str("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap())
+        );
+    }
+
+    #[test]
+    fn should_fail_on_malformed_word() {
+        assert_matches!(
+            decode_address(&hex!("d8da6bf26964af9d7eed9e03e53415d37aa96045")),
+            Err(EnsResolutionError::UnexpectedResponse(_))
+        );
+        assert_matches!(
+            decode_address(&hex!(
+                "010000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045"
+            )),
+            Err(EnsResolutionError::UnexpectedResponse(_))
+        );
+    }
+}
@@ -180,6 +180,8 @@ impl std::str::FromStr for Hash {
 
 impl HttpResponsePayload for Hash {}
 
+impl HttpResponsePayload for Data {}
+
 /// Block tags.
 /// See <https://ethereum.org/en/developers/docs/apis/json-rpc/#default-block>
 #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -196,6 +198,9 @@ pub enum BlockTag {
     /// See
     /// <https://www.alchemy.com/overviews/ethereum-commitment-levels#what-are-ethereum-commitment-levels>
     Finalized,
+    /// The pending block, i.e. the block currently being built on top of the latest mined block.
+    /// Most recent but least safe commitment level.
+    Pending,
 }
 
 impl From<CandidBlockTag> for BlockTag {
@@ -204,6 +209,7 @@ impl From<CandidBlockTag> for BlockTag {
             CandidBlockTag::Latest => BlockTag::Latest,
             CandidBlockTag::Safe => BlockTag::Safe,
             CandidBlockTag::Finalized => BlockTag::Finalized,
+            CandidBlockTag::Pending => BlockTag::Pending,
         }
     }
 }
@@ -214,6 +220,7 @@ impl From<BlockTag> for CandidBlockTag {
             BlockTag::Latest => CandidBlockTag::Latest,
             BlockTag::Safe => CandidBlockTag::Safe,
             BlockTag::Finalized => CandidBlockTag::Finalized,
+            BlockTag::Pending => CandidBlockTag::Pending,
         }
     }
 }
@@ -224,6 +231,7 @@ impl Display for BlockTag {
             Self::Latest => write!(f, "latest"),
             Self::Safe => write!(f, "safe"),
             Self::Finalized => write!(f, "finalized"),
+            Self::Pending => write!(f, "pending"),
         }
     }
 }
@@ -257,6 +265,7 @@ impl std::str::FromStr for BlockSpec {
             "latest" => BlockTag::Latest,
             "safe" => BlockTag::Safe,
             "finalized" => BlockTag::Finalized,
+            "pending" => BlockTag::Pending,
             _ => return Err(format!("unknown block tag '{s}'")),
         }))
     }
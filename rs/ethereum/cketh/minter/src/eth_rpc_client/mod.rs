@@ -7,7 +7,7 @@ use crate::eth_rpc::{
 use crate::eth_rpc_client::providers::{
     EthereumProvider, RpcNodeProvider, SepoliaProvider, MAINNET_PROVIDERS, SEPOLIA_PROVIDERS,
 };
-use crate::eth_rpc_client::requests::GetTransactionCountParams;
+use crate::eth_rpc_client::requests::{EthCallParams, GetTransactionCountParams};
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::lifecycle::EthereumNetwork;
 use crate::logs::{PrintProxySink, DEBUG, INFO, TRACE_HTTP};
@@ -285,6 +285,15 @@ impl EthRpcClient {
         )
         .await
     }
+
+    pub async fn eth_call(&self, params: EthCallParams) -> Result<Data, MultiCallError<Data>> {
+        // The calls issued by the minter return a single 32-byte word,
+        // i.e., a response of about 100 bytes.
+        let results: MultiCallResults<Data> = self
+            .parallel_call("eth_call", params, ResponseSizeEstimate::new(256))
+            .await;
+        results.reduce_with_equality()
+    }
 }
 
 /// Aggregates responses of different providers to the same query.
@@ -776,6 +785,7 @@ fn into_evm_block_tag(block: BlockSpec) -> EvmBlockTag {
         BlockSpec::Tag(BlockTag::Latest) => EvmBlockTag::Latest,
         BlockSpec::Tag(BlockTag::Safe) => EvmBlockTag::Safe,
         BlockSpec::Tag(BlockTag::Finalized) => EvmBlockTag::Finalized,
+        BlockSpec::Tag(BlockTag::Pending) => EvmBlockTag::Pending,
     }
 }
 
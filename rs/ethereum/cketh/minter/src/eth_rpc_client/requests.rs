@@ -1,4 +1,4 @@
-use crate::eth_rpc::BlockSpec;
+use crate::eth_rpc::{BlockSpec, Data};
 use ic_ethereum_types::Address;
 use serde::Serialize;
 
@@ -17,3 +17,33 @@ impl From<GetTransactionCountParams> for (Address, BlockSpec) {
         (params.address, params.block)
     }
 }
+
+/// Parameters of the [`eth_call`](https://ethereum.org/en/developers/docs/apis/json-rpc/#eth_call) call.
+#[derive(Debug, Serialize, Clone)]
+#[serde(into = "(TransactionCall, BlockSpec)")]
+pub struct EthCallParams {
+    /// The address of the called contract.
+    pub to: Address,
+    /// The ABI-encoded call data.
+    pub data: Data,
+    /// Integer block number, or a block tag, against which the call is executed.
+    pub block: BlockSpec,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TransactionCall {
+    to: Address,
+    data: Data,
+}
+
+impl From<EthCallParams> for (TransactionCall, BlockSpec) {
+    fn from(params: EthCallParams) -> Self {
+        (
+            TransactionCall {
+                to: params.to,
+                data: params.data,
+            },
+            params.block,
+        )
+    }
+}
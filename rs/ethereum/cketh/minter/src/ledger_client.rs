@@ -8,6 +8,7 @@ use ic_canister_log::log;
 use icrc_ledger_client_cdk::{CdkRuntime, ICRC1Client};
 use icrc_ledger_types::icrc1::account::Account;
 use icrc_ledger_types::icrc1::transfer::Memo;
+use icrc_ledger_types::icrc2::allowance::AllowanceArgs;
 use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
 use num_traits::ToPrimitive;
 
@@ -150,17 +151,59 @@ impl LedgerClient {
                 };
                 Err(burn_error)
             }
-            Err((error_code, message)) => {
-                let err_msg = format!(
-                    "failed to call {} ledger with error_code: {error_code} and message: {message}",
-                    self.token_symbol
-                );
-                log!(DEBUG, "[burn]: {err_msg}",);
-                Err(LedgerBurnError::TemporarilyUnavailable {
-                    message: err_msg,
-                    ledger: self.ck_ledger(),
-                })
-            }
+            Err(e) => Err(self.unreachable_error(e)),
+        }
+    }
+
+    /// Checks that the given account holds at least `amount` tokens and allowed the minter
+    /// to spend them, without burning anything. A subsequent [`Self::burn_from`] can still
+    /// fail if the balance or the allowance changes in the meantime.
+    pub async fn check_burn_from<A: Into<Nat>>(
+        &self,
+        from: Account,
+        amount: A,
+    ) -> Result<(), LedgerBurnError> {
+        let amount = amount.into();
+        let allowance = self
+            .client
+            .allowance(AllowanceArgs {
+                account: from,
+                spender: ic_cdk::id().into(),
+            })
+            .await
+            .map_err(|e| self.unreachable_error(e))?
+            .allowance;
+        if allowance < amount {
+            return Err(LedgerBurnError::InsufficientAllowance {
+                allowance,
+                failed_burn_amount: amount,
+                ledger: self.ck_ledger(),
+            });
+        }
+        let balance = self
+            .client
+            .balance_of(from)
+            .await
+            .map_err(|e| self.unreachable_error(e))?;
+        if balance < amount {
+            return Err(LedgerBurnError::InsufficientFunds {
+                balance,
+                failed_burn_amount: amount,
+                ledger: self.ck_ledger(),
+            });
+        }
+        Ok(())
+    }
+
+    fn unreachable_error(&self, (error_code, message): (i32, String)) -> LedgerBurnError {
+        let err_msg = format!(
+            "failed to call {} ledger with error_code: {error_code} and message: {message}",
+            self.token_symbol
+        );
+        log!(DEBUG, "[burn]: {err_msg}",);
+        LedgerBurnError::TemporarilyUnavailable {
+            message: err_msg,
+            ledger: self.ck_ledger(),
         }
     }
 
@@ -83,6 +83,7 @@ impl TryFrom<InitArg> for State {
             last_scraped_block_number,
             last_erc20_scraped_block_number: last_scraped_block_number,
             last_observed_block_number: None,
+            reorg_stats: Default::default(),
             events_to_mint: Default::default(),
             minted_events: Default::default(),
             ecdsa_public_key: None,
@@ -92,8 +93,11 @@ impl TryFrom<InitArg> for State {
             active_tasks: Default::default(),
             http_request_counter: 0,
             last_transaction_price_estimate: None,
+            recent_transaction_price_estimates: Default::default(),
+            withdrawal_idempotency_keys: Default::default(),
             ledger_suite_orchestrator_id: None,
             evm_rpc_id: None,
+            withdrawal_pause_reason: None,
             ckerc20_tokens: Default::default(),
             erc20_balances: Default::default(),
         };
@@ -26,6 +26,22 @@ pub struct UpgradeArg {
     pub last_erc20_scraped_block_number: Option<Nat>,
     #[cbor(n(7), with = "crate::cbor::principal::option")]
     pub evm_rpc_id: Option<Principal>,
+    #[n(8)]
+    pub withdrawal_pause: Option<WithdrawalPause>,
+}
+
+/// Administrative switch to stop or restart accepting ckETH and ckERC20 withdrawals.
+#[derive(CandidType, Deserialize, Clone, Debug, Encode, Decode, PartialEq, Eq)]
+pub enum WithdrawalPause {
+    /// Reject all new withdrawal requests with the given reason.
+    #[n(0)]
+    Paused {
+        #[n(0)]
+        reason: String,
+    },
+    /// Accept withdrawal requests again.
+    #[n(1)]
+    Resumed,
 }
 
 pub fn post_upgrade(upgrade_args: Option<UpgradeArg>) {
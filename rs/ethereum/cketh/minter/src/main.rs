@@ -5,18 +5,19 @@ use ic_cdk_macros::{init, post_upgrade, pre_upgrade, query, update};
 use ic_cketh_minter::address::{validate_address_as_destination, AddressValidationError};
 use ic_cketh_minter::deposit::scrape_logs;
 use ic_cketh_minter::endpoints::ckerc20::{
-    RetrieveErc20Request, WithdrawErc20Arg, WithdrawErc20Error,
-};
-use ic_cketh_minter::endpoints::events::{
-    Event as CandidEvent, EventSource as CandidEventSource, GetEventsArg, GetEventsResult,
+    RetrieveErc20Request, TokenStats, WithdrawErc20Arg, WithdrawErc20Error,
 };
+use ic_cketh_minter::endpoints::events::{Event as CandidEvent, GetEventsArg, GetEventsResult};
 use ic_cketh_minter::endpoints::{
-    AddCkErc20Token, Eip1559TransactionPrice, Eip1559TransactionPriceArg, Erc20Balance,
-    GasFeeEstimate, MinterInfo, RetrieveEthRequest, RetrieveEthStatus, WithdrawalArg,
-    WithdrawalDetail, WithdrawalError, WithdrawalSearchParameter,
+    AddCkErc20Token, ConsistencyViolation, Eip1559TransactionPrice, Eip1559TransactionPriceArg,
+    Erc20Balance, GasFeeEstimate, MinterInfo, ReimbursementInfo, ReorgStats, RetrieveEthRequest,
+    RetrieveEthStatus, RetrieveEthStatusBatchError, WithdrawalArg, WithdrawalDetail,
+    WithdrawalError, WithdrawalSearchParameter, MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE,
 };
+use ic_cketh_minter::ens::resolve_ens_name;
 use ic_cketh_minter::erc20::CkTokenSymbol;
-use ic_cketh_minter::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent};
+use ic_cketh_minter::eth_logs::{ReceivedErc20Event, ReceivedEthEvent};
+use ic_cketh_minter::eth_rpc_client::EthRpcClient;
 use ic_cketh_minter::guard::retrieve_withdraw_guard;
 use ic_cketh_minter::ledger_client::{LedgerBurnError, LedgerClient};
 use ic_cketh_minter::lifecycle::MinterArg;
@@ -215,13 +216,21 @@ async fn get_minter_info() -> MinterInfo {
             ethereum_block_height: Some(s.ethereum_block_height.into()),
             last_observed_block_number: s.last_observed_block_number.map(|n| n.into()),
             eth_balance: Some(s.eth_balance.eth_balance().into()),
-            last_gas_fee_estimate: s.last_transaction_price_estimate.as_ref().map(
-                |(timestamp, estimate)| GasFeeEstimate {
-                    max_fee_per_gas: estimate.estimate_max_fee_per_gas().into(),
-                    max_priority_fee_per_gas: estimate.max_priority_fee_per_gas.into(),
-                    timestamp: *timestamp,
-                },
+            last_gas_fee_estimate: s
+                .last_transaction_price_estimate
+                .as_ref()
+                .map(to_candid_gas_fee_estimate),
+            recent_gas_fee_estimates: Some(
+                s.recent_transaction_price_estimates
+                    .iter()
+                    .map(to_candid_gas_fee_estimate)
+                    .collect(),
             ),
+            last_transaction_nonce: s
+                .eth_transactions
+                .last_transaction_nonce()
+                .map(|nonce| nonce.into()),
+            pending_transaction_count: Some(s.eth_transactions.pending_transaction_count().into()),
             erc20_balances,
             last_eth_scraped_block_number: Some(s.last_scraped_block_number.into()),
             last_erc20_scraped_block_number: Some(s.last_erc20_scraped_block_number.into()),
@@ -230,11 +239,29 @@ async fn get_minter_info() -> MinterInfo {
     })
 }
 
+fn to_candid_gas_fee_estimate(
+    (timestamp, estimate): &(u64, ic_cketh_minter::tx::GasFeeEstimate),
+) -> GasFeeEstimate {
+    GasFeeEstimate {
+        max_fee_per_gas: estimate.estimate_max_fee_per_gas().into(),
+        max_priority_fee_per_gas: estimate.max_priority_fee_per_gas.into(),
+        timestamp: *timestamp,
+    }
+}
+
 #[update]
 async fn withdraw_eth(
-    WithdrawalArg { amount, recipient }: WithdrawalArg,
+    WithdrawalArg {
+        amount,
+        recipient,
+        recipient_ens,
+        idempotency_key,
+    }: WithdrawalArg,
 ) -> Result<RetrieveEthRequest, WithdrawalError> {
     let caller = validate_caller_not_anonymous();
+    if let Some(reason) = read_state(|s| s.withdrawal_pause_reason.clone()) {
+        return Err(WithdrawalError::MinterPaused { reason });
+    }
     let _guard = retrieve_withdraw_guard(caller).unwrap_or_else(|e| {
         ic_cdk::trap(&format!(
             "Failed retrieving guard for principal {}: {:?}",
@@ -242,15 +269,6 @@ async fn withdraw_eth(
         ))
     });
 
-    let destination = validate_address_as_destination(&recipient).map_err(|e| match e {
-        AddressValidationError::Invalid { .. } | AddressValidationError::NotSupported(_) => {
-            ic_cdk::trap(&e.to_string())
-        }
-        AddressValidationError::Blocked(address) => WithdrawalError::RecipientAddressBlocked {
-            address: address.to_string(),
-        },
-    })?;
-
     let amount = Wei::try_from(amount).expect("failed to convert Nat to u256");
 
     let minimum_withdrawal_amount = read_state(|s| s.cketh_minimum_withdrawal_amount);
@@ -260,8 +278,59 @@ async fn withdraw_eth(
         });
     }
 
+    let recipient = match recipient_ens {
+        Some(name) => {
+            if !recipient.is_empty() {
+                return Err(WithdrawalError::InvalidDestination {
+                    reason: "recipient and recipient_ens are mutually exclusive".to_string(),
+                });
+            }
+            // Resolving an ENS name requires HTTPs outcalls paid by the minter.
+            // To prevent them from being triggered for free, the caller must have
+            // enough funds and must have approved the minter to burn them.
+            read_state(LedgerClient::cketh_ledger_from_state)
+                .check_burn_from(caller.into(), amount)
+                .await?;
+            let client = read_state(EthRpcClient::from_state);
+            let address = resolve_ens_name(&client, &name).await.map_err(|e| {
+                WithdrawalError::EnsResolutionFailed {
+                    name: name.clone(),
+                    reason: e.to_string(),
+                }
+            })?;
+            log!(INFO, "[withdraw]: resolved ENS name {name} to {address}");
+            address.to_string()
+        }
+        None => recipient,
+    };
+
+    let destination = validate_address_as_destination(&recipient).map_err(|e| match e {
+        AddressValidationError::Invalid { .. } | AddressValidationError::NotSupported(_) => {
+            WithdrawalError::InvalidDestination {
+                reason: e.to_string(),
+            }
+        }
+        AddressValidationError::Blocked(address) => WithdrawalError::RecipientAddressBlocked {
+            address: address.to_string(),
+        },
+    })?;
+
+    // The minter's address is resolved before burning,
+    // so that it can be returned along with the accepted withdrawal request.
+    let minter_address = state::minter_address().await;
     let client = read_state(LedgerClient::cketh_ledger_from_state);
     let now = ic_cdk::api::time();
+
+    // The idempotency key is checked after the last await preceding the burn and recorded
+    // together with the accepted withdrawal request. In between, the guard above prevents any
+    // other withdrawal of the same caller, so that no key can be used twice.
+    if let Some(original_burn_index) = idempotency_key
+        .and_then(|key| read_state(|s| s.find_withdrawal_by_idempotency_key(caller, key, now)))
+    {
+        return Err(WithdrawalError::DuplicateRequest {
+            original_block_index: Nat::from(original_burn_index.get()),
+        });
+    }
     log!(INFO, "[withdraw]: burning {:?}", amount);
     match client
         .burn_from(
@@ -294,8 +363,22 @@ async fn withdraw_eth(
                     s,
                     EventType::AcceptedEthWithdrawalRequest(withdrawal_request.clone()),
                 );
+                if let Some(idempotency_key) = idempotency_key {
+                    process_event(
+                        s,
+                        EventType::AcceptedWithdrawalIdempotencyKey {
+                            caller,
+                            idempotency_key,
+                            ledger_burn_index: withdrawal_request.ledger_burn_index,
+                            accepted_at: now,
+                        },
+                    );
+                }
             });
-            Ok(RetrieveEthRequest::from(withdrawal_request))
+            Ok(RetrieveEthRequest {
+                block_index: Nat::from(withdrawal_request.ledger_burn_index.get()),
+                minter_address: minter_address.to_string(),
+            })
         }
         Err(e) => Err(WithdrawalError::from(e)),
     }
@@ -307,6 +390,40 @@ async fn retrieve_eth_status(block_index: u64) -> RetrieveEthStatus {
     read_state(|s| s.eth_transactions.transaction_status(&ledger_burn_index))
 }
 
+#[query]
+async fn retrieve_eth_status_batch(
+    block_indices: Vec<Nat>,
+) -> Result<Vec<(Nat, RetrieveEthStatus)>, RetrieveEthStatusBatchError> {
+    use num_traits::ToPrimitive;
+
+    if block_indices.len() > MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE {
+        return Err(RetrieveEthStatusBatchError::TooManyWithdrawalIds {
+            max_withdrawal_ids: MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE as u64,
+        });
+    }
+    read_state(|s| {
+        Ok(block_indices
+            .into_iter()
+            .map(|block_index| {
+                // A block index that does not fit into u64 cannot be a ledger burn index.
+                let status = match block_index.0.to_u64() {
+                    Some(index) => s
+                        .eth_transactions
+                        .transaction_status(&LedgerBurnIndex::new(index)),
+                    None => RetrieveEthStatus::NotFound,
+                };
+                (block_index, status)
+            })
+            .collect())
+    })
+}
+
+#[query]
+async fn get_reimbursement(ledger_burn_index: u64) -> Option<ReimbursementInfo> {
+    let ledger_burn_index = LedgerBurnIndex::new(ledger_burn_index);
+    read_state(|s| s.eth_transactions.reimbursement(&ledger_burn_index))
+}
+
 #[query]
 async fn withdrawal_status(parameter: WithdrawalSearchParameter) -> Vec<WithdrawalDetail> {
     use transactions::WithdrawalRequest::*;
@@ -358,6 +475,9 @@ async fn withdraw_erc20(
 ) -> Result<RetrieveErc20Request, WithdrawErc20Error> {
     validate_ckerc20_active();
     let caller = validate_caller_not_anonymous();
+    if let Some(reason) = read_state(|s| s.withdrawal_pause_reason.clone()) {
+        return Err(WithdrawErc20Error::MinterPaused { reason });
+    }
     let _guard = retrieve_withdraw_guard(caller).unwrap_or_else(|e| {
         ic_cdk::trap(&format!(
             "Failed retrieving guard for principal {}: {:?}",
@@ -486,6 +606,23 @@ async fn withdraw_erc20(
     }
 }
 
+#[query]
+fn get_token_stats(ledger_canister_id: candid::Principal) -> Option<TokenStats> {
+    read_state(|s| {
+        let token = s.find_ck_erc20_token_by_ledger_id(&ledger_canister_id)?;
+        let stats = s
+            .eth_transactions
+            .erc20_withdrawal_stats(&ledger_canister_id);
+        Some(TokenStats {
+            ckerc20_token_symbol: token.ckerc20_token_symbol.to_string(),
+            erc20_contract_address: token.erc20_contract_address.to_string(),
+            total_withdrawn: stats.total_withdrawn.into(),
+            pending_withdrawal_count: stats.pending_withdrawal_count as u64,
+            last_finalized_block_number: stats.last_finalized_block.map(|n| n.into()),
+        })
+    })
+}
+
 async fn estimate_erc20_transaction_fee() -> Option<Wei> {
     lazy_refresh_gas_fee_estimate()
         .await
@@ -503,6 +640,11 @@ fn is_address_blocked(address_string: String) -> bool {
     ic_cketh_minter::blocklist::is_blocked(&address)
 }
 
+#[query]
+fn reorg_stats() -> ReorgStats {
+    read_state(|s| ReorgStats::from(s.reorg_stats.clone()))
+}
+
 #[update]
 async fn add_ckerc20_token(erc20_token: AddCkErc20Token) {
     let orchestrator_id = read_state(|s| s.ledger_suite_orchestrator_id)
@@ -543,18 +685,6 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
 
     const MAX_EVENTS_PER_RESPONSE: u64 = 100;
 
-    fn map_event_source(
-        EventSource {
-            transaction_hash,
-            log_index,
-        }: EventSource,
-    ) -> CandidEventSource {
-        CandidEventSource {
-            transaction_hash: transaction_hash.to_string(),
-            log_index: log_index.into(),
-        }
-    }
-
     fn map_reimbursement_index(index: ReimbursementIndex) -> CandidReimbursementIndex {
         match index {
             ReimbursementIndex::CkEth { ledger_burn_index } => CandidReimbursementIndex::CkEth {
@@ -595,6 +725,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                         .collect(),
                 })
                 .collect(),
+            blob: None,
         }
     }
 
@@ -610,6 +741,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                 TransactionStatus::Failure => CandidTransactionStatus::Failure,
             },
             transaction_hash: receipt.transaction_hash.to_string(),
+            blob_gas_used: None,
         }
     }
 
@@ -656,14 +788,14 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     event_source,
                     reason,
                 } => EP::InvalidDeposit {
-                    event_source: map_event_source(event_source),
+                    event_source: event_source.into(),
                     reason,
                 },
                 EventType::MintedCkEth {
                     event_source,
                     mint_block_index,
                 } => EP::MintedCkEth {
-                    event_source: map_event_source(event_source),
+                    event_source: event_source.into(),
                     mint_block_index: mint_block_index.get().into(),
                 },
                 EventType::SyncedToBlock { block_number } => EP::SyncedToBlock {
@@ -780,7 +912,7 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     ckerc20_token_symbol,
                     erc20_contract_address,
                 } => EP::MintedCkErc20 {
-                    event_source: map_event_source(event_source),
+                    event_source: event_source.into(),
                     mint_block_index: mint_block_index.get().into(),
                     ckerc20_token_symbol,
                     erc20_contract_address: erc20_contract_address.to_string(),
@@ -798,25 +930,68 @@ fn get_events(arg: GetEventsArg) -> GetEventsResult {
                     to_subaccount: to_subaccount.map(|s| s.0),
                 },
                 EventType::QuarantinedDeposit { event_source } => EP::QuarantinedDeposit {
-                    event_source: map_event_source(event_source),
+                    event_source: event_source.into(),
                 },
                 EventType::QuarantinedReimbursement { index } => EP::QuarantinedReimbursement {
                     index: map_reimbursement_index(index),
                 },
+                EventType::AcceptedWithdrawalIdempotencyKey {
+                    caller,
+                    idempotency_key,
+                    ledger_burn_index,
+                    accepted_at,
+                } => EP::AcceptedWithdrawalIdempotencyKey {
+                    caller,
+                    idempotency_key,
+                    ledger_burn_index: ledger_burn_index.get().into(),
+                    accepted_at,
+                },
             },
         }
     }
 
-    let events = storage::with_event_iter(|it| {
-        it.skip(arg.start as usize)
+    /// Version prefix of event cursors, so that their encoding can evolve.
+    const EVENT_CURSOR_VERSION: u8 = 1;
+
+    fn encode_cursor(next_event_index: u64) -> Vec<u8> {
+        let mut cursor = vec![EVENT_CURSOR_VERSION];
+        cursor.extend_from_slice(&next_event_index.to_be_bytes());
+        cursor
+    }
+
+    fn decode_cursor(cursor: &[u8]) -> u64 {
+        match cursor {
+            [EVENT_CURSOR_VERSION, index @ ..] => u64::from_be_bytes(
+                index
+                    .try_into()
+                    .unwrap_or_else(|_| ic_cdk::trap("invalid event cursor")),
+            ),
+            _ => ic_cdk::trap("invalid event cursor"),
+        }
+    }
+
+    let start = match &arg.cursor {
+        Some(cursor) => decode_cursor(cursor),
+        None => arg.start,
+    };
+    let mut events: Vec<_> = storage::with_event_iter(|it| {
+        it.skip(start as usize)
             .take(arg.length.min(MAX_EVENTS_PER_RESPONSE) as usize)
             .map(map_event)
             .collect()
     });
+    let total_event_count = storage::total_event_count();
+    let next_event_index = start
+        .saturating_add(events.len() as u64)
+        .min(total_event_count);
+    if let Some(kinds) = &arg.kinds {
+        events.retain(|event| kinds.contains(&event.payload.kind()));
+    }
 
     GetEventsResult {
+        next_cursor: Some(encode_cursor(next_event_index)),
         events,
-        total_event_count: storage::total_event_count(),
+        total_event_count,
     }
 }
 
@@ -1040,6 +1215,11 @@ fn check_audit_log() {
     })
 }
 
+#[query]
+fn verify_event_consistency() -> Vec<ConsistencyViolation> {
+    ic_cketh_minter::state::audit::verify_event_consistency()
+}
+
 fn main() {}
 
 /// Checks the real candid interface against the one declared in the did file
@@ -3,7 +3,7 @@ use crate::erc20::{CkErc20Token, CkTokenSymbol};
 use crate::eth_logs::{EventSource, ReceivedEvent};
 use crate::eth_rpc::BlockTag;
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
-use crate::lifecycle::upgrade::UpgradeArg;
+use crate::lifecycle::upgrade::{UpgradeArg, WithdrawalPause};
 use crate::lifecycle::EthereumNetwork;
 use crate::logs::DEBUG;
 use crate::map::DedupMultiKeyMap;
@@ -18,8 +18,9 @@ use ic_cdk::api::management_canister::ecdsa::EcdsaPublicKeyResponse;
 use ic_crypto_ecdsa_secp256k1::PublicKey;
 use ic_ethereum_types::Address;
 use std::cell::RefCell;
-use std::collections::{btree_map, BTreeMap, BTreeSet, HashSet};
+use std::collections::{btree_map, BTreeMap, BTreeSet, HashSet, VecDeque};
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 use strum_macros::EnumIter;
 use transactions::EthTransactions;
 
@@ -30,6 +31,20 @@ pub mod transactions;
 #[cfg(test)]
 mod tests;
 
+/// Number of the most recent transaction price estimates kept in
+/// [`State::recent_transaction_price_estimates`].
+pub const MAX_RECENT_TRANSACTION_PRICE_ESTIMATES: usize = 10;
+
+/// Maximum number of idempotency keys kept in [`State::withdrawal_idempotency_keys`].
+pub const MAX_WITHDRAWAL_IDEMPOTENCY_KEYS: usize = 10_000;
+
+/// Duration during which the idempotency key of a withdrawal is retained.
+pub const WITHDRAWAL_IDEMPOTENCY_KEY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn is_idempotency_key_expired(accepted_at: u64, now: u64) -> bool {
+    now.saturating_sub(accepted_at) > WITHDRAWAL_IDEMPOTENCY_KEY_RETENTION.as_nanos() as u64
+}
+
 thread_local! {
     pub static STATE: RefCell<Option<State>> = RefCell::default();
 }
@@ -62,6 +77,8 @@ pub struct State {
     pub last_scraped_block_number: BlockNumber,
     pub last_erc20_scraped_block_number: BlockNumber,
     pub last_observed_block_number: Option<BlockNumber>,
+    /// Reorganizations observed since the last upgrade.
+    pub reorg_stats: ReorgStats,
     pub events_to_mint: BTreeMap<EventSource, ReceivedEvent>,
     pub minted_events: BTreeMap<EventSource, MintedEvent>,
     pub invalid_events: BTreeMap<EventSource, InvalidEventReason>,
@@ -88,6 +105,16 @@ pub struct State {
 
     pub last_transaction_price_estimate: Option<(u64, GasFeeEstimate)>,
 
+    /// The most recent transaction price estimates with their timestamps, oldest first.
+    /// Transient, like `last_transaction_price_estimate`.
+    pub recent_transaction_price_estimates: VecDeque<(u64, GasFeeEstimate)>,
+
+    /// Idempotency keys of the recently accepted ckETH withdrawals, per caller,
+    /// with the burn index of the withdrawal and the time at which it was accepted.
+    /// Rebuilt from `AcceptedWithdrawalIdempotencyKey` events on upgrade and
+    /// bounded by [`MAX_WITHDRAWAL_IDEMPOTENCY_KEYS`].
+    pub withdrawal_idempotency_keys: BTreeMap<(Principal, [u8; 32]), (LedgerBurnIndex, u64)>,
+
     /// Canister ID of the ledger suite orchestrator that
     /// can add new ERC-20 token to the minter
     pub ledger_suite_orchestrator_id: Option<Principal>,
@@ -96,6 +123,10 @@ pub struct State {
     /// handles communication with Ethereum
     pub evm_rpc_id: Option<Principal>,
 
+    /// Reason why withdrawals are administratively paused, if they are.
+    /// While set, new withdrawal requests are rejected.
+    pub withdrawal_pause_reason: Option<String>,
+
     /// ERC-20 tokens that the minter can mint:
     /// - primary key: ledger ID for the ckERC20 token
     /// - secondary key: ERC-20 contract address on Ethereum
@@ -103,6 +134,17 @@ pub struct State {
     pub ckerc20_tokens: DedupMultiKeyMap<Principal, Address, CkTokenSymbol>,
 }
 
+/// Statistics about chain reorganizations, detected when the number of the
+/// latest observed block is lower than the one previously observed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReorgStats {
+    pub total_reorgs: u64,
+    /// Largest difference between the previously and the newly observed block numbers.
+    pub max_depth_blocks: u64,
+    /// Time (in nanoseconds since the UNIX epoch) when the last reorganization was observed.
+    pub last_reorg_timestamp: Option<u64>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum InvalidStateError {
     InvalidTransactionNonce(String),
@@ -466,6 +508,23 @@ impl State {
         self.ethereum_block_height
     }
 
+    /// Records the number of the latest observed block at the given time,
+    /// updating the reorganization statistics if the chain went backwards.
+    pub fn update_last_observed_block_number(&mut self, block_number: BlockNumber, now: u64) {
+        if let Some(previous) = self.last_observed_block_number {
+            if block_number < previous {
+                let depth = previous
+                    .checked_sub(block_number)
+                    .expect("BUG: block number should be smaller");
+                let depth = u64::try_from(depth.into_inner()).unwrap_or(u64::MAX);
+                self.reorg_stats.total_reorgs += 1;
+                self.reorg_stats.max_depth_blocks = self.reorg_stats.max_depth_blocks.max(depth);
+                self.reorg_stats.last_reorg_timestamp = Some(now);
+            }
+        }
+        self.last_observed_block_number = Some(block_number);
+    }
+
     fn upgrade(&mut self, upgrade_args: UpgradeArg) -> Result<(), InvalidStateError> {
         use std::str::FromStr;
 
@@ -478,6 +537,7 @@ impl State {
             erc20_helper_contract_address,
             last_erc20_scraped_block_number,
             evm_rpc_id,
+            withdrawal_pause,
         } = upgrade_args;
         if let Some(nonce) = next_transaction_nonce {
             let nonce = TransactionNonce::try_from(nonce)
@@ -517,6 +577,12 @@ impl State {
         if let Some(evm_id) = evm_rpc_id {
             self.evm_rpc_id = Some(evm_id);
         }
+        if let Some(pause) = withdrawal_pause {
+            self.withdrawal_pause_reason = match pause {
+                WithdrawalPause::Paused { reason } => Some(reason),
+                WithdrawalPause::Resumed => None,
+            };
+        }
         self.validate_config()
     }
 
@@ -559,11 +625,65 @@ impl State {
             other.ledger_suite_orchestrator_id
         );
         ensure_eq!(self.ckerc20_tokens, other.ckerc20_tokens);
+        ensure_eq!(self.withdrawal_pause_reason, other.withdrawal_pause_reason);
 
         self.eth_transactions
             .is_equivalent_to(&other.eth_transactions)
     }
 
+    /// Records a new transaction price estimate, keeping only the
+    /// [`MAX_RECENT_TRANSACTION_PRICE_ESTIMATES`] most recent ones.
+    pub fn record_transaction_price_estimate(&mut self, timestamp: u64, estimate: GasFeeEstimate) {
+        if self.recent_transaction_price_estimates.len() >= MAX_RECENT_TRANSACTION_PRICE_ESTIMATES {
+            self.recent_transaction_price_estimates.pop_front();
+        }
+        self.recent_transaction_price_estimates
+            .push_back((timestamp, estimate.clone()));
+        self.last_transaction_price_estimate = Some((timestamp, estimate));
+    }
+
+    /// Returns the burn index of the withdrawal accepted for the given caller and idempotency key,
+    /// unless it was accepted more than [`WITHDRAWAL_IDEMPOTENCY_KEY_RETENTION`] ago.
+    pub fn find_withdrawal_by_idempotency_key(
+        &self,
+        caller: Principal,
+        idempotency_key: [u8; 32],
+        now: u64,
+    ) -> Option<LedgerBurnIndex> {
+        self.withdrawal_idempotency_keys
+            .get(&(caller, idempotency_key))
+            .filter(|(_burn_index, accepted_at)| !is_idempotency_key_expired(*accepted_at, now))
+            .map(|(burn_index, _accepted_at)| *burn_index)
+    }
+
+    /// Records the idempotency key of an accepted withdrawal.
+    /// Expired keys are discarded and, if there are still [`MAX_WITHDRAWAL_IDEMPOTENCY_KEYS`]
+    /// keys, the oldest one is evicted.
+    pub fn record_withdrawal_idempotency_key(
+        &mut self,
+        caller: Principal,
+        idempotency_key: [u8; 32],
+        burn_index: LedgerBurnIndex,
+        now: u64,
+    ) {
+        self.withdrawal_idempotency_keys
+            .retain(|_key, (_burn_index, accepted_at)| {
+                !is_idempotency_key_expired(*accepted_at, now)
+            });
+        if self.withdrawal_idempotency_keys.len() >= MAX_WITHDRAWAL_IDEMPOTENCY_KEYS {
+            let oldest_key = self
+                .withdrawal_idempotency_keys
+                .iter()
+                .min_by_key(|(_key, (_burn_index, accepted_at))| *accepted_at)
+                .map(|(key, _value)| *key);
+            if let Some(oldest_key) = oldest_key {
+                self.withdrawal_idempotency_keys.remove(&oldest_key);
+            }
+        }
+        self.withdrawal_idempotency_keys
+            .insert((caller, idempotency_key), (burn_index, now));
+    }
+
     pub fn eth_balance(&self) -> &EthBalance {
         &self.eth_balance
     }
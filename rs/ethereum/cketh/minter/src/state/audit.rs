@@ -3,9 +3,13 @@ mod tests;
 
 pub use super::event::{Event, EventType};
 use super::State;
+use crate::endpoints::ConsistencyViolation;
 use crate::erc20::CkTokenSymbol;
+use crate::eth_logs::EventSource;
+use crate::numeric::LedgerBurnIndex;
 use crate::state::transactions::{Reimbursed, ReimbursementIndex};
 use crate::storage::{record_event, with_event_iter};
+use std::collections::BTreeSet;
 
 /// Updates the state to reflect the given state transition.
 // public because it's used in tests since process_event
@@ -152,6 +156,19 @@ pub fn apply_state_transition(state: &mut State, payload: &EventType) {
                 .eth_transactions
                 .record_quarantined_reimbursement(index.clone());
         }
+        EventType::AcceptedWithdrawalIdempotencyKey {
+            caller,
+            idempotency_key,
+            ledger_burn_index,
+            accepted_at,
+        } => {
+            state.record_withdrawal_idempotency_key(
+                *caller,
+                *idempotency_key,
+                *ledger_burn_index,
+                *accepted_at,
+            );
+        }
     }
 }
 
@@ -190,3 +207,103 @@ fn replay_events_internal<T: IntoIterator<Item = Event>>(events: T) -> State {
     }
     state
 }
+
+/// Scans the event log and returns all dangling or orphaned references between events.
+/// The result is empty when the event log is consistent.
+pub fn verify_event_consistency() -> Vec<ConsistencyViolation> {
+    with_event_iter(|iter| verify_event_consistency_internal(iter))
+}
+
+fn verify_event_consistency_internal<T: IntoIterator<Item = Event>>(
+    events: T,
+) -> Vec<ConsistencyViolation> {
+    let mut accepted_deposits: BTreeSet<EventSource> = BTreeSet::new();
+    let mut minted_deposits: BTreeSet<EventSource> = BTreeSet::new();
+    let mut withdrawal_requests: BTreeSet<LedgerBurnIndex> = BTreeSet::new();
+    let mut created_transactions: BTreeSet<LedgerBurnIndex> = BTreeSet::new();
+    let mut violations = Vec::new();
+
+    for event in events {
+        match &event.payload {
+            EventType::AcceptedDeposit(eth_event) => {
+                accepted_deposits.insert(eth_event.source());
+            }
+            EventType::AcceptedErc20Deposit(erc20_event) => {
+                accepted_deposits.insert(erc20_event.source());
+            }
+            EventType::MintedCkEth { event_source, .. }
+            | EventType::MintedCkErc20 { event_source, .. } => {
+                if !accepted_deposits.contains(event_source) {
+                    violations.push(ConsistencyViolation::MintWithoutDeposit {
+                        event_source: (*event_source).into(),
+                    });
+                }
+                if !minted_deposits.insert(*event_source) {
+                    violations.push(ConsistencyViolation::DuplicateMint {
+                        event_source: (*event_source).into(),
+                    });
+                }
+            }
+            EventType::AcceptedEthWithdrawalRequest(request) => {
+                withdrawal_requests.insert(request.ledger_burn_index);
+            }
+            EventType::AcceptedErc20WithdrawalRequest(request) => {
+                withdrawal_requests.insert(request.cketh_ledger_burn_index);
+            }
+            EventType::CreatedTransaction { withdrawal_id, .. } => {
+                if !withdrawal_requests.contains(withdrawal_id) {
+                    violations.push(ConsistencyViolation::TransactionWithoutWithdrawalRequest {
+                        withdrawal_id: withdrawal_id.get().into(),
+                    });
+                }
+                created_transactions.insert(*withdrawal_id);
+            }
+            EventType::SignedTransaction { withdrawal_id, .. }
+            | EventType::ReplacedTransaction { withdrawal_id, .. }
+            | EventType::FinalizedTransaction { withdrawal_id, .. } => {
+                if !created_transactions.contains(withdrawal_id) {
+                    violations.push(
+                        ConsistencyViolation::TransactionEventWithoutCreatedTransaction {
+                            withdrawal_id: withdrawal_id.get().into(),
+                        },
+                    );
+                }
+            }
+            EventType::ReimbursedEthWithdrawal(Reimbursed { burn_in_block, .. }) => {
+                if !withdrawal_requests.contains(burn_in_block) {
+                    violations.push(
+                        ConsistencyViolation::ReimbursementWithoutWithdrawalRequest {
+                            withdrawal_id: burn_in_block.get().into(),
+                        },
+                    );
+                }
+            }
+            EventType::ReimbursedErc20Withdrawal {
+                cketh_ledger_burn_index,
+                ..
+            } => {
+                if !withdrawal_requests.contains(cketh_ledger_burn_index) {
+                    violations.push(
+                        ConsistencyViolation::ReimbursementWithoutWithdrawalRequest {
+                            withdrawal_id: cketh_ledger_burn_index.get().into(),
+                        },
+                    );
+                }
+            }
+            EventType::FailedErc20WithdrawalRequest(request) => {
+                withdrawal_requests.insert(request.ledger_burn_index);
+            }
+            EventType::Init(_)
+            | EventType::Upgrade(_)
+            | EventType::InvalidDeposit { .. }
+            | EventType::SyncedToBlock { .. }
+            | EventType::SyncedErc20ToBlock { .. }
+            | EventType::AddedCkErc20Token(_)
+            | EventType::QuarantinedDeposit { .. }
+            | EventType::QuarantinedReimbursement { .. }
+            | EventType::SkippedBlockForContract { .. }
+            | EventType::AcceptedWithdrawalIdempotencyKey { .. } => {}
+        }
+    }
+    violations
+}
@@ -50,6 +50,67 @@ async fn should_replay_events_for_sepolia() {
     );
 }
 
+mod event_consistency {
+    use crate::endpoints::ConsistencyViolation;
+    use crate::eth_logs::ReceivedEthEvent;
+    use crate::numeric::{BlockNumber, LedgerMintIndex, LogIndex, Wei};
+    use crate::state::audit::{verify_event_consistency_internal, Event, EventType};
+
+    #[test]
+    fn should_return_no_violation_for_consistent_log() {
+        let deposit = received_eth_event();
+        let events = vec![
+            event(EventType::AcceptedDeposit(deposit.clone())),
+            event(EventType::MintedCkEth {
+                event_source: deposit.source(),
+                mint_block_index: LedgerMintIndex::new(1),
+            }),
+        ];
+
+        assert_eq!(verify_event_consistency_internal(events), vec![]);
+    }
+
+    #[test]
+    fn should_detect_mint_without_deposit() {
+        let deposit = received_eth_event();
+        let events = vec![event(EventType::MintedCkEth {
+            event_source: deposit.source(),
+            mint_block_index: LedgerMintIndex::new(1),
+        })];
+
+        assert_eq!(
+            verify_event_consistency_internal(events),
+            vec![ConsistencyViolation::MintWithoutDeposit {
+                event_source: deposit.source().into()
+            }]
+        );
+    }
+
+    fn event(payload: EventType) -> Event {
+        Event {
+            timestamp: 0,
+            payload,
+        }
+    }
+
+    fn received_eth_event() -> ReceivedEthEvent {
+        ReceivedEthEvent {
+            transaction_hash: "0xf1ac37d920fa57d9caeebc7136fea591191250309ffca95ae0e8a7739de89cc2"
+                .parse()
+                .unwrap(),
+            block_number: BlockNumber::new(3960623u128),
+            log_index: LogIndex::from(29u8),
+            from_address: "0xdd2851cdd40ae6536831558dd46db62fac7a844d"
+                .parse()
+                .unwrap(),
+            value: Wei::from(10_000_000_000_000_000_u128),
+            principal: "k2t6j-2nvnp-4zjm3-25dtz-6xhaa-c7boj-5gayf-oj3xs-i43lp-teztq-6ae"
+                .parse()
+                .unwrap(),
+        }
+    }
+}
+
 enum GetEventsFile {
     Mainnet,
     Sepolia,
@@ -449,6 +510,17 @@ impl GetEventsFile {
                 EventPayload::QuarantinedReimbursement { index } => ET::QuarantinedReimbursement {
                     index: map_reimbursement_index(index),
                 },
+                EventPayload::AcceptedWithdrawalIdempotencyKey {
+                    caller,
+                    idempotency_key,
+                    ledger_burn_index,
+                    accepted_at,
+                } => ET::AcceptedWithdrawalIdempotencyKey {
+                    caller,
+                    idempotency_key,
+                    ledger_burn_index: map_nat(ledger_burn_index),
+                    accepted_at,
+                },
             },
         }
     }
@@ -485,6 +557,7 @@ impl GetEventsFile {
         let GetEventsResult {
             mut events,
             total_event_count,
+            ..
         } = get_events(&agent, &self.minter_canister_id(), 0, FIRST_BATCH_SIZE).await;
         while events.len() < total_event_count as usize {
             let mut next_batch = get_events(
@@ -499,7 +572,8 @@ impl GetEventsFile {
 
         let encoded_all_events = Encode!(&GetEventsResult {
             events,
-            total_event_count
+            total_event_count,
+            next_cursor: None,
         })
         .unwrap();
         let mut gz = GzEncoder::new(
@@ -164,6 +164,21 @@ pub enum EventType {
         #[n(1)]
         block_number: BlockNumber,
     },
+    /// The minter accepted the ckETH withdrawal with the given burn index under
+    /// the idempotency key chosen by the caller. Recorded as an event, so that
+    /// retried withdrawals are also recognized after an upgrade.
+    #[n(24)]
+    AcceptedWithdrawalIdempotencyKey {
+        #[cbor(n(0), with = "crate::cbor::principal")]
+        caller: Principal,
+        #[cbor(n(1), with = "minicbor::bytes")]
+        idempotency_key: [u8; 32],
+        #[cbor(n(2), with = "crate::cbor::id")]
+        ledger_burn_index: LedgerBurnIndex,
+        /// The IC time at which the withdrawal was accepted.
+        #[n(3)]
+        accepted_at: u64,
+    },
 }
 
 impl ReceivedEvent {
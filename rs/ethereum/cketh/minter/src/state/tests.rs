@@ -4,7 +4,7 @@ use crate::eth_logs::{EventSource, ReceivedErc20Event, ReceivedEthEvent, Receive
 use crate::eth_rpc::{BlockTag, Hash};
 use crate::eth_rpc_client::responses::{TransactionReceipt, TransactionStatus};
 use crate::lifecycle::init::InitArg;
-use crate::lifecycle::upgrade::UpgradeArg;
+use crate::lifecycle::upgrade::{UpgradeArg, WithdrawalPause};
 use crate::lifecycle::EthereumNetwork;
 use crate::map::DedupMultiKeyMap;
 use crate::numeric::{
@@ -290,9 +290,194 @@ fn received_erc20_event() -> ReceivedErc20Event {
     }
 }
 
+mod reorg_stats {
+    use crate::numeric::BlockNumber;
+    use crate::state::tests::initial_state;
+    use crate::state::ReorgStats;
+
+    #[test]
+    fn should_not_record_reorg_when_block_number_increases() {
+        let mut state = initial_state();
+
+        state.update_last_observed_block_number(BlockNumber::from(100_u64), 1);
+        state.update_last_observed_block_number(BlockNumber::from(100_u64), 2);
+        state.update_last_observed_block_number(BlockNumber::from(110_u64), 3);
+
+        assert_eq!(
+            state.last_observed_block_number,
+            Some(BlockNumber::from(110_u64))
+        );
+        assert_eq!(state.reorg_stats, ReorgStats::default());
+    }
+
+    #[test]
+    fn should_record_reorgs() {
+        let mut state = initial_state();
+
+        state.update_last_observed_block_number(BlockNumber::from(100_u64), 1);
+        state.update_last_observed_block_number(BlockNumber::from(95_u64), 2);
+        state.update_last_observed_block_number(BlockNumber::from(105_u64), 3);
+        state.update_last_observed_block_number(BlockNumber::from(103_u64), 4);
+
+        assert_eq!(
+            state.last_observed_block_number,
+            Some(BlockNumber::from(103_u64))
+        );
+        assert_eq!(
+            state.reorg_stats,
+            ReorgStats {
+                total_reorgs: 2,
+                max_depth_blocks: 5,
+                last_reorg_timestamp: Some(4),
+            }
+        );
+    }
+}
+
+mod withdrawal_idempotency_keys {
+    use crate::numeric::LedgerBurnIndex;
+    use crate::state::audit::{apply_state_transition, EventType};
+    use crate::state::tests::initial_state;
+    use crate::state::{MAX_WITHDRAWAL_IDEMPOTENCY_KEYS, WITHDRAWAL_IDEMPOTENCY_KEY_RETENTION};
+    use candid::Principal;
+
+    const RETENTION_NANOS: u64 = WITHDRAWAL_IDEMPOTENCY_KEY_RETENTION.as_nanos() as u64;
+
+    #[test]
+    fn should_find_withdrawal_by_idempotency_key_of_same_caller() {
+        let mut state = initial_state();
+        let caller = Principal::from_slice(&[1_u8; 29]);
+        let other_caller = Principal::from_slice(&[2_u8; 29]);
+        let key = [42_u8; 32];
+        let burn_index = LedgerBurnIndex::new(7);
+
+        state.record_withdrawal_idempotency_key(caller, key, burn_index, 0);
+
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, key, RETENTION_NANOS),
+            Some(burn_index)
+        );
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, [43_u8; 32], 0),
+            None
+        );
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(other_caller, key, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn should_restore_idempotency_key_when_replaying_event() {
+        let mut state = initial_state();
+        let caller = Principal::from_slice(&[1_u8; 29]);
+        let key = [42_u8; 32];
+        let burn_index = LedgerBurnIndex::new(7);
+
+        apply_state_transition(
+            &mut state,
+            &EventType::AcceptedWithdrawalIdempotencyKey {
+                caller,
+                idempotency_key: key,
+                ledger_burn_index: burn_index,
+                accepted_at: 0,
+            },
+        );
+
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, key, RETENTION_NANOS),
+            Some(burn_index)
+        );
+    }
+
+    #[test]
+    fn should_expire_idempotency_keys() {
+        let mut state = initial_state();
+        let caller = Principal::from_slice(&[1_u8; 29]);
+
+        state.record_withdrawal_idempotency_key(caller, [1_u8; 32], LedgerBurnIndex::new(1), 0);
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, [1_u8; 32], RETENTION_NANOS + 1),
+            None
+        );
+
+        state.record_withdrawal_idempotency_key(
+            caller,
+            [2_u8; 32],
+            LedgerBurnIndex::new(2),
+            RETENTION_NANOS + 1,
+        );
+        assert_eq!(state.withdrawal_idempotency_keys.len(), 1);
+    }
+
+    #[test]
+    fn should_evict_oldest_idempotency_key_when_full() {
+        let mut state = initial_state();
+        let caller = Principal::from_slice(&[1_u8; 29]);
+        let key = |i: u64| {
+            let mut key = [0_u8; 32];
+            key[..8].copy_from_slice(&i.to_be_bytes());
+            key
+        };
+
+        for i in 0..=MAX_WITHDRAWAL_IDEMPOTENCY_KEYS as u64 {
+            state.record_withdrawal_idempotency_key(caller, key(i), LedgerBurnIndex::new(i), i);
+        }
+
+        assert_eq!(
+            state.withdrawal_idempotency_keys.len(),
+            MAX_WITHDRAWAL_IDEMPOTENCY_KEYS
+        );
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, key(0), 0),
+            None
+        );
+        assert_eq!(
+            state.find_withdrawal_by_idempotency_key(caller, key(1), 1),
+            Some(LedgerBurnIndex::new(1))
+        );
+    }
+}
+
+mod transaction_price_estimates {
+    use crate::numeric::WeiPerGas;
+    use crate::state::tests::initial_state;
+    use crate::state::MAX_RECENT_TRANSACTION_PRICE_ESTIMATES;
+    use crate::tx::GasFeeEstimate;
+
+    #[test]
+    fn should_keep_most_recent_estimates() {
+        let mut state = initial_state();
+        let estimate = |timestamp: u64| GasFeeEstimate {
+            base_fee_per_gas: WeiPerGas::new(timestamp as u128),
+            max_priority_fee_per_gas: WeiPerGas::new(1_000_000),
+        };
+        let num_estimates = MAX_RECENT_TRANSACTION_PRICE_ESTIMATES as u64 + 5;
+
+        for timestamp in 0..num_estimates {
+            state.record_transaction_price_estimate(timestamp, estimate(timestamp));
+        }
+
+        assert_eq!(
+            state.last_transaction_price_estimate,
+            Some((num_estimates - 1, estimate(num_estimates - 1)))
+        );
+        assert_eq!(
+            state
+                .recent_transaction_price_estimates
+                .iter()
+                .cloned()
+                .collect::<Vec<_>>(),
+            (5..num_estimates)
+                .map(|timestamp| (timestamp, estimate(timestamp)))
+                .collect::<Vec<_>>()
+        );
+    }
+}
+
 mod upgrade {
     use crate::eth_rpc::BlockTag;
-    use crate::lifecycle::upgrade::UpgradeArg;
+    use crate::lifecycle::upgrade::{UpgradeArg, WithdrawalPause};
     use crate::lifecycle::EthereumNetwork;
     use crate::numeric::{TransactionNonce, Wei};
     use crate::state::tests::initial_state;
@@ -396,6 +581,41 @@ mod upgrade {
         );
         assert_eq!(state.ethereum_block_height, BlockTag::Safe);
     }
+
+    #[test]
+    fn should_pause_and_resume_withdrawals() {
+        let mut state = initial_state();
+        assert_eq!(state.withdrawal_pause_reason, None);
+
+        state
+            .upgrade(UpgradeArg {
+                withdrawal_pause: Some(WithdrawalPause::Paused {
+                    reason: "incident response".to_string(),
+                }),
+                ..Default::default()
+            })
+            .expect("valid upgrade args");
+        assert_eq!(
+            state.withdrawal_pause_reason,
+            Some("incident response".to_string())
+        );
+
+        state
+            .upgrade(UpgradeArg::default())
+            .expect("valid upgrade args");
+        assert_eq!(
+            state.withdrawal_pause_reason,
+            Some("incident response".to_string())
+        );
+
+        state
+            .upgrade(UpgradeArg {
+                withdrawal_pause: Some(WithdrawalPause::Resumed),
+                ..Default::default()
+            })
+            .expect("valid upgrade args");
+        assert_eq!(state.withdrawal_pause_reason, None);
+    }
 }
 
 mod erc20 {
@@ -559,6 +779,7 @@ fn arb_block_tag() -> impl Strategy<Value = CandidBlockTag> {
         Just(CandidBlockTag::Safe),
         Just(CandidBlockTag::Latest),
         Just(CandidBlockTag::Finalized),
+        Just(CandidBlockTag::Pending),
     ]
 }
 
@@ -616,6 +837,7 @@ prop_compose! {
         erc20_helper_contract_address in proptest::option::of(arb_address()),
         last_erc20_scraped_block_number in proptest::option::of(arb_nat()),
         evm_rpc_id in proptest::option::of(arb_principal()),
+        withdrawal_pause in proptest::option::of(arb_withdrawal_pause()),
     ) -> UpgradeArg {
         UpgradeArg {
             ethereum_contract_address: contract_address.map(|addr| addr.to_string()),
@@ -625,11 +847,19 @@ prop_compose! {
             ledger_suite_orchestrator_id,
             erc20_helper_contract_address: erc20_helper_contract_address.map(|addr| addr.to_string()),
             last_erc20_scraped_block_number,
-            evm_rpc_id
+            evm_rpc_id,
+            withdrawal_pause,
         }
     }
 }
 
+fn arb_withdrawal_pause() -> impl Strategy<Value = WithdrawalPause> {
+    prop_oneof![
+        ".*".prop_map(|reason| WithdrawalPause::Paused { reason }),
+        Just(WithdrawalPause::Resumed),
+    ]
+}
+
 prop_compose! {
     fn arb_received_eth_event()(
         transaction_hash in arb_hash(),
@@ -786,6 +1016,22 @@ fn arb_event_type() -> impl Strategy<Value = EventType> {
                 transaction_receipt,
             }
         }),
+        (
+            arb_principal(),
+            any::<[u8; 32]>(),
+            any::<u64>(),
+            any::<u64>()
+        )
+            .prop_map(
+                |(caller, idempotency_key, ledger_burn_index, accepted_at)| {
+                    EventType::AcceptedWithdrawalIdempotencyKey {
+                        caller,
+                        idempotency_key,
+                        ledger_burn_index: ledger_burn_index.into(),
+                        accepted_at,
+                    }
+                }
+            ),
     ]
 }
 
@@ -1053,8 +1299,11 @@ fn state_equivalence() {
         erc20_balances: Default::default(),
         skipped_blocks: Default::default(),
         last_transaction_price_estimate: None,
+        recent_transaction_price_estimates: Default::default(),
+        withdrawal_idempotency_keys: Default::default(),
         ledger_suite_orchestrator_id: Some("2s5qh-7aaaa-aaaar-qadya-cai".parse().unwrap()),
         evm_rpc_id: Some("7hfb6-caaaa-aaaar-qadga-cai".parse().unwrap()),
+        withdrawal_pause_reason: None,
         ckerc20_tokens,
     };
 
@@ -1,15 +1,17 @@
 #[cfg(test)]
 mod tests;
 
-use crate::endpoints::{EthTransaction, RetrieveEthStatus, TxFinalizedStatus, WithdrawalStatus};
+use crate::endpoints::{
+    EthTransaction, ReimbursementInfo, RetrieveEthStatus, TxFinalizedStatus, WithdrawalStatus,
+};
 use crate::eth_rpc::Hash;
 use crate::eth_rpc_client::responses::TransactionReceipt;
 use crate::eth_rpc_client::responses::TransactionStatus;
 use crate::lifecycle::EthereumNetwork;
 use crate::map::MultiKeyMap;
 use crate::numeric::{
-    CkTokenAmount, Erc20Value, GasAmount, LedgerBurnIndex, LedgerMintIndex, TransactionCount,
-    TransactionNonce, Wei,
+    BlockNumber, CkTokenAmount, Erc20Value, GasAmount, LedgerBurnIndex, LedgerMintIndex,
+    TransactionCount, TransactionNonce, Wei,
 };
 use crate::state::event::EventType;
 use crate::tx::{
@@ -374,6 +376,17 @@ pub struct EthTransactions {
     pub(in crate::state) reimbursed: BTreeMap<ReimbursementIndex, ReimbursedResult>,
 }
 
+/// Statistics over the withdrawals of a single ckERC20 token.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Erc20WithdrawalStats {
+    /// Total amount of ERC-20 tokens sent by successfully finalized transactions.
+    pub total_withdrawn: Erc20Value,
+    /// Number of withdrawal requests whose transaction is not finalized yet.
+    pub pending_withdrawal_count: usize,
+    /// Block number of the most recently finalized withdrawal transaction, if any.
+    pub last_finalized_block: Option<BlockNumber>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum CreateTransactionError {
     InsufficientTransactionFee {
@@ -412,6 +425,21 @@ impl EthTransactions {
         self.next_nonce
     }
 
+    /// Returns the nonce of the last created transaction, if any.
+    pub fn last_transaction_nonce(&self) -> Option<TransactionNonce> {
+        self.next_nonce.checked_decrement()
+    }
+
+    /// Returns the number of distinct nonces of transactions that were created or sent
+    /// but are not finalized yet.
+    pub fn pending_transaction_count(&self) -> usize {
+        self.created_tx
+            .keys()
+            .chain(self.sent_tx.keys())
+            .collect::<BTreeSet<_>>()
+            .len()
+    }
+
     pub fn update_next_transaction_nonce(&mut self, new_nonce: TransactionNonce) {
         self.next_nonce = new_nonce;
     }
@@ -817,7 +845,8 @@ impl EthTransactions {
                     (RetrieveEthStatus::TxCreated, Some(tx)) => {
                         (request, WithdrawalStatus::TxCreated, Some(tx))
                     }
-                    (RetrieveEthStatus::TxSent(sent), Some(tx)) => {
+                    (RetrieveEthStatus::TxSent(sent), Some(tx))
+                    | (RetrieveEthStatus::TxReplaced { new_tx: sent, .. }, Some(tx)) => {
                         (request, WithdrawalStatus::TxSent(sent), Some(tx))
                     }
                     (RetrieveEthStatus::TxFinalized(status), Some(tx)) => {
@@ -843,6 +872,28 @@ impl EthTransactions {
         self.processed_transaction_status(burn_index).0
     }
 
+    /// Returns the reimbursement of the withdrawal with the given ckETH ledger burn index,
+    /// or `None` if the withdrawal was not reimbursed (yet). Quarantined reimbursements
+    /// are not considered reimbursed.
+    pub fn reimbursement(&self, burn_index: &LedgerBurnIndex) -> Option<ReimbursementInfo> {
+        let reimbursed = self
+            .find_reimbursed_transaction_by_cketh_ledger_burn_index(burn_index)?
+            .as_ref()
+            .ok()?;
+        let transaction_hash = match self.finalized_tx.get_alt(burn_index) {
+            Some(tx) => Some(tx.transaction_hash().to_string()),
+            None => reimbursed
+                .transaction_hash
+                .as_ref()
+                .map(ToString::to_string),
+        };
+        Some(ReimbursementInfo {
+            reimbursed_amount: reimbursed.reimbursed_amount.into(),
+            reimbursed_in_block: reimbursed.reimbursed_in_block.get().into(),
+            transaction_hash,
+        })
+    }
+
     fn processed_transaction_status(
         &self,
         burn_index: &LedgerBurnIndex,
@@ -851,11 +902,24 @@ impl EthTransactions {
             return (RetrieveEthStatus::TxCreated, Some(tx.as_ref()));
         }
 
-        if let Some(tx) = self.sent_tx.get_alt(burn_index).and_then(|txs| txs.last()) {
-            return (
-                RetrieveEthStatus::TxSent(EthTransaction::from(tx.as_ref())),
-                Some(tx.as_ref().as_ref()),
-            );
+        if let Some(sent_txs) = self.sent_tx.get_alt(burn_index) {
+            // A sent transaction is only ever followed by another one when it was
+            // replaced by a transaction with a higher fee.
+            if let [.., old_tx, new_tx] = sent_txs.as_slice() {
+                return (
+                    RetrieveEthStatus::TxReplaced {
+                        old_tx: EthTransaction::from(old_tx.as_ref()),
+                        new_tx: EthTransaction::from(new_tx.as_ref()),
+                    },
+                    Some(new_tx.as_ref().as_ref()),
+                );
+            }
+            if let Some(tx) = sent_txs.last() {
+                return (
+                    RetrieveEthStatus::TxSent(EthTransaction::from(tx.as_ref())),
+                    Some(tx.as_ref().as_ref()),
+                );
+            }
         }
 
         if let Some(tx) = self.finalized_tx.get_alt(burn_index) {
@@ -900,11 +964,8 @@ impl EthTransactions {
         // However, once a nonce is chosen for a withdrawal request, it's in our interest that the corresponding transaction be finalized asap.
         // Limiting the number of transactions would be counter-productive.
         const MAX_NUM_PENDING_TRANSACTION_NONCES: usize = 1000;
-        let unique_pending_transaction_nonces: BTreeSet<_> =
-            self.created_tx.keys().chain(self.sent_tx.keys()).collect();
         let actual_batch_size = min(
-            MAX_NUM_PENDING_TRANSACTION_NONCES
-                .saturating_sub(unique_pending_transaction_nonces.len()),
+            MAX_NUM_PENDING_TRANSACTION_NONCES.saturating_sub(self.pending_transaction_count()),
             requested_batch_size,
         );
         self.withdrawal_requests_iter()
@@ -1081,6 +1142,46 @@ impl EthTransactions {
         Ok(())
     }
 
+    /// Aggregates statistics over the withdrawals of the ckERC20 tokens of the given ledger.
+    pub fn erc20_withdrawal_stats(&self, ckerc20_ledger_id: &Principal) -> Erc20WithdrawalStats {
+        let is_withdrawal_of_token = |request: &&WithdrawalRequest| match request {
+            WithdrawalRequest::CkEth(_) => false,
+            WithdrawalRequest::CkErc20(request) => &request.ckerc20_ledger_id == ckerc20_ledger_id,
+        };
+        let mut stats = Erc20WithdrawalStats {
+            total_withdrawn: Erc20Value::ZERO,
+            pending_withdrawal_count: self
+                .pending_withdrawal_requests
+                .iter()
+                .filter(is_withdrawal_of_token)
+                .count(),
+            last_finalized_block: None,
+        };
+        for (burn_index, request) in self
+            .processed_withdrawal_requests
+            .iter()
+            .filter(|(_burn_index, request)| is_withdrawal_of_token(request))
+        {
+            match self.finalized_tx.get_alt(burn_index) {
+                Some(finalized_tx) => {
+                    if finalized_tx.transaction_status() == &TransactionStatus::Success {
+                        if let WithdrawalRequest::CkErc20(request) = request {
+                            stats.total_withdrawn = stats
+                                .total_withdrawn
+                                .checked_add(request.withdrawal_amount)
+                                .expect("BUG: total withdrawn amount overflowed");
+                        }
+                    }
+                    stats.last_finalized_block = stats
+                        .last_finalized_block
+                        .max(Some(*finalized_tx.block_number()));
+                }
+                None => stats.pending_withdrawal_count += 1,
+            }
+        }
+        stats
+    }
+
     pub fn oldest_incomplete_withdrawal_timestamp(&self) -> Option<u64> {
         self.withdrawal_requests_iter()
             .chain(self.maybe_reimburse_requests_iter())
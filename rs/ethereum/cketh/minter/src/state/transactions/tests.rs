@@ -1277,6 +1277,54 @@ mod eth_transactions {
         }
     }
 
+    mod pending_transaction_count {
+        use crate::numeric::TransactionNonce;
+        use crate::state::transactions::tests::{
+            create_and_record_ck_withdrawal_requests, create_and_record_signed_transaction,
+            create_and_record_transaction, gas_fee_estimate,
+            resubmit_transaction_with_bumped_price, transaction_receipt,
+        };
+        use crate::state::transactions::{EthTransactions, TransactionStatus};
+        use ic_crypto_test_utils_reproducible_rng::reproducible_rng;
+
+        #[test]
+        fn should_count_distinct_nonces_until_finalized() {
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let mut rng = reproducible_rng();
+            assert_eq!(transactions.last_transaction_nonce(), None);
+            assert_eq!(transactions.pending_transaction_count(), 0);
+
+            let [first_request, second_request] =
+                create_and_record_ck_withdrawal_requests(&mut transactions, &mut rng);
+            let first_created_tx = create_and_record_transaction(
+                &mut transactions,
+                first_request.clone(),
+                gas_fee_estimate(),
+            );
+            create_and_record_transaction(&mut transactions, second_request, gas_fee_estimate());
+            assert_eq!(
+                transactions.last_transaction_nonce(),
+                Some(TransactionNonce::ONE)
+            );
+            assert_eq!(transactions.pending_transaction_count(), 2);
+
+            create_and_record_signed_transaction(&mut transactions, first_created_tx.clone());
+            let resubmitted_tx =
+                resubmit_transaction_with_bumped_price(&mut transactions, first_created_tx);
+            assert_eq!(transactions.pending_transaction_count(), 2);
+
+            transactions.record_finalized_transaction(
+                first_request.cketh_ledger_burn_index(),
+                transaction_receipt(&resubmitted_tx, TransactionStatus::Success),
+            );
+            assert_eq!(
+                transactions.last_transaction_nonce(),
+                Some(TransactionNonce::ONE)
+            );
+            assert_eq!(transactions.pending_transaction_count(), 1);
+        }
+    }
+
     mod transactions_to_send_batch {
         use crate::numeric::{TransactionCount, TransactionNonce};
         use crate::state::transactions::tests::arbitrary::arb_checked_amount_of;
@@ -1840,7 +1888,7 @@ mod eth_transactions {
     }
 
     mod transaction_status {
-        use crate::endpoints::{RetrieveEthStatus, TxFinalizedStatus};
+        use crate::endpoints::{ReimbursementInfo, RetrieveEthStatus, TxFinalizedStatus};
         use crate::numeric::{LedgerBurnIndex, LedgerMintIndex, TransactionNonce};
         use crate::state::transactions::tests::{
             ckerc20_withdrawal_request_with_index, cketh_withdrawal_request_with_index,
@@ -1897,6 +1945,57 @@ mod eth_transactions {
             );
         }
 
+        #[test]
+        fn should_have_replaced_status_after_resubmission() {
+            use crate::endpoints::EthTransaction;
+            use crate::state::transactions::tests::{
+                create_and_record_signed_transaction, create_and_record_transaction,
+                gas_fee_estimate, resubmit_transaction_with_bumped_price,
+            };
+
+            let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+            let mut rng = reproducible_rng();
+            let [withdrawal_request] = create_ck_withdrawal_requests(&mut rng);
+            let cketh_ledger_burn_index = withdrawal_request.cketh_ledger_burn_index();
+            transactions.record_withdrawal_request(withdrawal_request.clone());
+            let created_tx = create_and_record_transaction(
+                &mut transactions,
+                withdrawal_request.clone(),
+                gas_fee_estimate(),
+            );
+            let signed_tx =
+                create_and_record_signed_transaction(&mut transactions, created_tx.clone());
+            let resubmitted_tx =
+                resubmit_transaction_with_bumped_price(&mut transactions, created_tx);
+
+            let new_tx = EthTransaction {
+                transaction_hash: resubmitted_tx.hash().to_string(),
+            };
+            let replaced = RetrieveEthStatus::TxReplaced {
+                old_tx: EthTransaction {
+                    transaction_hash: signed_tx.hash().to_string(),
+                },
+                new_tx: new_tx.clone(),
+            };
+            assert_eq!(
+                transactions.transaction_status(&cketh_ledger_burn_index),
+                replaced
+            );
+            assert_eq!(
+                replaced.to_string(),
+                format!(
+                    "Replaced({} by {})",
+                    signed_tx.hash(),
+                    resubmitted_tx.hash()
+                )
+            );
+            assert_withdrawal_status(
+                &transactions,
+                &withdrawal_request,
+                vec![WithdrawalStatus::TxSent(new_tx)],
+            );
+        }
+
         #[test]
         fn should_have_finalized_success_status() {
             let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
@@ -1947,6 +2046,8 @@ mod eth_transactions {
                 vec![WithdrawalStatus::TxFinalized(pending_reimbursedment)],
             );
 
+            assert_eq!(transactions.reimbursement(&cketh_ledger_burn_index), None);
+
             let cketh_reimbursement_index = ReimbursementIndex::CkEth {
                 ledger_burn_index: cketh_ledger_burn_index,
             };
@@ -1960,14 +2061,23 @@ mod eth_transactions {
                 .expect("finalized tx not found");
             let effective_fee_paid = finalized_transaction.effective_transaction_fee();
 
+            let reimbursed_amount: candid::Nat = withdrawal_request
+                .withdrawal_amount
+                .checked_sub(effective_fee_paid)
+                .unwrap()
+                .into();
+            assert_eq!(
+                transactions.reimbursement(&cketh_ledger_burn_index),
+                Some(ReimbursementInfo {
+                    reimbursed_amount: reimbursed_amount.clone(),
+                    reimbursed_in_block: candid::Nat::from(16_u8),
+                    transaction_hash: Some(receipt.transaction_hash.to_string()),
+                })
+            );
             let reimbursed = TxFinalizedStatus::Reimbursed {
                 reimbursed_in_block: candid::Nat::from(16_u8),
                 transaction_hash: receipt.transaction_hash.to_string(),
-                reimbursed_amount: withdrawal_request
-                    .withdrawal_amount
-                    .checked_sub(effective_fee_paid)
-                    .unwrap()
-                    .into(),
+                reimbursed_amount,
             };
             assert_eq!(
                 transactions.transaction_status(&cketh_ledger_burn_index),
@@ -2005,6 +2115,8 @@ mod eth_transactions {
                 vec![WithdrawalStatus::TxFinalized(pending_reimbursement)],
             );
 
+            assert_eq!(transactions.reimbursement(&cketh_ledger_burn_index), None);
+
             let ckerc20_reimbursement_index = ReimbursementIndex::CkErc20 {
                 cketh_ledger_burn_index: withdrawal_request.cketh_ledger_burn_index,
                 ledger_id: withdrawal_request.ckerc20_ledger_id,
@@ -2015,6 +2127,14 @@ mod eth_transactions {
                 LedgerMintIndex::new(16),
             );
 
+            assert_eq!(
+                transactions.reimbursement(&cketh_ledger_burn_index),
+                Some(ReimbursementInfo {
+                    reimbursed_amount: withdrawal_request.withdrawal_amount.into(),
+                    reimbursed_in_block: candid::Nat::from(16_u8),
+                    transaction_hash: Some(receipt.transaction_hash.to_string()),
+                })
+            );
             let reimbursed = TxFinalizedStatus::Reimbursed {
                 reimbursed_in_block: candid::Nat::from(16_u8),
                 transaction_hash: receipt.transaction_hash.to_string(),
@@ -2050,6 +2170,10 @@ mod eth_transactions {
                     (&receipt).into()
                 ))
             );
+            assert_eq!(
+                transactions.reimbursement(&reimbursement_index.withdrawal_id()),
+                None
+            );
         }
     }
 
@@ -2220,6 +2344,114 @@ mod oldest_incomplete_withdrawal_timestamp {
     }
 }
 
+mod erc20_withdrawal_stats {
+    use super::*;
+    use crate::state::transactions::Erc20WithdrawalStats;
+    use candid::Principal;
+    use std::str::FromStr;
+
+    #[test]
+    fn should_return_empty_stats_when_no_requests() {
+        let transactions = EthTransactions::new(TransactionNonce::ZERO);
+
+        assert_eq!(
+            transactions.erc20_withdrawal_stats(&ckerc20_ledger_id()),
+            empty_stats()
+        );
+    }
+
+    #[test]
+    fn should_aggregate_pending_and_finalized_withdrawals() {
+        let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+        let [finalized_request, sent_request, _pending_request] =
+            create_and_record_ckerc20_withdrawal_requests(&mut transactions);
+        transactions.record_withdrawal_request(cketh_withdrawal_request_with_index(
+            LedgerBurnIndex::from(100_u64),
+        ));
+        finalize_transaction(
+            &mut transactions,
+            finalized_request,
+            TransactionStatus::Success,
+        );
+        let created_tx =
+            create_and_record_transaction(&mut transactions, sent_request, gas_fee_estimate());
+        create_and_record_signed_transaction(&mut transactions, created_tx);
+
+        assert_eq!(
+            transactions.erc20_withdrawal_stats(&ckerc20_ledger_id()),
+            Erc20WithdrawalStats {
+                total_withdrawn: Erc20Value::new(DEFAULT_WITHDRAWAL_AMOUNT),
+                pending_withdrawal_count: 2,
+                last_finalized_block: Some(BlockNumber::new(4190269)),
+            }
+        );
+    }
+
+    #[test]
+    fn should_not_count_failed_withdrawals_as_withdrawn() {
+        let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+        let [withdrawal_request] = create_and_record_ckerc20_withdrawal_requests(&mut transactions);
+        finalize_transaction(
+            &mut transactions,
+            withdrawal_request,
+            TransactionStatus::Failure,
+        );
+
+        assert_eq!(
+            transactions.erc20_withdrawal_stats(&ckerc20_ledger_id()),
+            Erc20WithdrawalStats {
+                total_withdrawn: Erc20Value::ZERO,
+                pending_withdrawal_count: 0,
+                last_finalized_block: Some(BlockNumber::new(4190269)),
+            }
+        );
+    }
+
+    #[test]
+    fn should_ignore_withdrawals_of_other_tokens() {
+        let mut transactions = EthTransactions::new(TransactionNonce::ZERO);
+        let [withdrawal_request, _pending_request] =
+            create_and_record_ckerc20_withdrawal_requests(&mut transactions);
+        finalize_transaction(
+            &mut transactions,
+            withdrawal_request,
+            TransactionStatus::Success,
+        );
+
+        assert_eq!(
+            transactions.erc20_withdrawal_stats(&Principal::anonymous()),
+            empty_stats()
+        );
+    }
+
+    fn finalize_transaction(
+        transactions: &mut EthTransactions,
+        withdrawal_request: WithdrawalRequest,
+        status: TransactionStatus,
+    ) {
+        let cketh_ledger_burn_index = withdrawal_request.cketh_ledger_burn_index();
+        let created_tx =
+            create_and_record_transaction(transactions, withdrawal_request, gas_fee_estimate());
+        let signed_tx = create_and_record_signed_transaction(transactions, created_tx);
+        transactions.record_finalized_transaction(
+            cketh_ledger_burn_index,
+            transaction_receipt(&signed_tx, status),
+        );
+    }
+
+    fn ckerc20_ledger_id() -> Principal {
+        Principal::from_str(DEFAULT_CKERC20_LEDGER_ID).unwrap()
+    }
+
+    fn empty_stats() -> Erc20WithdrawalStats {
+        Erc20WithdrawalStats {
+            total_withdrawn: Erc20Value::ZERO,
+            pending_withdrawal_count: 0,
+            last_finalized_block: None,
+        }
+    }
+}
+
 mod eth_withdrawal_request {
     use crate::numeric::LedgerBurnIndex;
     use crate::state::transactions::tests::cketh_withdrawal_request_with_index;
@@ -22,6 +22,30 @@ fn deserialize_block_spec() {
         BlockSpec::Tag(BlockTag::Finalized),
         serde_json::from_str("\"finalized\"").unwrap()
     );
+    assert_eq!(
+        BlockSpec::Tag(BlockTag::Pending),
+        serde_json::from_str("\"pending\"").unwrap()
+    );
+}
+
+#[test]
+fn should_keep_stable_cbor_encoding_of_block_tag() {
+    use crate::endpoints::CandidBlockTag;
+
+    for (block_tag, index) in [
+        (CandidBlockTag::Latest, 0_u8),
+        (CandidBlockTag::Safe, 1),
+        (CandidBlockTag::Finalized, 2),
+        (CandidBlockTag::Pending, 3),
+    ] {
+        let mut encoded = vec![];
+        minicbor::encode(&block_tag, &mut encoded).unwrap();
+        assert_eq!(encoded, vec![index]);
+        assert_eq!(
+            minicbor::decode::<CandidBlockTag>(&encoded).unwrap(),
+            block_tag
+        );
+    }
 }
 
 #[test]
@@ -841,3 +865,126 @@ mod eth_fee_history {
         )
     }
 }
+
+mod candid_events {
+    use crate::endpoints::events::{
+        BlobTransactionFields, EventPayload, TransactionReceipt, TransactionStatus,
+        UnsignedTransaction,
+    };
+    use candid::{CandidType, Decode, Deserialize, Encode, Nat};
+    use serde_bytes::ByteBuf;
+
+    #[test]
+    fn should_round_trip_blob_transaction() {
+        let created_transaction = EventPayload::CreatedTransaction {
+            withdrawal_id: Nat::from(1_u8),
+            transaction: UnsignedTransaction {
+                blob: Some(BlobTransactionFields {
+                    max_fee_per_blob_gas: Nat::from(1_000_000_000_u64),
+                    blob_versioned_hashes: vec![
+                        "0x01a915e4d060149eb4365960e6a7a45f334393093061116b197e3240065ff2d8"
+                            .to_string(),
+                    ],
+                }),
+                ..eip_1559_transaction()
+            },
+        };
+        let finalized_transaction = EventPayload::FinalizedTransaction {
+            withdrawal_id: Nat::from(1_u8),
+            transaction_receipt: TransactionReceipt {
+                blob_gas_used: Some(Nat::from(131_072_u32)),
+                ..transaction_receipt()
+            },
+        };
+
+        for payload in [created_transaction, finalized_transaction] {
+            let encoded = Encode!(&payload).unwrap();
+            assert_eq!(Decode!(&encoded, EventPayload).unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn should_decode_transaction_without_blob_fields() {
+        #[derive(CandidType, Deserialize)]
+        struct LegacyUnsignedTransaction {
+            chain_id: Nat,
+            nonce: Nat,
+            max_priority_fee_per_gas: Nat,
+            max_fee_per_gas: Nat,
+            gas_limit: Nat,
+            destination: String,
+            value: Nat,
+            data: ByteBuf,
+            access_list: Vec<crate::endpoints::events::AccessListItem>,
+        }
+        #[derive(CandidType, Deserialize)]
+        struct LegacyTransactionReceipt {
+            block_hash: String,
+            block_number: Nat,
+            effective_gas_price: Nat,
+            gas_used: Nat,
+            status: TransactionStatus,
+            transaction_hash: String,
+        }
+
+        let tx = eip_1559_transaction();
+        let legacy_tx = LegacyUnsignedTransaction {
+            chain_id: tx.chain_id.clone(),
+            nonce: tx.nonce.clone(),
+            max_priority_fee_per_gas: tx.max_priority_fee_per_gas.clone(),
+            max_fee_per_gas: tx.max_fee_per_gas.clone(),
+            gas_limit: tx.gas_limit.clone(),
+            destination: tx.destination.clone(),
+            value: tx.value.clone(),
+            data: tx.data.clone(),
+            access_list: tx.access_list.clone(),
+        };
+        assert_eq!(
+            Decode!(&Encode!(&legacy_tx).unwrap(), UnsignedTransaction).unwrap(),
+            tx
+        );
+
+        let receipt = transaction_receipt();
+        let legacy_receipt = LegacyTransactionReceipt {
+            block_hash: receipt.block_hash.clone(),
+            block_number: receipt.block_number.clone(),
+            effective_gas_price: receipt.effective_gas_price.clone(),
+            gas_used: receipt.gas_used.clone(),
+            status: receipt.status.clone(),
+            transaction_hash: receipt.transaction_hash.clone(),
+        };
+        assert_eq!(
+            Decode!(&Encode!(&legacy_receipt).unwrap(), TransactionReceipt).unwrap(),
+            receipt
+        );
+    }
+
+    fn eip_1559_transaction() -> UnsignedTransaction {
+        UnsignedTransaction {
+            chain_id: Nat::from(1_u8),
+            nonce: Nat::from(0_u8),
+            max_priority_fee_per_gas: Nat::from(1_500_000_000_u64),
+            max_fee_per_gas: Nat::from(33_003_708_258_u64),
+            gas_limit: Nat::from(21_000_u32),
+            destination: "0x221E931fbFcb9bd54DdD26cE6f5e29E98AdD01C0".to_string(),
+            value: Nat::from(99_999_999_999_999_u64),
+            data: ByteBuf::new(),
+            access_list: vec![],
+            blob: None,
+        }
+    }
+
+    fn transaction_receipt() -> TransactionReceipt {
+        TransactionReceipt {
+            block_hash: "0x82005d2f17b251900968f01b0ed482cb49b7e1d797342bc504904d442b64dbe4"
+                .to_string(),
+            block_number: Nat::from(4_190_269_u32),
+            effective_gas_price: Nat::from(4_277_923_390_u64),
+            gas_used: Nat::from(21_000_u32),
+            status: TransactionStatus::Success,
+            transaction_hash: "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5"
+                .to_string(),
+            blob_gas_used: None,
+        }
+    }
+}
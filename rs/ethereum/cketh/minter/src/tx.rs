@@ -625,8 +625,7 @@ pub async fn lazy_refresh_gas_fee_estimate() -> Option<GasFeeEstimate> {
         let gas_fee_estimate = match estimate_transaction_fee(&fee_history) {
             Ok(estimate) => {
                 mutate_state(|s| {
-                    s.last_transaction_price_estimate =
-                        Some((ic_cdk::api::time(), estimate.clone()));
+                    s.record_transaction_price_estimate(ic_cdk::api::time(), estimate.clone());
                 });
                 estimate
             }
@@ -785,6 +785,7 @@ mod withdraw_erc20 {
                                 &TWO_USDC.into(),
                             )),
                             access_list: vec![],
+                            blob: None,
                         },
                     },
                     EventPayload::SignedTransaction {
@@ -801,6 +802,7 @@ mod withdraw_erc20 {
                             status: transaction_status.clone(),
                             transaction_hash: DEFAULT_CKERC20_WITHDRAWAL_TRANSACTION_HASH
                                 .to_string(),
+                            blob_gas_used: None,
                         },
                     },
                 ]);
@@ -1093,6 +1095,7 @@ mod withdraw_erc20 {
                             &TWO_USDC.into(),
                         )),
                         access_list: vec![],
+                        blob: None,
                     },
                 },
                 EventPayload::SignedTransaction {
@@ -1108,6 +1111,7 @@ mod withdraw_erc20 {
                         gas_used: Nat::from(21_000_u64),
                         status: TransactionStatus::Success,
                         transaction_hash: format!("{:?}", resubmitted_tx_hash),
+                        blob_gas_used: None,
                     },
                 },
             ]);
@@ -1543,6 +1547,9 @@ fn should_retrieve_minter_info() {
             last_observed_block_number: None,
             eth_balance: Some(Nat::from(0_u8)),
             last_gas_fee_estimate: None,
+            recent_gas_fee_estimates: Some(vec![]),
+            last_transaction_nonce: None,
+            pending_transaction_count: Some(Nat::from(0_u8)),
             erc20_balances: Some(erc20_balances),
             last_eth_scraped_block_number: Some(LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into()),
             last_erc20_scraped_block_number: Some(LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into()),
@@ -2,14 +2,16 @@ use assert_matches::assert_matches;
 use candid::{Nat, Principal};
 use ic_base_types::PrincipalId;
 use ic_cketh_minter::endpoints::events::{
-    EventPayload, EventSource, TransactionReceipt, TransactionStatus, UnsignedTransaction,
+    EventKind, EventPayload, EventSource, TransactionReceipt, TransactionStatus,
+    UnsignedTransaction,
 };
 use ic_cketh_minter::endpoints::CandidBlockTag::Finalized;
 use ic_cketh_minter::endpoints::{
-    CandidBlockTag, EthTransaction, GasFeeEstimate, MinterInfo, RetrieveEthStatus,
-    TxFinalizedStatus, WithdrawalError, WithdrawalStatus,
+    CandidBlockTag, EthTransaction, GasFeeEstimate, MinterInfo, ReimbursementInfo,
+    RetrieveEthStatus, RetrieveEthStatusBatchError, TxFinalizedStatus, WithdrawalError,
+    WithdrawalStatus, MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE,
 };
-use ic_cketh_minter::lifecycle::upgrade::UpgradeArg;
+use ic_cketh_minter::lifecycle::upgrade::{UpgradeArg, WithdrawalPause};
 use ic_cketh_minter::memo::{BurnMemo, MintMemo};
 use ic_cketh_minter::numeric::BlockNumber;
 use ic_cketh_minter::{PROCESS_REIMBURSEMENT, SCRAPING_ETH_LOGS_INTERVAL};
@@ -66,6 +68,10 @@ fn should_deposit_and_withdraw() {
         .expect_ok(1)
         .call_minter_withdraw_eth(caller, withdrawal_amount.clone(), destination.clone())
         .expect_withdrawal_request_accepted();
+    assert_eq!(
+        cketh.withdrawal_request.minter_address,
+        cketh.setup.minter_address()
+    );
 
     let withdrawal_id = cketh.withdrawal_id().clone();
 
@@ -119,6 +125,7 @@ fn should_deposit_and_withdraw() {
                     value: withdrawal_amount - max_fee_per_gas * gas_limit,
                     data: Default::default(),
                     access_list: vec![],
+                    blob: None,
                 },
             },
             EventPayload::SignedTransaction {
@@ -135,6 +142,7 @@ fn should_deposit_and_withdraw() {
                     status: TransactionStatus::Success,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    blob_gas_used: None,
                 },
             },
         ]);
@@ -246,6 +254,188 @@ fn should_block_withdrawal_to_blocked_address() {
         });
 }
 
+#[test]
+fn should_reject_withdrawal_while_minter_paused() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let withdrawal_amount = Nat::from(CKETH_WITHDRAWAL_AMOUNT);
+    let blocked_address = "0x01e2919679362dFBC9ee1644Ba9C6da6D6245BB1".to_string();
+
+    cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .check_audit_logs_and_upgrade(UpgradeArg {
+            withdrawal_pause: Some(WithdrawalPause::Paused {
+                reason: "incident response".to_string(),
+            }),
+            ..Default::default()
+        })
+        .call_minter_withdraw_eth(caller, withdrawal_amount.clone(), blocked_address.clone())
+        .expect_error(WithdrawalError::MinterPaused {
+            reason: "incident response".to_string(),
+        })
+        .check_audit_logs_and_upgrade(UpgradeArg {
+            withdrawal_pause: Some(WithdrawalPause::Resumed),
+            ..Default::default()
+        })
+        .call_minter_withdraw_eth(caller, withdrawal_amount, blocked_address.clone())
+        .expect_error(WithdrawalError::RecipientAddressBlocked {
+            address: blocked_address,
+        });
+}
+
+#[test]
+fn should_fail_to_withdraw_to_invalid_destination() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let withdrawal_amount = Nat::from(CKETH_WITHDRAWAL_AMOUNT);
+    let destination_with_invalid_checksum =
+        DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.replace("E9", "e9");
+    assert_ne!(
+        destination_with_invalid_checksum,
+        DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS
+    );
+
+    cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth(caller, withdrawal_amount, destination_with_invalid_checksum)
+        .expect_error(WithdrawalError::InvalidDestination {
+            reason: format!(
+                "Invalid address: address has an invalid EIP-55 checksum: expected {}",
+                DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS
+            ),
+        });
+}
+
+#[test]
+fn should_withdraw_to_ens_name() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let resolver = "0x000000000000000000000000231b0ee14048e9dccd1d247744d114a4eb5e8e63";
+    let resolved_address = format!(
+        "0x000000000000000000000000{}",
+        DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS
+            .strip_prefix("0x")
+            .unwrap()
+            .to_lowercase()
+    );
+
+    cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth_to_ens(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            "cketh-recipient.eth".to_string(),
+        )
+        .respond_to_eth_call(resolver)
+        .respond_to_eth_call(&resolved_address)
+        .expect_withdrawal_request_accepted()
+        .wait_and_validate_withdrawal(ProcessWithdrawalParams::default())
+        .expect_finalized_status(TxFinalizedStatus::Success {
+            transaction_hash: DEFAULT_WITHDRAWAL_TRANSACTION_HASH.to_string(),
+            effective_transaction_fee: Some((GAS_USED * EFFECTIVE_GAS_PRICE).into()),
+        });
+}
+
+#[test]
+fn should_fail_to_withdraw_when_ens_resolution_fails() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let no_resolver = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+    let cketh = cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth_to_ens(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            "unknown-recipient.eth".to_string(),
+        )
+        .respond_to_eth_call(no_resolver)
+        .expect_error(WithdrawalError::EnsResolutionFailed {
+            name: "unknown-recipient.eth".to_string(),
+            reason: "no resolver is set for the name".to_string(),
+        });
+
+    assert_eq!(cketh.balance_of(caller), Nat::from(EXPECTED_BALANCE));
+}
+
+#[test]
+fn should_not_resolve_ens_name_without_approval() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+
+    cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_minter_withdraw_eth_to_ens(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            "cketh-recipient.eth".to_string(),
+        )
+        .expect_error(WithdrawalError::InsufficientAllowance {
+            allowance: Nat::from(0_u64),
+        })
+        .assert_has_no_rpc_call(&JsonRpcMethod::EthCall);
+}
+
+#[test]
+fn should_not_resolve_ens_name_without_sufficient_funds() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let deposit_amount = CKETH_MINIMUM_WITHDRAWAL_AMOUNT + CKETH_TRANSFER_FEE;
+    let amount_after_approval = CKETH_MINIMUM_WITHDRAWAL_AMOUNT;
+
+    cketh
+        .deposit(DepositParams {
+            amount: deposit_amount,
+            ..Default::default()
+        })
+        .expect_mint()
+        .call_ledger_approve_minter(caller, deposit_amount, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth_to_ens(
+            caller,
+            Nat::from(deposit_amount),
+            "cketh-recipient.eth".to_string(),
+        )
+        .expect_error(WithdrawalError::InsufficientFunds {
+            balance: Nat::from(amount_after_approval),
+        })
+        .assert_has_no_rpc_call(&JsonRpcMethod::EthCall);
+}
+
+#[test]
+fn should_fail_to_withdraw_to_both_address_and_ens_name() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+
+    cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth_to_address_and_ens(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.to_string(),
+            "cketh-recipient.eth".to_string(),
+        )
+        .expect_error(WithdrawalError::InvalidDestination {
+            reason: "recipient and recipient_ens are mutually exclusive".to_string(),
+        });
+}
+
 #[test]
 fn should_fail_to_withdraw_without_approval() {
     let cketh = CkEthSetup::default_with_maybe_evm_rpc();
@@ -309,6 +499,132 @@ fn should_fail_to_withdraw_too_small_amount() {
         });
 }
 
+#[test]
+fn should_not_withdraw_twice_with_same_idempotency_key() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+    let withdrawal_amount = Nat::from(CKETH_WITHDRAWAL_AMOUNT);
+    let destination = DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.to_string();
+    let idempotency_key = [42_u8; 32];
+
+    let cketh = cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth_with_idempotency_key(
+            caller,
+            withdrawal_amount.clone(),
+            destination.clone(),
+            idempotency_key,
+        )
+        .expect_withdrawal_request_accepted();
+    let withdrawal_id = cketh.withdrawal_id().clone();
+
+    // The idempotency key is still known after an upgrade.
+    cketh
+        .setup
+        .check_audit_logs_and_upgrade_as_ref(Default::default());
+
+    cketh
+        .setup
+        .call_minter_withdraw_eth_with_idempotency_key(
+            caller,
+            withdrawal_amount,
+            destination,
+            idempotency_key,
+        )
+        .expect_error(WithdrawalError::DuplicateRequest {
+            original_block_index: withdrawal_id,
+        });
+}
+
+#[test]
+fn should_retrieve_eth_status_batch() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let caller: Principal = cketh.caller.into();
+
+    let withdrawal = cketh
+        .deposit(DepositParams::default())
+        .expect_mint()
+        .call_ledger_approve_minter(caller, EXPECTED_BALANCE, None)
+        .expect_ok(1)
+        .call_minter_withdraw_eth(
+            caller,
+            Nat::from(CKETH_WITHDRAWAL_AMOUNT),
+            DEFAULT_WITHDRAWAL_DESTINATION_ADDRESS.to_string(),
+        )
+        .expect_withdrawal_request_accepted();
+    let withdrawal_id = withdrawal.withdrawal_id().clone();
+    let unknown_id = withdrawal_id.clone() + Nat::from(1_u8);
+    let cketh = withdrawal.setup;
+
+    assert_eq!(
+        cketh.retrieve_eth_status_batch(&[withdrawal_id.clone(), unknown_id.clone()]),
+        Ok(vec![
+            (withdrawal_id.clone(), RetrieveEthStatus::Pending),
+            (unknown_id, RetrieveEthStatus::NotFound),
+        ])
+    );
+    assert_eq!(
+        cketh.retrieve_eth_status_batch(&vec![
+            withdrawal_id;
+            MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE + 1
+        ]),
+        Err(RetrieveEthStatusBatchError::TooManyWithdrawalIds {
+            max_withdrawal_ids: MAX_RETRIEVE_ETH_STATUS_BATCH_SIZE as u64,
+        })
+    );
+}
+
+#[test]
+fn should_paginate_events_with_cursor() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc()
+        .deposit(DepositParams::default())
+        .expect_mint();
+    let all_events = cketh.get_all_events();
+    assert!(all_events.len() > 2);
+
+    let mut events = vec![];
+    let mut cursor = None;
+    loop {
+        let result = cketh.get_events_with_cursor(cursor, 2);
+        assert_eq!(result.total_event_count, all_events.len() as u64);
+        if result.events.is_empty() {
+            break;
+        }
+        events.extend(result.events);
+        cursor = result.next_cursor;
+    }
+    assert_eq!(events, all_events);
+}
+
+#[test]
+fn should_filter_events_by_kind() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc()
+        .deposit(DepositParams::default())
+        .expect_mint();
+    let all_events = cketh.get_all_events();
+
+    let result = cketh.get_events_of_kinds(
+        vec![EventKind::AcceptedDeposit, EventKind::MintedCkEth],
+        100,
+    );
+
+    assert_eq!(result.total_event_count, all_events.len() as u64);
+    assert_eq!(
+        result.events,
+        all_events
+            .into_iter()
+            .filter(|event| matches!(
+                event.payload,
+                EventPayload::AcceptedDeposit { .. } | EventPayload::MintedCkEth { .. }
+            ))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(result.events.len(), 2);
+}
+
 #[test]
 fn should_not_finalize_transaction_when_receipts_do_not_match() {
     let cketh = CkEthSetup::default_with_maybe_evm_rpc();
@@ -472,6 +788,14 @@ fn should_reimburse() {
             transaction_hash: failed_tx_hash.clone(),
         })
     );
+    assert_eq!(
+        cketh.get_reimbursement(&withdrawal_id),
+        Some(ReimbursementInfo {
+            reimbursed_amount: reimbursed_amount.clone(),
+            reimbursed_in_block: reimbursed_in_block.clone(),
+            transaction_hash: Some(failed_tx_hash.clone()),
+        })
+    );
 
     let max_fee_per_gas = Nat::from(33003708258u64);
     let gas_limit = Nat::from(21_000_u32);
@@ -511,6 +835,7 @@ fn should_reimburse() {
                     value: withdrawal_amount - max_fee_per_gas * gas_limit,
                     data: Default::default(),
                     access_list: vec![],
+                    blob: None,
                 },
             },
             EventPayload::SignedTransaction {
@@ -527,6 +852,7 @@ fn should_reimburse() {
                     status: TransactionStatus::Failure,
                     transaction_hash:
                     "0x2cf1763e8ee3990103a31a5709b17b83f167738abb400844e67f608a98b0bdb5".to_string(),
+                    blob_gas_used: None,
                 },
             },
             EventPayload::ReimbursedEthWithdrawal {
@@ -619,6 +945,7 @@ fn should_resubmit_new_transaction_when_price_increased() {
                     value: Nat::from(resubmitted_tx.value.unwrap().as_u128()),
                     data: Default::default(),
                     access_list: vec![],
+                    blob: None,
                 },
             },
             EventPayload::SignedTransaction {
@@ -634,6 +961,7 @@ fn should_resubmit_new_transaction_when_price_increased() {
                     gas_used: Nat::from(21_000_u32),
                     status: TransactionStatus::Success,
                     transaction_hash: format!("{:?}", resubmitted_tx_hash),
+                    blob_gas_used: None,
                 },
             },
         ]);
@@ -869,6 +1197,42 @@ fn should_skip_scrapping_when_last_seen_block_newer_than_current_height() {
         });
 }
 
+#[test]
+fn should_record_reorg_stats() {
+    let cketh = CkEthSetup::default_with_maybe_evm_rpc();
+    let latest_block_number = LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL + 10;
+    let reorg_depth = 3;
+
+    cketh.env.advance_time(SCRAPING_ETH_LOGS_INTERVAL);
+    MockJsonRpcProviders::when(JsonRpcMethod::EthGetBlockByNumber)
+        .respond_for_all_with(block_response(latest_block_number))
+        .build()
+        .expect_rpc_calls(&cketh);
+    MockJsonRpcProviders::when(JsonRpcMethod::EthGetLogs)
+        .with_request_params(json!([{
+            "fromBlock": BlockNumber::from(LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL + 1),
+            "toBlock": BlockNumber::from(latest_block_number),
+            "address": [ETH_HELPER_CONTRACT_ADDRESS],
+            "topics": [cketh.received_eth_event_topic()]
+        }]))
+        .respond_for_all_with(empty_logs())
+        .build()
+        .expect_rpc_calls(&cketh);
+    assert_eq!(cketh.reorg_stats().total_reorgs, 0);
+
+    cketh.env.advance_time(SCRAPING_ETH_LOGS_INTERVAL);
+    let reorg_time = cketh.env.get_time().as_nanos_since_unix_epoch();
+    MockJsonRpcProviders::when(JsonRpcMethod::EthGetBlockByNumber)
+        .respond_for_all_with(block_response(latest_block_number - reorg_depth))
+        .build()
+        .expect_rpc_calls(&cketh);
+
+    let stats = cketh.reorg_stats();
+    assert_eq!(stats.total_reorgs, 1);
+    assert_eq!(stats.max_depth_blocks, reorg_depth);
+    assert_matches!(stats.last_reorg_timestamp, Some(t) if t >= reorg_time);
+}
+
 #[test]
 fn should_half_range_of_scrapped_logs_when_response_over_two_mega_bytes() {
     let cketh = CkEthSetup::default_with_maybe_evm_rpc();
@@ -1039,6 +1403,9 @@ fn should_retrieve_minter_info() {
             last_observed_block_number: None,
             eth_balance: Some(Nat::from(0_u8)),
             last_gas_fee_estimate: None,
+            recent_gas_fee_estimates: Some(vec![]),
+            last_transaction_nonce: None,
+            pending_transaction_count: Some(Nat::from(0_u8)),
             erc20_balances: None,
             last_eth_scraped_block_number: Some(LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into()),
             last_erc20_scraped_block_number: Some(LAST_SCRAPED_BLOCK_NUMBER_AT_INSTALL.into()),
@@ -1069,14 +1436,25 @@ fn should_retrieve_minter_info() {
         .setup;
     let info_after_withdrawal = cketh.get_minter_info();
     let price = cketh.eip_1559_transaction_price_expecting_ok(None);
+    let last_gas_fee_estimate = GasFeeEstimate {
+        max_fee_per_gas: price.max_fee_per_gas,
+        max_priority_fee_per_gas: price.max_priority_fee_per_gas,
+        timestamp: price.timestamp.unwrap(),
+    };
+    let recent_gas_fee_estimates = info_after_withdrawal
+        .recent_gas_fee_estimates
+        .clone()
+        .unwrap();
+    assert_eq!(
+        recent_gas_fee_estimates.last(),
+        Some(&last_gas_fee_estimate)
+    );
     assert_eq!(
         info_after_withdrawal,
         MinterInfo {
-            last_gas_fee_estimate: Some(GasFeeEstimate {
-                max_fee_per_gas: price.max_fee_per_gas,
-                max_priority_fee_per_gas: price.max_priority_fee_per_gas,
-                timestamp: price.timestamp.unwrap(),
-            }),
+            last_gas_fee_estimate: Some(last_gas_fee_estimate),
+            recent_gas_fee_estimates: Some(recent_gas_fee_estimates),
+            last_transaction_nonce: Some(Nat::from(0_u8)),
             ..info_after_deposit
         }
     );
@@ -264,6 +264,15 @@ pub struct WithdrawalFlow {
 }
 
 impl WithdrawalFlow {
+    /// Responds to the next `eth_call` of the minter with the same result for all providers.
+    pub fn respond_to_eth_call(self, result: &str) -> Self {
+        MockJsonRpcProviders::when(JsonRpcMethod::EthCall)
+            .respond_for_all_with(result)
+            .build()
+            .expect_rpc_calls(&self.setup);
+        self
+    }
+
     pub fn expect_withdrawal_request_accepted(
         self,
     ) -> ProcessWithdrawal<CkEthSetup, RetrieveEthRequest> {
@@ -708,17 +717,20 @@ impl<T: AsRef<CkEthSetup>, Req: HasWithdrawalId> SendRawTransactionProcessWithdr
     }
 
     pub fn expect_status_sent(self) -> FinalizedTransactionCountProcessWithdrawal<T, Req> {
-        let tx_hash = match self
+        let sent_status = self
             .setup
             .as_ref()
-            .retrieve_eth_status(self.withdrawal_request.withdrawal_id())
-        {
-            RetrieveEthStatus::TxSent(tx) => tx.transaction_hash,
+            .retrieve_eth_status(self.withdrawal_request.withdrawal_id());
+        let tx_hash = match &sent_status {
+            RetrieveEthStatus::TxSent(tx) | RetrieveEthStatus::TxReplaced { new_tx: tx, .. } => {
+                tx.transaction_hash.clone()
+            }
             other => panic!("BUG: unexpected transactions status {:?}", other),
         };
         FinalizedTransactionCountProcessWithdrawal {
             setup: self.setup,
             withdrawal_request: self.withdrawal_request,
+            sent_status,
             sent_transaction_hash: tx_hash,
         }
     }
@@ -743,6 +755,7 @@ impl<T: AsRef<CkEthSetup>, Req: HasWithdrawalId> SendRawTransactionProcessWithdr
 pub struct FinalizedTransactionCountProcessWithdrawal<T, Req> {
     setup: T,
     withdrawal_request: Req,
+    sent_status: RetrieveEthStatus,
     sent_transaction_hash: String,
 }
 
@@ -770,9 +783,7 @@ impl<T: AsRef<CkEthSetup>, Req: HasWithdrawalId>
             self.setup
                 .as_ref()
                 .retrieve_eth_status(self.withdrawal_request.withdrawal_id()),
-            RetrieveEthStatus::TxSent(EthTransaction {
-                transaction_hash: self.sent_transaction_hash.clone()
-            }),
+            self.sent_status,
             "BUG: unexpected status while processing withdrawal"
         );
         TransactionReceiptProcessWithdrawal {
@@ -787,9 +798,7 @@ impl<T: AsRef<CkEthSetup>, Req: HasWithdrawalId>
             self.setup
                 .as_ref()
                 .retrieve_eth_status(self.withdrawal_request.withdrawal_id()),
-            RetrieveEthStatus::TxSent(EthTransaction {
-                transaction_hash: self.sent_transaction_hash.clone()
-            }),
+            self.sent_status,
             "BUG: unexpected status while processing withdrawal"
         );
         ProcessWithdrawal {
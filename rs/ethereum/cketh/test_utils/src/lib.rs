@@ -6,10 +6,13 @@ use crate::mock::JsonRpcMethod;
 use assert_matches::assert_matches;
 use candid::{CandidType, Decode, Deserialize, Encode, Nat, Principal};
 use ic_canisters_http_types::{HttpRequest, HttpResponse};
-use ic_cketh_minter::endpoints::events::{Event, EventPayload, GetEventsResult};
+use ic_cketh_minter::endpoints::events::{
+    Event, EventKind, EventPayload, GetEventsArg, GetEventsResult,
+};
 use ic_cketh_minter::endpoints::{
-    AddCkErc20Token, Eip1559TransactionPriceArg, MinterInfo, RetrieveEthStatus, WithdrawalArg,
-    WithdrawalDetail, WithdrawalSearchParameter,
+    AddCkErc20Token, Eip1559TransactionPriceArg, MinterInfo, ReimbursementInfo, ReorgStats,
+    RetrieveEthStatus, RetrieveEthStatusBatchError, WithdrawalArg, WithdrawalDetail,
+    WithdrawalSearchParameter,
 };
 use ic_cketh_minter::lifecycle::upgrade::UpgradeArg;
 use ic_cketh_minter::logs::Log;
@@ -209,6 +212,43 @@ impl CkEthSetup {
         .unwrap()
     }
 
+    pub fn get_reimbursement(&self, block_index: &Nat) -> Option<ReimbursementInfo> {
+        Decode!(
+            &assert_reply(
+                self.env
+                    .query_as(
+                        self.caller,
+                        self.minter_id,
+                        "get_reimbursement",
+                        Encode!(&block_index.0.to_u64().unwrap()).unwrap(),
+                    )
+                    .expect("failed to get reimbursement")
+            ),
+            Option<ReimbursementInfo>
+        )
+        .unwrap()
+    }
+
+    pub fn retrieve_eth_status_batch(
+        &self,
+        block_indices: &[Nat],
+    ) -> Result<Vec<(Nat, RetrieveEthStatus)>, RetrieveEthStatusBatchError> {
+        Decode!(
+            &assert_reply(
+                self.env
+                    .query_as(
+                        self.caller,
+                        self.minter_id,
+                        "retrieve_eth_status_batch",
+                        Encode!(&block_indices).unwrap(),
+                    )
+                    .expect("failed to get eth statuses")
+            ),
+            Result<Vec<(Nat, RetrieveEthStatus)>, RetrieveEthStatusBatchError>
+        )
+        .unwrap()
+    }
+
     pub fn withdrawal_status(
         &self,
         parameter: &WithdrawalSearchParameter,
@@ -319,6 +359,18 @@ impl CkEthSetup {
         .unwrap()
     }
 
+    pub fn reorg_stats(&self) -> ReorgStats {
+        Decode!(
+            &assert_reply(
+                self.env
+                    .query(self.minter_id, "reorg_stats", Encode!().unwrap())
+                    .unwrap()
+            ),
+            ReorgStats
+        )
+        .unwrap()
+    }
+
     pub fn call_ledger_approve_minter(
         self,
         from: Principal,
@@ -390,7 +442,71 @@ impl CkEthSetup {
         amount: Nat,
         recipient: String,
     ) -> WithdrawalFlow {
-        let arg = WithdrawalArg { amount, recipient };
+        self.send_withdraw_eth(
+            from,
+            WithdrawalArg {
+                amount,
+                recipient,
+                recipient_ens: None,
+                idempotency_key: None,
+            },
+        )
+    }
+
+    pub fn call_minter_withdraw_eth_with_idempotency_key(
+        self,
+        from: Principal,
+        amount: Nat,
+        recipient: String,
+        idempotency_key: [u8; 32],
+    ) -> WithdrawalFlow {
+        self.send_withdraw_eth(
+            from,
+            WithdrawalArg {
+                amount,
+                recipient,
+                recipient_ens: None,
+                idempotency_key: Some(idempotency_key),
+            },
+        )
+    }
+
+    pub fn call_minter_withdraw_eth_to_ens(
+        self,
+        from: Principal,
+        amount: Nat,
+        recipient_ens: String,
+    ) -> WithdrawalFlow {
+        self.send_withdraw_eth(
+            from,
+            WithdrawalArg {
+                amount,
+                recipient: String::new(),
+                recipient_ens: Some(recipient_ens),
+                idempotency_key: None,
+            },
+        )
+    }
+
+    pub fn call_minter_withdraw_eth_to_address_and_ens(
+        self,
+        from: Principal,
+        amount: Nat,
+        recipient: String,
+        recipient_ens: String,
+    ) -> WithdrawalFlow {
+        self.send_withdraw_eth(
+            from,
+            WithdrawalArg {
+                amount,
+                recipient,
+                recipient_ens: Some(recipient_ens),
+                idempotency_key: None,
+            },
+        )
+    }
+
+    fn send_withdraw_eth(self, from: Principal, arg: WithdrawalArg) -> WithdrawalFlow {
         let message_id = self.env.send_ingress(
             PrincipalId::from(from),
             self.minter_id,
@@ -439,16 +555,37 @@ impl CkEthSetup {
     }
 
     fn get_events(&self, start: u64, length: u64) -> GetEventsResult {
-        use ic_cketh_minter::endpoints::events::GetEventsArg;
+        self.get_events_with_arg(GetEventsArg {
+            start,
+            length,
+            cursor: None,
+            kinds: None,
+        })
+    }
+
+    pub fn get_events_with_cursor(&self, cursor: Option<Vec<u8>>, length: u64) -> GetEventsResult {
+        self.get_events_with_arg(GetEventsArg {
+            start: 0,
+            length,
+            cursor,
+            kinds: None,
+        })
+    }
 
+    pub fn get_events_of_kinds(&self, kinds: Vec<EventKind>, length: u64) -> GetEventsResult {
+        self.get_events_with_arg(GetEventsArg {
+            start: 0,
+            length,
+            cursor: None,
+            kinds: Some(kinds),
+        })
+    }
+
+    fn get_events_with_arg(&self, arg: GetEventsArg) -> GetEventsResult {
         Decode!(
             &assert_reply(
                 self.env
-                    .execute_ingress(
-                        self.minter_id,
-                        "get_events",
-                        Encode!(&GetEventsArg { start, length }).unwrap(),
-                    )
+                    .execute_ingress(self.minter_id, "get_events", Encode!(&arg).unwrap())
                     .expect("failed to get minter info")
             ),
             GetEventsResult
@@ -461,6 +598,7 @@ impl CkEthSetup {
         let GetEventsResult {
             mut events,
             total_event_count,
+            ..
         } = self.get_events(0, FIRST_BATCH_SIZE);
         while events.len() < total_event_count as usize {
             let mut next_batch =
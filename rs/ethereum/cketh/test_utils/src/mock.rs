@@ -44,6 +44,9 @@ pub enum JsonRpcMethod {
 
     #[strum(serialize = "eth_sendRawTransaction")]
     EthSendRawTransaction,
+
+    #[strum(serialize = "eth_call")]
+    EthCall,
 }
 
 #[derive(Copy, Debug, PartialEq, Eq, Clone, PartialOrd, Ord, strum_macros::EnumIter)]
@@ -643,6 +643,22 @@ fn get_proposal_info_(id: ProposalId) -> Option<ProposalInfo> {
         .map(ProposalInfo::from)
 }
 
+/// Returns the topic of proposals managing the given canister (e.g., `InstallCode` and
+/// `StopOrStartCanister`) or the error with which such proposals would be rejected.
+#[export_name = "canister_query get_topic_to_manage_canister"]
+fn get_topic_to_manage_canister() {
+    debug_log("get_topic_to_manage_canister");
+    over(candid_one, get_topic_to_manage_canister_)
+}
+
+#[candid_method(query, rename = "get_topic_to_manage_canister")]
+fn get_topic_to_manage_canister_(canister_id: CanisterId) -> Result<i32, GovernanceError> {
+    governance()
+        .get_topic_to_manage_canister(&canister_id)
+        .map(|topic| topic as i32)
+        .map_err(GovernanceError::from)
+}
+
 #[export_name = "canister_query get_neurons_fund_audit_info"]
 fn get_neurons_fund_audit_info() {
     debug_log("get_neurons_fund_audit_info");
@@ -560,7 +560,7 @@ pub struct Proposal {
     /// take.
     #[prost(
         oneof = "proposal::Action",
-        tags = "10, 12, 13, 14, 15, 16, 17, 18, 19, 21, 22, 23, 24, 25, 26"
+        tags = "10, 12, 13, 14, 15, 16, 17, 18, 19, 21, 22, 23, 24, 25, 26, 27"
     )]
     pub action: ::core::option::Option<proposal::Action>,
 }
@@ -651,6 +651,9 @@ pub mod proposal {
         /// Stops or starts a canister controlled by Root.
         #[prost(message, tag = "26")]
         StopOrStartCanister(super::StopOrStartCanister),
+        /// Adds canisters to or removes them from the protocol canisters.
+        #[prost(message, tag = "27")]
+        UpdateProtocolCanisters(super::UpdateProtocolCanisters),
     }
 }
 /// Empty message to use in oneof fields that represent empty
@@ -2318,7 +2321,7 @@ pub struct InstallCode {
     /// The install mode. Either install, reinstall, or upgrade. Required.
     #[prost(enumeration = "install_code::CanisterInstallMode", optional, tag = "2")]
     pub install_mode: ::core::option::Option<i32>,
-    /// The wasm module to install. required.
+    /// The wasm module to install. Either this or `chunked_wasm_module` is required.
     #[prost(bytes = "vec", optional, tag = "3")]
     #[serde(deserialize_with = "ic_utils::deserialize::deserialize_option_blob")]
     pub wasm_module: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
@@ -2329,9 +2332,31 @@ pub struct InstallCode {
     /// Whether to skip stopping the canister before installing. Optional. Default is false.
     #[prost(bool, optional, tag = "5")]
     pub skip_stopping_before_installing: ::core::option::Option<bool>,
+    /// The wasm module to install, in chunks. Either this or `wasm_module` is required.
+    #[prost(message, optional, tag = "6")]
+    pub chunked_wasm_module: ::core::option::Option<install_code::ChunkedWasmModule>,
 }
 /// Nested message and enum types in `InstallCode`.
 pub mod install_code {
+    /// A wasm module that is too large to be passed as a single blob, split into chunks (in the same
+    /// way as for the management canister's install_chunked_code).
+    /// Note that the chunks are stored inline in the proposal, so a chunked wasm module does not make
+    /// the proposal any smaller than passing the assembled module as `wasm_module`.
+    #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ChunkedWasmModule {
+        /// The SHA-256 hash of the assembled wasm module. Required.
+        #[prost(bytes = "vec", optional, tag = "1")]
+        pub wasm_module_hash: ::core::option::Option<::prost::alloc::vec::Vec<u8>>,
+        /// The SHA-256 hashes of the chunks, in the order in which they are concatenated. Required.
+        /// Every hash may appear at most once.
+        #[prost(bytes = "vec", repeated, tag = "2")]
+        pub chunk_hashes_list: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+        /// The contents of the chunks. Every hash in `chunk_hashes_list` must refer to one of them.
+        #[prost(bytes = "vec", repeated, tag = "3")]
+        pub chunks: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    }
     #[derive(
         candid::CandidType,
         candid::Deserialize,
@@ -2384,7 +2409,8 @@ pub mod install_code {
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StopOrStartCanister {
     /// The target canister ID to call stop_canister or start_canister on. The canister must be
-    /// controlled by NNS Root, and it cannot be NNS Governance or Lifeline. Required.
+    /// controlled by NNS Root, and it cannot be NNS Governance or Lifeline. Either this or
+    /// `canister_ids` is required.
     #[prost(message, optional, tag = "1")]
     pub canister_id: ::core::option::Option<::ic_base_types::PrincipalId>,
     #[prost(
@@ -2393,6 +2419,15 @@ pub struct StopOrStartCanister {
         tag = "2"
     )]
     pub action: ::core::option::Option<i32>,
+    /// The target canister IDs, when the same action should be taken on several canisters. The same
+    /// requirements as for `canister_id` apply to each of them, and all of them must be managed under
+    /// the same topic. The proposal is only accepted if all of them are valid, and upon execution the
+    /// action is taken on them in order until it fails on one of them. The execution is not atomic: if
+    /// stopping a canister fails, the canisters stopped before are started again (on a best-effort
+    /// basis), but if starting a canister fails, the canisters started before are left running. Either
+    /// this or `canister_id` is required.
+    #[prost(message, repeated, tag = "3")]
+    pub canister_ids: ::prost::alloc::vec::Vec<::ic_base_types::PrincipalId>,
 }
 /// Nested message and enum types in `StopOrStartCanister`.
 pub mod stop_or_start_canister {
@@ -2441,6 +2476,22 @@ pub mod stop_or_start_canister {
         }
     }
 }
+/// Adds canisters to or removes them from the protocol canisters, i.e., the canisters such that
+/// proposals managing them are classified under the topic PROTOCOL_CANISTER_MANAGEMENT. At least one
+/// of the fields is required, and a canister cannot be both added and removed.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateProtocolCanisters {
+    /// The canisters to consider protocol canisters from now on. They cannot be managed under another
+    /// topic, e.g., SNS-W.
+    #[prost(message, repeated, tag = "1")]
+    pub canister_ids_to_add: ::prost::alloc::vec::Vec<::ic_base_types::PrincipalId>,
+    /// The canisters not to consider protocol canisters anymore. The Registry, Governance, Ledger and
+    /// Root canisters cannot be removed.
+    #[prost(message, repeated, tag = "2")]
+    pub canister_ids_to_remove: ::prost::alloc::vec::Vec<::ic_base_types::PrincipalId>,
+}
 /// This represents the whole NNS governance system. It contains all
 /// information about the NNS governance system that must be kept
 /// across upgrades of the NNS governance system.
@@ -2566,6 +2617,10 @@ pub struct Governance {
     /// The summary of restore aging event.
     #[prost(message, optional, tag = "27")]
     pub restore_aging_summary: ::core::option::Option<RestoreAgingSummary>,
+    /// Protocol canisters in addition to the ones compiled into the governance canister.
+    /// Proposals managing them are classified under the topic PROTOCOL_CANISTER_MANAGEMENT.
+    #[prost(message, optional, tag = "28")]
+    pub additional_protocol_canisters: ::core::option::Option<AdditionalProtocolCanisters>,
 }
 /// Nested message and enum types in `Governance`.
 pub mod governance {
@@ -2891,6 +2946,19 @@ pub mod governance {
         }
     }
 }
+/// Canisters that are considered part of the IC protocol in addition to the
+/// ones compiled into the governance canister.
+#[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AdditionalProtocolCanisters {
+    #[prost(message, repeated, tag = "1")]
+    pub canister_ids: ::prost::alloc::vec::Vec<::ic_base_types::PrincipalId>,
+    /// Canisters compiled into the governance canister as protocol canisters, which are no longer
+    /// considered protocol canisters.
+    #[prost(message, repeated, tag = "2")]
+    pub removed_canister_ids: ::prost::alloc::vec::Vec<::ic_base_types::PrincipalId>,
+}
 #[derive(candid::CandidType, candid::Deserialize, serde::Serialize, comparable::Comparable)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
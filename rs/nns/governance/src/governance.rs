@@ -42,25 +42,27 @@ use crate::{
         reward_node_provider::{RewardMode, RewardToAccount},
         settle_neurons_fund_participation_request, settle_neurons_fund_participation_response,
         settle_neurons_fund_participation_response::NeuronsFundNeuron as NeuronsFundNeuronPb,
-        swap_background_information, Ballot, CreateServiceNervousSystem, ExecuteNnsFunction,
-        GetNeuronsFundAuditInfoRequest, GetNeuronsFundAuditInfoResponse,
-        Governance as GovernanceProto, GovernanceError, InstallCode, KnownNeuron,
-        ListKnownNeuronsResponse, ListNeurons, ListNeuronsResponse, ListProposalInfo,
-        ListProposalInfoResponse, ManageNeuron, ManageNeuronResponse, MonthlyNodeProviderRewards,
-        Motion, NetworkEconomics, Neuron as NeuronProto, NeuronInfo, NeuronState,
-        NeuronsFundAuditInfo, NeuronsFundData,
+        stop_or_start_canister::CanisterAction,
+        swap_background_information, AdditionalProtocolCanisters, Ballot,
+        CreateServiceNervousSystem, ExecuteNnsFunction, GetNeuronsFundAuditInfoRequest,
+        GetNeuronsFundAuditInfoResponse, Governance as GovernanceProto, GovernanceError,
+        InstallCode, KnownNeuron, ListKnownNeuronsResponse, ListNeurons, ListNeuronsResponse,
+        ListProposalInfo, ListProposalInfoResponse, ManageNeuron, ManageNeuronResponse,
+        MonthlyNodeProviderRewards, Motion, NetworkEconomics, Neuron as NeuronProto, NeuronInfo,
+        NeuronState, NeuronsFundAuditInfo, NeuronsFundData,
         NeuronsFundEconomics as NeuronsFundNetworkEconomicsPb,
         NeuronsFundParticipation as NeuronsFundParticipationPb,
         NeuronsFundSnapshot as NeuronsFundSnapshotPb, NnsFunction, NodeProvider, Proposal,
         ProposalData, ProposalInfo, ProposalRewardStatus, ProposalStatus, RestoreAgingSummary,
         RewardEvent, RewardNodeProvider, RewardNodeProviders,
         SettleNeuronsFundParticipationRequest, SettleNeuronsFundParticipationResponse,
-        StopOrStartCanister, Tally, Topic, UpdateNodeProvider, Vote, WaitForQuietState,
-        XdrConversionRate as XdrConversionRatePb,
+        StopOrStartCanister, Tally, Topic, UpdateNodeProvider, UpdateProtocolCanisters, Vote,
+        WaitForQuietState, XdrConversionRate as XdrConversionRatePb,
     },
     proposals::{
         call_canister::CallCanister,
         create_service_nervous_system::ExecutedCreateServiceNervousSystemProposal,
+        proposal_submission, topic_to_manage_canister,
     },
 };
 use async_trait::async_trait;
@@ -828,6 +830,7 @@ impl Proposal {
                     // lot of places.
                     stop_or_start.valid_topic().unwrap_or(Topic::Unspecified)
                 }
+                Action::UpdateProtocolCanisters(_) => Topic::ProtocolCanisterManagement,
             }
         } else {
             println!("{}ERROR: No action -> no topic.", LOG_PREFIX);
@@ -900,6 +903,7 @@ impl Action {
             Action::ExecuteNnsFunction(_) => "ACTION_EXECUTE_NNS_FUNCTION",
             Action::InstallCode(_) => "ACTION_CHANGE_CANISTER",
             Action::StopOrStartCanister(_) => "ACTION_STOP_OR_START_CANISTER",
+            Action::UpdateProtocolCanisters(_) => "ACTION_UPDATE_PROTOCOL_CANISTERS",
         }
     }
 
@@ -1708,6 +1712,7 @@ impl Governance {
 
         let (neurons, topic_followee_index, heap_governance_proto) =
             split_governance_proto(governance_proto);
+        load_additional_protocol_canister_ids(&heap_governance_proto);
 
         assert!(
             topic_followee_index.is_empty(),
@@ -1744,6 +1749,7 @@ impl Governance {
     ) -> Self {
         let (heap_neurons, topic_followee_map, heap_governance_proto) =
             split_governance_proto(governance_proto);
+        load_additional_protocol_canister_ids(&heap_governance_proto);
 
         Self {
             heap_data: heap_governance_proto,
@@ -3373,6 +3379,16 @@ impl Governance {
         }
     }
 
+    /// Returns the topic of proposals managing the given canister (e.g., `InstallCode` and
+    /// `StopOrStartCanister`) or the error with which such proposals would be rejected.
+    /// This method does not require authorization.
+    pub fn get_topic_to_manage_canister(
+        &self,
+        canister_id: &CanisterId,
+    ) -> Result<Topic, GovernanceError> {
+        topic_to_manage_canister(canister_id)
+    }
+
     /// Returns the neuron info for a given neuron `id`. This method
     /// does not require authorization, so the `NeuronInfo` of a
     /// neuron is accessible to any caller.
@@ -4425,6 +4441,10 @@ impl Governance {
                 self.perform_stop_or_start_canister(pid, stop_or_start)
                     .await;
             }
+            Action::UpdateProtocolCanisters(update_protocol_canisters) => {
+                let result = self.update_protocol_canisters(&update_protocol_canisters);
+                self.set_proposal_execution_status(pid, result);
+            }
         }
     }
 
@@ -4452,10 +4472,62 @@ impl Governance {
         proposal_id: u64,
         stop_or_start: StopOrStartCanister,
     ) {
-        let result = self.perform_call_canister(proposal_id, stop_or_start).await;
+        let result = self
+            .perform_stop_or_start_canister_requests(proposal_id, stop_or_start)
+            .await;
         self.set_proposal_execution_status(proposal_id, result);
     }
 
+    /// Stops or starts the target canisters one by one, in order, and stops at the first failure.
+    /// The requests for all target canisters are checked before any of them is made. If stopping
+    /// a canister fails, the canisters stopped by this proposal so far are started again, so that
+    /// a failed proposal leaves no canister stopped. Canisters started before a failure to start
+    /// another one are left running.
+    async fn perform_stop_or_start_canister_requests(
+        &mut self,
+        proposal_id: u64,
+        stop_or_start: StopOrStartCanister,
+    ) -> Result<(), GovernanceError> {
+        let canister_requests = stop_or_start.canister_requests()?;
+        for canister_request in &canister_requests {
+            canister_request.valid_payload()?;
+        }
+
+        for (index, canister_request) in canister_requests.iter().enumerate() {
+            let Err(err) = self
+                .perform_call_canister(proposal_id, canister_request.clone())
+                .await
+            else {
+                continue;
+            };
+            if canister_request.action != Some(CanisterAction::Stop as i32) {
+                return Err(err);
+            }
+            let mut undo_errors = vec![];
+            for stopped in canister_requests[..index].iter().rev() {
+                let start = StopOrStartCanister {
+                    action: Some(CanisterAction::Start as i32),
+                    ..stopped.clone()
+                };
+                if let Err(undo_err) = self.perform_call_canister(proposal_id, start).await {
+                    undo_errors.push(undo_err.error_message);
+                }
+            }
+            if undo_errors.is_empty() {
+                return Err(err);
+            }
+            return Err(GovernanceError::new_with_message(
+                ErrorType::External,
+                format!(
+                    "{} Failed to start the canisters stopped before: {}",
+                    err.error_message,
+                    undo_errors.join("; ")
+                ),
+            ));
+        }
+        Ok(())
+    }
+
     async fn perform_call_canister(
         &mut self,
         proposal_id: u64,
@@ -4482,7 +4554,9 @@ impl Governance {
     }
 
     /// Always fails, because this type of proposal is obsolete.
-    fn validate_obsolete_proposal_action<T>(obsolete_action: T) -> Result<(), GovernanceError>
+    pub(crate) fn validate_obsolete_proposal_action<T>(
+        obsolete_action: T,
+    ) -> Result<(), GovernanceError>
     where
         T: std::fmt::Debug,
     {
@@ -4779,50 +4853,43 @@ impl Governance {
             }
         }
 
-        if proposal.topic() == Topic::Unspecified {
-            Err(format!("Topic not specified. proposal: {:#?}", proposal))?;
-        }
-
-        validate_user_submitted_proposal_fields(proposal)?;
+        // First, perform the validation that does not depend on the state, which is shared with
+        // dry runs.
+        proposal_submission::validate_proposal(proposal)?;
 
         if !proposal.allowed_when_resources_are_low() {
             self.check_heap_can_grow()?;
         }
 
-        // Require that oneof action is populated.
         let action = proposal
             .action
             .as_ref()
             .ok_or(format!("Proposal lacks an action: {:?}", proposal))?;
 
-        // Finally, perform Action-specific validation.
+        // Finally, perform the Action-specific validation that depends on the state.
         match action {
             Action::ExecuteNnsFunction(execute_nns_function) => {
                 self.validate_execute_nns_function(execute_nns_function)
             }
-            Action::Motion(motion) => validate_motion(motion),
             Action::CreateServiceNervousSystem(create_service_nervous_system) => {
                 self.validate_create_service_nervous_system(create_service_nervous_system)
             }
             Action::ManageNeuron(manage_neuron) => {
                 self.validate_manage_neuron_proposal(manage_neuron)
             }
-            Action::ManageNetworkEconomics(_)
+            Action::Motion(_)
+            | Action::ManageNetworkEconomics(_)
             | Action::ApproveGenesisKyc(_)
             | Action::AddOrRemoveNodeProvider(_)
             | Action::RewardNodeProvider(_)
             | Action::SetDefaultFollowees(_)
             | Action::RewardNodeProviders(_)
-            | Action::RegisterKnownNeuron(_) => Ok(()),
-
-            Action::SetSnsTokenSwapOpenTimeWindow(obsolete_action) => {
-                Self::validate_obsolete_proposal_action(obsolete_action)
-            }
-            Action::OpenSnsTokenSwap(obsolete_action) => {
-                Self::validate_obsolete_proposal_action(obsolete_action)
-            }
-            Action::InstallCode(install_code) => install_code.validate(),
-            Action::StopOrStartCanister(stop_or_start) => stop_or_start.validate(),
+            | Action::RegisterKnownNeuron(_)
+            | Action::SetSnsTokenSwapOpenTimeWindow(_)
+            | Action::OpenSnsTokenSwap(_)
+            | Action::InstallCode(_)
+            | Action::StopOrStartCanister(_)
+            | Action::UpdateProtocolCanisters(_) => Ok(()),
         }?;
 
         Ok(action.clone())
@@ -7726,6 +7793,87 @@ impl Governance {
     pub fn get_restore_aging_summary(&self) -> Option<RestoreAgingSummary> {
         self.heap_data.restore_aging_summary.clone()
     }
+
+    /// Replaces the protocol canisters in addition to the ones compiled into the governance
+    /// canister. Proposals managing them are classified under the topic ProtocolCanisterManagement.
+    pub fn set_additional_protocol_canister_ids(&mut self, canister_ids: Vec<CanisterId>) {
+        let removed_canister_ids = removed_protocol_canister_ids(&self.heap_data);
+        self.store_protocol_canister_ids(canister_ids, removed_canister_ids);
+    }
+
+    pub fn get_additional_protocol_canister_ids(&self) -> Vec<CanisterId> {
+        additional_protocol_canister_ids(&self.heap_data)
+    }
+
+    /// Returns the protocol canisters compiled into the governance canister that have been
+    /// removed by `UpdateProtocolCanisters` proposals.
+    pub fn get_removed_protocol_canister_ids(&self) -> Vec<CanisterId> {
+        removed_protocol_canister_ids(&self.heap_data)
+    }
+
+    /// Executes an `UpdateProtocolCanisters` proposal. The changes are merged with the protocol
+    /// canisters compiled into the governance canister when classifying proposals by their topic.
+    fn update_protocol_canisters(
+        &mut self,
+        update_protocol_canisters: &UpdateProtocolCanisters,
+    ) -> Result<(), GovernanceError> {
+        let mut additional_canister_ids = additional_protocol_canister_ids(&self.heap_data)
+            .into_iter()
+            .collect();
+        let mut removed_canister_ids = removed_protocol_canister_ids(&self.heap_data)
+            .into_iter()
+            .collect();
+        update_protocol_canisters.apply(&mut additional_canister_ids, &mut removed_canister_ids)?;
+        self.store_protocol_canister_ids(additional_canister_ids, removed_canister_ids);
+        Ok(())
+    }
+
+    fn store_protocol_canister_ids(
+        &mut self,
+        additional_canister_ids: impl IntoIterator<Item = CanisterId>,
+        removed_canister_ids: impl IntoIterator<Item = CanisterId>,
+    ) {
+        self.heap_data.additional_protocol_canisters = Some(AdditionalProtocolCanisters {
+            canister_ids: additional_canister_ids
+                .into_iter()
+                .map(|canister_id| canister_id.get())
+                .collect(),
+            removed_canister_ids: removed_canister_ids
+                .into_iter()
+                .map(|canister_id| canister_id.get())
+                .collect(),
+        });
+        load_additional_protocol_canister_ids(&self.heap_data);
+    }
+}
+
+fn additional_protocol_canister_ids(heap_data: &HeapGovernanceData) -> Vec<CanisterId> {
+    heap_data
+        .additional_protocol_canisters
+        .iter()
+        .flat_map(|additional_protocol_canisters| &additional_protocol_canisters.canister_ids)
+        .filter_map(|principal_id| CanisterId::try_from(*principal_id).ok())
+        .collect()
+}
+
+fn removed_protocol_canister_ids(heap_data: &HeapGovernanceData) -> Vec<CanisterId> {
+    heap_data
+        .additional_protocol_canisters
+        .iter()
+        .flat_map(|additional_protocol_canisters| {
+            &additional_protocol_canisters.removed_canister_ids
+        })
+        .filter_map(|principal_id| CanisterId::try_from(*principal_id).ok())
+        .collect()
+}
+
+/// Makes the additional and removed protocol canisters persisted in the heap data available
+/// for classifying proposals by their topic (which is computed without access to `Governance`).
+fn load_additional_protocol_canister_ids(heap_data: &HeapGovernanceData) {
+    crate::proposals::set_additional_protocol_canister_ids(additional_protocol_canister_ids(
+        heap_data,
+    ));
+    crate::proposals::set_removed_protocol_canister_ids(removed_protocol_canister_ids(heap_data));
 }
 
 impl From<NeuronSubsetMetrics> for NeuronSubsetMetricsPb {
@@ -7910,7 +8058,7 @@ pub fn validate_proposal_url(url: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn validate_motion(motion: &Motion) -> Result<(), GovernanceError> {
+pub(crate) fn validate_motion(motion: &Motion) -> Result<(), GovernanceError> {
     if motion.motion_text.len() > PROPOSAL_MOTION_TEXT_BYTES_MAX {
         return Err(GovernanceError::new_with_message(
             ErrorType::InvalidProposal,
@@ -1654,3 +1654,75 @@ fn topic_min_max_test() {
         assert!(topic <= Topic::MAX, "Topic::MAX needs to be updated");
     }
 }
+
+#[tokio::test]
+async fn test_failure_to_stop_canister_starts_canisters_stopped_before() {
+    use crate::{
+        pb::v1::stop_or_start_canister::CanisterAction,
+        test_utils::ExpectedCallCanisterMethodCallArguments,
+    };
+    use ic_nervous_system_root::change_canister::{
+        CanisterAction as RootCanisterAction, StopOrStartCanisterRequest,
+    };
+    use ic_nns_constants::{CYCLES_MINTING_CANISTER_ID, REGISTRY_CANISTER_ID, ROOT_CANISTER_ID};
+
+    let root_call = |canister_id, action| {
+        ExpectedCallCanisterMethodCallArguments::new(
+            ROOT_CANISTER_ID,
+            "stop_or_start_nns_canister",
+            Encode!(&StopOrStartCanisterRequest {
+                canister_id,
+                action,
+            })
+            .unwrap(),
+        )
+    };
+    let stop_or_start = StopOrStartCanister {
+        canister_id: None,
+        action: Some(CanisterAction::Stop as i32),
+        canister_ids: vec![CYCLES_MINTING_CANISTER_ID.get(), REGISTRY_CANISTER_ID.get()],
+    };
+
+    // The canister stopped before the failure is started again.
+    let mut governance = Governance::new(
+        GovernanceProto::default(),
+        Box::new(MockEnvironment::new(
+            vec![
+                (
+                    root_call(CYCLES_MINTING_CANISTER_ID, RootCanisterAction::Stop),
+                    Ok(Encode!(&()).unwrap()),
+                ),
+                (
+                    root_call(REGISTRY_CANISTER_ID, RootCanisterAction::Stop),
+                    Err((Some(5), "registry is busy".to_string())),
+                ),
+                (
+                    root_call(CYCLES_MINTING_CANISTER_ID, RootCanisterAction::Start),
+                    Err((Some(5), "cmc is busy".to_string())),
+                ),
+            ],
+            100,
+        )),
+        Box::new(StubIcpLedger {}),
+        Box::new(StubCMC {}),
+    );
+
+    let err = governance
+        .perform_stop_or_start_canister_requests(1, stop_or_start)
+        .await
+        .unwrap_err();
+    assert_eq!(err.error_type, ErrorType::External as i32);
+    assert!(
+        err.error_message.contains("registry is busy"),
+        "{}",
+        err.error_message
+    );
+    // The failed attempt to start the first canister again is reported, too.
+    assert!(
+        err.error_message
+            .contains("Failed to start the canisters stopped before")
+            && err.error_message.contains("cmc is busy"),
+        "{}",
+        err.error_message
+    );
+}
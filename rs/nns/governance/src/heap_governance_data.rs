@@ -3,9 +3,9 @@ use crate::pb::v1::{
         FollowersMap, GovernanceCachedMetrics, MakingSnsProposal, Migrations, NeuronInFlightCommand,
     },
     neuron::Followees,
-    Governance as GovernanceProto, MonthlyNodeProviderRewards, NetworkEconomics, Neuron,
-    NeuronStakeTransfer, NodeProvider, ProposalData, RestoreAgingSummary, RewardEvent,
-    XdrConversionRate as XdrConversionRatePb,
+    AdditionalProtocolCanisters, Governance as GovernanceProto, MonthlyNodeProviderRewards,
+    NetworkEconomics, Neuron, NeuronStakeTransfer, NodeProvider, ProposalData, RestoreAgingSummary,
+    RewardEvent, XdrConversionRate as XdrConversionRatePb,
 };
 use std::collections::{BTreeMap, HashMap};
 
@@ -34,6 +34,7 @@ pub struct HeapGovernanceData {
     pub migrations: Option<Migrations>,
     pub xdr_conversion_rate: XdrConversionRate,
     pub restore_aging_summary: Option<RestoreAgingSummary>,
+    pub additional_protocol_canisters: Option<AdditionalProtocolCanisters>,
 }
 
 /// Internal representation for `XdrConversionRatePb`.
@@ -123,6 +124,7 @@ pub fn split_governance_proto(
         topic_followee_index,
         xdr_conversion_rate,
         restore_aging_summary,
+        additional_protocol_canisters,
     } = governance_proto;
 
     let neuron_management_voting_period_seconds =
@@ -160,6 +162,7 @@ pub fn split_governance_proto(
             migrations,
             xdr_conversion_rate,
             restore_aging_summary,
+            additional_protocol_canisters,
         },
     )
 }
@@ -197,6 +200,7 @@ pub fn reassemble_governance_proto(
         migrations,
         xdr_conversion_rate,
         restore_aging_summary,
+        additional_protocol_canisters,
     } = heap_governance_proto;
 
     let neuron_management_voting_period_seconds = Some(neuron_management_voting_period_seconds);
@@ -226,6 +230,7 @@ pub fn reassemble_governance_proto(
         topic_followee_index,
         xdr_conversion_rate: Some(xdr_conversion_rate),
         restore_aging_summary,
+        additional_protocol_canisters,
     }
 }
 
@@ -278,6 +283,10 @@ mod tests {
                 xdr_permyriad_per_icp: Some(50_000),
             }),
             restore_aging_summary: None,
+            additional_protocol_canisters: Some(AdditionalProtocolCanisters {
+                canister_ids: vec![ic_base_types::PrincipalId::new_user_test_id(8)],
+                removed_canister_ids: vec![ic_base_types::PrincipalId::new_user_test_id(9)],
+            }),
         }
     }
 
@@ -600,6 +600,9 @@ impl From<pb::proposal::Action> for pb_api::proposal::Action {
             pb::proposal::Action::StopOrStartCanister(v) => {
                 pb_api::proposal::Action::StopOrStartCanister(v.into())
             }
+            pb::proposal::Action::UpdateProtocolCanisters(v) => {
+                pb_api::proposal::Action::UpdateProtocolCanisters(v.into())
+            }
         }
     }
 }
@@ -647,6 +650,9 @@ impl From<pb_api::proposal::Action> for pb::proposal::Action {
             pb_api::proposal::Action::StopOrStartCanister(v) => {
                 pb::proposal::Action::StopOrStartCanister(v.into())
             }
+            pb_api::proposal::Action::UpdateProtocolCanisters(v) => {
+                pb::proposal::Action::UpdateProtocolCanisters(v.into())
+            }
         }
     }
 }
@@ -2784,6 +2790,7 @@ impl From<pb::InstallCode> for pb_api::InstallCode {
             wasm_module: item.wasm_module,
             arg: item.arg,
             skip_stopping_before_installing: item.skip_stopping_before_installing,
+            chunked_wasm_module: item.chunked_wasm_module.map(|x| x.into()),
         }
     }
 }
@@ -2795,6 +2802,26 @@ impl From<pb_api::InstallCode> for pb::InstallCode {
             wasm_module: item.wasm_module,
             arg: item.arg,
             skip_stopping_before_installing: item.skip_stopping_before_installing,
+            chunked_wasm_module: item.chunked_wasm_module.map(|x| x.into()),
+        }
+    }
+}
+
+impl From<pb::install_code::ChunkedWasmModule> for pb_api::install_code::ChunkedWasmModule {
+    fn from(item: pb::install_code::ChunkedWasmModule) -> Self {
+        Self {
+            wasm_module_hash: item.wasm_module_hash,
+            chunk_hashes_list: item.chunk_hashes_list,
+            chunks: item.chunks,
+        }
+    }
+}
+impl From<pb_api::install_code::ChunkedWasmModule> for pb::install_code::ChunkedWasmModule {
+    fn from(item: pb_api::install_code::ChunkedWasmModule) -> Self {
+        Self {
+            wasm_module_hash: item.wasm_module_hash,
+            chunk_hashes_list: item.chunk_hashes_list,
+            chunks: item.chunks,
         }
     }
 }
@@ -2841,6 +2868,7 @@ impl From<pb::StopOrStartCanister> for pb_api::StopOrStartCanister {
         Self {
             canister_id: item.canister_id,
             action: item.action,
+            canister_ids: item.canister_ids,
         }
     }
 }
@@ -2850,6 +2878,7 @@ impl From<pb_api::StopOrStartCanister> for pb::StopOrStartCanister {
         Self {
             canister_id: item.canister_id,
             action: item.action,
+            canister_ids: item.canister_ids,
         }
     }
 }
@@ -2890,6 +2919,23 @@ impl From<pb_api::stop_or_start_canister::CanisterAction>
     }
 }
 
+impl From<pb::UpdateProtocolCanisters> for pb_api::UpdateProtocolCanisters {
+    fn from(item: pb::UpdateProtocolCanisters) -> Self {
+        Self {
+            canister_ids_to_add: item.canister_ids_to_add,
+            canister_ids_to_remove: item.canister_ids_to_remove,
+        }
+    }
+}
+impl From<pb_api::UpdateProtocolCanisters> for pb::UpdateProtocolCanisters {
+    fn from(item: pb_api::UpdateProtocolCanisters) -> Self {
+        Self {
+            canister_ids_to_add: item.canister_ids_to_add,
+            canister_ids_to_remove: item.canister_ids_to_remove,
+        }
+    }
+}
+
 impl From<pb::Governance> for pb_api::Governance {
     fn from(item: pb::Governance) -> Self {
         Self {
@@ -2943,6 +2989,7 @@ impl From<pb::Governance> for pb_api::Governance {
                 .collect(),
             xdr_conversion_rate: item.xdr_conversion_rate.map(|x| x.into()),
             restore_aging_summary: item.restore_aging_summary.map(|x| x.into()),
+            additional_protocol_canisters: item.additional_protocol_canisters.map(|x| x.into()),
         }
     }
 }
@@ -2999,6 +3046,7 @@ impl From<pb_api::Governance> for pb::Governance {
                 .collect(),
             xdr_conversion_rate: item.xdr_conversion_rate.map(|x| x.into()),
             restore_aging_summary: item.restore_aging_summary.map(|x| x.into()),
+            additional_protocol_canisters: item.additional_protocol_canisters.map(|x| x.into()),
         }
     }
 }
@@ -3419,6 +3467,23 @@ impl From<pb_api::governance::followers_map::Followers>
     }
 }
 
+impl From<pb::AdditionalProtocolCanisters> for pb_api::AdditionalProtocolCanisters {
+    fn from(item: pb::AdditionalProtocolCanisters) -> Self {
+        Self {
+            canister_ids: item.canister_ids,
+            removed_canister_ids: item.removed_canister_ids,
+        }
+    }
+}
+impl From<pb_api::AdditionalProtocolCanisters> for pb::AdditionalProtocolCanisters {
+    fn from(item: pb_api::AdditionalProtocolCanisters) -> Self {
+        Self {
+            canister_ids: item.canister_ids,
+            removed_canister_ids: item.removed_canister_ids,
+        }
+    }
+}
+
 impl From<pb::XdrConversionRate> for pb_api::XdrConversionRate {
     fn from(item: pb::XdrConversionRate) -> Self {
         Self {
@@ -1,6 +1,15 @@
+use super::invalid_proposal_error;
 use crate::pb::v1::GovernanceError;
 
 use ic_base_types::CanisterId;
+use ic_types::messages::MAX_INTER_CANISTER_PAYLOAD_IN_BYTES_U64;
+
+/// The maximum size (in bytes) of the payload of a proposal that calls a canister method. Since
+/// the payload is derived from the proposal, which is stored in the governance canister's state,
+/// this bounds how much a single such proposal can add to that state. It equals the maximum size
+/// of an inter-canister request, as a larger payload could not be sent to the target canister
+/// when the proposal is executed anyway.
+pub const MAX_CALL_CANISTER_PAYLOAD_BYTES: usize = MAX_INTER_CANISTER_PAYLOAD_IN_BYTES_U64 as usize;
 
 /// A trait for proposal types that simply calls a canister method with a payload.
 pub trait CallCanister {
@@ -8,6 +17,79 @@ pub trait CallCanister {
     fn canister_and_function(&self) -> Result<(CanisterId, &str), GovernanceError>;
     /// Returns the payload to send to the target canister.
     fn payload(&self) -> Result<Vec<u8>, GovernanceError>;
+
+    /// Returns the maximum size (in bytes) of the payload accepted by `valid_payload`.
+    fn max_payload_bytes(&self) -> usize {
+        MAX_CALL_CANISTER_PAYLOAD_BYTES
+    }
+
+    /// Returns the payload to send to the target canister, or an error if it is larger than
+    /// `max_payload_bytes`. Should be called during proposal validation.
+    fn valid_payload(&self) -> Result<Vec<u8>, GovernanceError> {
+        let payload = self.payload()?;
+        let max_payload_bytes = self.max_payload_bytes();
+        if payload.len() > max_payload_bytes {
+            return Err(invalid_proposal_error(&format!(
+                "payload is {} bytes, which exceeds the maximum of {} bytes",
+                payload.len(),
+                max_payload_bytes
+            )));
+        }
+        Ok(payload)
+    }
 }
 
 // TODO: impl CallCanister for ExecuteNnsFunciton
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pb::v1::governance_error::ErrorType;
+
+    use ic_nns_constants::REGISTRY_CANISTER_ID;
+
+    struct TestCallCanister {
+        payload: Vec<u8>,
+    }
+
+    impl CallCanister for TestCallCanister {
+        fn canister_and_function(&self) -> Result<(CanisterId, &str), GovernanceError> {
+            Ok((REGISTRY_CANISTER_ID, "test_method"))
+        }
+
+        fn payload(&self) -> Result<Vec<u8>, GovernanceError> {
+            Ok(self.payload.clone())
+        }
+
+        fn max_payload_bytes(&self) -> usize {
+            3
+        }
+    }
+
+    #[test]
+    fn test_valid_payload_within_limit() {
+        let call_canister = TestCallCanister {
+            payload: vec![1, 2, 3],
+        };
+
+        assert_eq!(call_canister.valid_payload(), Ok(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_valid_payload_exceeds_limit() {
+        let call_canister = TestCallCanister {
+            payload: vec![1, 2, 3, 4],
+        };
+
+        let error = call_canister.valid_payload().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error
+                .error_message
+                .contains("payload is 4 bytes, which exceeds the maximum of 3 bytes"),
+            "{}",
+            error.error_message
+        );
+    }
+}
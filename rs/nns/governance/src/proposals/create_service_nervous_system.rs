@@ -1,10 +1,11 @@
+use super::invalid_proposal_error;
 use crate::pb::v1::{
     create_service_nervous_system,
     create_service_nervous_system::swap_parameters::NeuronBasketConstructionParameters,
-    CreateServiceNervousSystem,
+    CreateServiceNervousSystem, GovernanceError,
 };
 use ic_nervous_system_common::ONE_DAY_SECONDS;
-use ic_nervous_system_proto::pb::v1::{Duration, GlobalTimeOfDay};
+use ic_nervous_system_proto::pb::v1::{Duration, GlobalTimeOfDay, Tokens};
 use ic_sns_init::pb::v1::{self as sns_init_pb, sns_init_payload, SnsInitPayload};
 use ic_sns_swap::pb::v1::{self as sns_swap_pb, NeuronsFundParticipationConstraints};
 
@@ -151,6 +152,144 @@ impl CreateServiceNervousSystem {
     }
 }
 
+impl CreateServiceNervousSystem {
+    /// Checks the arithmetic invariants of the initial token distribution and of the swap
+    /// parameters, so that violating them is detected when the proposal is submitted rather than
+    /// when it is executed. Parameters that are not set are not checked here; missing required
+    /// parameters are reported when converting the proposal to an `SnsInitPayload`.
+    pub fn validate(&self) -> Result<(), GovernanceError> {
+        self.validate_initial_token_distribution()
+            .and_then(|()| self.validate_swap_parameters())
+            .map_err(|defect| invalid_proposal_error(&defect))
+    }
+
+    fn validate_initial_token_distribution(&self) -> Result<(), String> {
+        let Some(initial_token_distribution) = &self.initial_token_distribution else {
+            return Ok(());
+        };
+
+        let developer_neuron_stakes_e8s = initial_token_distribution
+            .developer_distribution
+            .iter()
+            .flat_map(|developer_distribution| &developer_distribution.developer_neurons)
+            .map(|neuron_distribution| tokens_e8s(&neuron_distribution.stake));
+        let treasury_e8s = initial_token_distribution
+            .treasury_distribution
+            .as_ref()
+            .and_then(|treasury_distribution| tokens_e8s(&treasury_distribution.total));
+        let swap_e8s = initial_token_distribution
+            .swap_distribution
+            .as_ref()
+            .and_then(|swap_distribution| tokens_e8s(&swap_distribution.total));
+
+        // The total supply of the SNS token is the sum of all initial distributions, which must
+        // be representable.
+        developer_neuron_stakes_e8s
+            .chain([treasury_e8s, swap_e8s])
+            .flatten()
+            .try_fold(0_u64, |total_e8s, e8s| total_e8s.checked_add(e8s))
+            .ok_or(format!(
+                "the total token supply (the sum of the developer neuron stakes, the treasury \
+                 distribution, and the swap distribution) exceeds the maximum of {} e8s",
+                u64::MAX,
+            ))?;
+
+        if swap_e8s == Some(0) {
+            return Err(
+                "initial_token_distribution.swap_distribution.total must be positive".to_string(),
+            );
+        }
+
+        Ok(())
+    }
+
+    fn validate_swap_parameters(&self) -> Result<(), String> {
+        let Some(swap_parameters) = &self.swap_parameters else {
+            return Ok(());
+        };
+
+        let minimum_direct_participation_icp_e8s =
+            tokens_e8s(&swap_parameters.minimum_direct_participation_icp);
+        let maximum_direct_participation_icp_e8s =
+            tokens_e8s(&swap_parameters.maximum_direct_participation_icp);
+        let minimum_participant_icp_e8s = tokens_e8s(&swap_parameters.minimum_participant_icp);
+        let maximum_participant_icp_e8s = tokens_e8s(&swap_parameters.maximum_participant_icp);
+
+        if swap_parameters.minimum_participants == Some(0) {
+            return Err("swap_parameters.minimum_participants must be at least 1".to_string());
+        }
+
+        validate_at_most(
+            (
+                "minimum_direct_participation_icp",
+                minimum_direct_participation_icp_e8s,
+            ),
+            (
+                "maximum_direct_participation_icp",
+                maximum_direct_participation_icp_e8s,
+            ),
+        )?;
+        validate_at_most(
+            ("minimum_participant_icp", minimum_participant_icp_e8s),
+            ("maximum_participant_icp", maximum_participant_icp_e8s),
+        )?;
+        validate_at_most(
+            ("maximum_participant_icp", maximum_participant_icp_e8s),
+            (
+                "maximum_direct_participation_icp",
+                maximum_direct_participation_icp_e8s,
+            ),
+        )?;
+
+        // Otherwise, the swap could never reach the required number of participants.
+        if let (
+            Some(minimum_participants),
+            Some(minimum_participant_icp_e8s),
+            Some(maximum_direct_participation_icp_e8s),
+        ) = (
+            swap_parameters.minimum_participants,
+            minimum_participant_icp_e8s,
+            maximum_direct_participation_icp_e8s,
+        ) {
+            let minimum_total_participation_icp_e8s =
+                minimum_participants.checked_mul(minimum_participant_icp_e8s);
+            if minimum_total_participation_icp_e8s
+                .map_or(true, |e8s| e8s > maximum_direct_participation_icp_e8s)
+            {
+                return Err(format!(
+                    "swap_parameters.minimum_participants ({}) times \
+                     swap_parameters.minimum_participant_icp ({} e8s) must not exceed \
+                     swap_parameters.maximum_direct_participation_icp ({} e8s)",
+                    minimum_participants,
+                    minimum_participant_icp_e8s,
+                    maximum_direct_participation_icp_e8s,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn tokens_e8s(tokens: &Option<Tokens>) -> Option<u64> {
+    tokens.as_ref().and_then(|tokens| tokens.e8s)
+}
+
+/// Checks that the swap parameter `lower` does not exceed the swap parameter `upper`, if both
+/// are set.
+fn validate_at_most(
+    (lower_name, lower_e8s): (&str, Option<u64>),
+    (upper_name, upper_e8s): (&str, Option<u64>),
+) -> Result<(), String> {
+    match (lower_e8s, upper_e8s) {
+        (Some(lower_e8s), Some(upper_e8s)) if lower_e8s > upper_e8s => Err(format!(
+            "swap_parameters.{} ({} e8s) must not exceed swap_parameters.{} ({} e8s)",
+            lower_name, lower_e8s, upper_name, upper_e8s,
+        )),
+        _ => Ok(()),
+    }
+}
+
 fn divide_perfectly(field_name: &str, dividend: u64, divisor: u64) -> Result<u64, String> {
     match dividend.checked_rem(divisor) {
         None => Err(format!(
@@ -629,3 +768,137 @@ impl TryFrom<NeuronBasketConstructionParameters>
         Ok(params)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        governance::test_data::CREATE_SERVICE_NERVOUS_SYSTEM, pb::v1::governance_error::ErrorType,
+    };
+
+    fn assert_invalid(create_service_nervous_system: CreateServiceNervousSystem, keyword: &str) {
+        let error = create_service_nervous_system.validate().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains(keyword),
+            "{} not found in {}",
+            keyword,
+            error.error_message
+        );
+    }
+
+    fn with_swap_parameters(
+        update: impl FnOnce(&mut create_service_nervous_system::SwapParameters),
+    ) -> CreateServiceNervousSystem {
+        let mut result = CREATE_SERVICE_NERVOUS_SYSTEM.clone();
+        update(result.swap_parameters.as_mut().unwrap());
+        result
+    }
+
+    fn with_initial_token_distribution(
+        update: impl FnOnce(&mut create_service_nervous_system::InitialTokenDistribution),
+    ) -> CreateServiceNervousSystem {
+        let mut result = CREATE_SERVICE_NERVOUS_SYSTEM.clone();
+        update(result.initial_token_distribution.as_mut().unwrap());
+        result
+    }
+
+    #[test]
+    fn test_validate_valid_proposal() {
+        assert_eq!(CREATE_SERVICE_NERVOUS_SYSTEM.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_total_token_supply_overflow() {
+        assert_invalid(
+            with_initial_token_distribution(|initial_token_distribution| {
+                initial_token_distribution
+                    .treasury_distribution
+                    .as_mut()
+                    .unwrap()
+                    .total = Some(Tokens {
+                    e8s: Some(u64::MAX),
+                });
+            }),
+            "the total token supply",
+        );
+    }
+
+    #[test]
+    fn test_validate_empty_swap_distribution() {
+        assert_invalid(
+            with_initial_token_distribution(|initial_token_distribution| {
+                initial_token_distribution
+                    .swap_distribution
+                    .as_mut()
+                    .unwrap()
+                    .total = Some(Tokens { e8s: Some(0) });
+            }),
+            "swap_distribution.total must be positive",
+        );
+    }
+
+    #[test]
+    fn test_validate_zero_minimum_participants() {
+        assert_invalid(
+            with_swap_parameters(|swap_parameters| {
+                swap_parameters.minimum_participants = Some(0);
+            }),
+            "minimum_participants must be at least 1",
+        );
+    }
+
+    #[test]
+    fn test_validate_minimum_direct_participation_exceeds_maximum() {
+        assert_invalid(
+            with_swap_parameters(|swap_parameters| {
+                swap_parameters.minimum_direct_participation_icp =
+                    swap_parameters.maximum_direct_participation_icp;
+                swap_parameters.maximum_direct_participation_icp = Some(Tokens {
+                    e8s: Some(100_000_000),
+                });
+            }),
+            "swap_parameters.minimum_direct_participation_icp (18900000000 e8s) must not exceed \
+             swap_parameters.maximum_direct_participation_icp (100000000 e8s)",
+        );
+    }
+
+    #[test]
+    fn test_validate_minimum_participant_icp_exceeds_maximum() {
+        assert_invalid(
+            with_swap_parameters(|swap_parameters| {
+                swap_parameters.minimum_participant_icp = Some(Tokens {
+                    e8s: Some(10_000_000_001),
+                });
+            }),
+            "swap_parameters.minimum_participant_icp (10000000001 e8s) must not exceed \
+             swap_parameters.maximum_participant_icp (10000000000 e8s)",
+        );
+    }
+
+    #[test]
+    fn test_validate_maximum_participant_icp_exceeds_maximum_direct_participation() {
+        assert_invalid(
+            with_swap_parameters(|swap_parameters| {
+                swap_parameters.maximum_participant_icp = Some(Tokens {
+                    e8s: Some(20_000_000_000),
+                });
+            }),
+            "swap_parameters.maximum_participant_icp (20000000000 e8s) must not exceed \
+             swap_parameters.maximum_direct_participation_icp (18900000000 e8s)",
+        );
+    }
+
+    #[test]
+    fn test_validate_minimum_participants_cannot_be_reached() {
+        assert_invalid(
+            with_swap_parameters(|swap_parameters| {
+                swap_parameters.minimum_participants = Some(190);
+            }),
+            "swap_parameters.minimum_participants (190) times \
+             swap_parameters.minimum_participant_icp (100000000 e8s) must not exceed \
+             swap_parameters.maximum_direct_participation_icp (18900000000 e8s)",
+        );
+    }
+}
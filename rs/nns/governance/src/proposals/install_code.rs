@@ -1,15 +1,29 @@
 use super::{invalid_proposal_error, topic_to_manage_canister};
 use crate::{
-    pb::v1::{install_code::CanisterInstallMode, GovernanceError, InstallCode, Topic},
-    proposals::call_canister::CallCanister,
+    governance::LOG_PREFIX,
+    pb::v1::{
+        install_code::{CanisterInstallMode, ChunkedWasmModule},
+        GovernanceError, InstallCode, Topic,
+    },
+    proposals::call_canister::{CallCanister, MAX_CALL_CANISTER_PAYLOAD_BYTES},
 };
 
 use candid::{CandidType, Deserialize, Encode};
 use ic_base_types::CanisterId;
+use ic_crypto_sha2::Sha256;
 use ic_management_canister_types::CanisterInstallMode as RootCanisterInstallMode;
 use ic_nervous_system_root::change_canister::ChangeCanisterRequest;
 use ic_nns_constants::{LIFELINE_CANISTER_ID, ROOT_CANISTER_ID};
 use serde::Serialize;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+};
+
+/// The maximum number of chunks a chunked wasm module may consist of, which bounds the work done
+/// when validating such a proposal. This matches the maximum number of chunks in a canister's
+/// chunk store.
+const MAX_WASM_MODULE_CHUNKS: usize = 100;
 
 // When calling lifeline's upgrade_root method, this is the request. Keep this in sync with
 // `rs/nns/handlers/lifeline/impl/lifeline.mo`.
@@ -31,8 +45,14 @@ impl InstallCode {
         let _ = self.valid_canister_id()?;
         let _ = self.valid_install_mode()?;
         let _ = self.valid_wasm_module()?;
-        let _ = self.valid_topic()?;
+        let _ = self.valid_topic().inspect_err(|err| {
+            println!(
+                "{}Rejecting proposal to manage a canister: {}",
+                LOG_PREFIX, err.error_message
+            )
+        })?;
         let _ = self.canister_and_function()?;
+        let _ = self.valid_payload()?;
 
         // In the future, we could potentially validate the wasm module to see if it's a valid gzip
         // or a valid WASM.
@@ -66,12 +86,18 @@ impl InstallCode {
         }
     }
 
-    fn valid_wasm_module(&self) -> Result<&Vec<u8>, GovernanceError> {
+    fn valid_wasm_module(&self) -> Result<Cow<'_, Vec<u8>>, GovernanceError> {
         // We do not want to copy the (potentially large) wasm module when validating, so we return
-        // a reference and let the caller clone it if needed.
-        self.wasm_module
-            .as_ref()
-            .ok_or(invalid_proposal_error("Wasm module is required"))
+        // a reference and let the caller clone it if needed. A chunked wasm module has to be
+        // assembled though.
+        match (&self.wasm_module, &self.chunked_wasm_module) {
+            (Some(wasm_module), None) => Ok(Cow::Borrowed(wasm_module)),
+            (None, Some(chunked_wasm_module)) => chunked_wasm_module.assemble().map(Cow::Owned),
+            (Some(_), Some(_)) => Err(invalid_proposal_error(
+                "Wasm module and chunked wasm module cannot both be specified",
+            )),
+            (None, None) => Err(invalid_proposal_error("Wasm module is required")),
+        }
     }
 
     pub fn valid_topic(&self) -> Result<Topic, GovernanceError> {
@@ -81,7 +107,7 @@ impl InstallCode {
 
     fn payload_to_upgrade_root(&self) -> Result<Vec<u8>, GovernanceError> {
         let stop_upgrade_start = !self.skip_stopping_before_installing.unwrap_or(false);
-        let wasm_module = self.valid_wasm_module()?.clone();
+        let wasm_module = self.valid_wasm_module()?.into_owned();
         let module_arg = self.arg.clone().unwrap_or_default();
 
         Encode!(&UpgradeRootProposalPayload {
@@ -96,7 +122,7 @@ impl InstallCode {
         let stop_before_installing = !self.skip_stopping_before_installing.unwrap_or(false);
         let mode = self.valid_install_mode()?;
         let canister_id = self.valid_canister_id()?;
-        let wasm_module = self.valid_wasm_module()?.clone();
+        let wasm_module = self.valid_wasm_module()?.into_owned();
         let arg = self.arg.clone().unwrap_or_default();
         let compute_allocation = None;
         let memory_allocation = None;
@@ -114,6 +140,83 @@ impl InstallCode {
     }
 }
 
+impl ChunkedWasmModule {
+    /// Assembles the wasm module from its chunks, checking that every referenced chunk is present
+    /// (and referenced at most once), that the assembled module does not exceed
+    /// `MAX_CALL_CANISTER_PAYLOAD_BYTES`, and that it has the declared hash.
+    fn assemble(&self) -> Result<Vec<u8>, GovernanceError> {
+        let wasm_module_hash = self
+            .wasm_module_hash
+            .as_ref()
+            .ok_or(invalid_proposal_error("Wasm module hash is required"))?;
+        if self.chunk_hashes_list.is_empty() {
+            return Err(invalid_proposal_error("Chunk hashes list is required"));
+        }
+        // Check the lengths before hashing anything, so that a huge chunk list is rejected
+        // cheaply.
+        if self.chunk_hashes_list.len() > MAX_WASM_MODULE_CHUNKS
+            || self.chunks.len() > MAX_WASM_MODULE_CHUNKS
+        {
+            return Err(invalid_proposal_error(&format!(
+                "Chunked wasm module cannot have more than {} chunks",
+                MAX_WASM_MODULE_CHUNKS
+            )));
+        }
+
+        let chunks_by_hash: BTreeMap<[u8; 32], &Vec<u8>> = self
+            .chunks
+            .iter()
+            .map(|chunk| (Sha256::hash(chunk), chunk))
+            .collect();
+
+        // Look up all chunks and check the assembled size before copying anything.
+        let mut seen_chunk_hashes = BTreeSet::new();
+        let mut chunks = Vec::with_capacity(self.chunk_hashes_list.len());
+        let mut wasm_module_len = 0_usize;
+        for chunk_hash in &self.chunk_hashes_list {
+            if !seen_chunk_hashes.insert(chunk_hash) {
+                return Err(invalid_proposal_error(&format!(
+                    "Chunk {} appears more than once in the chunk hashes list",
+                    format_hash(chunk_hash)
+                )));
+            }
+            let chunk = <[u8; 32]>::try_from(chunk_hash.as_slice())
+                .ok()
+                .and_then(|chunk_hash| chunks_by_hash.get(&chunk_hash))
+                .ok_or_else(|| {
+                    invalid_proposal_error(&format!(
+                        "Chunk {} is missing from the chunked wasm module",
+                        format_hash(chunk_hash)
+                    ))
+                })?;
+            wasm_module_len = wasm_module_len.saturating_add(chunk.len());
+            chunks.push(chunk);
+        }
+        if wasm_module_len > MAX_CALL_CANISTER_PAYLOAD_BYTES {
+            return Err(invalid_proposal_error(&format!(
+                "Assembled wasm module is {} bytes, which exceeds the maximum of {} bytes",
+                wasm_module_len, MAX_CALL_CANISTER_PAYLOAD_BYTES
+            )));
+        }
+        let wasm_module = chunks.concat();
+
+        let assembled_wasm_module_hash = Sha256::hash(&wasm_module);
+        if assembled_wasm_module_hash.as_slice() != wasm_module_hash.as_slice() {
+            return Err(invalid_proposal_error(&format!(
+                "Assembled wasm module hash {} does not match the declared hash {}",
+                format_hash(&assembled_wasm_module_hash),
+                format_hash(wasm_module_hash)
+            )));
+        }
+
+        Ok(wasm_module)
+    }
+}
+
+fn format_hash(hash: &[u8]) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 impl CallCanister for InstallCode {
     fn canister_and_function(&self) -> Result<(CanisterId, &str), GovernanceError> {
         let canister_id = self.valid_canister_id()?;
@@ -160,7 +263,10 @@ impl CallCanister for InstallCode {
 mod tests {
     use super::*;
 
-    use crate::pb::v1::governance_error::ErrorType;
+    use crate::{
+        pb::v1::governance_error::ErrorType,
+        proposals::call_canister::MAX_CALL_CANISTER_PAYLOAD_BYTES,
+    };
 
     use ic_nns_constants::REGISTRY_CANISTER_ID;
 
@@ -176,6 +282,7 @@ mod tests {
             install_mode: Some(CanisterInstallMode::Upgrade as i32),
             arg: None,
             skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
         };
 
         assert_eq!(
@@ -198,6 +305,7 @@ mod tests {
             install_mode: Some(CanisterInstallMode::Upgrade as i32),
             arg: Some(vec![4, 5, 6]),
             skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
         };
 
         let is_invalid_proposal_with_keywords = |install_code: InstallCode, keywords: Vec<&str>| {
@@ -282,11 +390,35 @@ mod tests {
 
         is_invalid_proposal_with_keywords(
             InstallCode {
-                canister_id: Some(ic_nns_constants::SNS_WASM_CANISTER_ID.get()),
+                canister_id: Some(ic_nns_constants::NNS_UI_CANISTER_ID.get()),
                 ..valid_install_code.clone()
             },
             vec!["canister id", "not a protocol canister"],
         );
+
+        is_invalid_proposal_with_keywords(
+            InstallCode {
+                wasm_module: Some(vec![0; MAX_CALL_CANISTER_PAYLOAD_BYTES]),
+                ..valid_install_code.clone()
+            },
+            vec!["payload", "exceeds the maximum"],
+        );
+    }
+
+    #[cfg(feature = "test")]
+    #[test]
+    fn test_install_code_proposal_payload_just_below_limit() {
+        // Leave some room for the encoding overhead of the payload.
+        let install_code = InstallCode {
+            canister_id: Some(REGISTRY_CANISTER_ID.get()),
+            wasm_module: Some(vec![0; MAX_CALL_CANISTER_PAYLOAD_BYTES - 1024]),
+            install_mode: Some(CanisterInstallMode::Upgrade as i32),
+            arg: Some(vec![4, 5, 6]),
+            skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
+        };
+
+        assert_eq!(install_code.validate(), Ok(()));
     }
 
     #[cfg(feature = "test")]
@@ -298,6 +430,7 @@ mod tests {
             install_mode: Some(CanisterInstallMode::Upgrade as i32),
             arg: Some(vec![4, 5, 6]),
             skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
         };
 
         assert_eq!(install_code.validate(), Ok(()));
@@ -334,6 +467,7 @@ mod tests {
             install_mode: Some(CanisterInstallMode::Upgrade as i32),
             arg: Some(vec![4, 5, 6]),
             skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
         };
 
         assert_eq!(install_code.validate(), Ok(()));
@@ -366,6 +500,7 @@ mod tests {
             install_mode: Some(CanisterInstallMode::Reinstall as i32),
             arg: None,
             skip_stopping_before_installing: Some(true),
+            chunked_wasm_module: None,
         };
 
         assert_eq!(install_code.validate(), Ok(()));
@@ -392,4 +527,136 @@ mod tests {
             }
         );
     }
+
+    fn chunked_wasm_module(chunks: Vec<Vec<u8>>) -> ChunkedWasmModule {
+        ChunkedWasmModule {
+            wasm_module_hash: Some(Sha256::hash(&chunks.concat()).to_vec()),
+            chunk_hashes_list: chunks
+                .iter()
+                .map(|chunk| Sha256::hash(chunk).to_vec())
+                .collect(),
+            chunks,
+        }
+    }
+
+    #[test]
+    fn test_assemble_chunked_wasm_module() {
+        let chunked_wasm_module = chunked_wasm_module(vec![vec![1, 2], vec![3], vec![4, 5]]);
+
+        assert_eq!(chunked_wasm_module.assemble(), Ok(vec![1, 2, 3, 4, 5]));
+    }
+
+    #[test]
+    fn test_chunked_wasm_module_repeated_chunk() {
+        let chunked_wasm_module = chunked_wasm_module(vec![vec![1, 2], vec![3], vec![1, 2]]);
+
+        let error = chunked_wasm_module.assemble().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains(&format!(
+                "Chunk {} appears more than once",
+                format_hash(&Sha256::hash(&[1, 2]))
+            )),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[test]
+    fn test_chunked_wasm_module_too_large() {
+        let chunk_bytes = MAX_CALL_CANISTER_PAYLOAD_BYTES / 2 + 1;
+        let chunked_wasm_module =
+            chunked_wasm_module(vec![vec![1; chunk_bytes], vec![2; chunk_bytes]]);
+
+        let error = chunked_wasm_module.assemble().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains(&format!(
+                "Assembled wasm module is {} bytes, which exceeds the maximum",
+                2 * chunk_bytes
+            )),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[test]
+    fn test_chunked_wasm_module_missing_chunk() {
+        let mut chunked_wasm_module = chunked_wasm_module(vec![vec![1, 2], vec![3]]);
+        chunked_wasm_module.chunks.pop();
+
+        let error = chunked_wasm_module.assemble().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains(&format!(
+                "Chunk {} is missing",
+                format_hash(&Sha256::hash(&[3]))
+            )),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[test]
+    fn test_chunked_wasm_module_hash_mismatch() {
+        let chunked_wasm_module = ChunkedWasmModule {
+            wasm_module_hash: Some(Sha256::hash(&[1, 2, 4]).to_vec()),
+            ..chunked_wasm_module(vec![vec![1, 2], vec![3]])
+        };
+
+        let error = chunked_wasm_module.assemble().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains(&format!(
+                "Assembled wasm module hash {} does not match the declared hash {}",
+                format_hash(&Sha256::hash(&[1, 2, 3])),
+                format_hash(&Sha256::hash(&[1, 2, 4]))
+            )),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[test]
+    fn test_chunked_wasm_module_too_many_chunks() {
+        let chunked_wasm_module = chunked_wasm_module(vec![vec![1]; MAX_WASM_MODULE_CHUNKS + 1]);
+
+        let error = chunked_wasm_module.assemble().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains("more than 100 chunks"),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[cfg(feature = "test")]
+    #[test]
+    fn test_upgrade_non_root_protocol_canister_with_chunked_wasm_module() {
+        let install_code = InstallCode {
+            canister_id: Some(REGISTRY_CANISTER_ID.get()),
+            wasm_module: None,
+            install_mode: Some(CanisterInstallMode::Upgrade as i32),
+            arg: Some(vec![4, 5, 6]),
+            skip_stopping_before_installing: None,
+            chunked_wasm_module: Some(chunked_wasm_module(vec![vec![1, 2], vec![3]])),
+        };
+
+        assert_eq!(install_code.validate(), Ok(()));
+        let decoded_payload =
+            Decode!(&install_code.payload().unwrap(), ChangeCanisterRequest).unwrap();
+        assert_eq!(decoded_payload.wasm_module, vec![1, 2, 3]);
+
+        let error = InstallCode {
+            wasm_module: Some(vec![1, 2, 3]),
+            ..install_code
+        }
+        .validate()
+        .unwrap_err();
+        assert!(
+            error.error_message.contains("cannot both be specified"),
+            "{}",
+            error.error_message
+        );
+    }
 }
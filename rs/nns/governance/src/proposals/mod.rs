@@ -5,43 +5,191 @@ use ic_nns_constants::{
     CYCLES_LEDGER_INDEX_CANISTER_ID, CYCLES_MINTING_CANISTER_ID, EXCHANGE_RATE_CANISTER_ID,
     GENESIS_TOKEN_CANISTER_ID, GOVERNANCE_CANISTER_ID, ICP_LEDGER_ARCHIVE_1_CANISTER_ID,
     ICP_LEDGER_ARCHIVE_CANISTER_ID, LEDGER_CANISTER_ID, LEDGER_INDEX_CANISTER_ID,
-    LIFELINE_CANISTER_ID, REGISTRY_CANISTER_ID, ROOT_CANISTER_ID, SUBNET_RENTAL_CANISTER_ID,
+    LIFELINE_CANISTER_ID, REGISTRY_CANISTER_ID, ROOT_CANISTER_ID, SNS_WASM_CANISTER_ID,
+    SUBNET_RENTAL_CANISTER_ID,
 };
+use std::{cell::RefCell, collections::BTreeSet};
 
 pub mod call_canister;
 pub mod create_service_nervous_system;
 pub mod install_code;
 pub mod proposal_submission;
 pub mod stop_or_start_canister;
+pub mod update_protocol_canisters;
 
-const PROTOCOL_CANISTER_IDS: [&CanisterId; 16] = [
+thread_local! {
+    // Protocol canisters in addition to `PROTOCOL_CANISTER_IDS`, which can be updated without
+    // upgrading the governance canister. Mirrors `Governance.additional_protocol_canisters`.
+    static ADDITIONAL_PROTOCOL_CANISTER_IDS: RefCell<BTreeSet<CanisterId>> =
+        const { RefCell::new(BTreeSet::new()) };
+    // Canisters in `PROTOCOL_CANISTER_IDS` that are no longer protocol canisters. Mirrors
+    // `Governance.additional_protocol_canisters.removed_canister_ids`.
+    static REMOVED_PROTOCOL_CANISTER_IDS: RefCell<BTreeSet<CanisterId>> =
+        const { RefCell::new(BTreeSet::new()) };
+}
+
+/// Canisters that are considered part of the IC protocol,
+/// without which the IC will not be able to function properly.
+const PROTOCOL_CANISTER_IDS: [(&CanisterId, &str); 16] = [
+    (&REGISTRY_CANISTER_ID, "Registry"),
+    (&GOVERNANCE_CANISTER_ID, "Governance"),
+    (&LEDGER_CANISTER_ID, "Ledger"),
+    (&ROOT_CANISTER_ID, "Root"),
+    (&CYCLES_MINTING_CANISTER_ID, "Cycles Minting"),
+    (&LIFELINE_CANISTER_ID, "Lifeline"),
+    (&GENESIS_TOKEN_CANISTER_ID, "Genesis Token"),
+    (&ICP_LEDGER_ARCHIVE_CANISTER_ID, "ICP Ledger Archive"),
+    (&LEDGER_INDEX_CANISTER_ID, "Ledger Index"),
+    (&ICP_LEDGER_ARCHIVE_1_CANISTER_ID, "ICP Ledger Archive 1"),
+    (&SUBNET_RENTAL_CANISTER_ID, "Subnet Rental"),
+    (&EXCHANGE_RATE_CANISTER_ID, "Exchange Rate"),
+    (&BITCOIN_MAINNET_CANISTER_ID, "Bitcoin Mainnet"),
+    (&BITCOIN_TESTNET_CANISTER_ID, "Bitcoin Testnet"),
+    (&CYCLES_LEDGER_CANISTER_ID, "Cycles Ledger"),
+    (&CYCLES_LEDGER_INDEX_CANISTER_ID, "Cycles Ledger Index"),
+];
+
+/// Protocol canisters that cannot be removed from the protocol canisters by proposals.
+const CORE_PROTOCOL_CANISTER_IDS: [&CanisterId; 4] = [
     &REGISTRY_CANISTER_ID,
     &GOVERNANCE_CANISTER_ID,
     &LEDGER_CANISTER_ID,
     &ROOT_CANISTER_ID,
-    &CYCLES_MINTING_CANISTER_ID,
-    &LIFELINE_CANISTER_ID,
-    &GENESIS_TOKEN_CANISTER_ID,
-    &ICP_LEDGER_ARCHIVE_CANISTER_ID,
-    &LEDGER_INDEX_CANISTER_ID,
-    &ICP_LEDGER_ARCHIVE_1_CANISTER_ID,
-    &SUBNET_RENTAL_CANISTER_ID,
-    &EXCHANGE_RATE_CANISTER_ID,
-    &BITCOIN_MAINNET_CANISTER_ID,
-    &BITCOIN_TESTNET_CANISTER_ID,
-    &CYCLES_LEDGER_CANISTER_ID,
-    &CYCLES_LEDGER_INDEX_CANISTER_ID,
 ];
 
+/// NNS canisters that are not protocol canisters, but related to the Service Nervous System (SNS).
+const SNS_MANAGEMENT_CANISTER_IDS: [(&CanisterId, &str); 1] = [(&SNS_WASM_CANISTER_ID, "SNS-W")];
+
+/// The maximum difference between the index of a rejected canister id and the index of
+/// a well-known canister id for the latter to be suggested in the error message.
+const MAX_NEAR_MISS_DISTANCE: u64 = 2;
+
+/// The topic of proposals managing canisters (e.g., `InstallCode` and `StopOrStartCanister`)
+/// for every set of canisters that can be managed by such proposals. This is the single source
+/// of truth for classifying proposals managing canisters.
+const MANAGED_CANISTER_TOPICS: [(&[(&CanisterId, &str)], Topic); 2] = [
+    (&PROTOCOL_CANISTER_IDS, Topic::ProtocolCanisterManagement),
+    (
+        &SNS_MANAGEMENT_CANISTER_IDS,
+        Topic::ServiceNervousSystemManagement,
+    ),
+];
+
+/// Replaces the protocol canisters in addition to the compiled-in `PROTOCOL_CANISTER_IDS`.
+pub(crate) fn set_additional_protocol_canister_ids(
+    canister_ids: impl IntoIterator<Item = CanisterId>,
+) {
+    ADDITIONAL_PROTOCOL_CANISTER_IDS.with(|additional_protocol_canister_ids| {
+        *additional_protocol_canister_ids.borrow_mut() = canister_ids.into_iter().collect();
+    });
+}
+
+fn is_additional_protocol_canister(canister_id: &CanisterId) -> bool {
+    ADDITIONAL_PROTOCOL_CANISTER_IDS.with(|additional_protocol_canister_ids| {
+        additional_protocol_canister_ids
+            .borrow()
+            .contains(canister_id)
+    })
+}
+
+/// Replaces the canisters in the compiled-in `PROTOCOL_CANISTER_IDS` that are no longer protocol
+/// canisters.
+pub(crate) fn set_removed_protocol_canister_ids(
+    canister_ids: impl IntoIterator<Item = CanisterId>,
+) {
+    REMOVED_PROTOCOL_CANISTER_IDS.with(|removed_protocol_canister_ids| {
+        *removed_protocol_canister_ids.borrow_mut() = canister_ids.into_iter().collect();
+    });
+}
+
+fn is_removed_protocol_canister(canister_id: &CanisterId) -> bool {
+    REMOVED_PROTOCOL_CANISTER_IDS.with(|removed_protocol_canister_ids| {
+        removed_protocol_canister_ids.borrow().contains(canister_id)
+    })
+}
+
+pub(crate) fn is_core_protocol_canister(canister_id: &CanisterId) -> bool {
+    CORE_PROTOCOL_CANISTER_IDS.contains(&canister_id)
+}
+
+/// Returns the topic under which the given canister is managed according to the compiled-in
+/// `MANAGED_CANISTER_TOPICS`, regardless of any updates to the protocol canisters.
+pub(crate) fn compiled_in_topic_to_manage_canister(canister_id: &CanisterId) -> Option<Topic> {
+    MANAGED_CANISTER_TOPICS
+        .iter()
+        .find(|(canisters, _)| {
+            canisters
+                .iter()
+                .any(|(managed_canister_id, _)| *managed_canister_id == canister_id)
+        })
+        .map(|(_, topic)| *topic)
+}
+
+fn well_known_canisters() -> impl Iterator<Item = (&'static CanisterId, &'static str)> {
+    MANAGED_CANISTER_TOPICS
+        .iter()
+        .flat_map(|(canisters, _)| canisters.iter().copied())
+}
+
+/// Returns the human-readable name (e.g., "Registry") of a well-known canister
+/// that can be managed by proposals.
+pub(crate) fn canister_name(canister_id: &CanisterId) -> Option<&'static str> {
+    well_known_canisters()
+        .find(|(well_known_canister_id, _)| *well_known_canister_id == canister_id)
+        .map(|(_, name)| name)
+}
+
+/// Returns the index of a canister id created by `CanisterId::from_u64`.
+fn canister_index(canister_id: &CanisterId) -> Option<u64> {
+    match canister_id.get_ref().as_slice() {
+        [index @ .., 0x01, 0x01] => Some(u64::from_be_bytes(index.try_into().ok()?)),
+        _ => None,
+    }
+}
+
+/// Returns the well-known canister whose index differs from the index of the given
+/// canister id by at most `MAX_NEAR_MISS_DISTANCE` (if any), e.g., due to a typo.
+fn near_miss_canister(canister_id: &CanisterId) -> Option<(&'static CanisterId, &'static str)> {
+    let index = canister_index(canister_id)?;
+    well_known_canisters()
+        .filter_map(|(well_known_canister_id, name)| {
+            let distance = canister_index(well_known_canister_id)?.abs_diff(index);
+            (distance <= MAX_NEAR_MISS_DISTANCE).then_some((distance, well_known_canister_id, name))
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .map(|(_, well_known_canister_id, name)| (well_known_canister_id, name))
+}
+
+/// Returns the topic of proposals managing the given canister, or an error if the canister is not
+/// managed by the NNS. Since this is also used outside of proposal validation (e.g., by queries),
+/// it does not log anything.
 pub(crate) fn topic_to_manage_canister(canister_id: &CanisterId) -> Result<Topic, GovernanceError> {
-    if PROTOCOL_CANISTER_IDS.contains(&canister_id) {
-        Ok(Topic::ProtocolCanisterManagement)
-    } else {
-        Err(invalid_proposal_error(&format!(
-            "Canister id {:?} is not a protocol canister",
-            canister_id
-        )))
+    // The compiled-in classification takes precedence over the additional protocol canisters,
+    // unless the canister has been removed from the protocol canisters.
+    let topic = compiled_in_topic_to_manage_canister(canister_id)
+        .filter(|topic| {
+            *topic != Topic::ProtocolCanisterManagement
+                || !is_removed_protocol_canister(canister_id)
+        })
+        .or_else(|| {
+            is_additional_protocol_canister(canister_id)
+                .then_some(Topic::ProtocolCanisterManagement)
+        });
+    if let Some(topic) = topic {
+        return Ok(topic);
     }
+
+    let suggestion = match near_miss_canister(canister_id) {
+        Some((near_miss_canister_id, name)) => format!(
+            " (did you mean the {} canister {}?)",
+            name, near_miss_canister_id
+        ),
+        None => String::new(),
+    };
+    Err(invalid_proposal_error(&format!(
+        "Canister id {:?} is not a protocol canister or another canister managed by the NNS{}",
+        canister_id, suggestion
+    )))
 }
 
 pub(crate) fn invalid_proposal_error(reason: &str) -> GovernanceError {
@@ -50,3 +198,145 @@ pub(crate) fn invalid_proposal_error(reason: &str) -> GovernanceError {
         format!("Proposal invalid because of {}", reason),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ic_nns_constants::{IDENTITY_CANISTER_ID, NNS_UI_CANISTER_ID};
+
+    #[test]
+    fn test_topic_to_manage_canister() {
+        let protocol_canister_ids = [
+            REGISTRY_CANISTER_ID,
+            GOVERNANCE_CANISTER_ID,
+            LEDGER_CANISTER_ID,
+            ROOT_CANISTER_ID,
+            CYCLES_MINTING_CANISTER_ID,
+            LIFELINE_CANISTER_ID,
+            GENESIS_TOKEN_CANISTER_ID,
+            ICP_LEDGER_ARCHIVE_CANISTER_ID,
+            LEDGER_INDEX_CANISTER_ID,
+            ICP_LEDGER_ARCHIVE_1_CANISTER_ID,
+            SUBNET_RENTAL_CANISTER_ID,
+            EXCHANGE_RATE_CANISTER_ID,
+            BITCOIN_MAINNET_CANISTER_ID,
+            BITCOIN_TESTNET_CANISTER_ID,
+            CYCLES_LEDGER_CANISTER_ID,
+            CYCLES_LEDGER_INDEX_CANISTER_ID,
+        ];
+        let sns_management_canister_ids = [SNS_WASM_CANISTER_ID];
+        let unmanaged_canister_ids = [
+            IDENTITY_CANISTER_ID,
+            NNS_UI_CANISTER_ID,
+            CanisterId::from_u64(123_456_789),
+        ];
+
+        for canister_id in &protocol_canister_ids {
+            assert_eq!(
+                topic_to_manage_canister(canister_id),
+                Ok(Topic::ProtocolCanisterManagement),
+                "{}",
+                canister_id
+            );
+        }
+        for canister_id in &sns_management_canister_ids {
+            assert_eq!(
+                topic_to_manage_canister(canister_id),
+                Ok(Topic::ServiceNervousSystemManagement),
+                "{}",
+                canister_id
+            );
+        }
+        for canister_id in &unmanaged_canister_ids {
+            let error = topic_to_manage_canister(canister_id).unwrap_err();
+            assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+            assert!(error.error_message.contains("is not a protocol canister"));
+        }
+
+        // The canisters above cover the table exhaustively.
+        let managed_canister_count: usize = MANAGED_CANISTER_TOPICS
+            .iter()
+            .map(|(canister_ids, _)| canister_ids.len())
+            .sum();
+        assert_eq!(
+            managed_canister_count,
+            protocol_canister_ids.len() + sns_management_canister_ids.len()
+        );
+    }
+
+    #[test]
+    fn test_topic_to_manage_additional_protocol_canister() {
+        let new_protocol_canister_id = CanisterId::from_u64(123_456_789);
+        assert!(topic_to_manage_canister(&new_protocol_canister_id).is_err());
+
+        set_additional_protocol_canister_ids(vec![new_protocol_canister_id, SNS_WASM_CANISTER_ID]);
+        assert_eq!(
+            topic_to_manage_canister(&new_protocol_canister_id),
+            Ok(Topic::ProtocolCanisterManagement)
+        );
+        // The compiled-in classification cannot be overridden.
+        assert_eq!(
+            topic_to_manage_canister(&SNS_WASM_CANISTER_ID),
+            Ok(Topic::ServiceNervousSystemManagement)
+        );
+        assert!(topic_to_manage_canister(&NNS_UI_CANISTER_ID).is_err());
+
+        set_additional_protocol_canister_ids(vec![]);
+        assert!(topic_to_manage_canister(&new_protocol_canister_id).is_err());
+    }
+
+    #[test]
+    fn test_topic_to_manage_removed_protocol_canister() {
+        set_removed_protocol_canister_ids(vec![
+            CYCLES_LEDGER_INDEX_CANISTER_ID,
+            SNS_WASM_CANISTER_ID,
+        ]);
+        let error = topic_to_manage_canister(&CYCLES_LEDGER_INDEX_CANISTER_ID).unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        // Only protocol canisters can be removed.
+        assert_eq!(
+            topic_to_manage_canister(&SNS_WASM_CANISTER_ID),
+            Ok(Topic::ServiceNervousSystemManagement)
+        );
+        assert_eq!(
+            topic_to_manage_canister(&CYCLES_LEDGER_CANISTER_ID),
+            Ok(Topic::ProtocolCanisterManagement)
+        );
+
+        set_removed_protocol_canister_ids(vec![]);
+        assert_eq!(
+            topic_to_manage_canister(&CYCLES_LEDGER_INDEX_CANISTER_ID),
+            Ok(Topic::ProtocolCanisterManagement)
+        );
+    }
+
+    #[test]
+    fn test_canister_name() {
+        assert_eq!(canister_name(&REGISTRY_CANISTER_ID), Some("Registry"));
+        assert_eq!(
+            canister_name(&CYCLES_MINTING_CANISTER_ID),
+            Some("Cycles Minting")
+        );
+        assert_eq!(canister_name(&SNS_WASM_CANISTER_ID), Some("SNS-W"));
+        assert_eq!(canister_name(&NNS_UI_CANISTER_ID), None);
+    }
+
+    #[test]
+    fn test_near_miss_error_names_canister() {
+        // A canister id next to the bitcoin mainnet canister id (0x1a00004).
+        let error = topic_to_manage_canister(&CanisterId::from_u64(0x1a00005)).unwrap_err();
+        assert!(
+            error.error_message.contains(&format!(
+                "did you mean the Bitcoin Mainnet canister {}",
+                BITCOIN_MAINNET_CANISTER_ID
+            )),
+            "{}",
+            error.error_message
+        );
+
+        // Unrelated canister ids are not reported as near misses.
+        let error = topic_to_manage_canister(&CanisterId::from_u64(123_456_789)).unwrap_err();
+        assert!(!error.error_message.contains("did you mean"));
+    }
+}
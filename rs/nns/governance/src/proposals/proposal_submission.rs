@@ -1,6 +1,11 @@
-use crate::pb::v1::{
-    manage_neuron::Command, manage_neuron_response::Command as CommandResponse, proposal,
-    ExecuteNnsFunction, ManageNeuron, ManageNeuronResponse, NnsFunction, Proposal,
+use crate::{
+    governance::{validate_motion, validate_user_submitted_proposal_fields, Governance},
+    pb::v1::{
+        governance_error::ErrorType, manage_neuron::Command,
+        manage_neuron_response::Command as CommandResponse, proposal, proposal::Action,
+        ExecuteNnsFunction, GovernanceError, ManageNeuron, ManageNeuronResponse, NnsFunction,
+        Proposal, Topic,
+    },
 };
 use candid::{CandidType, Decode, Encode};
 use ic_nns_common::types::{NeuronId, ProposalId};
@@ -40,6 +45,65 @@ pub fn create_external_update_proposal_binary(
     }
 }
 
+/// Validates the given proposal as far as possible without depending on the state of the
+/// governance canister, and without side effects: the topic, the user-submitted fields, and the
+/// structure of the action (e.g., its payload and target canister ids). Returns the topic of the
+/// proposal if it is valid.
+///
+/// This is also the first step of the validation upon submission, so a proposal that fails here
+/// is guaranteed to be rejected. The converse does not hold: some checks (e.g., on the available
+/// memory, or on the neuron managed by a ManageNeuron proposal) can only be performed upon
+/// submission.
+pub fn validate_proposal(proposal: &Proposal) -> Result<Topic, GovernanceError> {
+    let invalid_proposal_error =
+        |message: String| GovernanceError::new_with_message(ErrorType::InvalidProposal, message);
+
+    let topic = proposal.topic();
+    if topic == Topic::Unspecified {
+        return Err(invalid_proposal_error(format!(
+            "Topic not specified. proposal: {:#?}",
+            proposal
+        )));
+    }
+
+    validate_user_submitted_proposal_fields(proposal).map_err(invalid_proposal_error)?;
+
+    // Require that oneof action is populated.
+    let action = proposal.action.as_ref().ok_or_else(|| {
+        invalid_proposal_error(format!("Proposal lacks an action: {:?}", proposal))
+    })?;
+
+    match action {
+        Action::Motion(motion) => validate_motion(motion),
+        Action::SetSnsTokenSwapOpenTimeWindow(obsolete_action) => {
+            Governance::validate_obsolete_proposal_action(obsolete_action)
+        }
+        Action::OpenSnsTokenSwap(obsolete_action) => {
+            Governance::validate_obsolete_proposal_action(obsolete_action)
+        }
+        Action::InstallCode(install_code) => install_code.validate(),
+        Action::StopOrStartCanister(stop_or_start) => stop_or_start.validate(),
+        Action::UpdateProtocolCanisters(update_protocol_canisters) => {
+            update_protocol_canisters.validate()
+        }
+        Action::CreateServiceNervousSystem(create_service_nervous_system) => {
+            create_service_nervous_system.validate()
+        }
+        // The validation of these actions depends on the state, so it can only be performed upon
+        // submission.
+        Action::ExecuteNnsFunction(_) | Action::ManageNeuron(_) => Ok(()),
+        Action::ManageNetworkEconomics(_)
+        | Action::ApproveGenesisKyc(_)
+        | Action::AddOrRemoveNodeProvider(_)
+        | Action::RewardNodeProvider(_)
+        | Action::SetDefaultFollowees(_)
+        | Action::RewardNodeProviders(_)
+        | Action::RegisterKnownNeuron(_) => Ok(()),
+    }?;
+
+    Ok(topic)
+}
+
 /// Wraps the given proposal into a MakeProposal command, and wraps the command
 /// into a payload to call `manage_neuron`.
 pub fn create_make_proposal_payload(
@@ -70,3 +134,72 @@ pub fn decode_make_proposal_response(response: Vec<u8>) -> Result<ProposalId, St
         _ => Err("Unexpected ManageNeuronResponse".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pb::v1::{install_code::CanisterInstallMode, InstallCode, Motion};
+    use ic_nns_constants::NNS_UI_CANISTER_ID;
+
+    fn motion_proposal() -> Proposal {
+        Proposal {
+            title: Some("A motion".to_string()),
+            summary: "Summary".to_string(),
+            url: "".to_string(),
+            action: Some(Action::Motion(Motion {
+                motion_text: "Motion text".to_string(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_validate_proposal_returns_topic() {
+        assert_eq!(validate_proposal(&motion_proposal()), Ok(Topic::Governance));
+    }
+
+    #[test]
+    fn test_validate_proposal_without_action() {
+        let proposal = Proposal {
+            action: None,
+            ..motion_proposal()
+        };
+
+        let error = validate_proposal(&proposal).unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+    }
+
+    #[test]
+    fn test_validate_proposal_without_title() {
+        let proposal = Proposal {
+            title: None,
+            ..motion_proposal()
+        };
+
+        let error = validate_proposal(&proposal).unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        assert!(
+            error.error_message.contains("Proposal lacks a title"),
+            "{}",
+            error.error_message
+        );
+    }
+
+    #[test]
+    fn test_validate_proposal_checks_canister_id() {
+        let proposal = Proposal {
+            action: Some(Action::InstallCode(InstallCode {
+                canister_id: Some(NNS_UI_CANISTER_ID.get()),
+                install_mode: Some(CanisterInstallMode::Upgrade as i32),
+                wasm_module: Some(vec![1, 2, 3]),
+                arg: None,
+                skip_stopping_before_installing: None,
+                chunked_wasm_module: None,
+            })),
+            ..motion_proposal()
+        };
+
+        let error = validate_proposal(&proposal).unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+    }
+}
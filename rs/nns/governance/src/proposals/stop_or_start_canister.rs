@@ -1,5 +1,6 @@
-use super::{invalid_proposal_error, topic_to_manage_canister};
+use super::{canister_name, invalid_proposal_error, topic_to_manage_canister};
 use crate::{
+    governance::LOG_PREFIX,
     pb::v1::{stop_or_start_canister::CanisterAction, GovernanceError, StopOrStartCanister, Topic},
     proposals::call_canister::CallCanister,
 };
@@ -17,6 +18,9 @@ const CANISTERS_NOT_ALLOWED_TO_STOP: [&CanisterId; 3] = [
     &LIFELINE_CANISTER_ID,
 ];
 
+/// The maximum number of canisters a single proposal can stop or start.
+const MAX_CANISTERS_PER_PROPOSAL: usize = 20;
+
 impl StopOrStartCanister {
     pub fn validate(&self) -> Result<(), GovernanceError> {
         if !cfg!(feature = "test") {
@@ -25,37 +29,115 @@ impl StopOrStartCanister {
             ));
         }
 
-        let canister_id = self.valid_canister_id()?;
+        let canister_ids = self.valid_canister_ids()?;
         let canister_action = self.valid_canister_action()?;
-        let _ = self.valid_topic()?;
+        let _ = self.valid_topic().inspect_err(|err| {
+            println!(
+                "{}Rejecting proposal to manage canisters: {}",
+                LOG_PREFIX, err.error_message
+            )
+        })?;
 
         // Note that any proposals trying to start governance/root does not make sense since if they
         // are stopped/stopping, they can't be started as they need to be running in order to
         // execute the proposal. However, we don't disallow them as they are harmless.
-        if CANISTERS_NOT_ALLOWED_TO_STOP.contains(&&canister_id)
-            && canister_action == RootCanisterAction::Stop
-        {
-            return Err(invalid_proposal_error(
-                "Canister is not allowed to be stopped",
-            ));
+        if canister_action == RootCanisterAction::Stop {
+            if let Some(canister_id) = canister_ids
+                .iter()
+                .find(|canister_id| CANISTERS_NOT_ALLOWED_TO_STOP.contains(canister_id))
+            {
+                return Err(invalid_proposal_error(&format!(
+                    "the {} canister is not allowed to be stopped",
+                    canister_name(canister_id).unwrap_or("given"),
+                )));
+            }
+        }
+
+        for canister_request in self.canister_requests()? {
+            let _ = canister_request.valid_payload()?;
         }
 
         Ok(())
     }
 
     pub fn valid_topic(&self) -> Result<Topic, GovernanceError> {
-        let canister_id = self.valid_canister_id()?;
-        topic_to_manage_canister(&canister_id)
+        let canister_ids = self.valid_canister_ids()?;
+        let mut topic = None;
+        for canister_id in &canister_ids {
+            let canister_topic = topic_to_manage_canister(canister_id)?;
+            match topic {
+                None => topic = Some(canister_topic),
+                Some(topic) if topic != canister_topic => {
+                    return Err(invalid_proposal_error(&format!(
+                        "canister {} is managed under topic {:?}, but the other canisters are \
+                         managed under topic {:?}",
+                        canister_id, canister_topic, topic
+                    )));
+                }
+                Some(_) => {}
+            }
+        }
+        topic.ok_or(invalid_proposal_error("Canister ID is required"))
     }
 
-    fn valid_canister_id(&self) -> Result<CanisterId, GovernanceError> {
-        let canister_principal_id = self
-            .canister_id
-            .ok_or(invalid_proposal_error("Canister ID is required"))?;
-        let canister_id = CanisterId::try_from(canister_principal_id)
-            .map_err(|_| invalid_proposal_error("Invalid canister ID"))?;
+    /// Splits the proposal into one request per target canister, in the order in which they are
+    /// to be executed.
+    pub fn canister_requests(&self) -> Result<Vec<StopOrStartCanister>, GovernanceError> {
+        let canister_requests = self
+            .valid_canister_ids()?
+            .into_iter()
+            .map(|canister_id| StopOrStartCanister {
+                canister_id: Some(canister_id.get()),
+                action: self.action,
+                canister_ids: vec![],
+            })
+            .collect();
+        Ok(canister_requests)
+    }
+
+    fn valid_canister_ids(&self) -> Result<Vec<CanisterId>, GovernanceError> {
+        let canister_principal_ids = match (self.canister_id, self.canister_ids.is_empty()) {
+            (Some(canister_principal_id), true) => vec![canister_principal_id],
+            (None, false) => self.canister_ids.clone(),
+            (Some(_), false) => {
+                return Err(invalid_proposal_error(
+                    "Canister ID and canister IDs cannot both be specified",
+                ))
+            }
+            (None, true) => return Err(invalid_proposal_error("Canister ID is required")),
+        };
+        if canister_principal_ids.len() > MAX_CANISTERS_PER_PROPOSAL {
+            return Err(invalid_proposal_error(&format!(
+                "at most {} canister IDs can be specified, but {} were",
+                MAX_CANISTERS_PER_PROPOSAL,
+                canister_principal_ids.len()
+            )));
+        }
+
+        let mut canister_ids = Vec::with_capacity(canister_principal_ids.len());
+        for canister_principal_id in canister_principal_ids {
+            let canister_id = CanisterId::try_from(canister_principal_id).map_err(|_| {
+                invalid_proposal_error(&format!("Invalid canister ID {}", canister_principal_id))
+            })?;
+            if canister_ids.contains(&canister_id) {
+                return Err(invalid_proposal_error(&format!(
+                    "canister ID {} is specified more than once",
+                    canister_id
+                )));
+            }
+            canister_ids.push(canister_id);
+        }
 
-        Ok(canister_id)
+        Ok(canister_ids)
+    }
+
+    fn valid_canister_id(&self) -> Result<CanisterId, GovernanceError> {
+        match self.valid_canister_ids()?.as_slice() {
+            [canister_id] => Ok(*canister_id),
+            _ => Err(invalid_proposal_error(
+                "Exactly one canister ID is required to call the canister",
+            )),
+        }
     }
 
     fn valid_canister_action(&self) -> Result<RootCanisterAction, GovernanceError> {
@@ -110,6 +192,7 @@ mod tests {
         let stop_or_start_canister = StopOrStartCanister {
             canister_id: Some(CYCLES_MINTING_CANISTER_ID.get()),
             action: Some(CanisterAction::Stop as i32),
+            canister_ids: vec![],
         };
 
         assert_eq!(
@@ -128,6 +211,7 @@ mod tests {
         let valid_stop_or_start_canister = StopOrStartCanister {
             canister_id: Some(CYCLES_MINTING_CANISTER_ID.get()),
             action: Some(CanisterAction::Stop as i32),
+            canister_ids: vec![],
         };
 
         let is_invalid_proposal_with_keywords =
@@ -179,7 +263,7 @@ mod tests {
 
         is_invalid_proposal_with_keywords(
             StopOrStartCanister {
-                canister_id: Some(ic_nns_constants::SNS_WASM_CANISTER_ID.get()),
+                canister_id: Some(ic_nns_constants::NNS_UI_CANISTER_ID.get()),
                 ..valid_stop_or_start_canister.clone()
             },
             vec!["canister id", "not a protocol canister"],
@@ -189,25 +273,120 @@ mod tests {
             StopOrStartCanister {
                 canister_id: Some(ROOT_CANISTER_ID.get()),
                 action: Some(CanisterAction::Stop as i32),
+                canister_ids: vec![],
             },
-            vec!["not allowed to be stopped"],
+            vec!["root canister", "not allowed to be stopped"],
         );
 
         is_invalid_proposal_with_keywords(
             StopOrStartCanister {
                 canister_id: Some(LIFELINE_CANISTER_ID.get()),
                 action: Some(CanisterAction::Stop as i32),
+                canister_ids: vec![],
             },
-            vec!["not allowed to be stopped"],
+            vec!["lifeline canister", "not allowed to be stopped"],
         );
 
         is_invalid_proposal_with_keywords(
             StopOrStartCanister {
                 canister_id: Some(GOVERNANCE_CANISTER_ID.get()),
                 action: Some(CanisterAction::Stop as i32),
+                canister_ids: vec![],
             },
-            vec!["not allowed to be stopped"],
+            vec!["governance canister", "not allowed to be stopped"],
+        );
+
+        is_invalid_proposal_with_keywords(
+            StopOrStartCanister {
+                canister_ids: vec![CYCLES_MINTING_CANISTER_ID.get()],
+                ..valid_stop_or_start_canister.clone()
+            },
+            vec!["cannot both be specified"],
+        );
+
+        is_invalid_proposal_with_keywords(
+            StopOrStartCanister {
+                canister_id: None,
+                canister_ids: vec![
+                    CYCLES_MINTING_CANISTER_ID.get(),
+                    ic_nns_constants::NNS_UI_CANISTER_ID.get(),
+                ],
+                ..valid_stop_or_start_canister.clone()
+            },
+            vec![
+                &ic_nns_constants::NNS_UI_CANISTER_ID.to_string()[..],
+                "not a protocol canister",
+            ],
+        );
+
+        is_invalid_proposal_with_keywords(
+            StopOrStartCanister {
+                canister_id: None,
+                canister_ids: vec![
+                    CYCLES_MINTING_CANISTER_ID.get(),
+                    CYCLES_MINTING_CANISTER_ID.get(),
+                ],
+                ..valid_stop_or_start_canister.clone()
+            },
+            vec!["specified more than once"],
+        );
+
+        is_invalid_proposal_with_keywords(
+            StopOrStartCanister {
+                canister_id: None,
+                canister_ids: vec![
+                    CYCLES_MINTING_CANISTER_ID.get(),
+                    ic_nns_constants::SNS_WASM_CANISTER_ID.get(),
+                ],
+                ..valid_stop_or_start_canister.clone()
+            },
+            vec!["managed under topic"],
+        );
+
+        is_invalid_proposal_with_keywords(
+            StopOrStartCanister {
+                canister_id: None,
+                canister_ids: vec![CYCLES_MINTING_CANISTER_ID.get(), ROOT_CANISTER_ID.get()],
+                ..valid_stop_or_start_canister.clone()
+            },
+            vec!["root canister", "not allowed to be stopped"],
+        );
+    }
+
+    #[cfg(feature = "test")]
+    #[test]
+    fn test_stop_or_start_multiple_canisters() {
+        let stop_or_start_canister = StopOrStartCanister {
+            canister_id: None,
+            action: Some(CanisterAction::Stop as i32),
+            canister_ids: vec![
+                CYCLES_MINTING_CANISTER_ID.get(),
+                ic_nns_constants::REGISTRY_CANISTER_ID.get(),
+            ],
+        };
+
+        assert_eq!(stop_or_start_canister.validate(), Ok(()));
+        assert_eq!(
+            stop_or_start_canister.valid_topic(),
+            Ok(Topic::ProtocolCanisterManagement)
+        );
+        assert_eq!(
+            stop_or_start_canister.canister_requests(),
+            Ok(vec![
+                StopOrStartCanister {
+                    canister_id: Some(CYCLES_MINTING_CANISTER_ID.get()),
+                    action: Some(CanisterAction::Stop as i32),
+                    canister_ids: vec![],
+                },
+                StopOrStartCanister {
+                    canister_id: Some(ic_nns_constants::REGISTRY_CANISTER_ID.get()),
+                    action: Some(CanisterAction::Stop as i32),
+                    canister_ids: vec![],
+                },
+            ])
         );
+        // The combined proposal cannot be executed as a single call.
+        assert!(stop_or_start_canister.payload().is_err());
     }
 
     #[cfg(feature = "test")]
@@ -220,6 +399,7 @@ mod tests {
             let stop_or_start_canister = StopOrStartCanister {
                 canister_id: Some(CYCLES_MINTING_CANISTER_ID.get()),
                 action: Some(*canister_action as i32),
+                canister_ids: vec![],
             };
 
             assert_eq!(stop_or_start_canister.validate(), Ok(()));
@@ -252,6 +432,7 @@ mod tests {
         let stop_or_start_canister = StopOrStartCanister {
             canister_id: Some(LIFELINE_CANISTER_ID.get()),
             action: Some(CanisterAction::Start as i32),
+            canister_ids: vec![],
         };
 
         assert_eq!(stop_or_start_canister.validate(), Ok(()));
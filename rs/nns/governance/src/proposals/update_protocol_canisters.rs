@@ -0,0 +1,249 @@
+use super::{
+    canister_name, compiled_in_topic_to_manage_canister, invalid_proposal_error,
+    is_core_protocol_canister,
+};
+use crate::pb::v1::{GovernanceError, Topic, UpdateProtocolCanisters};
+
+use ic_base_types::{CanisterId, PrincipalId};
+use std::collections::BTreeSet;
+
+/// The maximum number of canisters a single proposal can add or remove.
+const MAX_CANISTERS_PER_PROPOSAL: usize = 20;
+
+impl UpdateProtocolCanisters {
+    pub fn validate(&self) -> Result<(), GovernanceError> {
+        let _ = self.valid_canister_ids()?;
+        Ok(())
+    }
+
+    /// Applies the proposal to the additional protocol canisters and the compiled-in protocol
+    /// canisters that have been removed (see `Governance.additional_protocol_canisters`).
+    pub fn apply(
+        &self,
+        additional_protocol_canister_ids: &mut BTreeSet<CanisterId>,
+        removed_protocol_canister_ids: &mut BTreeSet<CanisterId>,
+    ) -> Result<(), GovernanceError> {
+        let (canister_ids_to_add, canister_ids_to_remove) = self.valid_canister_ids()?;
+        for canister_id in canister_ids_to_add {
+            if is_compiled_in_protocol_canister(&canister_id) {
+                removed_protocol_canister_ids.remove(&canister_id);
+            } else {
+                additional_protocol_canister_ids.insert(canister_id);
+            }
+        }
+        for canister_id in canister_ids_to_remove {
+            if is_compiled_in_protocol_canister(&canister_id) {
+                removed_protocol_canister_ids.insert(canister_id);
+            } else {
+                additional_protocol_canister_ids.remove(&canister_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn valid_canister_ids(
+        &self,
+    ) -> Result<(BTreeSet<CanisterId>, BTreeSet<CanisterId>), GovernanceError> {
+        if self.canister_ids_to_add.is_empty() && self.canister_ids_to_remove.is_empty() {
+            return Err(invalid_proposal_error(
+                "at least one canister ID to add or remove is required",
+            ));
+        }
+        let canister_count = self.canister_ids_to_add.len() + self.canister_ids_to_remove.len();
+        if canister_count > MAX_CANISTERS_PER_PROPOSAL {
+            return Err(invalid_proposal_error(&format!(
+                "at most {} canister IDs can be specified, but {} were",
+                MAX_CANISTERS_PER_PROPOSAL, canister_count
+            )));
+        }
+
+        let canister_ids_to_add = parse_canister_ids(&self.canister_ids_to_add)?;
+        let canister_ids_to_remove = parse_canister_ids(&self.canister_ids_to_remove)?;
+
+        if let Some(canister_id) = canister_ids_to_add
+            .intersection(&canister_ids_to_remove)
+            .next()
+        {
+            return Err(invalid_proposal_error(&format!(
+                "canister {} cannot be both added and removed",
+                canister_id
+            )));
+        }
+        if let Some(canister_id) = canister_ids_to_remove
+            .iter()
+            .find(|canister_id| is_core_protocol_canister(canister_id))
+        {
+            return Err(invalid_proposal_error(&format!(
+                "the {} canister cannot be removed from the protocol canisters",
+                canister_name(canister_id).unwrap_or("given"),
+            )));
+        }
+
+        Ok((canister_ids_to_add, canister_ids_to_remove))
+    }
+}
+
+fn is_compiled_in_protocol_canister(canister_id: &CanisterId) -> bool {
+    compiled_in_topic_to_manage_canister(canister_id) == Some(Topic::ProtocolCanisterManagement)
+}
+
+fn parse_canister_ids(
+    canister_principal_ids: &[PrincipalId],
+) -> Result<BTreeSet<CanisterId>, GovernanceError> {
+    let mut canister_ids = BTreeSet::new();
+    for canister_principal_id in canister_principal_ids {
+        let canister_id = CanisterId::try_from(*canister_principal_id).map_err(|_| {
+            invalid_proposal_error(&format!("Invalid canister ID {}", canister_principal_id))
+        })?;
+        // Canisters managed under another topic (e.g., SNS-W) cannot become protocol canisters.
+        if let Some(topic) = compiled_in_topic_to_manage_canister(&canister_id)
+            .filter(|topic| *topic != Topic::ProtocolCanisterManagement)
+        {
+            return Err(invalid_proposal_error(&format!(
+                "canister {} is managed under topic {:?}",
+                canister_id, topic
+            )));
+        }
+        if !canister_ids.insert(canister_id) {
+            return Err(invalid_proposal_error(&format!(
+                "canister ID {} is specified more than once",
+                canister_id
+            )));
+        }
+    }
+    Ok(canister_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::pb::v1::governance_error::ErrorType;
+    use ic_nns_constants::{
+        CYCLES_LEDGER_INDEX_CANISTER_ID, GOVERNANCE_CANISTER_ID, LEDGER_CANISTER_ID,
+        REGISTRY_CANISTER_ID, ROOT_CANISTER_ID, SNS_WASM_CANISTER_ID,
+    };
+
+    fn is_invalid_proposal_with_keywords(
+        update_protocol_canisters: UpdateProtocolCanisters,
+        keywords: Vec<&str>,
+    ) {
+        let error = update_protocol_canisters.validate().unwrap_err();
+        assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+        let error_message = error.error_message.to_lowercase();
+        for keyword in keywords {
+            assert!(
+                error_message.contains(keyword),
+                "{} not found in {:#?}",
+                keyword,
+                error_message
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_update_protocol_canisters() {
+        let new_protocol_canister_id = CanisterId::from_u64(123_456_789).get();
+
+        is_invalid_proposal_with_keywords(
+            UpdateProtocolCanisters {
+                canister_ids_to_add: vec![],
+                canister_ids_to_remove: vec![],
+            },
+            vec!["at least one canister id"],
+        );
+        is_invalid_proposal_with_keywords(
+            UpdateProtocolCanisters {
+                canister_ids_to_add: vec![new_protocol_canister_id],
+                canister_ids_to_remove: vec![new_protocol_canister_id],
+            },
+            vec!["both added and removed"],
+        );
+        is_invalid_proposal_with_keywords(
+            UpdateProtocolCanisters {
+                canister_ids_to_add: vec![new_protocol_canister_id, new_protocol_canister_id],
+                canister_ids_to_remove: vec![],
+            },
+            vec!["more than once"],
+        );
+        is_invalid_proposal_with_keywords(
+            UpdateProtocolCanisters {
+                canister_ids_to_add: vec![SNS_WASM_CANISTER_ID.get()],
+                canister_ids_to_remove: vec![],
+            },
+            vec!["servicenervoussystemmanagement"],
+        );
+        is_invalid_proposal_with_keywords(
+            UpdateProtocolCanisters {
+                canister_ids_to_add: (0..=MAX_CANISTERS_PER_PROPOSAL as u64)
+                    .map(|index| CanisterId::from_u64(1_000_000 + index).get())
+                    .collect(),
+                canister_ids_to_remove: vec![],
+            },
+            vec!["at most 20 canister ids"],
+        );
+    }
+
+    #[test]
+    fn test_core_protocol_canisters_cannot_be_removed() {
+        for (canister_id, name) in [
+            (REGISTRY_CANISTER_ID, "registry"),
+            (GOVERNANCE_CANISTER_ID, "governance"),
+            (LEDGER_CANISTER_ID, "ledger"),
+            (ROOT_CANISTER_ID, "root"),
+        ] {
+            is_invalid_proposal_with_keywords(
+                UpdateProtocolCanisters {
+                    canister_ids_to_add: vec![],
+                    canister_ids_to_remove: vec![canister_id.get()],
+                },
+                vec![name, "cannot be removed"],
+            );
+        }
+
+        // Other compiled-in protocol canisters can be removed.
+        let update_protocol_canisters = UpdateProtocolCanisters {
+            canister_ids_to_add: vec![],
+            canister_ids_to_remove: vec![CYCLES_LEDGER_INDEX_CANISTER_ID.get()],
+        };
+        assert_eq!(update_protocol_canisters.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_apply_update_protocol_canisters() {
+        let new_protocol_canister_id = CanisterId::from_u64(123_456_789);
+        let mut additional_protocol_canister_ids = BTreeSet::new();
+        let mut removed_protocol_canister_ids = BTreeSet::new();
+
+        UpdateProtocolCanisters {
+            canister_ids_to_add: vec![new_protocol_canister_id.get()],
+            canister_ids_to_remove: vec![CYCLES_LEDGER_INDEX_CANISTER_ID.get()],
+        }
+        .apply(
+            &mut additional_protocol_canister_ids,
+            &mut removed_protocol_canister_ids,
+        )
+        .unwrap();
+        assert_eq!(
+            additional_protocol_canister_ids,
+            BTreeSet::from([new_protocol_canister_id])
+        );
+        assert_eq!(
+            removed_protocol_canister_ids,
+            BTreeSet::from([CYCLES_LEDGER_INDEX_CANISTER_ID])
+        );
+
+        // Adding a removed compiled-in protocol canister restores it.
+        UpdateProtocolCanisters {
+            canister_ids_to_add: vec![CYCLES_LEDGER_INDEX_CANISTER_ID.get()],
+            canister_ids_to_remove: vec![new_protocol_canister_id.get()],
+        }
+        .apply(
+            &mut additional_protocol_canister_ids,
+            &mut removed_protocol_canister_ids,
+        )
+        .unwrap();
+        assert!(additional_protocol_canister_ids.is_empty());
+        assert!(removed_protocol_canister_ids.is_empty());
+    }
+}
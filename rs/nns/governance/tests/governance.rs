@@ -87,8 +87,8 @@ use ic_nns_governance::{
         ProposalStatus::{self, Rejected},
         RewardEvent, RewardNodeProvider, RewardNodeProviders, SetDefaultFollowees,
         SettleNeuronsFundParticipationRequest, SwapBackgroundInformation, SwapParticipationLimits,
-        Tally, TallyChange, Topic, UpdateNodeProvider, Visibility, Vote, WaitForQuietState,
-        WaitForQuietStateDesc,
+        Tally, TallyChange, Topic, UpdateNodeProvider, UpdateProtocolCanisters, Visibility, Vote,
+        WaitForQuietState, WaitForQuietStateDesc,
     },
     proposals::create_service_nervous_system::ExecutedCreateServiceNervousSystemProposal,
 };
@@ -7038,6 +7038,107 @@ fn test_manage_and_reward_multiple_node_providers() {
     );
 }
 
+#[test]
+fn test_get_topic_to_manage_canister() {
+    let (_, gov) = governance_with_neurons(&[]);
+
+    assert_eq!(
+        gov.get_topic_to_manage_canister(&GOVERNANCE_CANISTER_ID),
+        Ok(Topic::ProtocolCanisterManagement)
+    );
+    assert_eq!(
+        gov.get_topic_to_manage_canister(&SNS_WASM_CANISTER_ID),
+        Ok(Topic::ServiceNervousSystemManagement)
+    );
+    // The same error as for a proposal managing the canister is returned.
+    let error = gov
+        .get_topic_to_manage_canister(&CanisterId::from_u64(123_456))
+        .unwrap_err();
+    assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+}
+
+#[tokio::test]
+async fn test_update_protocol_canisters_proposal() {
+    let mut driver = fake::FakeDriver::default();
+    let governance_proto = GovernanceProtoBuilder::new()
+        .with_instant_neuron_operations()
+        .with_economics(NetworkEconomics::with_default_values())
+        .with_neurons(vec![Neuron {
+            id: Some(NeuronId { id: 1 }),
+            account: driver.random_byte_array().to_vec(),
+            controller: Some(principal(1)),
+            cached_neuron_stake_e8s: 100_000_000,
+            dissolve_state: Some(DissolveState::DissolveDelaySeconds(
+                MAX_DISSOLVE_DELAY_SECONDS,
+            )),
+            ..Default::default()
+        }])
+        .build();
+    let mut gov = Governance::new(
+        governance_proto,
+        driver.get_fake_env(),
+        driver.get_fake_ledger(),
+        driver.get_fake_cmc(),
+    );
+    let update_protocol_canisters_proposal = |update_protocol_canisters| Proposal {
+        title: Some("Update the protocol canisters".to_string()),
+        summary: "Update the protocol canisters".to_string(),
+        action: Some(proposal::Action::UpdateProtocolCanisters(
+            update_protocol_canisters,
+        )),
+        ..Default::default()
+    };
+    let new_protocol_canister_id = CanisterId::from_u64(123_456);
+    assert!(gov
+        .get_topic_to_manage_canister(&new_protocol_canister_id)
+        .is_err());
+
+    let proposal_id = gov
+        .make_proposal(
+            &NeuronId { id: 1 },
+            &principal(1),
+            &update_protocol_canisters_proposal(UpdateProtocolCanisters {
+                canister_ids_to_add: vec![new_protocol_canister_id.get()],
+                canister_ids_to_remove: vec![],
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(
+        gov.get_proposal_data(proposal_id).unwrap().status(),
+        ProposalStatus::Executed
+    );
+    assert_eq!(
+        gov.get_proposal_data(proposal_id).unwrap().topic(),
+        Topic::ProtocolCanisterManagement
+    );
+    assert_eq!(
+        gov.get_topic_to_manage_canister(&new_protocol_canister_id),
+        Ok(Topic::ProtocolCanisterManagement)
+    );
+    assert_eq!(
+        gov.get_additional_protocol_canister_ids(),
+        vec![new_protocol_canister_id]
+    );
+
+    // The core protocol canisters cannot be removed.
+    let error = gov
+        .make_proposal(
+            &NeuronId { id: 1 },
+            &principal(1),
+            &update_protocol_canisters_proposal(UpdateProtocolCanisters {
+                canister_ids_to_add: vec![],
+                canister_ids_to_remove: vec![GOVERNANCE_CANISTER_ID.get()],
+            }),
+        )
+        .unwrap_err();
+    assert_eq!(error.error_type, ErrorType::InvalidProposal as i32);
+    assert_eq!(
+        gov.get_topic_to_manage_canister(&GOVERNANCE_CANISTER_ID),
+        Ok(Topic::ProtocolCanisterManagement)
+    );
+}
+
 #[test]
 fn test_network_economics_proposal() {
     let p = match std::env::var("NEURON_CSV_PATH") {
@@ -42,6 +42,7 @@ fn test_stop_and_start_registry_canister(use_proposal_action: bool) {
         Action::StopOrStartCanister(StopOrStartCanister {
             canister_id: Some(REGISTRY_CANISTER_ID.get()),
             action: Some(CanisterAction::Stop as i32),
+            canister_ids: vec![],
         })
     } else {
         let stop_or_start_request = StopOrStartCanisterRequest {
@@ -77,6 +78,7 @@ fn test_stop_and_start_registry_canister(use_proposal_action: bool) {
         Action::StopOrStartCanister(StopOrStartCanister {
             canister_id: Some(REGISTRY_CANISTER_ID.get()),
             action: Some(CanisterAction::Start as i32),
+            canister_ids: vec![],
         })
     } else {
         let stop_or_start_request = StopOrStartCanisterRequest {
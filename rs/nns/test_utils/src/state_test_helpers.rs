@@ -1040,6 +1040,7 @@ pub fn nns_propose_upgrade_nns_canister(
             wasm_module: Some(wasm_module),
             arg: Some(module_arg),
             skip_stopping_before_installing: None,
+            chunked_wasm_module: None,
         }))
     } else if target_canister_id != ROOT_CANISTER_ID {
         let payload = ChangeCanisterRequest::new(
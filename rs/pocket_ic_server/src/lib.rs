@@ -55,6 +55,14 @@ pub trait Operation {
         false
     }
 
+    /// True iff this operation does not modify the instance.
+    /// Read-only operations on a busy instance are computed on a read-only view
+    /// of the instance concurrently with the operation the instance is busy with
+    /// (instead of returning `Busy` or being queued up) and are not isolated from it.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     /// Returns the unique identifier of this operation.
     fn id(&self) -> OpId;
 }
@@ -95,13 +103,21 @@ pub fn copy_dir(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pocket_ic::{CanisterCall, ExecuteIngressMessage, PocketIc};
+    use crate::pocket_ic::{
+        CanisterCall, ExecuteIngressMessage, GetCyclesBalance, GetTime, PocketIc,
+    };
     use crate::state_api::state::*;
+    use ::pocket_ic::common::rest::{
+        AutoProgressConfig, CanisterHttpMethod, CanisterHttpRequest, CanisterHttpResponse,
+        ExtendedSubnetConfigSet, ProgressOperationKind, SubnetKind, SubnetSpec,
+    };
     use ::pocket_ic::WasmResult;
     use candid::{decode_args, encode_args};
     use ic_cdk::api::management_canister::main::CreateCanisterArgument;
     use ic_cdk::api::management_canister::provisional::CanisterIdRecord;
+    use ic_state_machine_tests::RejectCode;
     use ic_types::{CanisterId, PrincipalId};
+    use std::sync::Arc;
     use std::time::Duration;
     use tokio::runtime::Runtime;
 
@@ -139,4 +155,824 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_create_instance_from_spec() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let api_state = PocketIcApiStateBuilder::new().build();
+        let spec = ExtendedSubnetConfigSet {
+            system: vec![SubnetSpec::default()],
+            application: vec![SubnetSpec::default(), SubnetSpec::default()],
+            ..Default::default()
+        };
+
+        let (instance_id, topology) = rt
+            .block_on(api_state.create_instance_from_spec(rt.clone(), spec))
+            .unwrap();
+
+        assert_eq!(instance_id, 0);
+        assert_eq!(topology.0.len(), 3);
+        assert_eq!(topology.get_app_subnets().len(), 2);
+        assert_eq!(topology.get_system_subnets().len(), 1);
+        assert!(topology.0.values().all(|config| matches!(
+            config.subnet_kind,
+            SubnetKind::Application | SubnetKind::System
+        )));
+    }
+
+    #[test]
+    fn test_create_instance_from_empty_spec() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let api_state = PocketIcApiStateBuilder::new().build();
+
+        let res = rt.block_on(
+            api_state.create_instance_from_spec(rt.clone(), ExtendedSubnetConfigSet::default()),
+        );
+
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_save_and_load_instance() {
+        let rt = Arc::new(Runtime::new().unwrap());
+        let snapshot_dir = tempfile::TempDir::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+
+        let res = rt
+            .block_on(api_state.update(create_canister(1), instance_id))
+            .unwrap();
+        let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(bytes)))) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        let (CanisterIdRecord { canister_id },) = decode_args(&bytes).unwrap();
+        let get_cycles = Arc::new(GetCyclesBalance {
+            canister_id: CanisterId::try_from(PrincipalId(canister_id)).unwrap(),
+        });
+        let UpdateReply::Output(OpOut::Cycles(cycles)) = rt
+            .block_on(api_state.update(get_cycles.clone(), instance_id))
+            .unwrap()
+        else {
+            panic!("failed to fetch cycles balance");
+        };
+
+        rt.block_on(api_state.save_instance(instance_id, snapshot_dir.path().to_path_buf()))
+            .unwrap();
+
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_instance_from_dir(rt.clone(), snapshot_dir.path().to_path_buf())
+            .unwrap()
+            .build();
+        let res = rt
+            .block_on(api_state.update(get_cycles, instance_id))
+            .unwrap();
+        let UpdateReply::Output(OpOut::Cycles(loaded_cycles)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        assert_eq!(cycles, loaded_cycles);
+    }
+
+    #[test]
+    fn test_canister_http_timeout() {
+        let rt = Runtime::new().unwrap();
+        // A server accepting connections (in the backlog of the listener), but never responding.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let canister_http_request = CanisterHttpRequest {
+            subnet_id: candid::Principal::anonymous(),
+            request_id: 0,
+            http_method: CanisterHttpMethod::GET,
+            url: format!("http://127.0.0.1:{}/", port),
+            headers: vec![],
+            body: vec![],
+            max_response_bytes: None,
+        };
+        let canister_http_policy = CanisterHttpPolicy {
+            total_timeout: Some(Duration::from_millis(500)),
+            max_retries: 2,
+            ..Default::default()
+        };
+
+        let start = std::time::Instant::now();
+        let res = rt.block_on(ApiState::make_http_request(
+            canister_http_request,
+            None,
+            None,
+            canister_http_policy,
+        ));
+        let (reject_code, message) = res.unwrap_err();
+        assert_eq!(reject_code, RejectCode::SysTransient);
+        assert!(message.contains("timed out"));
+        // The timeout covers all retries.
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    #[test]
+    fn test_canister_http_response_exceeding_max_response_bytes() {
+        use std::io::{Read, Write};
+
+        let rt = Runtime::new().unwrap();
+        let max_response_bytes = 1000;
+        // A server responding with a body just over the limit.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = vec![];
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let body = vec![b'x'; max_response_bytes as usize + 1];
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        let canister_http_request = CanisterHttpRequest {
+            subnet_id: candid::Principal::anonymous(),
+            request_id: 0,
+            http_method: CanisterHttpMethod::GET,
+            url: format!("http://127.0.0.1:{}/", port),
+            headers: vec![],
+            body: vec![],
+            max_response_bytes: Some(max_response_bytes),
+        };
+
+        let res = rt.block_on(ApiState::make_http_request(
+            canister_http_request,
+            None,
+            None,
+            CanisterHttpPolicy::default(),
+        ));
+        // The oversized response is rejected (by the adapter or by the post-check
+        // on the reassembled body) rather than handed back to the canister.
+        let (reject_code, _) = res.unwrap_err();
+        assert_eq!(reject_code, RejectCode::SysFatal);
+    }
+
+    #[test]
+    fn test_concurrent_canister_http() {
+        let rt = Runtime::new().unwrap();
+        // A server accepting connections (in the backlog of the listener), but never responding.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let canister_http_requests: Vec<_> = (0..4)
+            .map(|request_id| CanisterHttpRequest {
+                subnet_id: candid::Principal::anonymous(),
+                request_id,
+                http_method: CanisterHttpMethod::GET,
+                url: format!("http://127.0.0.1:{}/{}", port, request_id),
+                headers: vec![],
+                body: vec![],
+                max_response_bytes: None,
+            })
+            .collect();
+        let canister_http_policy = CanisterHttpPolicy {
+            total_timeout: Some(Duration::from_secs(2)),
+            max_concurrency: 4,
+            ..Default::default()
+        };
+
+        let start = std::time::Instant::now();
+        let responses = rt.block_on(ApiState::make_http_requests(
+            canister_http_requests,
+            None,
+            None,
+            canister_http_policy,
+        ));
+        // The requests time out concurrently rather than one after another.
+        assert!(start.elapsed() < Duration::from_secs(6));
+        // The responses are returned in the order of the requests.
+        let request_ids: Vec<_> = responses.iter().map(|r| r.request_id).collect();
+        assert_eq!(request_ids, vec![0, 1, 2, 3]);
+        for response in responses {
+            assert!(matches!(
+                response.response,
+                CanisterHttpResponse::CanisterHttpReject(_)
+            ));
+        }
+        drop(listener);
+    }
+
+    // An operation keeping the instance busy for a while.
+    struct Sleep(Duration);
+
+    impl Operation for Sleep {
+        fn compute(&self, _pocket_ic: &mut PocketIc) -> OpOut {
+            std::thread::sleep(self.0);
+            OpOut::NoOutput
+        }
+
+        fn id(&self) -> OpId {
+            OpId(format!("sleep({:?})", self.0))
+        }
+    }
+
+    fn create_canister(sender: u64) -> Arc<ExecuteIngressMessage> {
+        Arc::new(ExecuteIngressMessage(CanisterCall {
+            sender: PrincipalId::new_user_test_id(sender),
+            canister_id: CanisterId::ic_00(),
+            method: "provisional_create_canister_with_cycles".to_string(),
+            payload: encode_args((CreateCanisterArgument { settings: None },)).unwrap(),
+            effective_principal: pocket_ic::EffectivePrincipal::None,
+        }))
+    }
+
+    #[test]
+    fn test_queued_operations() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .with_max_queue_depth(2)
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let no_wait = Some(Duration::ZERO);
+
+        let sleep = Arc::new(Sleep(Duration::from_secs(2)));
+        let res = rt
+            .block_on(api_state.update_with_timeout(sleep, instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started { .. } = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+
+        // The following operations are queued up behind the running one
+        // until the queue is full.
+        let mut queued = vec![];
+        for sender in 1..=2 {
+            let res = rt
+                .block_on(api_state.update_with_timeout(
+                    create_canister(sender),
+                    instance_id,
+                    no_wait,
+                ))
+                .unwrap();
+            let UpdateReply::Started { state_label, op_id } = res else {
+                panic!("unexpected result: {:?}", res);
+            };
+            queued.push((state_label, op_id));
+        }
+        let res =
+            rt.block_on(api_state.update_with_timeout(create_canister(3), instance_id, no_wait));
+        assert_eq!(res.unwrap_err(), UpdateError::QueueFull);
+
+        // The queued operations are executed in submission order
+        // and their results can be read from the graph.
+        let canister_ids: Vec<_> = queued
+            .into_iter()
+            .map(|(state_label, op_id)| loop {
+                if let Some((_, op_out)) =
+                    ApiState::read_result(api_state.get_graph(), &state_label, &op_id)
+                {
+                    let OpOut::CanisterResult(Ok(WasmResult::Reply(bytes))) = op_out else {
+                        panic!("unexpected result: {:?}", op_out);
+                    };
+                    let (CanisterIdRecord { canister_id },) = decode_args(&bytes).unwrap();
+                    break canister_id;
+                }
+                std::thread::sleep(Duration::from_millis(100));
+            })
+            .collect();
+        assert!(canister_ids[0] < canister_ids[1]);
+
+        // The instance becomes available once the queue is drained.
+        let timeout = Some(Duration::from_secs(30));
+        let res = rt
+            .block_on(api_state.update_with_timeout(create_canister(3), instance_id, timeout))
+            .unwrap();
+        let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+    }
+
+    #[test]
+    fn test_read_only_operation_on_busy_instance() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let no_wait = Some(Duration::ZERO);
+
+        let sleep = Arc::new(Sleep(Duration::from_secs(5)));
+        let res = rt
+            .block_on(api_state.update_with_timeout(sleep, instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started { .. } = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+
+        // A mutating operation is rejected while the instance is busy...
+        let res = rt
+            .block_on(api_state.update_with_timeout(create_canister(1), instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Busy { .. } = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+
+        // ...but a read-only operation is computed concurrently with the running operation.
+        let start = std::time::Instant::now();
+        let timeout = Some(Duration::from_secs(2));
+        let res = rt
+            .block_on(api_state.update_with_timeout(Arc::new(GetTime), instance_id, timeout))
+            .unwrap();
+        let UpdateReply::Output(OpOut::Time(_)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_cancel_operation() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .with_max_queue_depth(1)
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let no_wait = Some(Duration::ZERO);
+        let read_result = |state_label: &StateLabel, op_id: &OpId| loop {
+            if let Some((_, op_out)) =
+                ApiState::read_result(api_state.get_graph(), state_label, op_id)
+            {
+                break op_out;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
+
+        let sleep = Arc::new(Sleep(Duration::from_secs(2)));
+        let res = rt
+            .block_on(api_state.update_with_timeout(sleep, instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started {
+            state_label: sleep_state_label,
+            op_id: sleep_op_id,
+        } = res
+        else {
+            panic!("unexpected result: {:?}", res);
+        };
+        let res = rt
+            .block_on(api_state.update_with_timeout(create_canister(1), instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started {
+            state_label: queued_state_label,
+            op_id: queued_op_id,
+        } = res
+        else {
+            panic!("unexpected result: {:?}", res);
+        };
+
+        // Cancelling a queued operation removes it from the queue.
+        rt.block_on(api_state.cancel_operation(instance_id, queued_op_id.clone()))
+            .unwrap();
+        let OpOut::Cancelled = read_result(&queued_state_label, &queued_op_id) else {
+            panic!("queued operation not cancelled");
+        };
+
+        // Cancelling the running operation discards its result immediately.
+        rt.block_on(api_state.cancel_operation(instance_id, sleep_op_id.clone()))
+            .unwrap();
+        let OpOut::Cancelled = read_result(&sleep_state_label, &sleep_op_id) else {
+            panic!("running operation not cancelled");
+        };
+
+        // Once the running operation returns, the instance becomes available again.
+        let timeout = Some(Duration::from_secs(30));
+        let res = rt
+            .block_on(api_state.update_with_timeout(create_canister(1), instance_id, timeout))
+            .unwrap();
+        let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        let OpOut::Cancelled = read_result(&sleep_state_label, &sleep_op_id) else {
+            panic!("result of cancelled operation recorded");
+        };
+        assert_eq!(
+            rt.block_on(api_state.cancel_operation(instance_id, sleep_op_id))
+                .unwrap_err(),
+            UpdateError::OperationNotFound
+        );
+    }
+
+    #[test]
+    fn test_await_result() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let no_wait = Some(Duration::ZERO);
+
+        let sleep = Arc::new(Sleep(Duration::from_secs(1)));
+        let res = rt
+            .block_on(api_state.update_with_timeout(sleep, instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started { state_label, op_id } = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+
+        // The result is not available before the operation completes.
+        let res = rt.block_on(ApiState::await_result(
+            api_state.get_graph(),
+            &state_label,
+            &op_id,
+            Duration::from_millis(10),
+        ));
+        assert!(res.is_none());
+
+        // The caller is woken up as soon as the result is added to the graph.
+        let start = std::time::Instant::now();
+        let res = rt.block_on(ApiState::await_result(
+            api_state.get_graph(),
+            &state_label,
+            &op_id,
+            Duration::from_secs(30),
+        ));
+        let Some((_, OpOut::NoOutput)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        assert!(start.elapsed() < Duration::from_secs(10));
+
+        // A result that is already available is returned immediately.
+        let res = rt.block_on(ApiState::await_result(
+            api_state.get_graph(),
+            &state_label,
+            &op_id,
+            Duration::from_secs(30),
+        ));
+        let Some((_, OpOut::NoOutput)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+    }
+
+    #[test]
+    fn test_op_timings() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let no_wait = Some(Duration::ZERO);
+        assert_eq!(rt.block_on(api_state.op_timings(instance_id)), Some(vec![]));
+        assert!(rt.block_on(api_state.op_timings(instance_id + 1)).is_none());
+
+        let sleep = Arc::new(Sleep(Duration::from_millis(500)));
+        let res = rt
+            .block_on(api_state.update_with_timeout(sleep.clone(), instance_id, no_wait))
+            .unwrap();
+        let UpdateReply::Started { state_label, op_id } = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        let (_, op_out, duration) = loop {
+            if let Some(res) =
+                ApiState::read_result_with_timing(api_state.get_graph(), &state_label, &op_id)
+            {
+                break res;
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        };
+        let OpOut::NoOutput = op_out else {
+            panic!("unexpected result: {:?}", op_out);
+        };
+        assert!(duration >= Duration::from_millis(500));
+
+        // The slowest operation is listed first.
+        let timeout = Some(Duration::from_secs(30));
+        rt.block_on(api_state.update_with_timeout(create_canister(1), instance_id, timeout))
+            .unwrap();
+        let op_timings = rt.block_on(api_state.op_timings(instance_id)).unwrap();
+        assert_eq!(op_timings.len(), 2);
+        assert_eq!(op_timings[0].op_id, sleep.id().0);
+        assert_eq!(op_timings[0].duration, duration);
+        assert_eq!(op_timings[1].op_id, create_canister(1).id().0);
+    }
+
+    #[test]
+    fn test_update_errors() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let timeout = Some(Duration::from_secs(30));
+
+        let err = rt
+            .block_on(api_state.update_with_timeout(create_canister(1), 1, timeout))
+            .unwrap_err();
+        assert_eq!(err, UpdateError::InstanceNotFound);
+        assert_eq!(err.to_string(), "Instance not found");
+
+        rt.block_on(api_state.delete_instance(0));
+        let err = rt
+            .block_on(api_state.update_with_timeout(create_canister(1), 0, timeout))
+            .unwrap_err();
+        assert_eq!(err, UpdateError::InstanceDeleted);
+        assert_eq!(err.to_string(), "Instance was deleted");
+    }
+
+    #[test]
+    fn test_instance_cpu_budget() {
+        let rt = Runtime::new().unwrap();
+        let pocket_ic = PocketIc::default();
+        let api_state = PocketIcApiStateBuilder::new()
+            .with_instance_cpu_budget(Duration::from_nanos(1))
+            .add_initial_instance(pocket_ic)
+            .build();
+        let instance_id = 0;
+        let create_canister = || {
+            Arc::new(ExecuteIngressMessage(CanisterCall {
+                sender: PrincipalId::default(),
+                canister_id: CanisterId::ic_00(),
+                method: "provisional_create_canister_with_cycles".to_string(),
+                payload: encode_args((CreateCanisterArgument { settings: None },)).unwrap(),
+                effective_principal: pocket_ic::EffectivePrincipal::None,
+            }))
+        };
+
+        let timeout = Some(Duration::from_secs(30));
+        let update = |op| {
+            rt.block_on(api_state.update_with_timeout(op, instance_id, timeout))
+                .unwrap()
+        };
+
+        // The first operation exhausts the tiny budget.
+        match update(create_canister()) {
+            UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) => (),
+            e => panic!("unexpected result: {:?}", e),
+        }
+
+        // Subsequent operations are refused.
+        for _ in 0..2 {
+            match update(create_canister()) {
+                UpdateReply::Output(OpOut::Error(PocketIcError::CpuBudgetExhausted(_))) => (),
+                e => panic!("unexpected result: {:?}", e),
+            }
+        }
+
+        // Operations are accepted again after a reset.
+        rt.block_on(api_state.reset_cpu_budget(instance_id))
+            .unwrap();
+        match update(create_canister()) {
+            UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) => (),
+            e => panic!("unexpected result: {:?}", e),
+        }
+        match update(create_canister()) {
+            UpdateReply::Output(OpOut::Error(PocketIcError::CpuBudgetExhausted(_))) => (),
+            e => panic!("unexpected result: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_graph_eviction() {
+        let rt = Runtime::new().unwrap();
+        let pocket_ic = PocketIc::default();
+        let initial_state_label = pocket_ic.get_state_label();
+        let api_state = PocketIcApiStateBuilder::new()
+            .with_graph_capacity(2)
+            .add_initial_instance(pocket_ic)
+            .build();
+        let instance_id = 0;
+        let timeout = Some(Duration::from_secs(30));
+
+        // Every operation moves the instance to a new state label.
+        for sender in 1..=4 {
+            let res = rt
+                .block_on(api_state.update_with_timeout(
+                    create_canister(sender),
+                    instance_id,
+                    timeout,
+                ))
+                .unwrap();
+            let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) = res else {
+                panic!("unexpected result: {:?}", res);
+            };
+        }
+
+        // The result computed on the initial state label has been evicted.
+        let res = ApiState::read_result(
+            api_state.get_graph(),
+            &initial_state_label,
+            &create_canister(1).id(),
+        );
+        let Some((_, OpOut::Expired)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        // An unknown operation on an evicted state label is reported as expired, too.
+        let res = ApiState::read_result(
+            api_state.get_graph(),
+            &initial_state_label,
+            &create_canister(5).id(),
+        );
+        let Some((_, OpOut::Expired)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        assert_eq!(rt.block_on(api_state.get_graph().read()).iter().count(), 2);
+    }
+
+    #[test]
+    fn test_graph_eviction_zero_capacity() {
+        let rt = Runtime::new().unwrap();
+        let pocket_ic = PocketIc::default();
+        let initial_state_label = pocket_ic.get_state_label();
+        let api_state = PocketIcApiStateBuilder::new()
+            .with_graph_capacity(0)
+            .add_initial_instance(pocket_ic)
+            .build();
+        let instance_id = 0;
+        let timeout = Some(Duration::from_secs(30));
+
+        // The results of synchronous operations are still returned to the client.
+        for sender in 1..=3 {
+            let res = rt
+                .block_on(api_state.update_with_timeout(
+                    create_canister(sender),
+                    instance_id,
+                    timeout,
+                ))
+                .unwrap();
+            let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) = res else {
+                panic!("unexpected result: {:?}", res);
+            };
+        }
+
+        let res = ApiState::read_result(
+            api_state.get_graph(),
+            &initial_state_label,
+            &create_canister(1).id(),
+        );
+        let Some((_, OpOut::Expired)) = res else {
+            panic!("unexpected result: {:?}", res);
+        };
+        // Only the state label pinned during the last operation might be left.
+        assert!(rt.block_on(api_state.get_graph().read()).iter().count() <= 1);
+    }
+
+    #[test]
+    fn test_graph_eviction_multiple_instances() {
+        let rt = Runtime::new().unwrap();
+        let num_instances = 4;
+        let mut api_state_builder = PocketIcApiStateBuilder::new().with_graph_capacity(1);
+        for _ in 0..num_instances {
+            api_state_builder = api_state_builder.add_initial_instance(PocketIc::default());
+        }
+        let api_state = api_state_builder.build();
+        let timeout = Some(Duration::from_secs(30));
+
+        // Operations on different instances evict each other's state labels concurrently.
+        for round in 0..3 {
+            let updates = (0..num_instances).map(|instance_id| {
+                api_state.update_with_timeout(
+                    create_canister(round * num_instances as u64 + instance_id as u64 + 1),
+                    instance_id,
+                    timeout,
+                )
+            });
+            for res in rt.block_on(futures::future::join_all(updates)) {
+                let UpdateReply::Output(OpOut::CanisterResult(Ok(WasmResult::Reply(_)))) =
+                    res.unwrap()
+                else {
+                    panic!("unexpected result");
+                };
+            }
+        }
+    }
+
+    #[test]
+    fn test_progress_thread_status() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        assert!(rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .is_none());
+
+        let start = std::time::SystemTime::now();
+        rt.block_on(api_state.auto_progress(instance_id));
+        std::thread::sleep(Duration::from_millis(500));
+        let status = rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .unwrap();
+        assert!(status.last_operation_started_at >= start);
+        assert_eq!(status.canister_http_requests, 0);
+
+        rt.block_on(api_state.stop_progress(instance_id));
+        assert!(rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .is_none());
+    }
+
+    #[test]
+    fn test_pause_and_resume_progress_thread() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+
+        rt.block_on(api_state.auto_progress(instance_id));
+        rt.block_on(api_state.pause_progress(instance_id));
+        std::thread::sleep(Duration::from_millis(500));
+        let status = rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .unwrap();
+        assert_eq!(status.last_operation, ProgressOperationKind::Paused);
+
+        rt.block_on(api_state.resume_progress(instance_id));
+        std::thread::sleep(Duration::from_millis(500));
+        let status = rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .unwrap();
+        assert_ne!(status.last_operation, ProgressOperationKind::Paused);
+
+        rt.block_on(api_state.stop_progress(instance_id));
+        assert!(rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .is_none());
+    }
+
+    #[test]
+    fn test_auto_progress_restarts_with_new_config() {
+        let rt = Runtime::new().unwrap();
+        let api_state = PocketIcApiStateBuilder::new()
+            .add_initial_instance(PocketIc::default())
+            .build();
+        let instance_id = 0;
+        let timeout = Some(Duration::from_secs(30));
+        let get_time = || {
+            let res = rt
+                .block_on(api_state.update_with_timeout(Arc::new(GetTime), instance_id, timeout))
+                .unwrap();
+            let UpdateReply::Output(OpOut::Time(nanos)) = res else {
+                panic!("unexpected result: {:?}", res);
+            };
+            nanos
+        };
+
+        rt.block_on(api_state.auto_progress(instance_id));
+        rt.block_on(api_state.pause_progress(instance_id));
+        std::thread::sleep(Duration::from_millis(500));
+        let time_before = get_time();
+
+        // Starting automatic progress with a different config restarts the thread,
+        // which keeps being paused.
+        let advance_by = Duration::from_secs(3600);
+        let config = AutoProgressConfig {
+            tick_interval: Some(Duration::from_millis(10)),
+            advance_by: Some(advance_by),
+        };
+        rt.block_on(api_state.auto_progress_with_config(instance_id, config));
+        std::thread::sleep(Duration::from_millis(500));
+        let status = rt
+            .block_on(api_state.progress_thread_status(instance_id))
+            .unwrap();
+        assert_eq!(status.last_operation, ProgressOperationKind::Paused);
+
+        // Once resumed, the time advances according to the new config.
+        rt.block_on(api_state.resume_progress(instance_id));
+        std::thread::sleep(Duration::from_millis(500));
+        rt.block_on(api_state.stop_progress(instance_id));
+        assert!(get_time() >= time_before + advance_by.as_nanos() as u64);
+    }
+
+    #[test]
+    fn test_describe_decodes_candid_reply() {
+        use candid::CandidType;
+
+        let reply = OpOut::CanisterResult(Ok(WasmResult::Reply(
+            encode_args((42_u64, "hello")).unwrap(),
+        )));
+        let description = reply.describe(None);
+        assert!(description.contains("42"), "{}", description);
+        assert!(description.contains("\"hello\""), "{}", description);
+
+        let reply =
+            OpOut::CanisterResult(Ok(WasmResult::Reply(encode_args((Some(7_u32),)).unwrap())));
+        let description = reply.describe(Some(&Option::<u32>::ty()));
+        assert!(
+            description.starts_with("CanisterResult: Ok(Reply((opt"),
+            "{}",
+            description
+        );
+        assert!(description.contains('7'), "{}", description);
+
+        // Bytes that are not Candid fall back to the `Debug` output.
+        let reply = OpOut::CanisterResult(Ok(WasmResult::Reply(vec![1, 2, 3])));
+        assert_eq!(reply.describe(None), format!("{:?}", reply));
+        let reject = OpOut::CanisterResult(Ok(WasmResult::Reject("oops".to_string())));
+        assert_eq!(reject.describe(None), format!("{:?}", reject));
+    }
 }
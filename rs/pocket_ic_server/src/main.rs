@@ -24,10 +24,12 @@ use ic_crypto_iccsa::{public_key_bytes_from_der, types::SignatureBytes, verify};
 use ic_crypto_sha2::Sha256;
 use ic_crypto_utils_threshold_sig_der::parse_threshold_sig_key_from_der;
 use pocket_ic::common::rest::{BinaryBlob, BlobCompression, BlobId, RawVerifyCanisterSigArg};
-use pocket_ic_server::state_api::routes::{handler_read_graph, timeout_or_default};
+use pocket_ic_server::state_api::routes::{
+    handler_await_graph, handler_read_graph, timeout_or_default,
+};
 use pocket_ic_server::state_api::{
     routes::{http_gateway_routes, instances_routes, status, AppState, RouterExt},
-    state::PocketIcApiStateBuilder,
+    state::{CanisterHttpPolicy, PocketIcApiStateBuilder},
 };
 use pocket_ic_server::BlobStore;
 use std::collections::HashMap;
@@ -71,6 +73,32 @@ struct Args {
     /// The time-to-live of the PocketIC server in seconds
     #[clap(long, default_value_t = TTL_SEC)]
     ttl: u64,
+    /// The wall-clock time budget in milliseconds for computing operations on an IC instance
+    /// after which further operations on that instance are refused until the budget is reset
+    #[clap(long)]
+    instance_cpu_budget_ms: Option<u64>,
+    /// The maximum number of operations queued up on a busy IC instance
+    /// (by default, operations on a busy instance are not queued up)
+    #[clap(long, default_value_t = 0)]
+    max_queue_depth: usize,
+    /// The timeout in milliseconds for canister HTTP outcalls (including retries)
+    /// made in auto progress mode (by default, there is no timeout)
+    #[clap(long)]
+    canister_http_timeout_ms: Option<u64>,
+    /// The maximum number of times a failed idempotent (GET and HEAD) canister HTTP outcall
+    /// made in auto progress mode is retried
+    #[clap(long, default_value_t = 0)]
+    canister_http_max_retries: u32,
+    /// The maximum number of canister HTTP outcalls made concurrently in auto progress mode
+    #[clap(long, default_value_t = 16)]
+    canister_http_max_concurrency: usize,
+    /// Record the cycles balances of all canisters after every tick
+    #[clap(long)]
+    record_cycle_history: bool,
+    /// The maximum number of state labels for which the results of operations are kept
+    /// (by default, the results are kept for all state labels)
+    #[clap(long)]
+    graph_capacity: Option<usize>,
 }
 
 /// Get the path of the current running binary.
@@ -156,9 +184,25 @@ async fn start(runtime: Arc<Runtime>) {
 
     let _guard = setup_tracing(args.pid);
     // The shared, mutable state of the PocketIC process.
-    let api_state = PocketIcApiStateBuilder::default()
+    let mut api_state_builder = PocketIcApiStateBuilder::default()
         .with_port(real_port)
-        .build();
+        .with_max_queue_depth(args.max_queue_depth)
+        .with_canister_http_policy(CanisterHttpPolicy {
+            total_timeout: args.canister_http_timeout_ms.map(Duration::from_millis),
+            max_retries: args.canister_http_max_retries,
+            max_concurrency: args.canister_http_max_concurrency,
+        });
+    if let Some(instance_cpu_budget_ms) = args.instance_cpu_budget_ms {
+        api_state_builder = api_state_builder
+            .with_instance_cpu_budget(Duration::from_millis(instance_cpu_budget_ms));
+    }
+    if args.record_cycle_history {
+        api_state_builder = api_state_builder.with_cycle_history();
+    }
+    if let Some(graph_capacity) = args.graph_capacity {
+        api_state_builder = api_state_builder.with_graph_capacity(graph_capacity);
+    }
+    let api_state = api_state_builder.build();
     // A time-to-live mechanism: Requests bump this value, and the server
     // gracefully shuts down when the value wasn't bumped for a while.
     let min_alive_until = Arc::new(RwLock::new(Instant::now()));
@@ -189,6 +233,10 @@ async fn start(runtime: Arc<Runtime>) {
         // Read state: Poll a result based on a received Started{} reply.
         .directory_route("/read_graph/:state_label/:op_id", get(handler_read_graph))
         //
+        // Long-poll a result based on a received Started{} reply:
+        // Waits for at most the processing timeout until the result is available.
+        .directory_route("/await_graph/:state_label/:op_id", get(handler_await_graph))
+        //
         // All instance routes.
         .nest("/instances", instances_routes::<AppState>())
         // All HTTP gateway routes.
@@ -1,5 +1,5 @@
 use crate::async_trait;
-use crate::state_api::state::{HasStateLabel, OpOut, PocketIcError, StateLabel};
+use crate::state_api::state::{HasStateLabel, OpOut, PocketIcError, RawApiResponse, StateLabel};
 use crate::OpId;
 use crate::Operation;
 use crate::{copy_dir, BlobStore};
@@ -8,17 +8,21 @@ use axum::{
     extract::State,
     response::{Html, IntoResponse, Response as AxumResponse},
 };
-use candid::Decode;
+use candid::{CandidType, Decode};
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use hyper::body::Bytes;
 use hyper::header::{HeaderValue, CONTENT_TYPE};
 use hyper::{Method, StatusCode};
+use hyper_rustls::ConfigBuilderExt;
 use ic_boundary::{Health, RootKey};
 use ic_config::{
     execution_environment, flag_status::FlagStatus, http_handler, subnet_config::SubnetConfig,
 };
 use ic_crypto_sha2::Sha256;
+use ic_crypto_tree_hash::{
+    sparse_labeled_tree_from_paths, Label, Path as StatePath, TooLongPathError,
+};
 use ic_http_endpoints_public::{
     metrics::HttpHandlerMetrics, CallServiceV2, CallServiceV3, CanisterReadStateServiceBuilder,
     IngressValidatorBuilder, QueryServiceBuilder,
@@ -32,8 +36,9 @@ use ic_interfaces::{crypto::BasicSigner, ingress_pool::IngressPoolThrottler};
 use ic_interfaces_adapter_client::NonBlockingChannel;
 use ic_interfaces_state_manager::StateReader;
 use ic_management_canister_types::{
-    CanisterIdRecord, CanisterInstallMode, EcdsaCurve, EcdsaKeyId, MasterPublicKeyId,
-    Method as Ic00Method, ProvisionalCreateCanisterWithCyclesArgs,
+    CanisterIdRecord, CanisterInstallMode, CanisterInstallModeV2, ClearChunkStoreArgs, EcdsaCurve,
+    EcdsaKeyId, InstallChunkedCodeArgs, MasterPublicKeyId, Method as Ic00Method, Payload,
+    ProvisionalCreateCanisterWithCyclesArgs, UploadChunkArgs, UploadChunkReply,
 };
 use ic_metrics::MetricsRegistry;
 use ic_protobuf::registry::routing_table::v1::RoutingTable as PbRoutingTable;
@@ -41,6 +46,8 @@ use ic_registry_keys::make_routing_table_record_key;
 use ic_registry_proto_data_provider::ProtoRegistryDataProvider;
 use ic_registry_routing_table::{CanisterIdRange, RoutingTable, CANISTER_IDS_PER_SUBNET};
 use ic_registry_subnet_type::SubnetType;
+use ic_replicated_state::num_bytes_try_from;
+use ic_replicated_state::replicated_state::ReplicatedStateMessageRouting;
 use ic_state_machine_tests::{
     finalize_registry, IngressState, IngressStatus, RejectCode, StateMachine, StateMachineBuilder,
     StateMachineConfig, StateMachineStateDir, SubmitIngressError, Time,
@@ -51,8 +58,9 @@ use ic_types::{
     canister_http::{CanisterHttpReject, CanisterHttpRequestId, CanisterHttpResponseContent},
     crypto::{BasicSig, BasicSigOf, CryptoResult, Signable},
     messages::{
-        CertificateDelegation, HttpCallContent, HttpRequestEnvelope, MessageId as OtherMessageId,
-        QueryResponseHash, ReplicaHealthStatus, SignedIngress,
+        Blob, CanisterMessage, Certificate, CertificateDelegation, HttpCallContent,
+        HttpRequestEnvelope, MessageId as OtherMessageId, QueryResponseHash, ReplicaHealthStatus,
+        SignedIngress,
     },
     time::GENESIS,
     CanisterId, Height, NodeId, NumInstructions, PrincipalId, RegistryVersion, SubnetId,
@@ -61,10 +69,13 @@ use ic_validator_ingress_message::StandaloneIngressSigVerifier;
 use itertools::Itertools;
 use pocket_ic::common::rest::{
     self, BinaryBlob, BlobCompression, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpRequest,
-    CanisterHttpResponse, DtsFlag, ExtendedSubnetConfigSet, MockCanisterHttpResponse, RawAddCycles,
-    RawCanisterCall, RawEffectivePrincipal, RawMessageId, RawSetStableMemory,
-    SubnetInstructionConfig, SubnetKind, SubnetSpec, Topology,
+    CanisterHttpResponse, CycleConsumptionRecord, DtsFlag, ExtendedSubnetConfigSet,
+    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawEffectivePrincipal,
+    RawFaultInjection, RawMessageId, RawSetControllers, RawSetFreezingThreshold,
+    RawSetStableMemory, ScheduledMessage, ScheduledMessageKind, SubnetInstructionConfig,
+    SubnetKind, SubnetMetrics, SubnetSpec, Topology, TopologyDelta, XNetPolicy,
 };
+use pocket_ic::UserError;
 use rand::rngs::StdRng;
 use rand::Rng;
 use rand::SeedableRng;
@@ -72,12 +83,15 @@ use serde::{Deserialize, Serialize};
 use std::hash::Hash;
 use std::str::FromStr;
 use std::{
-    cmp::max,
+    cmp::{max, Reverse},
     collections::BTreeMap,
     fs::File,
     io::{BufReader, Write},
-    path::PathBuf,
-    sync::{Arc, Mutex, RwLock},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, RwLock,
+    },
     time::{Duration, SystemTime},
 };
 use tempfile::TempDir;
@@ -89,6 +103,7 @@ use tower::{
     service_fn,
     util::{BoxCloneService, ServiceExt},
 };
+use zeroize::Zeroizing;
 
 // See build.rs
 include!(concat!(env!("OUT_DIR"), "/dashboard.rs"));
@@ -123,6 +138,57 @@ fn compute_subnet_seed(
     hasher.finish()
 }
 
+fn compute_topology_hash(topology: &Topology) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.write(&serde_json::to_vec(topology).unwrap());
+    hasher.finish()
+}
+
+/// Computes the subnets and nodes added and removed between the two topologies.
+fn compute_topology_delta(previous: &Topology, current: &Topology) -> TopologyDelta {
+    let mut delta = TopologyDelta {
+        topology_hash: hex::encode(compute_topology_hash(current)),
+        added_subnets: BTreeMap::new(),
+        removed_subnets: vec![],
+        added_nodes: BTreeMap::new(),
+        removed_nodes: BTreeMap::new(),
+    };
+    for (subnet_id, config) in current.0.iter() {
+        match previous.0.get(subnet_id) {
+            None => {
+                delta.added_subnets.insert(*subnet_id, config.clone());
+            }
+            Some(previous_config) => {
+                let added_nodes: Vec<_> = config
+                    .node_ids
+                    .iter()
+                    .filter(|node_id| !previous_config.node_ids.contains(node_id))
+                    .cloned()
+                    .collect();
+                if !added_nodes.is_empty() {
+                    delta.added_nodes.insert(*subnet_id, added_nodes);
+                }
+                let removed_nodes: Vec<_> = previous_config
+                    .node_ids
+                    .iter()
+                    .filter(|node_id| !config.node_ids.contains(node_id))
+                    .cloned()
+                    .collect();
+                if !removed_nodes.is_empty() {
+                    delta.removed_nodes.insert(*subnet_id, removed_nodes);
+                }
+            }
+        }
+    }
+    delta.removed_subnets = previous
+        .0
+        .keys()
+        .filter(|subnet_id| !current.0.contains_key(subnet_id))
+        .cloned()
+        .collect();
+    delta
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 struct RawTopologyInternal(pub BTreeMap<String, RawSubnetConfigInternal>);
 
@@ -163,10 +229,71 @@ pub struct PocketIc {
     registry_data_provider: Arc<ProtoRegistryDataProvider>,
     runtime: Arc<Runtime>,
     nonmainnet_features: bool,
+    // Subnet-wide limits on the size of canister HTTP outcall responses
+    // configured by the test driver.
+    canister_http_response_limits: BTreeMap<SubnetId, u64>,
+    // Freezing thresholds of canisters set by the test driver (see [SetFreezingThreshold]).
+    freezing_thresholds: BTreeMap<CanisterId, u64>,
+    // SOCKS proxy through which canister HTTP outcalls are made
+    // if a direct connection fails (configured by the test driver).
+    canister_http_socks_proxy: Option<String>,
+    // TLS client identity presented by canister HTTP outcalls (configured by the test driver).
+    canister_http_client_identity: Option<Arc<CanisterHttpClientIdentity>>,
+    // Key-value metadata attached to the instance by the test driver.
+    metadata: BTreeMap<String, String>,
+    // Wall-clock time budget for computing operations on this instance
+    // and the time spent computing operations so far.
+    cpu_budget: Option<Duration>,
+    cpu_time_used: Duration,
+    // Set if the operation currently computed on this instance has been cancelled.
+    operation_cancelled: Arc<AtomicBool>,
+    // History of cycles balances of canisters (only recorded if enabled).
+    cycle_history: Option<CycleHistory>,
+    // Topologies returned by [GetTopologyDelta] keyed by their hashes.
+    topology_history: BTreeMap<[u8; 32], Topology>,
+    // Mocked canister HTTP outcall responses held back until the IC time reaches
+    // their due time (see [MockCanisterHttpResponse::apply_after]).
+    held_canister_http_responses: Vec<(SystemTime, MockCanisterHttpResponse)>,
+    // Faults injected into canister calls by the test driver (see [FaultInjection]).
+    // Shared with read-only views so that queries computed on a view consume them, too.
+    injected_faults: Arc<Mutex<Vec<FaultInjection>>>,
+    // Set for a read-only view of an instance (see [PocketIc::read_only_view])
+    // which shares the subnets with the instance and thus must not tear them down on drop.
+    is_read_only_view: bool,
+    // A copy of the snapshot this instance was loaded from (if any).
+    // DO NOT PUT ANY FIELDS AFTER `snapshot_dir`: it must be dropped after the subnets.
+    snapshot_dir: Option<TempDir>,
+}
+
+/// Cycles balances of canisters recorded after every tick, i.e., after every round
+/// executed on all subnets of an instance (see [PocketIc::execute_round_on_all_subnets]).
+#[derive(Default)]
+struct CycleHistory {
+    // Number of ticks since the recording was enabled.
+    tick: u64,
+    records: BTreeMap<CanisterId, Vec<CycleConsumptionRecord>>,
+    // Cycles consumed by canisters (as reported by their metrics) at the last tick.
+    consumed_cycles: BTreeMap<CanisterId, u128>,
+}
+
+/// The name of the file describing a snapshot of an instance (see `PocketIc::save_to_dir`).
+const SNAPSHOT_METADATA_FILE: &str = "snapshot.json";
+/// The version of the format of instance snapshots.
+/// Must be bumped whenever the format changes in an incompatible way.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotMetadata {
+    version: u32,
+    initial_state_hash: String,
+    nonmainnet_features: bool,
 }
 
 impl Drop for PocketIc {
     fn drop(&mut self) {
+        if self.is_read_only_view {
+            return;
+        }
         let subnets = self.subnets.read().unwrap();
         if let Some(ref state_dir) = self.state_dir {
             for subnet in subnets.values() {
@@ -175,26 +302,7 @@ impl Drop for PocketIc {
             for subnet in subnets.values() {
                 subnet.await_state_hash();
             }
-            let mut topology_file = File::create(state_dir.join("topology.json")).unwrap();
-            let raw_topology: RawTopologyInternal = RawTopologyInternal(
-                self.topology
-                    .0
-                    .clone()
-                    .into_iter()
-                    .map(|(seed, config)| {
-                        let time = subnets.get(&config.subnet_id).unwrap().time();
-                        (
-                            hex::encode(seed),
-                            RawSubnetConfigInternal {
-                                subnet_config: config,
-                                time,
-                            },
-                        )
-                    })
-                    .collect(),
-            );
-            let topology_json = serde_json::to_string(&raw_topology).unwrap();
-            topology_file.write_all(topology_json.as_bytes()).unwrap();
+            self.write_topology(&subnets, state_dir).unwrap();
         }
         for subnet in subnets.values() {
             subnet.drop_payload_builder();
@@ -203,6 +311,271 @@ impl Drop for PocketIc {
 }
 
 impl PocketIc {
+    fn write_topology(
+        &self,
+        subnets: &BTreeMap<SubnetId, Arc<StateMachine>>,
+        state_dir: &Path,
+    ) -> std::io::Result<()> {
+        let mut topology_file = File::create(state_dir.join("topology.json"))?;
+        let raw_topology: RawTopologyInternal = RawTopologyInternal(
+            self.topology
+                .0
+                .clone()
+                .into_iter()
+                .map(|(seed, config)| {
+                    let time = subnets.get(&config.subnet_id).unwrap().time();
+                    (
+                        hex::encode(seed),
+                        RawSubnetConfigInternal {
+                            subnet_config: config,
+                            time,
+                        },
+                    )
+                })
+                .collect(),
+        );
+        let topology_json = serde_json::to_string(&raw_topology).unwrap();
+        topology_file.write_all(topology_json.as_bytes())
+    }
+
+    // Sync the time on the subnets (if only the NNS subnet is loaded
+    // from a snapshot, then its time might diverge).
+    // Since time must be monotone, we pick the maximum time.
+    // Afterwards, we execute a round on every subnet to make sure it has a state to certify.
+    fn sync_time_and_execute_round(subnets: &BTreeMap<SubnetId, Arc<StateMachine>>) {
+        let mut max_time = GENESIS;
+        for subnet in subnets.values() {
+            max_time = max(max_time, subnet.get_state_time());
+        }
+        for subnet in subnets.values() {
+            subnet.set_time(max_time.into());
+        }
+        for subnet in subnets.values() {
+            subnet.execute_round();
+        }
+    }
+
+    /// Saves a snapshot of this instance (topology, subnet states, and time) to the given
+    /// directory which must not exist yet or be empty. The snapshot can be loaded by
+    /// [PocketIc::from_snapshot] and the loaded instance has the same state label
+    /// as this instance after saving the snapshot.
+    pub(crate) fn save_to_dir(&self, dir: &Path) -> Result<(), String> {
+        if std::fs::read_dir(dir).map_or(false, |mut entries| entries.next().is_some()) {
+            return Err(format!("Directory {} is not empty", dir.display()));
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create directory {}: {}", dir.display(), e))?;
+        let subnets = self.subnets.read().unwrap();
+        for subnet in subnets.values() {
+            subnet.checkpointed_tick();
+        }
+        for subnet in subnets.values() {
+            subnet.await_state_hash();
+        }
+        for (subnet_seed, config) in self.topology.0.iter() {
+            let subnet = subnets.get(&config.subnet_id).unwrap();
+            copy_dir(subnet.state_dir(), dir.join(hex::encode(subnet_seed))).map_err(|e| {
+                format!(
+                    "Failed to copy the state of subnet {}: {}",
+                    config.subnet_id, e
+                )
+            })?;
+        }
+        self.write_topology(&subnets, dir)
+            .map_err(|e| format!("Failed to write topology: {}", e))?;
+        let metadata = SnapshotMetadata {
+            version: SNAPSHOT_VERSION,
+            initial_state_hash: hex::encode(self.initial_state_hash),
+            nonmainnet_features: self.nonmainnet_features,
+        };
+        std::fs::write(
+            dir.join(SNAPSHOT_METADATA_FILE),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .map_err(|e| format!("Failed to write snapshot metadata: {}", e))?;
+        // Loading a snapshot syncs the time and executes a round on every subnet.
+        // We do the same here so that the state labels match.
+        Self::sync_time_and_execute_round(&subnets);
+        Ok(())
+    }
+
+    /// Loads an instance from a snapshot saved by [PocketIc::save_to_dir].
+    /// The instance operates on a copy of the snapshot, i.e., the snapshot
+    /// itself is left unchanged and can be loaded again.
+    pub(crate) fn from_snapshot(runtime: Arc<Runtime>, dir: &Path) -> Result<Self, String> {
+        let metadata_file = File::open(dir.join(SNAPSHOT_METADATA_FILE)).map_err(|e| {
+            format!(
+                "Failed to open snapshot metadata in {}: {}",
+                dir.display(),
+                e
+            )
+        })?;
+        let metadata: SnapshotMetadata = serde_json::from_reader(BufReader::new(metadata_file))
+            .map_err(|e| format!("Malformed snapshot metadata: {}", e))?;
+        if metadata.version != SNAPSHOT_VERSION {
+            return Err(format!(
+                "Incompatible snapshot version {} (this server supports version {})",
+                metadata.version, SNAPSHOT_VERSION
+            ));
+        }
+        let initial_state_hash: [u8; 32] = hex::decode(&metadata.initial_state_hash)
+            .ok()
+            .and_then(|hash| hash.try_into().ok())
+            .ok_or_else(|| "Malformed initial state hash in snapshot metadata".to_string())?;
+        let snapshot_dir =
+            TempDir::new().map_err(|e| format!("Failed to create a temporary directory: {}", e))?;
+        copy_dir(dir, snapshot_dir.path())
+            .map_err(|e| format!("Failed to copy snapshot: {}", e))?;
+
+        let mut pocket_ic = Self::new(
+            runtime,
+            ExtendedSubnetConfigSet::default(),
+            Some(snapshot_dir.path().to_path_buf()),
+            metadata.nonmainnet_features,
+        );
+        pocket_ic.initial_state_hash = initial_state_hash;
+        // The copy of the snapshot is discarded when the instance is dropped.
+        pocket_ic.state_dir = None;
+        pocket_ic.snapshot_dir = Some(snapshot_dir);
+        Ok(pocket_ic)
+    }
+
+    /// Starts recording the cycles balances of all canisters after every tick
+    /// (see [GetCycleConsumption]). Has no effect if the recording is already enabled.
+    pub(crate) fn enable_cycle_history(&mut self) {
+        if self.cycle_history.is_none() {
+            self.cycle_history = Some(CycleHistory::default());
+        }
+    }
+
+    /// Executes a round on all subnets (a tick) and records the cycles balances
+    /// of all canisters afterwards (if enabled).
+    fn execute_round_on_all_subnets(&mut self) {
+        self.apply_due_canister_http_responses();
+        for subnet in self.subnets.read().unwrap().values() {
+            subnet.execute_round();
+        }
+        self.record_cycle_balances();
+    }
+
+    /// Applies all held mocked canister HTTP outcall responses
+    /// whose due time has been reached.
+    fn apply_due_canister_http_responses(&mut self) {
+        if self.held_canister_http_responses.is_empty() {
+            return;
+        }
+        let now = self.any_subnet().time();
+        let (due, held): (Vec<_>, Vec<_>) = std::mem::take(&mut self.held_canister_http_responses)
+            .into_iter()
+            .partition(|(due_time, _)| *due_time <= now);
+        self.held_canister_http_responses = held;
+        for (_, mock_canister_http_response) in due {
+            // The request might have been answered in the meantime
+            // in which case the held response is dropped.
+            let _ = process_mock_canister_https_response(self, &mock_canister_http_response);
+        }
+    }
+
+    /// Returns the subnet IDs and request IDs of canister HTTP outcalls
+    /// with a held mocked response and the IC time at which the response is applied.
+    pub(crate) fn held_canister_http_response_ids(
+        &self,
+    ) -> Vec<(candid::Principal, u64, SystemTime)> {
+        self.held_canister_http_responses
+            .iter()
+            .map(|(due_time, resp)| (resp.subnet_id, resp.request_id, *due_time))
+            .collect()
+    }
+
+    /// Returns the error of the first fault injected into calls of the given method
+    /// of the given canister (if any) and removes the fault unless it is repeated.
+    fn take_injected_fault(&self, canister_id: CanisterId, method: &str) -> Option<UserError> {
+        let mut injected_faults = self.injected_faults.lock().unwrap();
+        let index = injected_faults
+            .iter()
+            .position(|fault| fault.canister_id == canister_id && fault.method == method)?;
+        if injected_faults[index].repeat {
+            Some(injected_faults[index].error.clone())
+        } else {
+            Some(injected_faults.remove(index).error)
+        }
+    }
+
+    /// Returns the error of the first repeated fault injected into calls of the given method
+    /// of the given canister (if any). Unlike [Self::take_injected_fault], this does not change
+    /// the injected faults and can thus be used by read-only operations.
+    fn repeated_injected_fault(&self, canister_id: CanisterId, method: &str) -> Option<UserError> {
+        self.injected_faults
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|fault| {
+                fault.repeat && fault.canister_id == canister_id && fault.method == method
+            })
+            .map(|fault| fault.error.clone())
+    }
+
+    pub(crate) fn injected_faults(&self) -> Vec<FaultInjection> {
+        self.injected_faults.lock().unwrap().clone()
+    }
+
+    fn record_cycle_balances(&mut self) {
+        let Some(ref mut cycle_history) = self.cycle_history else {
+            return;
+        };
+        cycle_history.tick += 1;
+        for subnet in self.subnets.read().unwrap().values() {
+            let state = subnet.get_latest_state();
+            for canister in state.canisters_iter() {
+                let canister_id = canister.canister_id();
+                let consumed_cycles = canister.system_state.canister_metrics.consumed_cycles.get();
+                let previously_consumed_cycles = cycle_history
+                    .consumed_cycles
+                    .insert(canister_id, consumed_cycles)
+                    .unwrap_or_default();
+                cycle_history.records.entry(canister_id).or_default().push(
+                    CycleConsumptionRecord {
+                        tick: cycle_history.tick,
+                        balance: canister.system_state.balance().get(),
+                        consumed: consumed_cycles.saturating_sub(previously_consumed_cycles),
+                    },
+                );
+            }
+        }
+    }
+
+    pub(crate) fn set_cpu_budget(&mut self, cpu_budget: Option<Duration>) {
+        self.cpu_budget = cpu_budget;
+    }
+
+    /// Returns the wall-clock time spent computing operations so far
+    /// if it exceeds the CPU budget of this instance.
+    pub(crate) fn cpu_budget_exhausted(&self) -> Option<Duration> {
+        match self.cpu_budget {
+            Some(cpu_budget) if self.cpu_time_used > cpu_budget => Some(self.cpu_time_used),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn add_cpu_time_used(&mut self, elapsed: Duration) {
+        self.cpu_time_used = self.cpu_time_used.saturating_add(elapsed);
+    }
+
+    pub(crate) fn reset_cpu_time_used(&mut self) {
+        self.cpu_time_used = Duration::ZERO;
+    }
+
+    pub(crate) fn set_operation_cancelled_flag(&mut self, operation_cancelled: Arc<AtomicBool>) {
+        self.operation_cancelled = operation_cancelled;
+    }
+
+    /// Operations consisting of several steps (e.g., executing rounds until
+    /// a message completes) should check this flag between the steps and
+    /// return early if the operation has been cancelled.
+    pub(crate) fn is_operation_cancelled(&self) -> bool {
+        self.operation_cancelled.load(Ordering::Relaxed)
+    }
+
     pub(crate) fn topology(&self) -> Topology {
         let mut topology = Topology(BTreeMap::new());
         let subnets = self.subnets.read().unwrap();
@@ -502,21 +875,7 @@ impl PocketIc {
             registry_data_provider.write_to_file(registry_proto_path);
         }
 
-        // Sync the time on the subnets (if only the NNS subnet is loaded
-        // from a snapshot, then its time might diverge).
-        // Since time must be monotone, we pick the maximum time.
-        let mut max_time = GENESIS;
-        for subnet in subnets.read().unwrap().values() {
-            max_time = max(max_time, subnet.get_state_time());
-        }
-        for subnet in subnets.read().unwrap().values() {
-            subnet.set_time(max_time.into());
-        }
-
-        // We execute a round on every subnet to make sure it has a state to certify.
-        for subnet in subnets.read().unwrap().values() {
-            subnet.execute_round();
-        }
+        Self::sync_time_and_execute_round(&subnets.read().unwrap());
 
         let mut hasher = Sha256::new();
         let subnet_configs_string = format!("{:?}", subnet_configs);
@@ -524,6 +883,11 @@ impl PocketIc {
         let initial_state_hash = compute_state_label(
             &hasher.finish(),
             subnets.read().unwrap().values().cloned().collect(),
+            &[],
+            &BTreeMap::new(),
+            &[],
+            &BTreeMap::new(),
+            &BTreeMap::new(),
         )
         .0;
 
@@ -538,6 +902,55 @@ impl PocketIc {
             registry_data_provider,
             runtime,
             nonmainnet_features,
+            canister_http_response_limits: BTreeMap::new(),
+            freezing_thresholds: BTreeMap::new(),
+            canister_http_socks_proxy: None,
+            canister_http_client_identity: None,
+            metadata: BTreeMap::new(),
+            cpu_budget: None,
+            cpu_time_used: Duration::ZERO,
+            operation_cancelled: Arc::new(AtomicBool::new(false)),
+            cycle_history: None,
+            topology_history: BTreeMap::new(),
+            held_canister_http_responses: vec![],
+            injected_faults: Arc::new(Mutex::new(vec![])),
+            is_read_only_view: false,
+            snapshot_dir: None,
+        }
+    }
+
+    /// Returns a read-only view of this instance on which read-only operations
+    /// (see [Operation::is_read_only]) can be computed while this instance is busy.
+    /// The view shares the subnets with this instance and is thus *not* isolated
+    /// from the operation this instance is busy with: every read observes the latest
+    /// committed state of a subnet which might already reflect some (but not all)
+    /// rounds executed by that operation.
+    pub(crate) fn read_only_view(&self) -> PocketIc {
+        Self {
+            state_dir: None,
+            subnets: Arc::new(RwLock::new(self.subnets.read().unwrap().clone())),
+            routing_table: self.routing_table.clone(),
+            topology: self.topology.clone(),
+            randomness: self.randomness.clone(),
+            initial_state_hash: self.initial_state_hash,
+            range_gen: RangeGen::default(),
+            registry_data_provider: self.registry_data_provider.clone(),
+            runtime: self.runtime.clone(),
+            nonmainnet_features: self.nonmainnet_features,
+            canister_http_response_limits: self.canister_http_response_limits.clone(),
+            freezing_thresholds: self.freezing_thresholds.clone(),
+            canister_http_socks_proxy: self.canister_http_socks_proxy.clone(),
+            canister_http_client_identity: self.canister_http_client_identity.clone(),
+            metadata: self.metadata.clone(),
+            cpu_budget: None,
+            cpu_time_used: Duration::ZERO,
+            operation_cancelled: Arc::new(AtomicBool::new(false)),
+            cycle_history: None,
+            topology_history: BTreeMap::new(),
+            held_canister_http_responses: self.held_canister_http_responses.clone(),
+            injected_faults: self.injected_faults.clone(),
+            is_read_only_view: true,
+            snapshot_dir: None,
         }
     }
 
@@ -640,9 +1053,32 @@ impl Default for PocketIc {
 fn compute_state_label(
     initial_state_hash: &[u8; 32],
     subnets: Vec<Arc<StateMachine>>,
+    injected_faults: &[FaultInjection],
+    canister_http_response_limits: &BTreeMap<SubnetId, u64>,
+    held_canister_http_responses: &[(SystemTime, MockCanisterHttpResponse)],
+    freezing_thresholds: &BTreeMap<CanisterId, u64>,
+    topology_history: &BTreeMap<[u8; 32], Topology>,
 ) -> StateLabel {
     let mut hasher = Sha256::new();
     hasher.write(initial_state_hash);
+    injected_faults.hash(&mut hasher);
+    held_canister_http_responses.hash(&mut hasher);
+    // The limits on canister HTTP outcall responses are not part of the subnet states
+    // and thus need to be hashed explicitly.
+    for (subnet_id, max_bytes) in canister_http_response_limits {
+        hasher.write(subnet_id.get_ref().as_slice());
+        hasher.write(&max_bytes.to_be_bytes());
+    }
+    // The freezing thresholds set by the test driver are not part of the certified state
+    // and thus need to be hashed explicitly, too.
+    for (canister_id, threshold_secs) in freezing_thresholds {
+        hasher.write(canister_id.get_ref().as_slice());
+        hasher.write(&threshold_secs.to_be_bytes());
+    }
+    // A topology in the history is identified by its hash.
+    for topology_hash in topology_history.keys() {
+        hasher.write(topology_hash);
+    }
     for subnet in subnets {
         let subnet_state_hash = subnet
             .state_manager
@@ -652,6 +1088,17 @@ fn compute_state_label(
         let nanos = systemtime_to_unix_epoch_nanos(subnet.time());
         hasher.write(&subnet_state_hash[..]);
         hasher.write(&nanos.to_be_bytes());
+        // The clock skews of nodes are not part of the certified state
+        // and thus need to be hashed explicitly.
+        for (node_id, skew_nanos) in subnet.node_clock_skews() {
+            hasher.write(node_id.get_ref().as_slice());
+            hasher.write(&skew_nanos.to_be_bytes());
+        }
+        // The same holds for the policies applied to XNet messages to the subnet.
+        for (from_subnet_id, policy) in subnet.xnet_policies() {
+            hasher.write(from_subnet_id.get_ref().as_slice());
+            policy.hash(&mut hasher);
+        }
     }
     StateLabel(hasher.finish())
 }
@@ -661,6 +1108,11 @@ impl HasStateLabel for PocketIc {
         compute_state_label(
             &self.initial_state_hash,
             self.subnets.read().unwrap().values().cloned().collect(),
+            &self.injected_faults.lock().unwrap(),
+            &self.canister_http_response_limits,
+            &self.held_canister_http_responses,
+            &self.freezing_thresholds,
+            &self.topology_history,
         )
     }
 }
@@ -839,6 +1291,37 @@ impl Operation for SetTime {
     }
 }
 
+/// Sets the time on all subnets to an absolute timestamp and executes a round,
+/// e.g., to fire canister timers that are due by then. Since the IC clock is
+/// monotonic, moving the time backwards is rejected.
+#[derive(Clone, Copy, Debug)]
+pub struct SetTimeAndTick {
+    pub timestamp_nanos: u64,
+}
+
+impl Operation for SetTimeAndTick {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        // Time is kept in sync across subnets, so one can take any subnet.
+        let current_nanos = systemtime_to_unix_epoch_nanos(pic.any_subnet().time());
+        if self.timestamp_nanos < current_nanos {
+            return OpOut::Error(PocketIcError::TimeMovesBackwards((
+                current_nanos,
+                self.timestamp_nanos,
+            )));
+        }
+        let time = Time::from_nanos_since_unix_epoch(self.timestamp_nanos);
+        for subnet in pic.subnets.read().unwrap().values() {
+            subnet.set_time(time.into());
+        }
+        pic.execute_round_on_all_subnets();
+        OpOut::Time(systemtime_to_unix_epoch_nanos(pic.any_subnet().time()))
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("set_time_and_tick_{}", self.timestamp_nanos))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GetTopology;
 
@@ -847,11 +1330,48 @@ impl Operation for GetTopology {
         OpOut::Topology(pic.topology().clone())
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn id(&self) -> OpId {
         OpId("get_topology".into())
     }
 }
 
+/// Returns the changes to the topology since the topology with the given hash
+/// (returned by a previous [GetTopologyDelta]) or the entire topology if no hash is given.
+#[derive(Clone, Copy, Debug)]
+pub struct GetTopologyDelta {
+    pub since: Option<[u8; 32]>,
+}
+
+impl Operation for GetTopologyDelta {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let previous = match self.since {
+            Some(since) => match pic.topology_history.get(&since) {
+                Some(previous) => previous.clone(),
+                None => {
+                    return OpOut::Error(PocketIcError::TopologyNotFound(hex::encode(since)));
+                }
+            },
+            None => Topology(BTreeMap::new()),
+        };
+        let topology = pic.topology();
+        let delta = compute_topology_delta(&previous, &topology);
+        pic.topology_history
+            .insert(compute_topology_hash(&topology), topology);
+        OpOut::TopologyDelta(delta)
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "get_topology_delta({})",
+            self.since.map(hex::encode).unwrap_or_default()
+        ))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GetTime;
 
@@ -862,11 +1382,75 @@ impl Operation for GetTime {
         OpOut::Time(nanos)
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn id(&self) -> OpId {
         OpId("get_time".into())
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct SetNodeClockSkew {
+    pub subnet_id: SubnetId,
+    pub node_id: NodeId,
+    pub skew_nanos: i64,
+}
+
+impl Operation for SetNodeClockSkew {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.get_subnet_with_id(self.subnet_id) {
+            Some(subnet) if subnet.nodes.iter().any(|node| node.node_id == self.node_id) => {
+                subnet.set_node_clock_skew(self.node_id, self.skew_nanos);
+                OpOut::NoOutput
+            }
+            Some(_) => OpOut::Error(PocketIcError::NodeNotFound((self.subnet_id, self.node_id))),
+            None => OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "set_node_clock_skew({},{},{})",
+            self.subnet_id, self.node_id, self.skew_nanos
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetNodeTime {
+    pub subnet_id: SubnetId,
+    pub node_id: NodeId,
+}
+
+impl Operation for GetNodeTime {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.get_subnet_with_id(self.subnet_id) {
+            Some(subnet) if subnet.nodes.iter().any(|node| node.node_id == self.node_id) => {
+                OpOut::Time(
+                    subnet
+                        .get_node_time(self.node_id)
+                        .as_nanos_since_unix_epoch(),
+                )
+            }
+            Some(_) => OpOut::Error(PocketIcError::NodeNotFound((self.subnet_id, self.node_id))),
+            None => OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0)),
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "get_node_time({},{})",
+            self.subnet_id, self.node_id
+        ))
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct GetCanisterHttp;
 
@@ -889,12 +1473,26 @@ fn http_header_from(
     }
 }
 
-fn get_canister_http_requests(pic: &PocketIc) -> Vec<CanisterHttpRequest> {
+/// Returns the pending canister HTTP outcalls on all subnets.
+/// Outcalls with a held mocked response are only included if `include_held` is set.
+fn get_canister_http_requests(pic: &PocketIc, include_held: bool) -> Vec<CanisterHttpRequest> {
     let mut canister_http = vec![];
     for subnet in pic.subnets.read().unwrap().values() {
+        let response_limit = pic
+            .canister_http_response_limits
+            .get(&subnet.get_subnet_id())
+            .cloned();
         let mut cur: Vec<_> = subnet
             .canister_http_request_contexts()
             .into_iter()
+            // Requests with a held mocked response must not be processed again.
+            .filter(|(id, _)| {
+                include_held
+                    || !pic.held_canister_http_responses.iter().any(|(_, resp)| {
+                        resp.subnet_id == subnet.get_subnet_id().get().0
+                            && resp.request_id == id.get()
+                    })
+            })
             .map(|(id, c)| CanisterHttpRequest {
                 subnet_id: subnet.get_subnet_id().get().0,
                 request_id: id.get(),
@@ -902,7 +1500,11 @@ fn get_canister_http_requests(pic: &PocketIc) -> Vec<CanisterHttpRequest> {
                 url: c.url,
                 headers: c.headers.iter().map(http_header_from).collect(),
                 body: c.body.unwrap_or_default(),
-                max_response_bytes: c.max_response_bytes.map(|b| b.get()),
+                // The subnet-wide limit (if any) caps the limit specified in the request.
+                max_response_bytes: match (c.max_response_bytes.map(|b| b.get()), response_limit) {
+                    (Some(max_response_bytes), Some(limit)) => Some(max_response_bytes.min(limit)),
+                    (max_response_bytes, limit) => max_response_bytes.or(limit),
+                },
             })
             .collect();
         canister_http.append(&mut cur);
@@ -912,7 +1514,7 @@ fn get_canister_http_requests(pic: &PocketIc) -> Vec<CanisterHttpRequest> {
 
 impl Operation for GetCanisterHttp {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
-        let canister_http_requests = get_canister_http_requests(pic);
+        let canister_http_requests = get_canister_http_requests(pic, false);
         OpOut::CanisterHttp(canister_http_requests)
     }
 
@@ -921,45 +1523,189 @@ impl Operation for GetCanisterHttp {
     }
 }
 
-// START COPY from rs/https_outcalls/client/src/client.rs
+/// Lists all pending canister HTTP outcalls (including those with a held mocked response).
+/// This is a pure read: the outcalls are neither processed nor marked as processed
+/// and the state label of the instance does not change.
+#[derive(Clone, Copy, Debug)]
+pub struct PeekCanisterHttp;
 
-#[derive(Clone)]
-pub struct SingleResponseAdapter {
-    response: Result<CanisterHttpSendResponse, (Code, String)>,
-}
+impl Operation for PeekCanisterHttp {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let canister_http_requests = get_canister_http_requests(pic, true);
+        OpOut::CanisterHttp(canister_http_requests)
+    }
 
-impl SingleResponseAdapter {
-    fn new(response: Result<CanisterHttpSendResponse, (Code, String)>) -> Self {
-        Self { response }
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId("peek_canister_http".into())
     }
 }
 
-#[tonic::async_trait]
-impl CanisterHttpService for SingleResponseAdapter {
-    async fn canister_http_send(
-        &self,
-        _request: Request<CanisterHttpSendRequest>,
-    ) -> Result<Response<CanisterHttpSendResponse>, Status> {
-        match self.response.clone() {
-            Ok(resp) => Ok(Response::new(resp)),
-            Err((code, msg)) => Err(Status::new(code, msg)),
+#[derive(Clone, Copy, Debug)]
+pub struct GetScheduledMessages {
+    pub subnet_id: SubnetId,
+}
+
+/// Returns the messages that are pending execution on the given subnet in the order
+/// in which they are considered by the scheduler in the next round: the canisters
+/// are ordered by their accumulated priority and the messages of every canister
+/// in the order in which they are popped from its input queues. Messages from
+/// other subnets (and the loopback stream) that have not been inducted yet and
+/// ingress messages from the ingress pool are inducted into a copy of the state
+/// first. Messages to the management canister are not included.
+fn get_scheduled_messages(pic: &PocketIc, subnet: &StateMachine) -> Vec<ScheduledMessage> {
+    let own_subnet_id = subnet.get_subnet_id();
+    let mut state = subnet.get_latest_state().as_ref().clone();
+    let mut subnet_available_memory = i64::MAX;
+    for remote_subnet in pic.subnets.read().unwrap().values() {
+        let remote_subnet_id = remote_subnet.get_subnet_id();
+        let signals_end = state
+            .streams()
+            .get(&remote_subnet_id)
+            .map(|stream| stream.signals_end())
+            .unwrap_or_default();
+        let remote_state = remote_subnet.get_latest_state();
+        if let Some(stream) = remote_state.streams().get(&own_subnet_id) {
+            for (_, msg) in stream
+                .messages()
+                .iter()
+                .filter(|(index, _)| *index >= signals_end)
+            {
+                let _ = state.push_input(msg.clone(), &mut subnet_available_memory);
+            }
+        }
+    }
+    for msg in subnet.ingress_pool_messages() {
+        if let Some(canister) = state.canister_state_mut(&msg.canister_id()) {
+            canister.push_ingress((msg.content().clone(), None).into());
         }
     }
-}
 
-async fn setup_adapter_mock(
-    adapter_response: Result<CanisterHttpSendResponse, (Code, String)>,
-) -> Channel {
-    let (client, server) = tokio::io::duplex(1024);
-    let mock_adapter = SingleResponseAdapter::new(adapter_response);
-    tokio::spawn(async move {
-        Server::builder()
-            .add_service(CanisterHttpServiceServer::new(mock_adapter))
-            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
-            .await
+    let mut canisters: Vec<_> = state.canisters_iter().cloned().collect();
+    canisters.sort_by_key(|canister| {
+        (
+            Reverse(canister.scheduler_state.accumulated_priority),
+            canister.canister_id(),
+        )
     });
-
-    let mut client = Some(client);
+    canisters
+        .into_iter()
+        .flat_map(|mut canister| {
+            let canister_id = canister.canister_id().get().0;
+            std::iter::from_fn(move || canister.pop_input()).map(move |msg| ScheduledMessage {
+                canister_id,
+                kind: match msg {
+                    CanisterMessage::Ingress(_) => ScheduledMessageKind::Ingress,
+                    CanisterMessage::Request(_) => ScheduledMessageKind::Request,
+                    CanisterMessage::Response(_) => ScheduledMessageKind::Response,
+                },
+            })
+        })
+        .collect()
+}
+
+impl Operation for GetScheduledMessages {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.get_subnet_with_id(self.subnet_id) {
+            Some(subnet) => OpOut::ScheduledMessages(get_scheduled_messages(pic, &subnet)),
+            None => OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_scheduled_messages({})", self.subnet_id))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+/// Returns aggregate statistics of the canisters on the given subnet.
+#[derive(Clone, Debug)]
+pub struct GetSubnetMetrics {
+    pub subnet_id: SubnetId,
+}
+
+impl Operation for GetSubnetMetrics {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(subnet) = pic.get_subnet_with_id(self.subnet_id) else {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0));
+        };
+        // Ingress messages in the ingress pool have not been inducted into
+        // the ingress queues of the canisters yet.
+        let mut metrics = SubnetMetrics {
+            num_canisters: 0,
+            canister_memory_bytes: 0,
+            total_cycles: 0,
+            ingress_queue_length: subnet.ingress_pool_messages().len() as u64,
+        };
+        let state = subnet.get_latest_state();
+        for canister in state.canisters_iter() {
+            metrics.num_canisters += 1;
+            if let Some(execution_state) = &canister.execution_state {
+                let memory_bytes = num_bytes_try_from(execution_state.wasm_memory.size).unwrap()
+                    + num_bytes_try_from(execution_state.stable_memory.size).unwrap();
+                metrics.canister_memory_bytes += memory_bytes.get();
+            }
+            metrics.total_cycles += canister.system_state.balance().get();
+            metrics.ingress_queue_length +=
+                canister.system_state.queues().ingress_queue_message_count() as u64;
+        }
+        OpOut::SubnetMetrics(metrics)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_subnet_metrics({})", self.subnet_id))
+    }
+}
+
+// START COPY from rs/https_outcalls/client/src/client.rs
+
+#[derive(Clone)]
+pub struct SingleResponseAdapter {
+    response: Result<CanisterHttpSendResponse, (Code, String)>,
+}
+
+impl SingleResponseAdapter {
+    fn new(response: Result<CanisterHttpSendResponse, (Code, String)>) -> Self {
+        Self { response }
+    }
+}
+
+#[tonic::async_trait]
+impl CanisterHttpService for SingleResponseAdapter {
+    async fn canister_http_send(
+        &self,
+        _request: Request<CanisterHttpSendRequest>,
+    ) -> Result<Response<CanisterHttpSendResponse>, Status> {
+        match self.response.clone() {
+            Ok(resp) => Ok(Response::new(resp)),
+            Err((code, msg)) => Err(Status::new(code, msg)),
+        }
+    }
+}
+
+async fn setup_adapter_mock(
+    adapter_response: Result<CanisterHttpSendResponse, (Code, String)>,
+) -> Channel {
+    let (client, server) = tokio::io::duplex(1024);
+    let mock_adapter = SingleResponseAdapter::new(adapter_response);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(CanisterHttpServiceServer::new(mock_adapter))
+            .serve_with_incoming(futures::stream::iter(vec![Ok::<_, std::io::Error>(server)]))
+            .await
+    });
+
+    let mut client = Some(client);
     Endpoint::try_from("http://[::]:50051")
         .unwrap()
         .connect_with_connector(service_fn(move |_: Uri| {
@@ -1004,7 +1750,19 @@ fn process_mock_canister_https_response(
     };
     let timeout = context.time + Duration::from_secs(5 * 60);
     let canister_id = context.request.sender;
+    let response_limit = pic.canister_http_response_limits.get(&subnet_id).cloned();
     let content = match &mock_canister_http_response.response {
+        CanisterHttpResponse::CanisterHttpReply(reply)
+            if response_limit.is_some_and(|limit| reply.body.len() as u64 > limit) =>
+        {
+            CanisterHttpResponseContent::Reject(CanisterHttpReject {
+                reject_code: RejectCode::SysFatal,
+                message: format!(
+                    "Http body exceeds size limit of {} bytes.",
+                    response_limit.unwrap()
+                ),
+            })
+        }
         CanisterHttpResponse::CanisterHttpReply(reply) => {
             let grpc_channel =
                 pic.runtime
@@ -1079,7 +1837,34 @@ pub struct MockCanisterHttp {
 
 impl Operation for MockCanisterHttp {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
-        process_mock_canister_https_response(pic, &self.mock_canister_http_response)
+        let Some(apply_after) = self.mock_canister_http_response.apply_after else {
+            return process_mock_canister_https_response(pic, &self.mock_canister_http_response);
+        };
+        // The response is validated now, but only applied in the first round
+        // executed once the IC time has advanced by `apply_after`.
+        let subnet_id = ic_types::SubnetId::new(ic_types::PrincipalId(
+            self.mock_canister_http_response.subnet_id,
+        ));
+        let Some(subnet) = pic.get_subnet_with_id(subnet_id) else {
+            return OpOut::Error(PocketIcError::SubnetNotFound(
+                self.mock_canister_http_response.subnet_id,
+            ));
+        };
+        let canister_http_request_id =
+            CanisterHttpRequestId::from(self.mock_canister_http_response.request_id);
+        if !subnet
+            .canister_http_request_contexts()
+            .contains_key(&canister_http_request_id)
+        {
+            return OpOut::Error(PocketIcError::InvalidCanisterHttpRequestId((
+                subnet_id,
+                canister_http_request_id,
+            )));
+        }
+        let due_time = subnet.time() + apply_after;
+        pic.held_canister_http_responses
+            .push((due_time, self.mock_canister_http_response.clone()));
+        OpOut::NoOutput
     }
 
     fn id(&self) -> OpId {
@@ -1090,6 +1875,345 @@ impl Operation for MockCanisterHttp {
     }
 }
 
+/// Limits the size of canister HTTP outcall responses on a subnet.
+/// Mocked responses exceeding the limit are rejected.
+#[derive(Clone, Debug)]
+pub struct SetCanisterHttpResponseLimit {
+    pub subnet_id: SubnetId,
+    pub max_bytes: u64,
+}
+
+impl Operation for SetCanisterHttpResponseLimit {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if pic.get_subnet_with_id(self.subnet_id).is_none() {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.subnet_id.get().0));
+        }
+        pic.canister_http_response_limits
+            .insert(self.subnet_id, self.max_bytes);
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "set_canister_http_response_limit({},{})",
+            self.subnet_id, self.max_bytes
+        ))
+    }
+}
+
+/// Configures the policy applied to XNet messages from one subnet to another,
+/// e.g., to delay or drop them. Without a policy (`None`), XNet messages are delivered
+/// as soon as possible.
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigureXNet {
+    pub from_subnet: SubnetId,
+    pub to_subnet: SubnetId,
+    pub policy: Option<XNetPolicy>,
+}
+
+impl Operation for ConfigureXNet {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if pic.get_subnet_with_id(self.from_subnet).is_none() {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.from_subnet.get().0));
+        }
+        let Some(to_subnet) = pic.get_subnet_with_id(self.to_subnet) else {
+            return OpOut::Error(PocketIcError::SubnetNotFound(self.to_subnet.get().0));
+        };
+        let policy = match self.policy {
+            None => None,
+            Some(XNetPolicy::Delay { ticks }) => {
+                Some(ic_state_machine_tests::XNetPolicy::Delay { ticks })
+            }
+            Some(XNetPolicy::Drop {
+                probability_percent,
+                ..
+            }) if probability_percent > 100 => {
+                return OpOut::Error(PocketIcError::InvalidXNetPolicy(format!(
+                    "drop probability of {}% exceeds 100%",
+                    probability_percent
+                )));
+            }
+            Some(XNetPolicy::Drop {
+                probability_percent,
+                seed,
+            }) => Some(ic_state_machine_tests::XNetPolicy::Drop {
+                probability_percent,
+                seed,
+            }),
+        };
+        to_subnet.set_xnet_policy(self.from_subnet, policy);
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "configure_xnet({},{},{:?})",
+            self.from_subnet, self.to_subnet, self.policy
+        ))
+    }
+}
+
+/// Configures the SOCKS proxy (e.g., `socks5://127.0.0.1:1080`) through which canister HTTP
+/// outcalls are made if a direct connection fails (as done by the HTTPS outcalls adapter
+/// on system subnets). Outcalls are only made directly if no proxy is configured (default).
+#[derive(Clone, Debug)]
+pub struct SetCanisterHttpSocksProxy {
+    pub proxy_url: Option<String>,
+}
+
+impl Operation for SetCanisterHttpSocksProxy {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if let Some(ref proxy_url) = self.proxy_url {
+            if let Err(e) = proxy_url.parse::<tonic::transport::Uri>() {
+                return OpOut::Error(PocketIcError::InvalidCanisterHttpSocksProxy(format!(
+                    "{}: {}",
+                    proxy_url, e
+                )));
+            }
+        }
+        pic.canister_http_socks_proxy = self.proxy_url.clone();
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "set_canister_http_socks_proxy({:?})",
+            self.proxy_url
+        ))
+    }
+}
+
+/// Returns the SOCKS proxy configured by [SetCanisterHttpSocksProxy] (if any).
+#[derive(Clone, Copy, Debug)]
+pub struct GetCanisterHttpSocksProxy;
+
+impl Operation for GetCanisterHttpSocksProxy {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        OpOut::CanisterHttpSocksProxy(pic.canister_http_socks_proxy.clone())
+    }
+
+    fn id(&self) -> OpId {
+        OpId("get_canister_http_socks_proxy".into())
+    }
+}
+
+/// TLS client certificate (chain) and private key (both DER-encoded) presented by canister HTTP
+/// outcalls to servers requiring mutual TLS. The private key is zeroized on drop and never printed.
+#[derive(Clone)]
+pub struct CanisterHttpClientIdentity {
+    cert_chain: Vec<Vec<u8>>,
+    private_key: Zeroizing<Vec<u8>>,
+}
+
+impl CanisterHttpClientIdentity {
+    fn from_pem(client_identity: &rest::CanisterHttpClientIdentity) -> Result<Self, String> {
+        let cert_chain = rustls_pemfile::certs(&mut client_identity.cert_chain_pem.as_bytes())
+            .map(|cert| cert.map(|cert| cert.to_vec()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Failed to parse certificate chain: {}", e))?;
+        if cert_chain.is_empty() {
+            return Err("No certificate found".to_string());
+        }
+        // The error messages must not contain any key material.
+        let private_key =
+            match rustls_pemfile::private_key(&mut client_identity.private_key_pem.as_bytes()) {
+                Ok(Some(private_key)) => Zeroizing::new(private_key.secret_der().to_vec()),
+                Ok(None) => return Err("No private key found".to_string()),
+                Err(_) => return Err("Failed to parse private key".to_string()),
+            };
+        let client_identity = Self {
+            cert_chain,
+            private_key,
+        };
+        // Fail early if the private key is not supported.
+        client_identity.tls_config()?;
+        Ok(client_identity)
+    }
+
+    /// Returns a TLS client configuration trusting the native root certificates
+    /// and presenting this identity if the server requests a client certificate.
+    pub(crate) fn tls_config(&self) -> Result<rustls_legacy::ClientConfig, String> {
+        rustls_legacy::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_native_roots()
+            .with_client_auth_cert(
+                self.cert_chain
+                    .iter()
+                    .cloned()
+                    .map(rustls_legacy::Certificate)
+                    .collect(),
+                rustls_legacy::PrivateKey(self.private_key.to_vec()),
+            )
+            .map_err(|e| format!("Invalid client identity: {}", e))
+    }
+}
+
+impl std::fmt::Debug for CanisterHttpClientIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CanisterHttpClientIdentity")
+            .field("cert_chain", &self.cert_chain.len())
+            .field("private_key", &"<redacted>")
+            .finish()
+    }
+}
+
+/// The Candid representation of a client identity is its certificate chain:
+/// the private key is never serialized.
+impl CandidType for CanisterHttpClientIdentity {
+    fn _ty() -> candid::types::Type {
+        Vec::<Vec<u8>>::ty()
+    }
+
+    fn idl_serialize<S: candid::types::Serializer>(&self, serializer: S) -> Result<(), S::Error> {
+        self.cert_chain.idl_serialize(serializer)
+    }
+}
+
+/// Configures the TLS client certificate (chain) and private key presented by canister HTTP
+/// outcalls to servers requiring mutual TLS. No client certificate is presented
+/// if no client identity is configured (default).
+#[derive(Clone, Debug)]
+pub struct SetCanisterHttpClientIdentity {
+    pub client_identity: Option<rest::CanisterHttpClientIdentity>,
+}
+
+impl Operation for SetCanisterHttpClientIdentity {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let client_identity = match self.client_identity {
+            Some(ref client_identity) => {
+                match CanisterHttpClientIdentity::from_pem(client_identity) {
+                    Ok(client_identity) => Some(Arc::new(client_identity)),
+                    Err(e) => {
+                        return OpOut::Error(PocketIcError::InvalidCanisterHttpClientIdentity(e))
+                    }
+                }
+            }
+            None => None,
+        };
+        pic.canister_http_client_identity = client_identity;
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        // The operation ID is displayed and thus only contains a hash of the client identity.
+        let client_identity = self.client_identity.as_ref().map(|client_identity| {
+            let mut hasher = Sha256::new();
+            hasher.write(client_identity.cert_chain_pem.as_bytes());
+            hasher.write(&[0]);
+            hasher.write(client_identity.private_key_pem.as_bytes());
+            Digest(hasher.finish())
+        });
+        OpId(format!(
+            "set_canister_http_client_identity({:?})",
+            client_identity
+        ))
+    }
+}
+
+/// Returns the client identity configured by [SetCanisterHttpClientIdentity] (if any).
+#[derive(Clone, Copy, Debug)]
+pub struct GetCanisterHttpClientIdentity;
+
+impl Operation for GetCanisterHttpClientIdentity {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        OpOut::CanisterHttpClientIdentity(pic.canister_http_client_identity.clone())
+    }
+
+    fn id(&self) -> OpId {
+        OpId("get_canister_http_client_identity".into())
+    }
+}
+
+/// Maximum number of key-value pairs of instance metadata.
+const MAX_INSTANCE_METADATA_ENTRIES: usize = 100;
+/// Maximum size of a key of instance metadata in bytes.
+const MAX_INSTANCE_METADATA_KEY_BYTES: usize = 256;
+/// Maximum size of a value of instance metadata in bytes.
+const MAX_INSTANCE_METADATA_VALUE_BYTES: usize = 4096;
+
+/// Attaches a key-value pair of metadata to the instance,
+/// overwriting any previous value for the key.
+#[derive(Clone, Debug)]
+pub struct SetInstanceMetadata {
+    pub key: String,
+    pub value: String,
+}
+
+impl Operation for SetInstanceMetadata {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if self.key.len() > MAX_INSTANCE_METADATA_KEY_BYTES {
+            return OpOut::Error(PocketIcError::InstanceMetadataLimitExceeded(format!(
+                "key exceeds {} bytes",
+                MAX_INSTANCE_METADATA_KEY_BYTES
+            )));
+        }
+        if self.value.len() > MAX_INSTANCE_METADATA_VALUE_BYTES {
+            return OpOut::Error(PocketIcError::InstanceMetadataLimitExceeded(format!(
+                "value exceeds {} bytes",
+                MAX_INSTANCE_METADATA_VALUE_BYTES
+            )));
+        }
+        if !pic.metadata.contains_key(&self.key)
+            && pic.metadata.len() >= MAX_INSTANCE_METADATA_ENTRIES
+        {
+            return OpOut::Error(PocketIcError::InstanceMetadataLimitExceeded(format!(
+                "instance metadata has {} entries",
+                MAX_INSTANCE_METADATA_ENTRIES
+            )));
+        }
+        pic.metadata.insert(self.key.clone(), self.value.clone());
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        let mut hasher = Sha256::new();
+        hasher.write(self.key.as_bytes());
+        hasher.write(&[0]);
+        hasher.write(self.value.as_bytes());
+        OpId(format!(
+            "set_instance_metadata({})",
+            hex::encode(hasher.finish())
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetInstanceMetadata {
+    pub key: String,
+}
+
+impl Operation for GetInstanceMetadata {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        OpOut::InstanceMetadataValue(pic.metadata.get(&self.key).cloned())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        let mut hasher = Sha256::new();
+        hasher.write(self.key.as_bytes());
+        OpId(format!(
+            "get_instance_metadata({})",
+            hex::encode(hasher.finish())
+        ))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct GetAllInstanceMetadata;
+
+impl Operation for GetAllInstanceMetadata {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        OpOut::InstanceMetadata(pic.metadata.clone())
+    }
+
+    fn id(&self) -> OpId {
+        OpId("get_all_instance_metadata".into())
+    }
+}
+
 #[derive(Clone, Debug, Copy)]
 pub struct PubKey {
     pub subnet_id: SubnetId,
@@ -1114,9 +2238,7 @@ pub struct Tick;
 
 impl Operation for Tick {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
-        for subnet in pic.subnets.read().unwrap().values() {
-            subnet.execute_round();
-        }
+        pic.execute_round_on_all_subnets();
         OpOut::NoOutput
     }
 
@@ -1132,8 +2254,8 @@ impl Operation for AdvanceTimeAndTick {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
         for subnet in pic.subnets.read().unwrap().values() {
             subnet.advance_time(self.0);
-            subnet.execute_round();
         }
+        pic.execute_round_on_all_subnets();
         OpOut::NoOutput
     }
 
@@ -1147,6 +2269,9 @@ pub struct SubmitIngressMessage(pub CanisterCall);
 
 impl Operation for SubmitIngressMessage {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if let Some(error) = pic.take_injected_fault(self.0.canister_id, &self.0.method) {
+            return OpOut::CanisterResult(Err(error));
+        }
         let canister_call = self.0.clone();
         let subnet = route_call(pic, canister_call);
         match subnet {
@@ -1222,6 +2347,9 @@ impl Operation for AwaitIngressMessage {
                 // Now, we execute on all subnets until we have the result
                 let max_rounds = 100;
                 for _i in 0..max_rounds {
+                    if pic.is_operation_cancelled() {
+                        return OpOut::Cancelled;
+                    }
                     match subnet.ingress_status(&self.0.msg_id) {
                         IngressStatus::Known {
                             state: IngressState::Completed(result),
@@ -1239,9 +2367,7 @@ impl Operation for AwaitIngressMessage {
                         }
                         _ => {}
                     }
-                    for subnet_ in pic.subnets.read().unwrap().values() {
-                        subnet_.execute_round();
-                    }
+                    pic.execute_round_on_all_subnets();
                 }
                 OpOut::Error(PocketIcError::BadIngressMessage(format!(
                     "Failed to answer to ingress {} after {} rounds.",
@@ -1257,20 +2383,68 @@ impl Operation for AwaitIngressMessage {
     }
 }
 
+/// Returns the status of an ingress message without executing any rounds.
 #[derive(Clone, Debug)]
-pub struct ExecuteIngressMessage(pub CanisterCall);
+pub struct GetIngressStatus(pub MessageId);
 
-impl Operation for ExecuteIngressMessage {
+impl Operation for GetIngressStatus {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
-        let canister_call = self.0.clone();
-        let subnet = route_call(pic, canister_call);
-        match subnet {
-            Ok(subnet) => {
-                match subnet.submit_ingress_as(
-                    self.0.sender,
-                    self.0.canister_id,
-                    self.0.method.clone(),
-                    self.0.payload.clone(),
+        let subnet = match route(pic, self.0.effective_principal.clone(), false) {
+            Ok(subnet) => subnet,
+            Err(e) => return OpOut::Error(PocketIcError::BadIngressMessage(e)),
+        };
+        // The result of an executed message is converted as for `AwaitIngressMessage`.
+        let from_result = |result: Result<
+            ic_state_machine_tests::WasmResult,
+            ic_state_machine_tests::UserError,
+        >| match OpOut::from(result) {
+            OpOut::CanisterResult(Ok(wasm_result)) => {
+                ::pocket_ic::IngressStatus::Replied(wasm_result)
+            }
+            OpOut::CanisterResult(Err(user_error)) => {
+                ::pocket_ic::IngressStatus::Rejected(user_error)
+            }
+            _ => unreachable!(),
+        };
+        let status = match subnet.ingress_status(&self.0.msg_id) {
+            IngressStatus::Unknown => ::pocket_ic::IngressStatus::Unknown,
+            IngressStatus::Known { state, .. } => match state {
+                IngressState::Received => ::pocket_ic::IngressStatus::Received,
+                IngressState::Processing => ::pocket_ic::IngressStatus::Processing,
+                IngressState::Completed(result) => from_result(Ok(result)),
+                IngressState::Failed(error) => from_result(Err(error)),
+                IngressState::Done => ::pocket_ic::IngressStatus::Done,
+            },
+        };
+        OpOut::IngressStatus(status)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("ingress_status_{}", self.0.msg_id))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExecuteIngressMessage(pub CanisterCall);
+
+impl Operation for ExecuteIngressMessage {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        if let Some(error) = pic.take_injected_fault(self.0.canister_id, &self.0.method) {
+            return OpOut::CanisterResult(Err(error));
+        }
+        let canister_call = self.0.clone();
+        let subnet = route_call(pic, canister_call);
+        match subnet {
+            Ok(subnet) => {
+                match subnet.submit_ingress_as(
+                    self.0.sender,
+                    self.0.canister_id,
+                    self.0.method.clone(),
+                    self.0.payload.clone(),
                 ) {
                     Err(SubmitIngressError::HttpError(e)) => {
                         eprintln!("Failed to submit ingress message: {}", e);
@@ -1284,9 +2458,7 @@ impl Operation for ExecuteIngressMessage {
                         // Now, we execute on all subnets until we have the result
                         let max_rounds = 100;
                         for _i in 0..max_rounds {
-                            for subnet_ in pic.subnets.read().unwrap().values() {
-                                subnet_.execute_round();
-                            }
+                            pic.execute_round_on_all_subnets();
                             match subnet.ingress_status(&msg_id) {
                                 IngressStatus::Known {
                                     state: IngressState::Completed(result),
@@ -1326,6 +2498,11 @@ pub struct Query(pub CanisterCall);
 
 impl Operation for Query {
     fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        // Queries are read-only and thus only fail due to repeated faults
+        // (a one-shot fault is left for the next update call).
+        if let Some(error) = pic.repeated_injected_fault(self.0.canister_id, &self.0.method) {
+            return OpOut::CanisterResult(Err(error));
+        }
         let canister_call = self.0.clone();
         let subnet = route_call(pic, canister_call);
         match subnet {
@@ -1345,6 +2522,10 @@ impl Operation for Query {
         }
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn id(&self) -> OpId {
         let call_id = self.0.id();
         OpId(format!("canister_query_{}", call_id.0))
@@ -1396,7 +2577,7 @@ impl Operation for DashboardRequest {
         };
 
         let fut: ApiResponse = Box::pin(into_api_response(resp));
-        OpOut::RawResponse(fut.shared())
+        OpOut::RawResponse(RawApiResponse(fut.shared()))
     }
 
     fn retry_if_busy(&self) -> bool {
@@ -1486,7 +2667,7 @@ impl Operation for StatusRequest {
             .into_response();
 
         let fut: ApiResponse = Box::pin(into_api_response(resp));
-        OpOut::RawResponse(fut.shared())
+        OpOut::RawResponse(RawApiResponse(fut.shared()))
     }
 
     fn retry_if_busy(&self) -> bool {
@@ -1628,7 +2809,7 @@ impl Operation for CallRequest {
                 // and we can return from the operation.
                 while !service_task.is_finished() && !ingress_proxy_task.is_finished() {}
 
-                OpOut::RawResponse(api_resp)
+                OpOut::RawResponse(RawApiResponse(api_resp))
             }
         }
     }
@@ -1711,7 +2892,7 @@ impl Operation for QueryRequest {
                 let resp = pic.runtime.block_on(svc.oneshot(request)).unwrap();
 
                 let fut: ApiResponse = Box::pin(into_api_response(resp));
-                OpOut::RawResponse(fut.shared())
+                OpOut::RawResponse(RawApiResponse(fut.shared()))
             }
         }
     }
@@ -1765,7 +2946,7 @@ impl Operation for ReadStateRequest {
                 let resp = pic.runtime.block_on(svc.oneshot(request)).unwrap();
 
                 let fut: ApiResponse = Box::pin(into_api_response(resp));
-                OpOut::RawResponse(fut.shared())
+                OpOut::RawResponse(RawApiResponse(fut.shared()))
             }
         }
     }
@@ -1785,7 +2966,66 @@ impl Operation for ReadStateRequest {
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+/// Retrieves a certificate of the state tree of the subnet of the given canister
+/// for the given paths (each given as a sequence of labels), as `read_state` would.
+#[derive(Clone, Debug)]
+pub struct GetStateCertificate {
+    pub canister_id: CanisterId,
+    pub paths: Vec<Vec<Vec<u8>>>,
+}
+
+impl Operation for GetStateCertificate {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let subnet = match route(pic, EffectivePrincipal::CanisterId(self.canister_id), false) {
+            Ok(subnet) => subnet,
+            Err(e) => return OpOut::Error(PocketIcError::RequestRoutingError(e)),
+        };
+        let delegation = pic.get_nns_delegation_for_subnet(subnet.get_subnet_id());
+        subnet.certify_latest_state();
+
+        // As for `read_state`, the path `/time` is always included.
+        let mut paths: Vec<StatePath> = self
+            .paths
+            .iter()
+            .map(|labels| labels.iter().map(Label::from).collect())
+            .collect();
+        paths.push(StatePath::from(Label::from("time")));
+        let labeled_tree = match sparse_labeled_tree_from_paths(&paths) {
+            Ok(labeled_tree) => labeled_tree,
+            Err(TooLongPathError) => {
+                return OpOut::Error(PocketIcError::StateCertificateError(
+                    "Failed to parse requested paths: path is too long.".to_string(),
+                ))
+            }
+        };
+        let Some((_, tree, certification)) =
+            subnet.state_manager.read_certified_state(&labeled_tree)
+        else {
+            return OpOut::Error(PocketIcError::StateCertificateError(
+                "Certified state is not available.".to_string(),
+            ));
+        };
+
+        let certificate = Certificate {
+            tree,
+            signature: Blob(certification.signed.signature.signature.get().0),
+            delegation,
+        };
+        let mut ser = serde_cbor::Serializer::new(Vec::new());
+        ser.self_describe().unwrap();
+        certificate.serialize(&mut ser).unwrap();
+        OpOut::Certificate(ser.into_inner())
+    }
+
+    fn id(&self) -> OpId {
+        let mut hasher = Sha256::new();
+        self.paths.hash(&mut hasher);
+        let hash = Digest(hasher.finish());
+        OpId(format!("state_certificate({},{})", self.canister_id, hash))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, CandidType)]
 pub enum EffectivePrincipal {
     None,
     SubnetId(SubnetId),
@@ -1969,12 +3209,19 @@ pub struct GetStableMemory {
 
 impl Operation for GetStableMemory {
     fn compute(&self, pocket_ic: &mut PocketIc) -> OpOut {
-        OpOut::StableMemBytes(
-            pocket_ic
-                .try_route_canister(self.canister_id)
-                .unwrap()
-                .stable_memory(self.canister_id),
-        )
+        let range = GetStableMemoryRange {
+            canister_id: self.canister_id,
+            offset: 0,
+            length: u64::MAX,
+        };
+        match range.compute(pocket_ic) {
+            OpOut::StableMemRange { bytes, .. } => OpOut::StableMemBytes(bytes),
+            op_out => op_out,
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
     }
 
     fn id(&self) -> OpId {
@@ -1982,6 +3229,132 @@ impl Operation for GetStableMemory {
     }
 }
 
+/// Returns (at most) `length` bytes of the stable memory of the given canister
+/// starting at `offset` together with the total size of the stable memory
+/// so that clients can page through the stable memory.
+/// No bytes are returned if `offset` is beyond the end of the stable memory.
+#[derive(Clone, Debug)]
+pub struct GetStableMemoryRange {
+    pub canister_id: CanisterId,
+    pub offset: u64,
+    pub length: u64,
+}
+
+impl Operation for GetStableMemoryRange {
+    fn compute(&self, pocket_ic: &mut PocketIc) -> OpOut {
+        match pocket_ic.try_route_canister(self.canister_id) {
+            Some(subnet) if subnet.canister_exists(self.canister_id) => {
+                let (total_size, bytes) = subnet.stable_memory_range(
+                    self.canister_id,
+                    usize::try_from(self.offset).unwrap_or(usize::MAX),
+                    usize::try_from(self.length).unwrap_or(usize::MAX),
+                );
+                OpOut::StableMemRange {
+                    total_size: total_size as u64,
+                    bytes,
+                }
+            }
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "get_stable_memory_range({},{},{})",
+            self.canister_id, self.offset, self.length
+        ))
+    }
+}
+
+/// Returns the cycles balances of the given canister recorded after every tick
+/// starting at tick `since_tick` (ticks are counted from 1 once the recording is enabled)
+/// together with the cycles consumed by the canister since the previous tick.
+#[derive(Clone, Debug)]
+pub struct GetCycleConsumption {
+    pub canister_id: CanisterId,
+    pub since_tick: u64,
+}
+
+impl Operation for GetCycleConsumption {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let Some(ref cycle_history) = pic.cycle_history else {
+            return OpOut::Error(PocketIcError::CycleHistoryDisabled);
+        };
+        match cycle_history.records.get(&self.canister_id) {
+            Some(records) => OpOut::CycleConsumption(
+                records
+                    .iter()
+                    .filter(|record| record.tick >= self.since_tick)
+                    .cloned()
+                    .collect(),
+            ),
+            None => match pic.try_route_canister(self.canister_id) {
+                Some(subnet) if subnet.canister_exists(self.canister_id) => {
+                    OpOut::CycleConsumption(vec![])
+                }
+                _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+            },
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "get_cycle_consumption({},{})",
+            self.canister_id, self.since_tick
+        ))
+    }
+}
+
+/// Returns the log records the given canister recorded, e.g., via `ic0.debug_print`
+/// or when trapping, in the order in which they were recorded.
+#[derive(Clone, Debug)]
+pub struct GetCanisterLogs {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for GetCanisterLogs {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) if subnet.canister_exists(self.canister_id) => OpOut::CanisterLogs(
+                subnet
+                    .canister_log(self.canister_id)
+                    .records()
+                    .iter()
+                    .cloned()
+                    .collect(),
+            ),
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_canister_logs({})", self.canister_id))
+    }
+}
+
+/// Saves a snapshot of the instance to the given directory (see [PocketIc::save_to_dir]).
+#[derive(Clone, Debug)]
+pub struct SaveInstance {
+    pub path: PathBuf,
+}
+
+impl Operation for SaveInstance {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.save_to_dir(&self.path) {
+            Ok(()) => OpOut::NoOutput,
+            Err(msg) => OpOut::Error(PocketIcError::InstanceSnapshotError(msg)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("save_instance({})", self.path.display()))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct GetCyclesBalance {
     pub canister_id: CanisterId,
@@ -1996,6 +3369,10 @@ impl Operation for GetCyclesBalance {
         OpOut::Cycles(result)
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("get_cycles_balance({})", self.canister_id))
     }
@@ -2021,6 +3398,10 @@ impl Operation for GetSubnet {
         }
     }
 
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
     fn id(&self) -> OpId {
         OpId(format!("get_subnet({})", self.canister_id))
     }
@@ -2071,6 +3452,182 @@ impl Operation for AddCycles {
     }
 }
 
+/// Sets the freezing threshold (in seconds) of the given canister without going through
+/// the management canister and returns the resulting freezing threshold.
+#[derive(Clone, Debug)]
+pub struct SetFreezingThreshold {
+    pub canister_id: CanisterId,
+    pub threshold_secs: u64,
+}
+
+impl TryFrom<RawSetFreezingThreshold> for SetFreezingThreshold {
+    type Error = ConversionError;
+    fn try_from(
+        RawSetFreezingThreshold {
+            canister_id,
+            threshold_secs,
+        }: RawSetFreezingThreshold,
+    ) -> Result<Self, Self::Error> {
+        match CanisterId::try_from(canister_id) {
+            Ok(canister_id) => Ok(SetFreezingThreshold {
+                canister_id,
+                threshold_secs,
+            }),
+            Err(_) => Err(ConversionError {
+                message: "Bad canister id".to_string(),
+            }),
+        }
+    }
+}
+
+impl Operation for SetFreezingThreshold {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) if subnet.canister_exists(self.canister_id) => {
+                let threshold_secs =
+                    subnet.set_freezing_threshold(self.canister_id, self.threshold_secs);
+                pic.freezing_thresholds
+                    .insert(self.canister_id, threshold_secs);
+                OpOut::FreezingThreshold(threshold_secs)
+            }
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "set_freezing_threshold({},{})",
+            self.canister_id, self.threshold_secs
+        ))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GetFreezingThreshold {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for GetFreezingThreshold {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) if subnet.canister_exists(self.canister_id) => {
+                OpOut::FreezingThreshold(subnet.freezing_threshold(self.canister_id))
+            }
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("get_freezing_threshold({})", self.canister_id))
+    }
+}
+
+/// Registers a fault so that the next call (or every call if `repeat` is set)
+/// of the given method of the given canister fails with the given error
+/// without being executed by the canister. Faults apply to update calls
+/// (when they are submitted) and to query calls.
+#[derive(Clone, Debug, Hash)]
+pub struct FaultInjection {
+    pub canister_id: CanisterId,
+    pub method: String,
+    pub error: UserError,
+    pub repeat: bool,
+}
+
+impl TryFrom<RawFaultInjection> for FaultInjection {
+    type Error = ConversionError;
+    fn try_from(
+        RawFaultInjection {
+            canister_id,
+            method,
+            error,
+            repeat,
+        }: RawFaultInjection,
+    ) -> Result<Self, Self::Error> {
+        match CanisterId::try_from(canister_id) {
+            Ok(canister_id) => Ok(FaultInjection {
+                canister_id,
+                method,
+                error,
+                repeat,
+            }),
+            Err(_) => Err(ConversionError {
+                message: "Bad canister id".to_string(),
+            }),
+        }
+    }
+}
+
+impl Operation for FaultInjection {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        pic.injected_faults.lock().unwrap().push(self.clone());
+        OpOut::NoOutput
+    }
+
+    fn id(&self) -> OpId {
+        let mut hasher = Sha256::new();
+        self.hash(&mut hasher);
+        let hash = Digest(hasher.finish());
+        OpId(format!(
+            "inject_fault({},{},{})",
+            self.canister_id, self.method, hash
+        ))
+    }
+}
+
+/// Replaces the controllers of the given canister without going through
+/// the management canister and returns the resulting controllers.
+#[derive(Clone, Debug)]
+pub struct SetControllers {
+    pub canister_id: CanisterId,
+    pub controllers: Vec<PrincipalId>,
+}
+
+impl TryFrom<RawSetControllers> for SetControllers {
+    type Error = ConversionError;
+    fn try_from(
+        RawSetControllers {
+            canister_id,
+            controllers,
+        }: RawSetControllers,
+    ) -> Result<Self, Self::Error> {
+        match CanisterId::try_from(canister_id) {
+            Ok(canister_id) => Ok(SetControllers {
+                canister_id,
+                controllers: controllers.into_iter().map(PrincipalId).collect(),
+            }),
+            Err(_) => Err(ConversionError {
+                message: "Bad canister id".to_string(),
+            }),
+        }
+    }
+}
+
+impl Operation for SetControllers {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        match pic.try_route_canister(self.canister_id) {
+            Some(subnet) if subnet.canister_exists(self.canister_id) => {
+                let controllers =
+                    subnet.set_controllers(self.canister_id, self.controllers.clone());
+                OpOut::Controllers(controllers.into_iter().map(|p| p.0).collect())
+            }
+            _ => OpOut::Error(PocketIcError::CanisterNotFound(self.canister_id)),
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "set_controllers({},[{}])",
+            self.canister_id,
+            self.controllers.iter().map(|p| p.to_string()).join(",")
+        ))
+    }
+}
+
 struct Digest([u8; 32]);
 
 impl std::fmt::Debug for Digest {
@@ -2117,19 +3674,156 @@ impl Operation for InstallCanisterAsController {
     }
 }
 
-// ================================================================================================================= //
-// Helpers
+/// Executes a call to the management canister on behalf of a controller of the given canister.
+fn execute_ic00_call_as_controller(
+    pic: &PocketIc,
+    canister_id: CanisterId,
+    method: Ic00Method,
+    payload: Vec<u8>,
+) -> Result<ic_state_machine_tests::WasmResult, OpOut> {
+    let Some(subnet) = pic.try_route_canister(canister_id) else {
+        return Err(OpOut::Error(PocketIcError::CanisterNotFound(canister_id)));
+    };
+    let sender = subnet
+        .get_latest_state()
+        .canister_state(&canister_id)
+        .and_then(|canister| canister.controllers().iter().next().cloned())
+        .unwrap_or_else(PrincipalId::new_anonymous);
+    subnet
+        .execute_ingress_as(sender, CanisterId::ic_00(), method, payload)
+        .map_err(|e| Err::<ic_state_machine_tests::WasmResult, _>(e).into())
+}
 
-fn route(
-    pic: &mut PocketIc,
-    effective_principal: EffectivePrincipal,
-    is_provisional_create_canister: bool,
-) -> Result<Arc<StateMachine>, String> {
-    match effective_principal {
-        EffectivePrincipal::SubnetId(subnet_id) => pic
-            .get_subnet_with_id(subnet_id)
-            .ok_or(format!("Subnet with ID {subnet_id} not found")),
-        EffectivePrincipal::CanisterId(canister_id) => match pic.try_route_canister(canister_id) {
+/// Uploads a chunk of Wasm code to the chunk store of a canister
+/// (as the management canister's `upload_chunk`) and returns the hash of the chunk.
+/// The chunk store is part of the canister's state and thus round-trips through snapshots.
+#[derive(Clone, Debug)]
+pub struct UploadChunk {
+    pub canister_id: CanisterId,
+    pub chunk: Vec<u8>,
+}
+
+impl Operation for UploadChunk {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let args = UploadChunkArgs {
+            canister_id: self.canister_id.into(),
+            chunk: self.chunk.clone(),
+        };
+        match execute_ic00_call_as_controller(
+            pic,
+            self.canister_id,
+            Ic00Method::UploadChunk,
+            args.encode(),
+        ) {
+            Ok(ic_state_machine_tests::WasmResult::Reply(bytes)) => {
+                match Decode!(&bytes, UploadChunkReply) {
+                    Ok(reply) => OpOut::Bytes(reply.hash),
+                    Err(e) => OpOut::Error(PocketIcError::BadIngressMessage(format!(
+                        "Failed to decode reply of upload_chunk: {}",
+                        e
+                    ))),
+                }
+            }
+            Ok(reject) => Ok::<_, ic_state_machine_tests::UserError>(reject).into(),
+            Err(op_out) => op_out,
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "upload_chunk({},{})",
+            self.canister_id,
+            hex::encode(Sha256::hash(&self.chunk))
+        ))
+    }
+}
+
+/// Clears the chunk store of a canister (as the management canister's `clear_chunk_store`).
+#[derive(Clone, Copy, Debug)]
+pub struct ClearChunkStore {
+    pub canister_id: CanisterId,
+}
+
+impl Operation for ClearChunkStore {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let args = ClearChunkStoreArgs {
+            canister_id: self.canister_id.into(),
+        };
+        match execute_ic00_call_as_controller(
+            pic,
+            self.canister_id,
+            Ic00Method::ClearChunkStore,
+            args.encode(),
+        ) {
+            Ok(res) => Ok::<_, ic_state_machine_tests::UserError>(res).into(),
+            Err(op_out) => op_out,
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!("clear_chunk_store({})", self.canister_id))
+    }
+}
+
+/// Installs code assembled from chunks previously uploaded to the chunk store of a canister
+/// (as the management canister's `install_chunked_code`). The chunks are taken from the chunk
+/// store of the target canister itself unless a different store canister is specified.
+#[derive(Clone, Debug)]
+pub struct InstallChunkedCode {
+    pub mode: CanisterInstallModeV2,
+    pub target_canister: CanisterId,
+    pub store_canister: Option<CanisterId>,
+    pub chunk_hashes_list: Vec<Vec<u8>>,
+    pub wasm_module_hash: Vec<u8>,
+    pub arg: Vec<u8>,
+}
+
+impl Operation for InstallChunkedCode {
+    fn compute(&self, pic: &mut PocketIc) -> OpOut {
+        let args = InstallChunkedCodeArgs::new(
+            self.mode,
+            self.target_canister,
+            self.store_canister,
+            self.chunk_hashes_list.clone(),
+            self.wasm_module_hash.clone(),
+            self.arg.clone(),
+        );
+        match execute_ic00_call_as_controller(
+            pic,
+            self.target_canister,
+            Ic00Method::InstallChunkedCode,
+            args.encode(),
+        ) {
+            Ok(res) => Ok::<_, ic_state_machine_tests::UserError>(res).into(),
+            Err(op_out) => op_out,
+        }
+    }
+
+    fn id(&self) -> OpId {
+        OpId(format!(
+            "install_chunked_code({},{:?},{:?},{},{})",
+            self.target_canister,
+            self.store_canister,
+            self.mode,
+            hex::encode(&self.wasm_module_hash),
+            hex::encode(Sha256::hash(&self.arg))
+        ))
+    }
+}
+
+// ================================================================================================================= //
+// Helpers
+
+fn route(
+    pic: &mut PocketIc,
+    effective_principal: EffectivePrincipal,
+    is_provisional_create_canister: bool,
+) -> Result<Arc<StateMachine>, String> {
+    match effective_principal {
+        EffectivePrincipal::SubnetId(subnet_id) => pic
+            .get_subnet_with_id(subnet_id)
+            .ok_or(format!("Subnet with ID {subnet_id} not found")),
+        EffectivePrincipal::CanisterId(canister_id) => match pic.try_route_canister(canister_id) {
             Some(subnet) => Ok(subnet),
             None => {
                 if is_provisional_create_canister {
@@ -2298,7 +3992,7 @@ fn route_call(
     route(pic, effective_principal, is_provisional_create_canister)
 }
 
-fn systemtime_to_unix_epoch_nanos(st: SystemTime) -> u64 {
+pub(crate) fn systemtime_to_unix_epoch_nanos(st: SystemTime) -> u64 {
     st.duration_since(SystemTime::UNIX_EPOCH)
         .unwrap()
         .as_nanos()
@@ -2309,6 +4003,7 @@ fn systemtime_to_unix_epoch_nanos(st: SystemTime) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ic_crypto_tree_hash::{LookupStatus, MixedHashTree};
 
     #[test]
     fn state_label_test() {
@@ -2350,6 +4045,381 @@ mod tests {
         assert_eq!(state3, state4);
     }
 
+    #[test]
+    fn op_out_candid_test() {
+        use candid::types::value::{IDLValue, VariantValue};
+        use candid::types::Label;
+
+        // Returns the label and the payload of the Candid variant an output is serialized as.
+        let encode = |op_out: OpOut| {
+            let bytes = candid::encode_one(&op_out).unwrap();
+            match candid::decode_one::<IDLValue>(&bytes).unwrap() {
+                IDLValue::Variant(VariantValue(field, _)) => (field.id, field.val),
+                value => panic!("unexpected value: {:?}", value),
+            }
+        };
+
+        let canister_id = CanisterId::from_u64(42);
+        let (label, _) = encode(OpOut::CanisterId(canister_id));
+        assert_eq!(label, Label::Named("CanisterId".to_string()));
+        let (label, _) = encode(OpOut::Error(PocketIcError::CanisterNotFound(canister_id)));
+        assert_eq!(label, Label::Named("Error".to_string()));
+        let (label, value) = encode(OpOut::Time(7));
+        assert_eq!(label, Label::Named("Time".to_string()));
+        assert_eq!(value, IDLValue::Nat64(7));
+
+        // A raw API response is materialized once it is ready.
+        let materialize = |response: RawApiResponse| {
+            let bytes = candid::encode_one(&response).unwrap();
+            candid::decode_one::<Option<(u16, Vec<(String, String)>, Vec<u8>)>>(&bytes).unwrap()
+        };
+        let headers = BTreeMap::from([("content-type".to_string(), b"text/plain".to_vec())]);
+        let response: ApiResponse = futures::future::ready((200, headers, b"ok".to_vec())).boxed();
+        let response = response.shared();
+        assert_eq!(materialize(RawApiResponse(response.clone())), None);
+        futures::executor::block_on(response.clone());
+        assert_eq!(
+            materialize(RawApiResponse(response.clone())),
+            Some((
+                200,
+                vec![("content-type".to_string(), "text/plain".to_string())],
+                b"ok".to_vec()
+            ))
+        );
+        let (label, _) = encode(OpOut::RawResponse(RawApiResponse(response)));
+        assert_eq!(label, Label::Named("RawResponse".to_string()));
+    }
+
+    #[test]
+    fn canister_http_socks_proxy_test() {
+        let mut pic = PocketIc::default();
+        let get_proxy = |pic: &mut PocketIc| match GetCanisterHttpSocksProxy.compute(pic) {
+            OpOut::CanisterHttpSocksProxy(proxy_url) => proxy_url,
+            out => panic!("unexpected result: {:?}", out),
+        };
+        assert_eq!(get_proxy(&mut pic), None);
+
+        let proxy_url = Some("socks5://127.0.0.1:1080".to_string());
+        let op = SetCanisterHttpSocksProxy {
+            proxy_url: proxy_url.clone(),
+        };
+        assert!(matches!(op.compute(&mut pic), OpOut::NoOutput));
+        assert_eq!(get_proxy(&mut pic), proxy_url);
+
+        // An invalid proxy URL is rejected and the previous configuration is retained.
+        let op = SetCanisterHttpSocksProxy {
+            proxy_url: Some("not a url".to_string()),
+        };
+        assert!(matches!(
+            op.compute(&mut pic),
+            OpOut::Error(PocketIcError::InvalidCanisterHttpSocksProxy(_))
+        ));
+        assert_eq!(get_proxy(&mut pic), proxy_url);
+
+        let op = SetCanisterHttpSocksProxy { proxy_url: None };
+        assert!(matches!(op.compute(&mut pic), OpOut::NoOutput));
+        assert_eq!(get_proxy(&mut pic), None);
+    }
+
+    #[test]
+    fn canister_http_client_identity_test() {
+        let mut pic = PocketIc::default();
+        let get_client_identity =
+            |pic: &mut PocketIc| match GetCanisterHttpClientIdentity.compute(pic) {
+                OpOut::CanisterHttpClientIdentity(client_identity) => client_identity,
+                out => panic!("unexpected result: {:?}", out),
+            };
+        assert!(get_client_identity(&mut pic).is_none());
+
+        let key_pair = rcgen::KeyPair::generate().unwrap();
+        let cert = rcgen::CertificateParams::new(vec!["localhost".to_string()])
+            .unwrap()
+            .self_signed(&key_pair)
+            .unwrap();
+        let private_key_pem = key_pair.serialize_pem();
+        let op = SetCanisterHttpClientIdentity {
+            client_identity: Some(rest::CanisterHttpClientIdentity {
+                cert_chain_pem: cert.pem(),
+                private_key_pem: private_key_pem.clone(),
+            }),
+        };
+        assert!(matches!(op.compute(&mut pic), OpOut::NoOutput));
+        let client_identity = get_client_identity(&mut pic).unwrap();
+        assert!(client_identity.tls_config().is_ok());
+
+        // The private key is neither printed nor part of the operation ID.
+        let private_key_body = private_key_pem.lines().nth(1).unwrap();
+        assert!(!format!("{:?}", op).contains(private_key_body));
+        assert!(!format!("{:?}", client_identity).contains(private_key_body));
+        assert!(!op.id().0.contains(private_key_body));
+
+        // An invalid client identity is rejected and the previous configuration is retained.
+        let op = SetCanisterHttpClientIdentity {
+            client_identity: Some(rest::CanisterHttpClientIdentity {
+                cert_chain_pem: cert.pem(),
+                private_key_pem: "not a key".to_string(),
+            }),
+        };
+        assert!(matches!(
+            op.compute(&mut pic),
+            OpOut::Error(PocketIcError::InvalidCanisterHttpClientIdentity(_))
+        ));
+        assert!(get_client_identity(&mut pic).is_some());
+
+        let op = SetCanisterHttpClientIdentity {
+            client_identity: None,
+        };
+        assert!(matches!(op.compute(&mut pic), OpOut::NoOutput));
+        assert!(get_client_identity(&mut pic).is_none());
+    }
+
+    #[test]
+    fn set_controllers_test() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        let controllers = vec![
+            PrincipalId::new_user_test_id(2),
+            PrincipalId::new_user_test_id(1),
+        ];
+        let op = SetControllers {
+            canister_id,
+            controllers: controllers.clone(),
+        };
+        let OpOut::Controllers(new_controllers) = compute_assert_state_change(&mut pic, op) else {
+            panic!("unexpected result");
+        };
+        let expected: std::collections::BTreeSet<_> = controllers.into_iter().collect();
+        assert_eq!(
+            new_controllers,
+            expected.iter().map(|p| p.0).collect::<Vec<_>>()
+        );
+        let state = pic
+            .try_route_canister(canister_id)
+            .unwrap()
+            .get_latest_state();
+        assert_eq!(
+            state.canister_state(&canister_id).unwrap().controllers(),
+            &expected
+        );
+
+        let op = SetControllers {
+            canister_id: CanisterId::from_u64(42),
+            controllers: vec![],
+        };
+        let OpOut::Error(PocketIcError::CanisterNotFound(_)) = op.compute(&mut pic) else {
+            panic!("unexpected result");
+        };
+    }
+
+    #[test]
+    fn stable_memory_range_test() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        SetStableMemory {
+            canister_id,
+            data: b"deadbeef".to_vec(),
+        }
+        .compute(&mut pic);
+        let get_range = |pic: &mut PocketIc, offset, length| {
+            let OpOut::StableMemRange { total_size, bytes } = (GetStableMemoryRange {
+                canister_id,
+                offset,
+                length,
+            })
+            .compute(pic) else {
+                panic!("unexpected result");
+            };
+            (total_size, bytes)
+        };
+        // The stable memory consists of a single Wasm page.
+        let page_size = 64 * 1024;
+
+        assert_eq!(get_range(&mut pic, 4, 4), (page_size, b"beef".to_vec()));
+        // The range is truncated at the end of the stable memory.
+        assert_eq!(
+            get_range(&mut pic, page_size - 2, 4),
+            (page_size, vec![0; 2])
+        );
+        // No bytes are returned beyond the end of the stable memory.
+        assert_eq!(get_range(&mut pic, page_size, 4), (page_size, vec![]));
+        assert_eq!(get_range(&mut pic, u64::MAX, u64::MAX), (page_size, vec![]));
+        // The full read is built on the range read.
+        let OpOut::StableMemBytes(bytes) = (GetStableMemory { canister_id }).compute(&mut pic)
+        else {
+            panic!("unexpected result");
+        };
+        assert_eq!(bytes.len() as u64, page_size);
+        assert_eq!(&bytes[..8], b"deadbeef");
+    }
+
+    #[test]
+    fn topology_delta_test() {
+        let mut pic = PocketIc::default();
+        let topology = pic.topology();
+
+        // Without a previous topology, the entire topology is returned.
+        // The returned topology is recorded in the history, which changes the state.
+        let OpOut::TopologyDelta(delta) =
+            compute_assert_state_change(&mut pic, GetTopologyDelta { since: None })
+        else {
+            panic!("unexpected result");
+        };
+        assert_eq!(delta.added_subnets, topology.0);
+        assert!(delta.removed_subnets.is_empty());
+        let topology_hash: [u8; 32] = hex::decode(&delta.topology_hash)
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        // The topology has not changed since (and is already recorded in the history).
+        let OpOut::TopologyDelta(delta) = compute_assert_state_immutable(
+            &mut pic,
+            GetTopologyDelta {
+                since: Some(topology_hash),
+            },
+        ) else {
+            panic!("unexpected result");
+        };
+        assert!(delta.is_empty());
+        assert_eq!(delta.topology_hash, hex::encode(topology_hash));
+
+        // Compare against a previous topology with one more node and one more subnet.
+        let (subnet_id, config) = topology.0.iter().next().unwrap();
+        let mut previous = topology.clone();
+        let extra_node = rest::RawNodeId {
+            node_id: PrincipalId::new_node_test_id(42).as_slice().to_vec(),
+        };
+        previous
+            .0
+            .get_mut(subnet_id)
+            .unwrap()
+            .node_ids
+            .push(extra_node.clone());
+        let extra_subnet = PrincipalId::new_subnet_test_id(42).0;
+        previous.0.insert(extra_subnet, config.clone());
+        let previous_hash = compute_topology_hash(&previous);
+        pic.topology_history.insert(previous_hash, previous);
+        let OpOut::TopologyDelta(delta) = (GetTopologyDelta {
+            since: Some(previous_hash),
+        })
+        .compute(&mut pic) else {
+            panic!("unexpected result");
+        };
+        assert!(delta.added_subnets.is_empty());
+        assert_eq!(delta.removed_subnets, vec![extra_subnet]);
+        assert!(delta.added_nodes.is_empty());
+        assert_eq!(
+            delta.removed_nodes,
+            BTreeMap::from([(*subnet_id, vec![extra_node])])
+        );
+
+        // Unknown topology hash.
+        let OpOut::Error(PocketIcError::TopologyNotFound(_)) = (GetTopologyDelta {
+            since: Some([0; 32]),
+        })
+        .compute(&mut pic) else {
+            panic!("unexpected result");
+        };
+    }
+
+    #[test]
+    fn cycle_consumption_test() {
+        let mut pic = PocketIc::default();
+        let canister_id = pic.any_subnet().create_canister(None);
+        pic.any_subnet().add_cycles(canister_id, 2_000_000_000_000);
+        let get_cycle_consumption = |pic: &mut PocketIc, since_tick| {
+            GetCycleConsumption {
+                canister_id,
+                since_tick,
+            }
+            .compute(pic)
+        };
+
+        // Nothing is recorded by default.
+        assert!(matches!(
+            get_cycle_consumption(&mut pic, 0),
+            OpOut::Error(PocketIcError::CycleHistoryDisabled)
+        ));
+
+        pic.enable_cycle_history();
+        for _ in 0..3 {
+            Tick.compute(&mut pic);
+        }
+        let OpOut::CycleConsumption(records) = get_cycle_consumption(&mut pic, 0) else {
+            panic!("unexpected result");
+        };
+        let ticks: Vec<_> = records.iter().map(|r| r.tick).collect();
+        assert_eq!(ticks, vec![1, 2, 3]);
+        assert_eq!(
+            records.last().unwrap().balance,
+            pic.any_subnet().cycle_balance(canister_id)
+        );
+        let OpOut::CycleConsumption(records) = get_cycle_consumption(&mut pic, 3) else {
+            panic!("unexpected result");
+        };
+        assert_eq!(records.len(), 1);
+
+        // Non-existent canisters are reported.
+        let OpOut::Error(PocketIcError::CanisterNotFound(_)) = (GetCycleConsumption {
+            canister_id: CanisterId::from_u64(u64::MAX / 2),
+            since_tick: 0,
+        })
+        .compute(&mut pic) else {
+            panic!("unexpected result");
+        };
+    }
+
+    #[test]
+    fn snapshot_round_trip_test() {
+        let snapshot_dir = TempDir::new().unwrap();
+
+        let pic = PocketIc::default();
+        let canister_id = pic.any_subnet().create_canister(None);
+        pic.any_subnet().add_cycles(canister_id, 2_000_000_000_000);
+        pic.save_to_dir(snapshot_dir.path()).unwrap();
+        let state_label = pic.get_state_label();
+        let time = pic.any_subnet().time();
+        drop(pic);
+
+        // The snapshot can be loaded (repeatedly) and the loaded instance
+        // has the same state as the saved one.
+        for _ in 0..2 {
+            let pic = PocketIc::from_snapshot(Runtime::new().unwrap().into(), snapshot_dir.path())
+                .unwrap();
+            assert_eq!(pic.get_state_label(), state_label);
+            assert_eq!(pic.any_subnet().time(), time);
+            assert_eq!(
+                pic.any_subnet().cycle_balance(canister_id),
+                2_000_000_000_000
+            );
+        }
+
+        // Saving to a non-empty directory fails.
+        let pic = PocketIc::default();
+        assert!(pic.save_to_dir(snapshot_dir.path()).is_err());
+    }
+
+    #[test]
+    fn snapshot_version_mismatch_test() {
+        let snapshot_dir = TempDir::new().unwrap();
+        PocketIc::default()
+            .save_to_dir(snapshot_dir.path())
+            .unwrap();
+
+        let metadata_path = snapshot_dir.path().join(SNAPSHOT_METADATA_FILE);
+        let mut metadata: SnapshotMetadata =
+            serde_json::from_slice(&std::fs::read(&metadata_path).unwrap()).unwrap();
+        metadata.version = SNAPSHOT_VERSION + 1;
+        std::fs::write(&metadata_path, serde_json::to_string(&metadata).unwrap()).unwrap();
+
+        let err = PocketIc::from_snapshot(Runtime::new().unwrap().into(), snapshot_dir.path())
+            .err()
+            .unwrap();
+        assert!(err.contains("Incompatible snapshot version"));
+
+        // A directory without a snapshot cannot be loaded either.
+        let empty_dir = TempDir::new().unwrap();
+        assert!(PocketIc::from_snapshot(Runtime::new().unwrap().into(), empty_dir.path()).is_err());
+    }
+
     #[test]
     fn test_time() {
         let mut pic = PocketIc::default();
@@ -2365,6 +4435,125 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_install_chunked_code() {
+        let mut pic = PocketIc::default();
+        let canister_id = pic.any_subnet().create_canister(None);
+        pic.any_subnet().add_cycles(canister_id, 20_000_000_000_000);
+
+        let wasm = counter_wasm();
+        let mut chunk_hashes_list = vec![];
+        for chunk in wasm.chunks(wasm.len() / 2 + 1) {
+            let op = UploadChunk {
+                canister_id,
+                chunk: chunk.to_vec(),
+            };
+            match compute_assert_state_change(&mut pic, op) {
+                OpOut::Bytes(hash) => {
+                    assert_eq!(hash, Sha256::hash(chunk).to_vec());
+                    chunk_hashes_list.push(hash);
+                }
+                res => panic!("Unexpected OpOut: {:?}", res),
+            }
+        }
+        assert_eq!(chunk_hashes_list.len(), 2);
+
+        let install_chunked_code = InstallChunkedCode {
+            mode: CanisterInstallModeV2::Install,
+            target_canister: canister_id,
+            store_canister: None,
+            chunk_hashes_list,
+            wasm_module_hash: Sha256::hash(&wasm).to_vec(),
+            arg: vec![],
+        };
+        compute_assert_state_change(&mut pic, install_chunked_code.clone());
+        assert_eq!(
+            pic.any_subnet().module_hash(canister_id),
+            Some(Sha256::hash(&wasm))
+        );
+
+        // The chunks are gone once the chunk store is cleared.
+        compute_assert_state_change(&mut pic, ClearChunkStore { canister_id });
+        let reinstall = InstallChunkedCode {
+            mode: CanisterInstallModeV2::Reinstall,
+            ..install_chunked_code
+        };
+        assert!(matches!(
+            reinstall.compute(&mut pic),
+            OpOut::CanisterResult(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_peek_canister_http_is_immutable() {
+        let mut pic = PocketIc::default();
+
+        match compute_assert_state_immutable(&mut pic, PeekCanisterHttp) {
+            OpOut::CanisterHttp(requests) => assert!(requests.is_empty()),
+            res => panic!("Unexpected OpOut: {:?}", res),
+        };
+    }
+
+    #[test]
+    fn test_set_time_and_tick() {
+        let mut pic = PocketIc::default();
+        let canister_id = pic.any_subnet().create_canister(None);
+        pic.any_subnet().add_cycles(canister_id, 20_000_000_000_000);
+        let install_op = InstallCanisterAsController {
+            canister_id,
+            mode: CanisterInstallMode::Install,
+            module: wat::parse_str(TIMER_WAT).unwrap(),
+            payload: vec![],
+        };
+        compute_assert_state_change(&mut pic, install_op);
+
+        let OpOut::Time(now) = GetTime.compute(&mut pic) else {
+            unreachable!()
+        };
+        let timer_nanos = now + 60_000_000_000;
+        let set_timer = ExecuteIngressMessage(CanisterCall {
+            sender: PrincipalId::new_anonymous(),
+            canister_id,
+            method: "set_timer".into(),
+            payload: timer_nanos.to_le_bytes().to_vec(),
+            effective_principal: EffectivePrincipal::None,
+        });
+        compute_assert_state_change(&mut pic, set_timer);
+        let (query, _) = query_update_constructors(canister_id);
+        let timer_fired = |pic: &mut PocketIc| match query("read").compute(pic) {
+            OpOut::CanisterResult(Ok(::pocket_ic::WasmResult::Reply(bytes))) => {
+                bytes != [0, 0, 0, 0]
+            }
+            out => panic!("unexpected result: {:?}", out),
+        };
+
+        // The timer does not fire before it is due.
+        let op = SetTimeAndTick {
+            timestamp_nanos: timer_nanos - 1,
+        };
+        assert!(matches!(op.compute(&mut pic), OpOut::Time(t) if t >= timer_nanos - 1));
+        assert!(!timer_fired(&mut pic));
+
+        let op = SetTimeAndTick {
+            timestamp_nanos: timer_nanos + 1,
+        };
+        let OpOut::Time(new_time) = op.compute(&mut pic) else {
+            unreachable!()
+        };
+        assert!(new_time >= timer_nanos + 1);
+        assert!(timer_fired(&mut pic));
+
+        // The time cannot be set backwards.
+        let op = SetTimeAndTick {
+            timestamp_nanos: timer_nanos,
+        };
+        assert!(matches!(
+            compute_assert_state_immutable(&mut pic, op),
+            OpOut::Error(PocketIcError::TimeMovesBackwards((current, requested)))
+                if current == new_time && requested == timer_nanos
+        ));
+    }
+
     #[test]
     fn test_execute_message() {
         let (mut pic, canister_id) = new_pic_counter_installed();
@@ -2423,6 +4612,210 @@ mod tests {
         assert_eq!(initial_balance, new_balance);
     }
 
+    #[test]
+    fn test_set_freezing_threshold() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        let get_freezing_threshold = GetFreezingThreshold { canister_id };
+        let OpOut::FreezingThreshold(initial_threshold) =
+            compute_assert_state_immutable(&mut pic, get_freezing_threshold.clone())
+        else {
+            unreachable!()
+        };
+        let new_threshold = initial_threshold + 42;
+        let set_freezing_threshold = SetFreezingThreshold {
+            canister_id,
+            threshold_secs: new_threshold,
+        };
+        let OpOut::FreezingThreshold(threshold) =
+            compute_assert_state_change(&mut pic, set_freezing_threshold)
+        else {
+            unreachable!()
+        };
+        assert_eq!(threshold, new_threshold);
+        let OpOut::FreezingThreshold(threshold) =
+            compute_assert_state_immutable(&mut pic, get_freezing_threshold)
+        else {
+            unreachable!()
+        };
+        assert_eq!(threshold, new_threshold);
+    }
+
+    #[test]
+    fn test_configure_xnet_changes_state_label() {
+        let mut pic = PocketIc::new(
+            Runtime::new().unwrap().into(),
+            ExtendedSubnetConfigSet {
+                application: vec![SubnetSpec::default(), SubnetSpec::default()],
+                ..Default::default()
+            },
+            None,
+            false,
+        );
+        let subnet_ids: Vec<_> = pic.subnets.read().unwrap().keys().cloned().collect();
+        let configure_xnet = |policy| ConfigureXNet {
+            from_subnet: subnet_ids[0],
+            to_subnet: subnet_ids[1],
+            policy,
+        };
+        compute_assert_state_change(
+            &mut pic,
+            configure_xnet(Some(XNetPolicy::Delay { ticks: 3 })),
+        );
+        compute_assert_state_immutable(
+            &mut pic,
+            configure_xnet(Some(XNetPolicy::Delay { ticks: 3 })),
+        );
+        compute_assert_state_change(&mut pic, configure_xnet(None));
+    }
+
+    #[test]
+    fn test_held_canister_http_responses_change_state_label() {
+        let mut pic = PocketIc::default();
+        let state0 = pic.get_state_label();
+        pic.held_canister_http_responses.push((
+            SystemTime::UNIX_EPOCH,
+            MockCanisterHttpResponse {
+                subnet_id: pic.any_subnet().get_subnet_id().get().0,
+                request_id: 0,
+                response: CanisterHttpResponse::CanisterHttpReply(rest::CanisterHttpReply {
+                    status: 200,
+                    headers: vec![],
+                    body: vec![],
+                }),
+                apply_after: Some(Duration::from_secs(1)),
+            },
+        ));
+        assert_ne!(state0, pic.get_state_label());
+    }
+
+    #[test]
+    fn test_set_canister_http_response_limit() {
+        let mut pic = PocketIc::default();
+        let subnet_id = pic.any_subnet().get_subnet_id();
+        let set_limit = |max_bytes| SetCanisterHttpResponseLimit {
+            subnet_id,
+            max_bytes,
+        };
+        compute_assert_state_change(&mut pic, set_limit(42));
+        compute_assert_state_change(&mut pic, set_limit(1024));
+        compute_assert_state_immutable(&mut pic, set_limit(1024));
+    }
+
+    #[test]
+    fn test_get_state_certificate() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        let module_hash_path = vec![
+            b"canister".to_vec(),
+            canister_id.get().to_vec(),
+            b"module_hash".to_vec(),
+        ];
+        let get_state_certificate = GetStateCertificate {
+            canister_id,
+            paths: vec![module_hash_path.clone()],
+        };
+        let OpOut::Certificate(certificate) = get_state_certificate.compute(&mut pic) else {
+            unreachable!()
+        };
+        let certificate: Certificate = serde_cbor::from_slice(&certificate).unwrap();
+        assert_eq!(
+            certificate.tree.lookup(&module_hash_path),
+            LookupStatus::Found(&MixedHashTree::Leaf(Sha256::hash(&counter_wasm()).to_vec()))
+        );
+        // The time is always certified.
+        assert!(matches!(
+            certificate.tree.lookup(&[b"time"]),
+            LookupStatus::Found(_)
+        ));
+
+        let get_state_certificate = GetStateCertificate {
+            canister_id: CanisterId::from_u64(u64::MAX / 2),
+            paths: vec![],
+        };
+        assert!(matches!(
+            get_state_certificate.compute(&mut pic),
+            OpOut::Error(PocketIcError::RequestRoutingError(_))
+        ));
+    }
+
+    #[test]
+    fn test_fault_injection() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        let (query, update) = query_update_constructors(canister_id);
+        let error = UserError {
+            code: ::pocket_ic::ErrorCode::CanisterCalledTrap,
+            description: "injected fault".to_string(),
+        };
+
+        compute_assert_state_change(
+            &mut pic,
+            FaultInjection {
+                canister_id,
+                method: "write".to_string(),
+                error: error.clone(),
+                repeat: false,
+            },
+        );
+        assert_eq!(pic.injected_faults().len(), 1);
+
+        // The fault only applies to the given method.
+        let OpOut::CanisterResult(Ok(_)) = query("read").compute(&mut pic) else {
+            unreachable!()
+        };
+        let OpOut::CanisterResult(Err(user_error)) = update("write").compute(&mut pic) else {
+            unreachable!()
+        };
+        assert_eq!(user_error, error);
+        assert!(pic.injected_faults().is_empty());
+
+        // The fault was one-shot and the canister did not execute the faulty call.
+        let OpOut::CanisterResult(Ok(::pocket_ic::WasmResult::Reply(bytes))) =
+            update("write").compute(&mut pic)
+        else {
+            unreachable!()
+        };
+        assert_eq!(bytes, 1_u32.to_le_bytes());
+    }
+
+    #[test]
+    fn test_read_only_operations_are_immutable() {
+        let (mut pic, canister_id) = new_pic_counter_installed();
+        let (query, _) = query_update_constructors(canister_id);
+        let subnet_id = pic.any_subnet().get_subnet_id();
+        compute_assert_state_change(
+            &mut pic,
+            FaultInjection {
+                canister_id,
+                method: "read".to_string(),
+                error: UserError {
+                    code: ::pocket_ic::ErrorCode::CanisterCalledTrap,
+                    description: "injected fault".to_string(),
+                },
+                repeat: false,
+            },
+        );
+
+        fn assert_read_only(pic: &mut PocketIc, op: impl Operation) -> OpOut {
+            assert!(op.is_read_only(), "{} is not read-only", op.id().0);
+            compute_assert_state_immutable(pic, op)
+        }
+
+        // A query does not consume the one-shot fault.
+        let OpOut::CanisterResult(Ok(_)) = assert_read_only(&mut pic, query("read")) else {
+            unreachable!()
+        };
+        assert_eq!(pic.injected_faults().len(), 1);
+        assert_read_only(&mut pic, GetTime {});
+        assert_read_only(&mut pic, GetScheduledMessages { subnet_id });
+        assert_read_only(&mut pic, GetSubnetMetrics { subnet_id });
+        assert_read_only(&mut pic, GetCanisterLogs { canister_id });
+        assert_read_only(
+            &mut pic,
+            GetInstanceMetadata {
+                key: "key".to_string(),
+            },
+        );
+    }
+
     fn query_update_constructors(
         canister_id: CanisterId,
     ) -> (
@@ -2512,6 +4905,47 @@ mod tests {
         wat::parse_str(COUNTER_WAT).unwrap().as_slice().to_vec()
     }
 
+    /// Sets the global timer to the (little-endian) timestamp passed to
+    /// `set_timer` and counts how often the timer fired.
+    const TIMER_WAT: &str = r#"
+(module
+  (import "ic0" "msg_reply" (func $msg_reply))
+  (import "ic0" "msg_reply_data_append"
+    (func $msg_reply_data_append (param i32 i32)))
+  (import "ic0" "msg_arg_data_copy"
+    (func $msg_arg_data_copy (param i32 i32 i32)))
+  (import "ic0" "global_timer_set"
+    (func $global_timer_set (param i64) (result i64)))
+
+  (func $set_timer
+    (call $msg_arg_data_copy (i32.const 0) (i32.const 0) (i32.const 8))
+    (drop (call $global_timer_set (i64.load (i32.const 0))))
+    (call $msg_reply))
+
+  (func $timer
+    (global.set 0
+      (i32.add
+        (global.get 0)
+        (i32.const 1))))
+
+  (func $read
+    (i32.store
+      (i32.const 0)
+      (global.get 0))
+    (call $msg_reply_data_append
+      (i32.const 0)
+      (i32.const 4))
+    (call $msg_reply))
+
+  (memory $memory 1)
+  (export "memory" (memory $memory))
+  (global (mut i32) (i32.const 0))
+  (export "canister_update set_timer" (func $set_timer))
+  (export "canister_global_timer" (func $timer))
+  (export "canister_query read" (func $read))
+)
+    "#;
+
     const COUNTER_WAT: &str = r#"
 ;; Counter with global variable ;;
 (module
@@ -4,12 +4,20 @@
 /// body. This has to be canonicalized into a PocketIc Operation before we can
 /// deterministically update the PocketIc state machine.
 ///
-use super::state::{ApiState, OpOut, PocketIcError, StateLabel, UpdateReply};
+use super::state::{
+    ApiState, OpOut, PocketIcError, RawApiResponse, StateLabel, UpdateError, UpdateReply,
+};
 use crate::pocket_ic::{
-    AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, DashboardRequest,
-    ExecuteIngressMessage, GetCanisterHttp, GetCyclesBalance, GetStableMemory, GetSubnet, GetTime,
-    GetTopology, MockCanisterHttp, PubKey, Query, QueryRequest, ReadStateRequest, SetStableMemory,
-    SetTime, StatusRequest, SubmitIngressMessage, Tick,
+    AddCycles, AwaitIngressMessage, CallRequest, CallRequestVersion, ConfigureXNet,
+    DashboardRequest, ExecuteIngressMessage, FaultInjection, GetAllInstanceMetadata,
+    GetCanisterHttp, GetCanisterLogs, GetCycleConsumption, GetCyclesBalance, GetFreezingThreshold,
+    GetIngressStatus, GetInstanceMetadata, GetNodeTime, GetScheduledMessages, GetStableMemory,
+    GetStableMemoryRange, GetStateCertificate, GetSubnet, GetSubnetMetrics, GetTime, GetTopology,
+    GetTopologyDelta, MockCanisterHttp, PeekCanisterHttp, PubKey, Query, QueryRequest,
+    ReadStateRequest, SetCanisterHttpClientIdentity, SetCanisterHttpResponseLimit,
+    SetCanisterHttpSocksProxy, SetControllers, SetFreezingThreshold, SetInstanceMetadata,
+    SetNodeClockSkew, SetStableMemory, SetTime, SetTimeAndTick, StatusRequest,
+    SubmitIngressMessage, Tick,
 };
 use crate::{async_trait, pocket_ic::PocketIc, BlobStore, InstanceId, OpId, Operation};
 use aide::{
@@ -34,14 +42,21 @@ use hyper::header;
 use ic_http_endpoints_public::cors_layer;
 use ic_types::CanisterId;
 use pocket_ic::common::rest::{
-    self, ApiResponse, ExtendedSubnetConfigSet, HttpGatewayConfig, HttpGatewayInfo, InstanceConfig,
-    MockCanisterHttpResponse, RawAddCycles, RawCanisterCall, RawCanisterHttpRequest, RawCanisterId,
-    RawCanisterResult, RawCycles, RawMessageId, RawMockCanisterHttpResponse, RawSetStableMemory,
-    RawStableMemory, RawSubmitIngressResult, RawSubnetId, RawTime, RawWasmResult, Topology,
+    self, ApiResponse, AutoProgressConfig, CycleConsumptionRecord, ExtendedSubnetConfigSet,
+    GatewayStatus, HttpGatewayConfig, HttpGatewayDetails, HttpGatewayInfo, InstanceConfig,
+    MockCanisterHttpResponse, OpTiming, ProgressStatus, RawAddCycles, RawCanisterCall,
+    RawCanisterHttpRequest, RawCanisterId, RawCanisterResult, RawCertificate, RawConfigureXNet,
+    RawCycles, RawFaultInjection, RawFreezingThreshold, RawIngressStatus, RawInstanceMetadataEntry,
+    RawInstanceMetadataKey, RawMessageId, RawMockCanisterHttpResponse, RawOpId,
+    RawScheduledMessage, RawSetCanisterHttpClientIdentity, RawSetCanisterHttpResponseLimit,
+    RawSetCanisterHttpSocksProxy, RawSetControllers, RawSetFreezingThreshold, RawSetNodeClockSkew,
+    RawSetStableMemory, RawStableMemory, RawStableMemoryRangeRequest, RawStateCertificateRequest,
+    RawSubmitIngressResult, RawSubnetId, RawSubnetNode, RawTime, RawTopologyDeltaRequest,
+    RawWasmResult, StableMemoryRange, SubnetMetrics, Topology, TopologyDelta,
 };
 use pocket_ic::WasmResult;
 use serde::Serialize;
-use std::{collections::BTreeMap, fs::File, sync::Arc, time::Duration};
+use std::{collections::BTreeMap, fs::File, str::FromStr, sync::Arc, time::Duration};
 use tokio::{runtime::Runtime, sync::RwLock, time::Instant};
 use tower_http::limit::RequestBodyLimitLayer;
 use tracing::trace;
@@ -52,6 +67,8 @@ type PocketHttpResponse = (BTreeMap<String, Vec<u8>>, Vec<u8>);
 /// response on a open http request.
 pub static TIMEOUT_HEADER_NAME: HeaderName = HeaderName::from_static("processing-timeout-ms");
 const RETRY_TIMEOUT_S: u64 = 300;
+/// The maximum time an `await_graph` request is parked if no processing timeout is specified.
+pub const DEFAULT_AWAIT_GRAPH_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Clone)]
 pub struct AppState {
@@ -69,12 +86,45 @@ where
     ApiRouter::new()
         .directory_route("/query", post(handler_json_query))
         .directory_route("/topology", get(handler_topology))
+        .directory_route("/topology_delta", post(handler_topology_delta))
         .directory_route("/get_time", get(handler_get_time))
         .directory_route("/get_canister_http", get(handler_get_canister_http))
+        .directory_route("/peek_canister_http", get(handler_peek_canister_http))
         .directory_route("/get_cycles", post(handler_get_cycles))
+        .directory_route(
+            "/get_state_certificate",
+            post(handler_get_state_certificate),
+        )
+        .directory_route(
+            "/get_freezing_threshold",
+            post(handler_get_freezing_threshold),
+        )
         .directory_route("/get_stable_memory", post(handler_get_stable_memory))
+        .directory_route(
+            "/get_stable_memory_range",
+            post(handler_get_stable_memory_range),
+        )
         .directory_route("/get_subnet", post(handler_get_subnet))
         .directory_route("/pub_key", post(handler_pub_key))
+        .directory_route(
+            "/get_scheduled_messages",
+            post(handler_get_scheduled_messages),
+        )
+        .directory_route("/get_subnet_metrics", post(handler_get_subnet_metrics))
+        .directory_route("/ingress_status", post(handler_ingress_status))
+        .directory_route("/get_node_time", post(handler_get_node_time))
+        .directory_route(
+            "/get_instance_metadata",
+            post(handler_get_instance_metadata),
+        )
+        .directory_route(
+            "/canister_logs/:canister_id",
+            get(handler_get_canister_logs),
+        )
+        .directory_route(
+            "/cycle_consumption/:canister_id/:since_tick",
+            get(handler_get_cycle_consumption),
+        )
 }
 
 pub fn instance_update_routes<S>() -> ApiRouter<S>
@@ -96,10 +146,35 @@ where
             post(handler_execute_ingress_message),
         )
         .directory_route("/set_time", post(handler_set_time))
+        .directory_route("/set_time_and_tick", post(handler_set_time_and_tick))
+        .directory_route("/set_node_clock_skew", post(handler_set_node_clock_skew))
         .directory_route("/add_cycles", post(handler_add_cycles))
+        .directory_route(
+            "/set_freezing_threshold",
+            post(handler_set_freezing_threshold),
+        )
+        .directory_route("/set_controllers", post(handler_set_controllers))
         .directory_route("/set_stable_memory", post(handler_set_stable_memory))
+        .directory_route("/inject_fault", post(handler_inject_fault))
         .directory_route("/tick", post(handler_tick))
         .directory_route("/mock_canister_http", post(handler_mock_canister_http))
+        .directory_route(
+            "/set_canister_http_response_limit",
+            post(handler_set_canister_http_response_limit),
+        )
+        .directory_route(
+            "/set_canister_http_socks_proxy",
+            post(handler_set_canister_http_socks_proxy),
+        )
+        .directory_route(
+            "/set_canister_http_client_identity",
+            post(handler_set_canister_http_client_identity),
+        )
+        .directory_route(
+            "/set_instance_metadata",
+            post(handler_set_instance_metadata),
+        )
+        .directory_route("/configure_xnet", post(handler_configure_xnet))
 }
 
 pub fn instance_api_v2_routes<S>() -> ApiRouter<S>
@@ -140,14 +215,32 @@ where
     S: Clone + Send + Sync + 'static,
     AppState: extract::FromRef<S>,
 {
-    ApiRouter::new().directory_route(
-        "/canister/:ecid/call",
-        post(handler_call_v3)
-            .layer(RequestBodyLimitLayer::new(
-                4 * 1024 * 1024, // MAX_REQUEST_BODY_SIZE in BN
-            ))
-            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
-    )
+    ApiRouter::new()
+        .directory_route(
+            "/canister/:ecid/call",
+            post(handler_call_v3)
+                .layer(RequestBodyLimitLayer::new(
+                    4 * 1024 * 1024, // MAX_REQUEST_BODY_SIZE in BN
+                ))
+                .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+        )
+        // The v3 `query` and `read_state` endpoints behave as their v2 counterparts.
+        .directory_route(
+            "/canister/:ecid/query",
+            post(handler_query)
+                .layer(RequestBodyLimitLayer::new(
+                    4 * 1024 * 1024, // MAX_REQUEST_BODY_SIZE in BN
+                ))
+                .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+        )
+        .directory_route(
+            "/canister/:ecid/read_state",
+            post(handler_read_state)
+                .layer(RequestBodyLimitLayer::new(
+                    4 * 1024 * 1024, // MAX_REQUEST_BODY_SIZE in BN
+                ))
+                .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+        )
 }
 
 pub fn instances_routes<S>() -> ApiRouter<S>
@@ -189,6 +282,30 @@ where
         // Stop automatic progress (see endpoint `auto_progress`)
         // on an IC instance.
         .api_route("/:id/stop_progress", post(stop_progress))
+        //
+        // Pause automatic progress (see endpoint `auto_progress`)
+        // on an IC instance without terminating it.
+        .api_route("/:id/pause_progress", post(pause_progress))
+        //
+        // Resume automatic progress paused by the endpoint `pause_progress`.
+        .api_route("/:id/resume_progress", post(resume_progress))
+        //
+        // The status of the thread making an IC instance progress automatically.
+        .api_route("/:id/progress_status", get(progress_status))
+        //
+        // The slowest operations computed on an IC instance.
+        .api_route("/:id/op_timings", get(op_timings))
+        //
+        // Resets the wall-clock time spent computing operations
+        // on an IC instance with a CPU budget.
+        .api_route("/:id/reset_cpu_budget", post(reset_cpu_budget))
+        //
+        // Cancels an operation running or queued up on an IC instance
+        // (see `ApiState::cancel_operation`).
+        .api_route("/:id/cancel_operation", post(cancel_operation))
+        //
+        // All key-value metadata attached to an IC instance.
+        .api_route("/:id/metadata/kv", get(handler_get_all_instance_metadata))
         .layer(cors_layer())
 }
 
@@ -198,11 +315,17 @@ where
     AppState: extract::FromRef<S>,
 {
     ApiRouter::new()
+        // List all HTTP gateways with their ports, backends, domains, and status.
+        .api_route("/", get(list_http_gateways))
         // Create a new HTTP gateway instance. Takes a HttpGatewayConfig.
         // Returns an InstanceId and the HTTP gateway's port.
         .api_route("/", post(create_http_gateway))
+        // Returns the port, backend, domains, and status of an HTTP gateway.
+        .api_route("/:id", get(get_http_gateway))
         // Stops an HTTP gateway.
         .api_route("/:id/stop", post(stop_http_gateway))
+        // Returns the status of an HTTP gateway.
+        .api_route("/:id/status", get(http_gateway_status))
 }
 
 async fn run_operation<T: Serialize + FromOpOut>(
@@ -224,11 +347,29 @@ async fn run_operation<T: Serialize + FromOpOut>(
             .update_with_timeout(op.clone(), instance_id, timeout)
             .await
         {
+            // The queue of a busy instance may drain, so we retry (if applicable).
+            Err(UpdateError::QueueFull) if retry_if_busy => {
+                trace!(
+                    "run_operation::retry_queue_full instance_id={}",
+                    instance_id
+                );
+                match retry_policy.next_backoff() {
+                    Some(duration) => tokio::time::sleep(duration).await,
+                    None => {
+                        break (
+                            StatusCode::TOO_MANY_REQUESTS,
+                            ApiResponse::Error {
+                                message: "Service is overloaded, try again later.".to_string(),
+                            },
+                        )
+                    }
+                }
+            }
             Err(e) => {
                 break (
-                    StatusCode::BAD_REQUEST,
+                    update_error_status_code(&e),
                     ApiResponse::Error {
-                        message: format!("{:?}", e),
+                        message: e.to_string(),
                     },
                 )
             }
@@ -280,6 +421,19 @@ async fn run_operation<T: Serialize + FromOpOut>(
     }
 }
 
+/// Each kind of [UpdateError] is mapped to a distinct HTTP status code
+/// so that clients can react to it programmatically.
+fn update_error_status_code(e: &UpdateError) -> StatusCode {
+    match e {
+        UpdateError::InstanceNotFound => StatusCode::NOT_FOUND,
+        UpdateError::InstanceDeleted => StatusCode::GONE,
+        UpdateError::InstanceBusy => StatusCode::CONFLICT,
+        UpdateError::QueueFull => StatusCode::TOO_MANY_REQUESTS,
+        UpdateError::OperationNotFound => StatusCode::UNPROCESSABLE_ENTITY,
+        UpdateError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct OpConversionError;
 
@@ -299,6 +453,18 @@ impl<T: TryFrom<OpOut>> FromOpOut for T {
                     message: format!("{:?}", e),
                 },
             ),
+            OpOut::Cancelled => (
+                StatusCode::GONE,
+                ApiResponse::Error {
+                    message: "Cancelled".to_string(),
+                },
+            ),
+            OpOut::Expired => (
+                StatusCode::GONE,
+                ApiResponse::Error {
+                    message: "Expired".to_string(),
+                },
+            ),
             val => {
                 if let Ok(t) = T::try_from(val) {
                     (StatusCode::OK, ApiResponse::Success(t))
@@ -337,6 +503,16 @@ impl TryFrom<OpOut> for Topology {
     }
 }
 
+impl TryFrom<OpOut> for TopologyDelta {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::TopologyDelta(delta) => Ok(delta),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for () {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -357,6 +533,16 @@ impl TryFrom<OpOut> for RawCycles {
     }
 }
 
+impl TryFrom<OpOut> for RawFreezingThreshold {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::FreezingThreshold(threshold_secs) => Ok(RawFreezingThreshold { threshold_secs }),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for RawStableMemory {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -369,6 +555,19 @@ impl TryFrom<OpOut> for RawStableMemory {
     }
 }
 
+impl TryFrom<OpOut> for StableMemoryRange {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::StableMemRange { total_size, bytes } => Ok(StableMemoryRange {
+                total_size,
+                blob: bytes,
+            }),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for RawCanisterResult {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -390,6 +589,93 @@ impl TryFrom<OpOut> for RawCanisterResult {
     }
 }
 
+impl TryFrom<OpOut> for Option<String> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::InstanceMetadataValue(value) => Ok(value),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for BTreeMap<String, String> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::InstanceMetadata(metadata) => Ok(metadata),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for Vec<rest::CanisterLogRecord> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CanisterLogs(records) => Ok(records
+                .into_iter()
+                .map(|record| rest::CanisterLogRecord {
+                    idx: record.idx,
+                    timestamp_nanos: record.timestamp_nanos,
+                    content: record.content,
+                })
+                .collect()),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for Vec<candid::Principal> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::Controllers(controllers) => Ok(controllers),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for RawIngressStatus {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::IngressStatus(status) => Ok(status.into()),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for RawCertificate {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::Certificate(certificate) => Ok(RawCertificate { certificate }),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for SubnetMetrics {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::SubnetMetrics(metrics) => Ok(metrics),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
+impl TryFrom<OpOut> for Vec<CycleConsumptionRecord> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::CycleConsumption(records) => Ok(records),
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 impl TryFrom<OpOut> for PocketIcError {
     type Error = OpConversionError;
     fn try_from(value: OpOut) -> Result<Self, Self::Error> {
@@ -465,11 +751,23 @@ impl TryFrom<OpOut> for Vec<RawCanisterHttpRequest> {
     }
 }
 
+impl TryFrom<OpOut> for Vec<RawScheduledMessage> {
+    type Error = OpConversionError;
+    fn try_from(value: OpOut) -> Result<Self, Self::Error> {
+        match value {
+            OpOut::ScheduledMessages(scheduled_messages) => {
+                Ok(scheduled_messages.into_iter().map(|m| m.into()).collect())
+            }
+            _ => Err(OpConversionError),
+        }
+    }
+}
+
 #[async_trait]
 impl FromOpOut for PocketHttpResponse {
     async fn from(value: OpOut) -> (StatusCode, ApiResponse<PocketHttpResponse>) {
         match value {
-            OpOut::RawResponse(fut) => {
+            OpOut::RawResponse(RawApiResponse(fut)) => {
                 let (status, headers, bytes) = fut.await;
                 (
                     StatusCode::from_u16(status).unwrap(),
@@ -527,6 +825,35 @@ pub async fn handler_topology(
     (code, Json(response))
 }
 
+pub async fn handler_topology_delta(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    extract::Json(RawTopologyDeltaRequest { since }): extract::Json<RawTopologyDeltaRequest>,
+) -> (StatusCode, Json<ApiResponse<TopologyDelta>>) {
+    let timeout = timeout_or_default(headers);
+    let since = match since
+        .map(|since| {
+            hex::decode(&since)
+                .ok()
+                .and_then(|since| <[u8; 32]>::try_from(since).ok())
+                .ok_or(format!("Invalid topology hash: {}", since))
+        })
+        .transpose()
+    {
+        Ok(since) => since,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::Error { message }),
+            )
+        }
+    };
+    let op = GetTopologyDelta { since };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_get_time(
     State(AppState { api_state, .. }): State<AppState>,
     headers: HeaderMap,
@@ -549,6 +876,19 @@ pub async fn handler_get_canister_http(
     (code, Json(response))
 }
 
+/// Lists all pending canister HTTP outcalls without processing them
+/// (a pure read that does not change the state label of the instance).
+pub async fn handler_peek_canister_http(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<Vec<RawCanisterHttpRequest>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = PeekCanisterHttp {};
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_mock_canister_http(
     State(AppState { api_state, .. }): State<AppState>,
     headers: HeaderMap,
@@ -567,6 +907,80 @@ pub async fn handler_mock_canister_http(
     (code, Json(response))
 }
 
+pub async fn handler_set_canister_http_response_limit(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawSetCanisterHttpResponseLimit {
+        subnet_id,
+        max_bytes,
+    }): axum::extract::Json<RawSetCanisterHttpResponseLimit>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let subnet_id = ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+        &subnet_id.subnet_id,
+    )));
+    let op = SetCanisterHttpResponseLimit {
+        subnet_id,
+        max_bytes,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_configure_xnet(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawConfigureXNet {
+        from_subnet,
+        to_subnet,
+        policy,
+    }): axum::extract::Json<RawConfigureXNet>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let subnet_id = |raw_subnet_id: RawSubnetId| {
+        ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+            &raw_subnet_id.subnet_id,
+        )))
+    };
+    let op = ConfigureXNet {
+        from_subnet: subnet_id(from_subnet),
+        to_subnet: subnet_id(to_subnet),
+        policy,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_set_canister_http_socks_proxy(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawSetCanisterHttpSocksProxy { proxy_url }): axum::extract::Json<
+        RawSetCanisterHttpSocksProxy,
+    >,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SetCanisterHttpSocksProxy { proxy_url };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_set_canister_http_client_identity(
+    State(AppState { api_state, .. }): State<AppState>,
+    headers: HeaderMap,
+    Path(instance_id): Path<InstanceId>,
+    axum::extract::Json(RawSetCanisterHttpClientIdentity { client_identity }): axum::extract::Json<
+        RawSetCanisterHttpClientIdentity,
+    >,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SetCanisterHttpClientIdentity { client_identity };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_get_cycles(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -589,16 +1003,16 @@ pub async fn handler_get_cycles(
     }
 }
 
-pub async fn handler_get_stable_memory(
+pub async fn handler_get_freezing_threshold(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
     headers: HeaderMap,
-    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
-) -> (StatusCode, Json<ApiResponse<RawStableMemory>>) {
+    extract::Json(raw_canister_id): extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<RawFreezingThreshold>>) {
     let timeout = timeout_or_default(headers);
     match CanisterId::try_from(raw_canister_id.canister_id) {
         Ok(canister_id) => {
-            let get_op = GetStableMemory { canister_id };
+            let get_op = GetFreezingThreshold { canister_id };
             let (code, response) = run_operation(api_state, instance_id, timeout, get_op).await;
             (code, Json(response))
         }
@@ -611,18 +1025,25 @@ pub async fn handler_get_stable_memory(
     }
 }
 
-pub async fn handler_get_subnet(
+pub async fn handler_get_state_certificate(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
     headers: HeaderMap,
-    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
-) -> (StatusCode, Json<ApiResponse<Option<RawSubnetId>>>) {
+    extract::Json(raw_state_certificate_request): extract::Json<RawStateCertificateRequest>,
+) -> (StatusCode, Json<ApiResponse<RawCertificate>>) {
     let timeout = timeout_or_default(headers);
-    match CanisterId::try_from(raw_canister_id.canister_id) {
+    match CanisterId::try_from(raw_state_certificate_request.canister_id) {
         Ok(canister_id) => {
-            let op = GetSubnet { canister_id };
-            let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
-            (code, Json(res))
+            let get_op = GetStateCertificate {
+                canister_id,
+                paths: raw_state_certificate_request
+                    .paths
+                    .into_iter()
+                    .map(|path| path.into_iter().map(|label| label.0).collect())
+                    .collect(),
+            };
+            let (code, response) = run_operation(api_state, instance_id, timeout, get_op).await;
+            (code, Json(response))
         }
         Err(e) => (
             StatusCode::BAD_REQUEST,
@@ -633,13 +1054,132 @@ pub async fn handler_get_subnet(
     }
 }
 
-pub async fn handler_pub_key(
+pub async fn handler_get_stable_memory(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
     headers: HeaderMap,
-    extract::Json(RawSubnetId { subnet_id }): extract::Json<RawSubnetId>,
-) -> (StatusCode, Json<ApiResponse<Vec<u8>>>) {
-    let timeout = timeout_or_default(headers);
+    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<RawStableMemory>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let get_op = GetStableMemory { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, get_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_get_stable_memory_range(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    axum::extract::Json(RawStableMemoryRangeRequest {
+        canister_id,
+        offset,
+        length,
+    }): axum::extract::Json<RawStableMemoryRangeRequest>,
+) -> (StatusCode, Json<ApiResponse<StableMemoryRange>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(canister_id) {
+        Ok(canister_id) => {
+            let get_op = GetStableMemoryRange {
+                canister_id,
+                offset,
+                length,
+            };
+            let (code, response) = run_operation(api_state, instance_id, timeout, get_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_get_canister_logs(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path((instance_id, canister_id)): Path<(InstanceId, String)>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<Vec<rest::CanisterLogRecord>>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::from_str(&canister_id) {
+        Ok(canister_id) => {
+            let op = GetCanisterLogs { canister_id };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_get_cycle_consumption(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path((instance_id, canister_id, since_tick)): Path<(InstanceId, String, u64)>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<Vec<CycleConsumptionRecord>>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::from_str(&canister_id) {
+        Ok(canister_id) => {
+            let op = GetCycleConsumption {
+                canister_id,
+                since_tick,
+            };
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_get_subnet(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    axum::extract::Json(raw_canister_id): axum::extract::Json<RawCanisterId>,
+) -> (StatusCode, Json<ApiResponse<Option<RawSubnetId>>>) {
+    let timeout = timeout_or_default(headers);
+    match CanisterId::try_from(raw_canister_id.canister_id) {
+        Ok(canister_id) => {
+            let op = GetSubnet { canister_id };
+            let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(res))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_pub_key(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawSubnetId { subnet_id }): extract::Json<RawSubnetId>,
+) -> (StatusCode, Json<ApiResponse<Vec<u8>>>) {
+    let timeout = timeout_or_default(headers);
     let subnet_id = ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
         &subnet_id,
     )));
@@ -648,6 +1188,78 @@ pub async fn handler_pub_key(
     (code, Json(res))
 }
 
+pub async fn handler_get_scheduled_messages(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawSubnetId { subnet_id }): extract::Json<RawSubnetId>,
+) -> (StatusCode, Json<ApiResponse<Vec<RawScheduledMessage>>>) {
+    let timeout = timeout_or_default(headers);
+    let subnet_id = ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+        &subnet_id,
+    )));
+    let op = GetScheduledMessages { subnet_id };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_get_subnet_metrics(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawSubnetId { subnet_id }): extract::Json<RawSubnetId>,
+) -> (StatusCode, Json<ApiResponse<SubnetMetrics>>) {
+    let timeout = timeout_or_default(headers);
+    let subnet_id = ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+        &subnet_id,
+    )));
+    let op = GetSubnetMetrics { subnet_id };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_get_node_time(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawSubnetNode { subnet_id, node_id }): extract::Json<RawSubnetNode>,
+) -> (StatusCode, Json<ApiResponse<RawTime>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetNodeTime {
+        subnet_id: ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+            &subnet_id.subnet_id,
+        ))),
+        node_id: ic_types::NodeId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+            &node_id.node_id,
+        ))),
+    };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_get_instance_metadata(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawInstanceMetadataKey { key }): extract::Json<RawInstanceMetadataKey>,
+) -> (StatusCode, Json<ApiResponse<Option<String>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetInstanceMetadata { key };
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
+pub async fn handler_get_all_instance_metadata(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+) -> (StatusCode, Json<ApiResponse<BTreeMap<String, String>>>) {
+    let timeout = timeout_or_default(headers);
+    let op = GetAllInstanceMetadata {};
+    let (code, res) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(res))
+}
+
 pub async fn handler_dashboard(
     State(AppState { api_state, .. }): State<AppState>,
     NoApi(Path(instance_id)): NoApi<Path<InstanceId>>,
@@ -752,6 +1364,20 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             .into_response(),
         )
             .into_response(),
+        OpOut::Cancelled => (
+            StatusCode::GONE,
+            Json(ApiResponse::<()>::Error {
+                message: "Cancelled".to_owned(),
+            }),
+        )
+            .into_response(),
+        OpOut::Expired => (
+            StatusCode::GONE,
+            Json(ApiResponse::<()>::Error {
+                message: "Expired".to_owned(),
+            }),
+        )
+            .into_response(),
         opout @ OpOut::MessageId(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(Vec::<u8>::try_from(opout).unwrap())),
@@ -765,6 +1391,9 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
         OpOut::Topology(topology) => {
             (StatusCode::OK, Json(ApiResponse::Success(topology))).into_response()
         }
+        OpOut::TopologyDelta(delta) => {
+            (StatusCode::OK, Json(ApiResponse::Success(delta))).into_response()
+        }
         opout @ OpOut::Time(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(RawTime::try_from(opout).unwrap())),
@@ -789,6 +1418,13 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             Json(ApiResponse::Success(RawCycles::try_from(opout).unwrap())),
         )
             .into_response(),
+        opout @ OpOut::FreezingThreshold(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                RawFreezingThreshold::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         opout @ OpOut::Bytes(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(Vec::<u8>::try_from(opout).unwrap())),
@@ -801,6 +1437,13 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
+        opout @ OpOut::StableMemRange { .. } => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                StableMemoryRange::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
         opout @ OpOut::MaybeSubnetId(_) => (
             StatusCode::OK,
             Json(ApiResponse::Success(
@@ -822,7 +1465,57 @@ async fn op_out_to_response(op_out: OpOut) -> Response {
             )),
         )
             .into_response(),
-        OpOut::RawResponse(fut) => {
+        opout @ OpOut::ScheduledMessages(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                Vec::<RawScheduledMessage>::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
+        OpOut::InstanceMetadataValue(value) => {
+            (StatusCode::OK, Json(ApiResponse::Success(value))).into_response()
+        }
+        OpOut::CanisterHttpSocksProxy(proxy_url) => {
+            (StatusCode::OK, Json(ApiResponse::Success(proxy_url))).into_response()
+        }
+        // The client identity (in particular, its private key) is never returned.
+        OpOut::CanisterHttpClientIdentity(client_identity) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(client_identity.is_some())),
+        )
+            .into_response(),
+        OpOut::Controllers(controllers) => {
+            (StatusCode::OK, Json(ApiResponse::Success(controllers))).into_response()
+        }
+        OpOut::CycleConsumption(records) => {
+            (StatusCode::OK, Json(ApiResponse::Success(records))).into_response()
+        }
+        OpOut::SubnetMetrics(metrics) => {
+            (StatusCode::OK, Json(ApiResponse::Success(metrics))).into_response()
+        }
+        OpOut::IngressStatus(status) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(RawIngressStatus::from(status))),
+        )
+            .into_response(),
+        OpOut::InstanceMetadata(metadata) => {
+            (StatusCode::OK, Json(ApiResponse::Success(metadata))).into_response()
+        }
+        opout @ OpOut::Certificate(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                RawCertificate::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
+        opout @ OpOut::CanisterLogs(_) => (
+            StatusCode::OK,
+            Json(ApiResponse::Success(
+                Vec::<rest::CanisterLogRecord>::try_from(opout).unwrap(),
+            )),
+        )
+            .into_response(),
+        OpOut::RawResponse(RawApiResponse(fut)) => {
             let (status, headers, bytes) = fut.await;
             let code = StatusCode::from_u16(status).unwrap();
             let mut resp = Response::builder().status(code);
@@ -839,16 +1532,40 @@ pub async fn handler_read_graph(
     State(AppState { api_state, .. }): State<AppState>,
     // TODO: type state label and op id correctly but such that axum can handle it
     Path((state_label_str, op_id_str)): Path<(String, String)>,
+) -> Response {
+    read_graph(api_state, state_label_str, op_id_str, None).await
+}
+
+/// Same as [handler_read_graph] except that the request is parked until the result
+/// of the operation is available or the processing timeout (default: [DEFAULT_AWAIT_GRAPH_TIMEOUT]) elapses.
+pub async fn handler_await_graph(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path((state_label_str, op_id_str)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Response {
+    let max_wait = timeout_or_default(headers).unwrap_or(DEFAULT_AWAIT_GRAPH_TIMEOUT);
+    read_graph(api_state, state_label_str, op_id_str, Some(max_wait)).await
+}
+
+async fn read_graph(
+    api_state: Arc<ApiState>,
+    state_label_str: String,
+    op_id_str: String,
+    max_wait: Option<Duration>,
 ) -> Response {
     let Ok(vec) = base64::decode_config(state_label_str.as_bytes(), base64::URL_SAFE) else {
         return (StatusCode::BAD_REQUEST, "malformed state_label").into_response();
     };
     if let Ok(state_label) = StateLabel::try_from(vec) {
         let op_id = OpId(op_id_str.clone());
+        let result = match max_wait {
+            Some(max_wait) => {
+                ApiState::await_result(api_state.get_graph(), &state_label, &op_id, max_wait).await
+            }
+            None => ApiState::read_result(api_state.get_graph(), &state_label, &op_id),
+        };
         // TODO: use new_state_label and return it to library
-        if let Some((_new_state_label, op_out)) =
-            ApiState::read_result(api_state.get_graph(), &state_label, &op_id)
-        {
+        if let Some((_new_state_label, op_out)) = result {
             op_out_to_response(op_out).await
         } else {
             (
@@ -922,6 +1639,28 @@ pub async fn handler_await_ingress_message(
     }
 }
 
+pub async fn handler_ingress_status(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_message_id): extract::Json<RawMessageId>,
+) -> (StatusCode, Json<ApiResponse<RawIngressStatus>>) {
+    let timeout = timeout_or_default(headers);
+    match crate::pocket_ic::MessageId::try_from(raw_message_id) {
+        Ok(message_id) => {
+            let op = GetIngressStatus(message_id);
+            let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 pub async fn handler_execute_ingress_message(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -958,6 +1697,58 @@ pub async fn handler_set_time(
     (code, Json(response))
 }
 
+/// Sets the time to the given absolute timestamp (which must not be in the past)
+/// and executes a round, returning the resulting time.
+pub async fn handler_set_time_and_tick(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    axum::extract::Json(time): axum::extract::Json<rest::RawTime>,
+) -> (StatusCode, Json<ApiResponse<RawTime>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SetTimeAndTick {
+        timestamp_nanos: time.nanos_since_epoch,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_set_node_clock_skew(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawSetNodeClockSkew {
+        subnet_id,
+        node_id,
+        skew_nanos,
+    }): extract::Json<RawSetNodeClockSkew>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SetNodeClockSkew {
+        subnet_id: ic_types::SubnetId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+            &subnet_id.subnet_id,
+        ))),
+        node_id: ic_types::NodeId::new(ic_types::PrincipalId(candid::Principal::from_slice(
+            &node_id.node_id,
+        ))),
+        skew_nanos,
+    };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
+pub async fn handler_set_instance_metadata(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(RawInstanceMetadataEntry { key, value }): extract::Json<RawInstanceMetadataEntry>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    let op = SetInstanceMetadata { key, value };
+    let (code, response) = run_operation(api_state, instance_id, timeout, op).await;
+    (code, Json(response))
+}
+
 pub async fn handler_add_cycles(
     State(AppState { api_state, .. }): State<AppState>,
     Path(instance_id): Path<InstanceId>,
@@ -979,6 +1770,70 @@ pub async fn handler_add_cycles(
     }
 }
 
+pub async fn handler_set_freezing_threshold(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_set_freezing_threshold): extract::Json<RawSetFreezingThreshold>,
+) -> (StatusCode, Json<ApiResponse<RawFreezingThreshold>>) {
+    let timeout = timeout_or_default(headers);
+    match SetFreezingThreshold::try_from(raw_set_freezing_threshold) {
+        Ok(set_op) => {
+            let (code, response) = run_operation(api_state, instance_id, timeout, set_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_set_controllers(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_set_controllers): extract::Json<RawSetControllers>,
+) -> (StatusCode, Json<ApiResponse<Vec<candid::Principal>>>) {
+    let timeout = timeout_or_default(headers);
+    match SetControllers::try_from(raw_set_controllers) {
+        Ok(set_op) => {
+            let (code, response) = run_operation(api_state, instance_id, timeout, set_op).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
+pub async fn handler_inject_fault(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(instance_id): Path<InstanceId>,
+    headers: HeaderMap,
+    extract::Json(raw_fault_injection): extract::Json<RawFaultInjection>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    let timeout = timeout_or_default(headers);
+    match FaultInjection::try_from(raw_fault_injection) {
+        Ok(fault_injection) => {
+            let (code, response) =
+                run_operation(api_state, instance_id, timeout, fault_injection).await;
+            (code, Json(response))
+        }
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::Error {
+                message: format!("{:?}", e),
+            }),
+        ),
+    }
+}
+
 pub async fn handler_set_stable_memory(
     State(AppState {
         api_state,
@@ -1117,19 +1972,32 @@ pub async fn delete_instance(
 }
 
 /// Create a new HTTP gateway instance from a given HTTP gateway configuration.
-/// The new InstanceId and HTTP gateway's port will be returned.
+/// The new InstanceId and HTTP gateway's port will be returned once the HTTP gateway
+/// is ready to serve requests.
 pub async fn create_http_gateway(
     State(AppState { api_state, .. }): State<AppState>,
     extract::Json(http_gateway_config): extract::Json<HttpGatewayConfig>,
 ) -> (StatusCode, Json<rest::CreateHttpGatewayResponse>) {
-    let (instance_id, port) = api_state.create_http_gateway(http_gateway_config).await;
-    (
-        StatusCode::CREATED,
-        Json(rest::CreateHttpGatewayResponse::Created(HttpGatewayInfo {
-            instance_id,
-            port,
-        })),
-    )
+    match api_state.create_http_gateway(http_gateway_config).await {
+        Ok((instance_id, port)) => (
+            StatusCode::CREATED,
+            Json(rest::CreateHttpGatewayResponse::Created(HttpGatewayInfo {
+                instance_id,
+                port,
+            })),
+        ),
+        Err(message) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(rest::CreateHttpGatewayResponse::Error { message }),
+        ),
+    }
+}
+
+/// Lists all HTTP gateways, indexed by their ids.
+pub async fn list_http_gateways(
+    State(AppState { api_state, .. }): State<AppState>,
+) -> Json<Vec<HttpGatewayDetails>> {
+    Json(api_state.list_http_gateways().await)
 }
 
 /// Stops an HTTP gateway instance.
@@ -1141,14 +2009,83 @@ pub async fn stop_http_gateway(
     (StatusCode::OK, Json(ApiResponse::Success(())))
 }
 
+/// Returns the details of an HTTP gateway instance.
+pub async fn get_http_gateway(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<HttpGatewayDetails>>) {
+    match api_state.get_http_gateway(id).await {
+        Some(http_gateway) => (StatusCode::OK, Json(ApiResponse::Success(http_gateway))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::Error {
+                message: format!("HTTP gateway {} not found", id),
+            }),
+        ),
+    }
+}
+
+/// Returns the status of an HTTP gateway instance.
+pub async fn http_gateway_status(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<GatewayStatus>>) {
+    match api_state.http_gateway_status(id).await {
+        Some(status) => (StatusCode::OK, Json(ApiResponse::Success(status))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::Error {
+                message: format!("HTTP gateway {} not found", id),
+            }),
+        ),
+    }
+}
+
+/// The config is optional so that clients can keep making requests without a body.
 pub async fn auto_progress(
     State(AppState { api_state, .. }): State<AppState>,
     Path(id): Path<InstanceId>,
+    config: Option<extract::Json<AutoProgressConfig>>,
 ) -> (StatusCode, Json<ApiResponse<()>>) {
-    api_state.auto_progress(id).await;
+    let config = config
+        .map(|extract::Json(config)| config)
+        .unwrap_or_default();
+    api_state.auto_progress_with_config(id, config).await;
     (StatusCode::OK, Json(ApiResponse::Success(())))
 }
 
+/// Returns the status of the thread making an instance progress automatically.
+pub async fn progress_status(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<ProgressStatus>>) {
+    match api_state.progress_thread_status(id).await {
+        Some(status) => (StatusCode::OK, Json(ApiResponse::Success(status))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::Error {
+                message: format!("Instance {} does not make progress automatically", id),
+            }),
+        ),
+    }
+}
+
+/// Returns the slowest operations computed on an instance ordered by decreasing duration.
+pub async fn op_timings(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<Vec<OpTiming>>>) {
+    match api_state.op_timings(id).await {
+        Some(op_timings) => (StatusCode::OK, Json(ApiResponse::Success(op_timings))),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::Error {
+                message: format!("Instance {} not found", id),
+            }),
+        ),
+    }
+}
+
 pub async fn stop_progress(
     State(AppState { api_state, .. }): State<AppState>,
     Path(id): Path<InstanceId>,
@@ -1157,6 +2094,53 @@ pub async fn stop_progress(
     (StatusCode::OK, Json(ApiResponse::Success(())))
 }
 
+pub async fn pause_progress(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    api_state.pause_progress(id).await;
+    (StatusCode::OK, Json(ApiResponse::Success(())))
+}
+
+pub async fn resume_progress(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    api_state.resume_progress(id).await;
+    (StatusCode::OK, Json(ApiResponse::Success(())))
+}
+
+pub async fn cancel_operation(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+    extract::Json(raw_op_id): extract::Json<RawOpId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    match api_state.cancel_operation(id, OpId(raw_op_id.op_id)).await {
+        Ok(()) => (StatusCode::OK, Json(ApiResponse::Success(()))),
+        Err(e) => (
+            update_error_status_code(&e),
+            Json(ApiResponse::Error {
+                message: e.to_string(),
+            }),
+        ),
+    }
+}
+
+pub async fn reset_cpu_budget(
+    State(AppState { api_state, .. }): State<AppState>,
+    Path(id): Path<InstanceId>,
+) -> (StatusCode, Json<ApiResponse<()>>) {
+    match api_state.reset_cpu_budget(id).await {
+        Ok(()) => (StatusCode::OK, Json(ApiResponse::Success(()))),
+        Err(e) => (
+            update_error_status_code(&e),
+            Json(ApiResponse::Error {
+                message: e.to_string(),
+            }),
+        ),
+    }
+}
+
 pub trait RouterExt<S>
 where
     S: Clone + Send + Sync + 'static,
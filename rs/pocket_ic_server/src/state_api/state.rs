@@ -2,15 +2,18 @@
 /// Axum handlers operate on a global state of type ApiState, whose
 /// interface guarantees consistency and determinism.
 use crate::pocket_ic::{
-    AdvanceTimeAndTick, ApiResponse, EffectivePrincipal, GetCanisterHttp, MockCanisterHttp,
-    PocketIc,
+    systemtime_to_unix_epoch_nanos, AdvanceTimeAndTick, ApiResponse, CanisterHttpClientIdentity,
+    EffectivePrincipal, GetCanisterHttp, GetCanisterHttpClientIdentity, GetCanisterHttpSocksProxy,
+    MockCanisterHttp, PocketIc, SaveInstance,
 };
 use crate::InstanceId;
 use crate::{OpId, Operation};
 use axum_server::tls_rustls::RustlsConfig;
 use axum_server::Handle;
 use base64;
+use candid::CandidType;
 use futures::future::Shared;
+use futures::stream::{self, StreamExt};
 use hyper::header::{HeaderValue, HOST};
 use hyper::Version;
 use hyper_legacy::{client::connect::HttpConnector, Client};
@@ -24,37 +27,92 @@ use ic_https_outcalls_service::{
     CanisterHttpSendResponse, HttpHeader, HttpMethod,
 };
 use ic_logger::replica_logger::no_op_logger;
+use ic_management_canister_types::CanisterLogRecord;
 use ic_metrics::MetricsRegistry;
 use ic_state_machine_tests::RejectCode;
 use ic_types::canister_http::CanisterHttpRequestId;
-use ic_types::{canister_http::MAX_CANISTER_HTTP_RESPONSE_BYTES, CanisterId, SubnetId};
+use ic_types::{canister_http::MAX_CANISTER_HTTP_RESPONSE_BYTES, CanisterId, NodeId, SubnetId};
 use pocket_ic::common::rest::{
-    CanisterHttpHeader, CanisterHttpMethod, CanisterHttpReject, CanisterHttpReply,
-    CanisterHttpRequest, CanisterHttpResponse, HttpGatewayBackend, HttpGatewayConfig,
-    MockCanisterHttpResponse, Topology,
+    AutoProgressConfig, CanisterHttpHeader, CanisterHttpMethod, CanisterHttpReject,
+    CanisterHttpReply, CanisterHttpRequest, CanisterHttpResponse, CycleConsumptionRecord,
+    ExtendedSubnetConfigSet, GatewayStatus, HttpGatewayBackend, HttpGatewayConfig,
+    HttpGatewayDetails, MockCanisterHttpResponse, OpTiming, ProgressOperationKind, ProgressStatus,
+    ScheduledMessage, SubnetMetrics, Topology, TopologyDelta,
 };
-use pocket_ic::{ErrorCode, UserError, WasmResult};
+use pocket_ic::{ErrorCode, IngressStatus, UserError, WasmResult};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
 use tokio::{
+    runtime::Runtime,
     sync::mpsc::error::TryRecvError,
     sync::mpsc::Receiver,
-    sync::{mpsc, Mutex, RwLock},
+    sync::{mpsc, oneshot, watch, Mutex, Notify, RwLock},
     task::{spawn, spawn_blocking, JoinHandle},
     time::{self, sleep, Instant},
 };
 use tonic::Request;
-use tracing::{error, info, trace};
+use tracing::{debug, error, info, trace};
 
 // The maximum wait time for a computation to finish synchronously.
 const DEFAULT_SYNC_WAIT_DURATION: Duration = Duration::from_secs(10);
 
-// The timeout for executing an operation in auto progress mode.
-const AUTO_PROGRESS_OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
-// The minimum delay between consecutive attempts to run an operation in auto progress mode.
-const MIN_OPERATION_DELAY: Duration = Duration::from_millis(100);
-// The minimum delay between consecutive attempts to read the graph in auto progress mode.
-const READ_GRAPH_DELAY: Duration = Duration::from_millis(100);
+// The default timeout for executing an operation in auto progress mode.
+const DEFAULT_AUTO_PROGRESS_OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
+// The default minimum delay between consecutive attempts to run an operation in auto progress mode.
+const DEFAULT_MIN_OPERATION_DELAY: Duration = Duration::from_millis(100);
+// The default maximum time to await a result in the graph before checking for a stop signal in auto progress mode.
+const DEFAULT_READ_GRAPH_DELAY: Duration = Duration::from_millis(100);
+/// Saving a snapshot copies the state of all subnets and can thus take a while.
+const SAVE_INSTANCE_TIMEOUT: Duration = Duration::from_secs(300);
+/// Default maximum number of canister HTTP outcalls made concurrently in auto progress mode.
+const DEFAULT_CANISTER_HTTP_CONCURRENCY: usize = 16;
+/// Maximum duration for an HTTP gateway to become ready to serve requests.
+const HTTP_GATEWAY_READY_TIMEOUT: Duration = Duration::from_secs(30);
+/// Minimum size of a response of the HTTP gateway's API routes to be gzip-compressed
+/// (if the client accepts gzip-encoded responses).
+const HTTP_GATEWAY_COMPRESSION_THRESHOLD_BYTES: u16 = 1024;
+/// Default grace period for in-flight requests to complete when an HTTP gateway is stopped.
+const DEFAULT_HTTP_GATEWAY_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(5);
+/// Additional time (beyond the grace period) for a stopped HTTP gateway to release its port
+/// before stopping the HTTP gateway returns anyway.
+const HTTP_GATEWAY_SHUTDOWN_TIMEOUT_MARGIN: Duration = Duration::from_secs(5);
+/// Request headers forwarded by an HTTP gateway to its backend on API requests by default.
+const DEFAULT_HTTP_GATEWAY_FORWARDED_HEADERS: &[&str] = &[
+    "traceparent",
+    "tracestate",
+    "baggage",
+    "x-request-id",
+    "x-correlation-id",
+    "idempotency-key",
+    "user-agent",
+];
+/// Request headers never forwarded by an HTTP gateway to its backend:
+/// hop-by-hop headers and headers managed by the HTTP gateway itself.
+const HTTP_GATEWAY_NON_FORWARDABLE_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+    "content-type",
+    "content-length",
+    "content-encoding",
+    "accept-encoding",
+];
 
 pub const STATE_LABEL_HASH_SIZE: usize = 32;
 
@@ -91,20 +149,88 @@ impl std::convert::TryFrom<Vec<u8>> for StateLabel {
 struct ProgressThread {
     handle: JoinHandle<()>,
     sender: mpsc::Sender<()>,
+    // the thread parks (without terminating) at the beginning of its next iteration while set
+    paused: watch::Sender<bool>,
+    // updated by the thread whenever it starts executing an operation
+    status: Arc<std::sync::Mutex<ProgressStatus>>,
+    // the config the thread was started with
+    config: AutoProgressConfig,
+}
+
+fn set_progress_operation(
+    status: &std::sync::Mutex<ProgressStatus>,
+    operation: ProgressOperationKind,
+) {
+    let mut status = status.lock().unwrap();
+    status.last_operation = operation;
+    status.last_operation_started_at = SystemTime::now();
+}
+
+/// An HTTP gateway managed by the PocketIC server.
+struct HttpGateway {
+    details: HttpGatewayDetails,
+    // Grace period for in-flight requests to complete when the HTTP gateway is stopped.
+    shutdown_grace_period: Duration,
+    // Notified to shut the HTTP gateway down.
+    shutdown: Arc<Notify>,
+    // Set to `true` (or closed) once the HTTP gateway has terminated and released its port.
+    terminated: watch::Receiver<bool>,
 }
 
 /// The state of the PocketIC API.
 pub struct ApiState {
     // impl note: If locks are acquired on both fields, acquire first on instances, then on graph.
     instances: Arc<RwLock<Vec<Mutex<InstanceState>>>>,
-    graph: Arc<RwLock<HashMap<StateLabel, Computations>>>,
+    graph: Arc<RwLock<Graph>>,
     // threads making IC instances progress automatically
     progress_threads: RwLock<Vec<Mutex<Option<ProgressThread>>>>,
     sync_wait_time: Duration,
     // PocketIC server port
     port: Option<u16>,
-    // status of HTTP gateway (true = running, false = stopped)
-    http_gateways: Arc<RwLock<Vec<bool>>>,
+    // HTTP gateways indexed by their id; stopped and failed gateways are retained
+    // so that the ids of the remaining gateways do not change
+    http_gateways: Arc<RwLock<Vec<HttpGateway>>>,
+    // wall-clock time budget for computing operations on every instance
+    instance_cpu_budget: Option<Duration>,
+    // maximum number of operations queued up on a busy instance
+    max_queue_depth: usize,
+    // timeout and retry policy for canister HTTP outcalls made in auto progress mode
+    canister_http_policy: CanisterHttpPolicy,
+    // whether the cycles balances of canisters are recorded on every instance
+    record_cycle_history: bool,
+    auto_progress_timings: AutoProgressTimings,
+}
+
+/// Timing of the auto progress mode (see [PocketIcApiStateBuilder] for the individual settings).
+#[derive(Clone, Copy, Debug)]
+struct AutoProgressTimings {
+    operation_timeout: Duration,
+    min_operation_delay: Duration,
+    read_graph_delay: Duration,
+}
+
+/// Timeout, retry, and concurrency policy for canister HTTP outcalls made by the PocketIC server
+/// in auto progress mode. A round of auto progress only completes once all outcalls are processed
+/// so that a hanging outcall delays the instance unless a timeout is specified.
+#[derive(Clone, Copy, Debug)]
+pub struct CanisterHttpPolicy {
+    /// Timeout for an outcall including all its retries. An outcall exceeding the timeout
+    /// is rejected with reject code `SysTransient`. No timeout by default.
+    pub total_timeout: Option<Duration>,
+    /// Maximum number of times a failed idempotent (GET and HEAD) outcall is retried.
+    pub max_retries: u32,
+    /// Maximum number of outcalls made concurrently.
+    pub max_concurrency: usize,
+}
+
+impl Default for CanisterHttpPolicy {
+    fn default() -> Self {
+        Self {
+            total_timeout: None,
+            max_retries: 0,
+            max_concurrency: DEFAULT_CANISTER_HTTP_CONCURRENCY,
+        }
+    }
 }
 
 #[derive(Default)]
@@ -112,6 +238,14 @@ pub struct PocketIcApiStateBuilder {
     initial_instances: Vec<PocketIc>,
     sync_wait_time: Option<Duration>,
     port: Option<u16>,
+    instance_cpu_budget: Option<Duration>,
+    max_queue_depth: usize,
+    canister_http_policy: CanisterHttpPolicy,
+    record_cycle_history: bool,
+    auto_progress_operation_timeout: Option<Duration>,
+    min_operation_delay: Option<Duration>,
+    read_graph_delay: Option<Duration>,
+    graph_capacity: Option<usize>,
 }
 
 impl PocketIcApiStateBuilder {
@@ -128,6 +262,47 @@ impl PocketIcApiStateBuilder {
         }
     }
 
+    /// In auto progress mode, an operation (e.g., executing a round) that takes longer than
+    /// [auto_progress_operation_timeout] is computed in the background and its result is polled.
+    pub fn with_auto_progress_operation_timeout(
+        self,
+        auto_progress_operation_timeout: Duration,
+    ) -> Self {
+        Self {
+            auto_progress_operation_timeout: Some(auto_progress_operation_timeout),
+            ..self
+        }
+    }
+
+    /// In auto progress mode, rounds are executed at most once per [min_operation_delay].
+    pub fn with_min_operation_delay(self, min_operation_delay: Duration) -> Self {
+        Self {
+            min_operation_delay: Some(min_operation_delay),
+            ..self
+        }
+    }
+
+    /// In auto progress mode, the result of an operation computed in the background
+    /// is awaited for at most [read_graph_delay] before checking for a stop signal.
+    pub fn with_read_graph_delay(self, read_graph_delay: Duration) -> Self {
+        Self {
+            read_graph_delay: Some(read_graph_delay),
+            ..self
+        }
+    }
+
+    /// The graph keeps the results of operations for at most [graph_capacity] state labels.
+    /// Beyond that, the state labels updated least recently are evicted
+    /// (by default, the graph grows unbounded). A capacity of zero is allowed:
+    /// then only the state labels of running and queued operations and of operations
+    /// whose result is still awaited synchronously are kept.
+    pub fn with_graph_capacity(self, graph_capacity: usize) -> Self {
+        Self {
+            graph_capacity: Some(graph_capacity),
+            ..self
+        }
+    }
+
     pub fn with_port(self, port: u16) -> Self {
         Self {
             port: Some(port),
@@ -135,24 +310,80 @@ impl PocketIcApiStateBuilder {
         }
     }
 
+    /// Once the cumulative wall-clock time spent computing operations on an instance
+    /// exceeds [instance_cpu_budget], further operations on that instance are refused
+    /// with [PocketIcError::CpuBudgetExhausted] until the budget is reset.
+    pub fn with_instance_cpu_budget(self, instance_cpu_budget: Duration) -> Self {
+        Self {
+            instance_cpu_budget: Some(instance_cpu_budget),
+            ..self
+        }
+    }
+
+    /// Operations submitted against a busy instance are queued up (up to [max_queue_depth]
+    /// operations per instance) and executed in submission order instead of returning
+    /// [UpdateReply::Busy]. By default, no operations are queued up.
+    pub fn with_max_queue_depth(self, max_queue_depth: usize) -> Self {
+        Self {
+            max_queue_depth,
+            ..self
+        }
+    }
+
+    /// Applies the given timeout and retry policy to canister HTTP outcalls
+    /// made in auto progress mode.
+    pub fn with_canister_http_policy(self, canister_http_policy: CanisterHttpPolicy) -> Self {
+        Self {
+            canister_http_policy,
+            ..self
+        }
+    }
+
+    /// Records the cycles balances of all canisters after every tick on every instance
+    /// so that they can be retrieved by the operation [GetCycleConsumption].
+    /// Not recorded by default to avoid the overhead.
+    pub fn with_cycle_history(self) -> Self {
+        Self {
+            record_cycle_history: true,
+            ..self
+        }
+    }
+
     /// Will make the given instance available in the initial state.
     pub fn add_initial_instance(mut self, instance: PocketIc) -> Self {
         self.initial_instances.push(instance);
         self
     }
 
+    /// Will make the instance saved in the given directory by [ApiState::save_instance]
+    /// available in the initial state. Fails if the directory does not contain a snapshot
+    /// compatible with this server.
+    pub fn add_instance_from_dir(
+        self,
+        runtime: Arc<Runtime>,
+        path: PathBuf,
+    ) -> Result<Self, String> {
+        let instance = PocketIc::from_snapshot(runtime, &path)?;
+        Ok(self.add_initial_instance(instance))
+    }
+
     pub fn build(self) -> Arc<ApiState> {
-        let graph: HashMap<StateLabel, Computations> = self
-            .initial_instances
-            .iter()
-            .map(|i| (i.get_state_label(), Computations::default()))
-            .collect();
+        let mut graph = Graph::new(self.graph_capacity);
+        for instance in &self.initial_instances {
+            graph.computations_mut(&instance.get_state_label());
+        }
         let graph = RwLock::new(graph);
 
         let instances: Vec<_> = self
             .initial_instances
             .into_iter()
-            .map(|inst| Mutex::new(InstanceState::Available(inst)))
+            .map(|mut inst| {
+                inst.set_cpu_budget(self.instance_cpu_budget);
+                if self.record_cycle_history {
+                    inst.enable_cycle_history();
+                }
+                Mutex::new(InstanceState::Available(inst))
+            })
             .collect();
         let instances_len = instances.len();
         let instances = RwLock::new(instances);
@@ -160,6 +391,15 @@ impl PocketIcApiStateBuilder {
         let progress_threads = RwLock::new((0..instances_len).map(|_| Mutex::new(None)).collect());
 
         let sync_wait_time = self.sync_wait_time.unwrap_or(DEFAULT_SYNC_WAIT_DURATION);
+        let auto_progress_timings = AutoProgressTimings {
+            operation_timeout: self
+                .auto_progress_operation_timeout
+                .unwrap_or(DEFAULT_AUTO_PROGRESS_OPERATION_TIMEOUT),
+            min_operation_delay: self
+                .min_operation_delay
+                .unwrap_or(DEFAULT_MIN_OPERATION_DELAY),
+            read_graph_delay: self.read_graph_delay.unwrap_or(DEFAULT_READ_GRAPH_DELAY),
+        };
 
         Arc::new(ApiState {
             instances: instances.into(),
@@ -168,35 +408,92 @@ impl PocketIcApiStateBuilder {
             sync_wait_time,
             port: self.port,
             http_gateways: Arc::new(RwLock::new(Vec::new())),
+            instance_cpu_budget: self.instance_cpu_budget,
+            max_queue_depth: self.max_queue_depth,
+            canister_http_policy: self.canister_http_policy,
+            record_cycle_history: self.record_cycle_history,
+            auto_progress_timings,
         })
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, CandidType)]
 pub enum OpOut {
     NoOutput,
     Time(u64),
     CanisterResult(Result<WasmResult, UserError>),
     CanisterId(CanisterId),
     Cycles(u128),
+    FreezingThreshold(u64),
+    Controllers(Vec<candid::Principal>),
     Bytes(Vec<u8>),
     StableMemBytes(Vec<u8>),
+    StableMemRange { total_size: u64, bytes: Vec<u8> },
     MaybeSubnetId(Option<SubnetId>),
     Error(PocketIcError),
-    RawResponse(Shared<ApiResponse>),
+    RawResponse(RawApiResponse),
     Pruned,
+    Cancelled,
+    Expired,
     MessageId((EffectivePrincipal, Vec<u8>)),
     Topology(Topology),
+    TopologyDelta(TopologyDelta),
     CanisterHttp(Vec<CanisterHttpRequest>),
+    ScheduledMessages(Vec<ScheduledMessage>),
+    InstanceMetadataValue(Option<String>),
+    InstanceMetadata(BTreeMap<String, String>),
+    CanisterLogs(Vec<CanisterLogRecord>),
+    CanisterHttpSocksProxy(Option<String>),
+    CanisterHttpClientIdentity(Option<Arc<CanisterHttpClientIdentity>>),
+    CycleConsumption(Vec<CycleConsumptionRecord>),
+    SubnetMetrics(SubnetMetrics),
+    IngressStatus(IngressStatus),
+    Certificate(Vec<u8>),
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, CandidType)]
 pub enum PocketIcError {
     CanisterNotFound(CanisterId),
     BadIngressMessage(String),
     SubnetNotFound(candid::Principal),
     RequestRoutingError(String),
     InvalidCanisterHttpRequestId((SubnetId, CanisterHttpRequestId)),
+    NodeNotFound((SubnetId, NodeId)),
+    InstanceMetadataLimitExceeded(String),
+    CpuBudgetExhausted(Duration),
+    InstanceSnapshotError(String),
+    InvalidCanisterHttpSocksProxy(String),
+    InvalidCanisterHttpClientIdentity(String),
+    CycleHistoryDisabled,
+    TopologyNotFound(String),
+    TimeMovesBackwards((u64, u64)),
+    InvalidXNetPolicy(String),
+    StateCertificateError(String),
+}
+
+/// A raw API response which might still be pending. Its Candid representation
+/// is the status code, headers, and body of the response, or `None` if the response
+/// is not ready yet.
+#[derive(Clone)]
+pub struct RawApiResponse(pub Shared<ApiResponse>);
+
+type CandidRawApiResponse = Option<(u16, Vec<(String, String)>, Vec<u8>)>;
+
+impl CandidType for RawApiResponse {
+    fn _ty() -> candid::types::Type {
+        CandidRawApiResponse::ty()
+    }
+
+    fn idl_serialize<S: candid::types::Serializer>(&self, serializer: S) -> Result<(), S::Error> {
+        let response: CandidRawApiResponse = self.0.peek().map(|(status, headers, body)| {
+            let headers = headers
+                .iter()
+                .map(|(name, value)| (name.clone(), String::from_utf8_lossy(value).into_owned()))
+                .collect();
+            (*status, headers, body.clone())
+        });
+        response.idl_serialize(serializer)
+    }
 }
 
 impl From<Result<ic_state_machine_tests::WasmResult, ic_state_machine_tests::UserError>> for OpOut {
@@ -239,8 +536,11 @@ impl std::fmt::Debug for OpOut {
             OpOut::NoOutput => write!(f, "NoOutput"),
             OpOut::Time(x) => write!(f, "Time({})", x),
             OpOut::Topology(t) => write!(f, "Topology({:?})", t),
+            OpOut::Controllers(controllers) => write!(f, "Controllers({:?})", controllers),
+            OpOut::TopologyDelta(delta) => write!(f, "TopologyDelta({:?})", delta),
             OpOut::CanisterId(cid) => write!(f, "CanisterId({})", cid),
             OpOut::Cycles(x) => write!(f, "Cycles({})", x),
+            OpOut::FreezingThreshold(x) => write!(f, "FreezingThreshold({})", x),
             OpOut::CanisterResult(Ok(x)) => write!(f, "CanisterResult: Ok({:?})", x),
             OpOut::CanisterResult(Err(x)) => write!(f, "CanisterResult: Err({})", x),
             OpOut::Error(PocketIcError::CanisterNotFound(cid)) => {
@@ -265,11 +565,54 @@ impl std::fmt::Debug for OpOut {
                     subnet_id, canister_http_request_id
                 )
             }
+            OpOut::Error(PocketIcError::NodeNotFound((subnet_id, node_id))) => {
+                write!(f, "NodeNotFound({},{})", subnet_id, node_id)
+            }
+            OpOut::Error(PocketIcError::InstanceMetadataLimitExceeded(msg)) => {
+                write!(f, "InstanceMetadataLimitExceeded({})", msg)
+            }
+            OpOut::Error(PocketIcError::CpuBudgetExhausted(cpu_time_used)) => {
+                write!(f, "CpuBudgetExhausted({:?})", cpu_time_used)
+            }
+            OpOut::Error(PocketIcError::InstanceSnapshotError(msg)) => {
+                write!(f, "InstanceSnapshotError({})", msg)
+            }
+            OpOut::Error(PocketIcError::InvalidCanisterHttpSocksProxy(msg)) => {
+                write!(f, "InvalidCanisterHttpSocksProxy({})", msg)
+            }
+            OpOut::Error(PocketIcError::InvalidCanisterHttpClientIdentity(msg)) => {
+                write!(f, "InvalidCanisterHttpClientIdentity({})", msg)
+            }
+            OpOut::Error(PocketIcError::CycleHistoryDisabled) => {
+                write!(f, "CycleHistoryDisabled")
+            }
+            OpOut::Error(PocketIcError::TopologyNotFound(topology_hash)) => {
+                write!(f, "TopologyNotFound({})", topology_hash)
+            }
+            OpOut::Error(PocketIcError::TimeMovesBackwards((current_nanos, requested_nanos))) => {
+                write!(
+                    f,
+                    "TimeMovesBackwards({},{})",
+                    current_nanos, requested_nanos
+                )
+            }
+            OpOut::Error(PocketIcError::InvalidXNetPolicy(msg)) => {
+                write!(f, "InvalidXNetPolicy({})", msg)
+            }
+            OpOut::Error(PocketIcError::StateCertificateError(msg)) => {
+                write!(f, "StateCertificateError({})", msg)
+            }
             OpOut::Bytes(bytes) => write!(f, "Bytes({})", base64::encode(bytes)),
             OpOut::StableMemBytes(bytes) => write!(f, "StableMemory({})", base64::encode(bytes)),
+            OpOut::StableMemRange { total_size, bytes } => write!(
+                f,
+                "StableMemoryRange({},{})",
+                total_size,
+                base64::encode(bytes)
+            ),
             OpOut::MaybeSubnetId(Some(subnet_id)) => write!(f, "SubnetId({})", subnet_id),
             OpOut::MaybeSubnetId(None) => write!(f, "NoSubnetId"),
-            OpOut::RawResponse(fut) => {
+            OpOut::RawResponse(RawApiResponse(fut)) => {
                 write!(
                     f,
                     "ApiResp({:?})",
@@ -282,6 +625,8 @@ impl std::fmt::Debug for OpOut {
                 )
             }
             OpOut::Pruned => write!(f, "Pruned"),
+            OpOut::Cancelled => write!(f, "Cancelled"),
+            OpOut::Expired => write!(f, "Expired"),
             OpOut::MessageId((effective_principal, message_id)) => {
                 write!(
                     f,
@@ -293,29 +638,335 @@ impl std::fmt::Debug for OpOut {
             OpOut::CanisterHttp(canister_http_reqeusts) => {
                 write!(f, "CanisterHttp({:?})", canister_http_reqeusts)
             }
+            OpOut::ScheduledMessages(scheduled_messages) => {
+                write!(f, "ScheduledMessages({:?})", scheduled_messages)
+            }
+            OpOut::InstanceMetadataValue(value) => write!(f, "InstanceMetadataValue({:?})", value),
+            OpOut::CanisterHttpSocksProxy(proxy_url) => {
+                write!(f, "CanisterHttpSocksProxy({:?})", proxy_url)
+            }
+            OpOut::CanisterHttpClientIdentity(client_identity) => {
+                write!(f, "CanisterHttpClientIdentity({:?})", client_identity)
+            }
+            OpOut::CycleConsumption(records) => write!(f, "CycleConsumption({:?})", records),
+            OpOut::SubnetMetrics(metrics) => write!(f, "SubnetMetrics({:?})", metrics),
+            OpOut::IngressStatus(status) => write!(f, "IngressStatus({:?})", status),
+            OpOut::Certificate(certificate) => {
+                write!(f, "Certificate({})", base64::encode(certificate))
+            }
+            OpOut::InstanceMetadata(metadata) => write!(f, "InstanceMetadata({:?})", metadata),
+            OpOut::CanisterLogs(records) => {
+                write!(f, "CanisterLogs(")?;
+                for record in records {
+                    write!(
+                        f,
+                        "[{}@{}:{}]",
+                        record.idx,
+                        record.timestamp_nanos,
+                        base64::encode(&record.content)
+                    )?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
 
-pub type Computations = HashMap<OpId, (StateLabel, OpOut)>;
+impl OpOut {
+    /// Renders this output like its `Debug` impl, except that the reply of a
+    /// successful canister call is decoded as Candid: against `idl` if it is
+    /// given and on a best-effort basis (using the types in the message)
+    /// otherwise. Falls back to the `Debug` output if decoding fails.
+    pub fn describe(&self, idl: Option<&candid::types::Type>) -> String {
+        if let OpOut::CanisterResult(Ok(WasmResult::Reply(bytes))) = self {
+            let decoded = match idl {
+                Some(ty) => candid::IDLArgs::from_bytes_with_types(
+                    bytes,
+                    &candid::types::TypeEnv::new(),
+                    &[ty.clone()],
+                ),
+                None => candid::IDLArgs::from_bytes(bytes),
+            };
+            if let Ok(args) = decoded {
+                return format!("CanisterResult: Ok(Reply({}))", args);
+            }
+        }
+        format!("{:?}", self)
+    }
+}
+
+// The new state label, the result, and the wall-clock time it took to compute the result.
+pub type Computations = HashMap<OpId, (StateLabel, OpOut, Duration)>;
+
+// The maximum number of slowest operations retained per instance.
+const MAX_OP_TIMINGS: usize = 100;
+
+// The maximum number of evicted state labels remembered by the graph.
+const MAX_EVICTED_STATE_LABELS: usize = 10_000;
+
+/// The results of operations keyed by the state label they were computed on.
+/// If a capacity is set, the state labels updated least recently are evicted
+/// once the capacity is exceeded. Polling for a result on one of the
+/// [MAX_EVICTED_STATE_LABELS] state labels evicted most recently yields [OpOut::Expired];
+/// state labels evicted before are forgotten and polling on them yields no result.
+#[derive(Default)]
+pub struct Graph {
+    // for every state label, the stamp of its last update and its computations
+    computations: HashMap<StateLabel, (u64, Computations)>,
+    // state labels ordered by the stamp of their last update
+    recency: BTreeMap<u64, StateLabel>,
+    next_stamp: u64,
+    capacity: Option<usize>,
+    // the state labels evicted most recently (bounded by [MAX_EVICTED_STATE_LABELS])
+    // in the order of their eviction
+    evicted: HashSet<StateLabel>,
+    eviction_order: VecDeque<StateLabel>,
+    // state labels that are never evicted because a client awaits the result
+    // of an operation computed on them synchronously (with the number of such clients)
+    pinned: HashMap<StateLabel, usize>,
+    // notified once the result of the operation is added to the graph (or expires)
+    waiters: HashMap<(StateLabel, OpId), Arc<Notify>>,
+    // for every instance, its slowest operations ordered by decreasing duration
+    op_timings: HashMap<InstanceId, Vec<OpTiming>>,
+}
+
+impl Graph {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the computations on the given state label (inserting them if missing)
+    /// and marks the state label as updated most recently.
+    fn computations_mut(&mut self, state_label: &StateLabel) -> &mut Computations {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+        if self.evicted.remove(state_label) {
+            self.eviction_order
+                .retain(|evicted_state_label| evicted_state_label != state_label);
+        }
+        let (last_stamp, computations) = self
+            .computations
+            .entry(state_label.clone())
+            .or_insert_with(|| (stamp, Computations::default()));
+        self.recency.remove(last_stamp);
+        *last_stamp = stamp;
+        self.recency.insert(stamp, state_label.clone());
+        computations
+    }
+
+    /// Adds the result of an operation computed on the given state label
+    /// and wakes up all clients awaiting it.
+    fn insert(
+        &mut self,
+        state_label: &StateLabel,
+        op_id: OpId,
+        result: (StateLabel, OpOut, Duration),
+    ) {
+        self.computations_mut(state_label)
+            .insert(op_id.clone(), result);
+        if let Some(notify) = self.waiters.remove(&(state_label.clone(), op_id)) {
+            notify.notify_waiters();
+        }
+    }
+
+    /// Returns the waker notified once the result of the given operation is added to the graph.
+    fn waiter(&mut self, state_label: &StateLabel, op_id: &OpId) -> Arc<Notify> {
+        self.waiters
+            .entry((state_label.clone(), op_id.clone()))
+            .or_default()
+            .clone()
+    }
+
+    /// Pins the given state label so that it is not evicted until [Self::unpin] is called.
+    fn pin(&mut self, state_label: &StateLabel) {
+        *self.pinned.entry(state_label.clone()).or_default() += 1;
+    }
+
+    fn unpin(&mut self, state_label: &StateLabel) {
+        if let Some(count) = self.pinned.get_mut(state_label) {
+            *count -= 1;
+            if *count == 0 {
+                self.pinned.remove(state_label);
+            }
+        }
+    }
+
+    /// Replaces the result of an operation that has already been returned to the client
+    /// by [OpOut::Pruned]. Unlike [Self::insert], this does not mark the state label
+    /// as updated and does nothing if the state label has been evicted in the meantime.
+    fn prune(&mut self, state_label: &StateLabel, op_id: &OpId) {
+        if let Some((_, op_out, _)) = self
+            .computations
+            .get_mut(state_label)
+            .and_then(|(_, computations)| computations.get_mut(op_id))
+        {
+            *op_out = OpOut::Pruned;
+        }
+    }
+
+    fn get(&self, state_label: &StateLabel, op_id: &OpId) -> Option<(StateLabel, OpOut)> {
+        self.get_with_timing(state_label, op_id)
+            .map(|(new_state_label, op_out, _)| (new_state_label, op_out))
+    }
+
+    fn get_with_timing(
+        &self,
+        state_label: &StateLabel,
+        op_id: &OpId,
+    ) -> Option<(StateLabel, OpOut, Duration)> {
+        if self.evicted.contains(state_label) {
+            return Some((state_label.clone(), OpOut::Expired, Duration::ZERO));
+        }
+        self.computations.get(state_label)?.1.get(op_id).cloned()
+    }
+
+    /// Records the wall-clock time it took to compute an operation on the given instance
+    /// if it is among the slowest operations computed on that instance.
+    fn record_op_timing(&mut self, instance_id: InstanceId, op_id: &OpId, duration: Duration) {
+        let op_timings = self.op_timings.entry(instance_id).or_default();
+        if op_timings.len() == MAX_OP_TIMINGS
+            && op_timings
+                .last()
+                .is_some_and(|slowest| slowest.duration >= duration)
+        {
+            return;
+        }
+        let pos = op_timings.partition_point(|op_timing| op_timing.duration >= duration);
+        op_timings.insert(
+            pos,
+            OpTiming {
+                op_id: op_id.0.clone(),
+                duration,
+            },
+        );
+        op_timings.truncate(MAX_OP_TIMINGS);
+    }
+
+    fn needs_eviction(&self) -> bool {
+        self.capacity
+            .is_some_and(|capacity| self.computations.len() > capacity)
+    }
+
+    /// Evicts the state labels updated least recently until the capacity is no longer exceeded.
+    /// The given state labels and the state labels pinned by [Self::pin] are never evicted.
+    fn evict(&mut self, pinned: &HashSet<StateLabel>) {
+        let Some(capacity) = self.capacity else {
+            return;
+        };
+        let excess = self.computations.len().saturating_sub(capacity);
+        let victims: Vec<_> = self
+            .recency
+            .iter()
+            .filter(|(_, state_label)| {
+                !pinned.contains(*state_label) && !self.pinned.contains_key(*state_label)
+            })
+            .take(excess)
+            .map(|(stamp, state_label)| (*stamp, state_label.clone()))
+            .collect();
+        let mut evicted_now = HashSet::new();
+        for (stamp, state_label) in victims {
+            self.recency.remove(&stamp);
+            self.computations.remove(&state_label);
+            self.evicted.insert(state_label.clone());
+            self.eviction_order.push_back(state_label.clone());
+            evicted_now.insert(state_label);
+        }
+        while self.eviction_order.len() > MAX_EVICTED_STATE_LABELS {
+            let forgotten = self.eviction_order.pop_front().unwrap();
+            self.evicted.remove(&forgotten);
+        }
+        // Clients awaiting a result on an evicted state label are woken up to observe it as expired.
+        self.waiters.retain(|(state_label, _), notify| {
+            if evicted_now.contains(state_label) {
+                notify.notify_waiters();
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&StateLabel, &Computations)> {
+        self.computations
+            .iter()
+            .map(|(state_label, (_, computations))| (state_label, computations))
+    }
+}
 
 /// The PocketIcApiState has a vector with elements of InstanceState.
 /// When an operation is bound to an instance, the corresponding element in the
 /// vector is replaced by a Busy variant which contains information about the
-/// computation that is currently running. Afterwards, the instance is put back as
-/// Available.
+/// computation that is currently running and the operations queued up behind it.
+/// Once the queue is drained, the instance is put back as Available.
 pub enum InstanceState {
     Busy {
         state_label: StateLabel,
         op_id: OpId,
+        // set to cancel the running operation
+        cancelled: Arc<AtomicBool>,
+        queue: VecDeque<QueuedOperation>,
+        // read-only view of the instance on which read-only operations are computed
+        // (see [Operation::is_read_only]); not isolated from the running operation
+        read_only_view: Arc<std::sync::Mutex<PocketIc>>,
     },
     Available(PocketIc),
     Deleted,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UpdateError {
-    message: String,
+/// A task computing an operation on an instance in the background
+/// and returning its result and the state label it was computed on.
+type BackgroundTask = Box<dyn FnOnce() -> (OpOut, StateLabel) + Send>;
+
+/// An operation waiting for a busy instance to become available.
+pub struct QueuedOperation {
+    // The state label returned to the client in the `Started` reply.
+    // The result is stored in the graph under this state label
+    // so that the client can poll for it.
+    state_label: StateLabel,
+    op_id: OpId,
+    cancelled: Arc<AtomicBool>,
+    compute: Box<dyn FnOnce(&mut PocketIc) -> OpOut + Send>,
+}
+
+impl QueuedOperation {
+    fn new<O>(state_label: StateLabel, op: Arc<O>) -> Self
+    where
+        O: Operation + Send + Sync + 'static,
+    {
+        Self {
+            state_label,
+            op_id: op.id(),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            compute: Box::new(move |pocket_ic| op.compute(pocket_ic)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateError {
+    InstanceNotFound,
+    InstanceDeleted,
+    InstanceBusy,
+    // The instance is busy and no further operations can be queued up.
+    QueueFull,
+    OperationNotFound,
+    Internal(String),
+}
+
+impl std::fmt::Display for UpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateError::InstanceNotFound => write!(f, "Instance not found"),
+            UpdateError::InstanceDeleted => write!(f, "Instance was deleted"),
+            UpdateError::InstanceBusy => write!(f, "Instance is busy"),
+            UpdateError::QueueFull => write!(f, "Instance is busy and its queue is full"),
+            UpdateError::OperationNotFound => write!(f, "Operation not found"),
+            UpdateError::Internal(message) => write!(f, "{}", message),
+        }
+    }
 }
 
 pub type UpdateResult = std::result::Result<UpdateReply, UpdateError>;
@@ -386,9 +1037,10 @@ impl ApiState {
     // or `None` if the auto progress mode received a stop signal.
     async fn execute_operation(
         instances: Arc<RwLock<Vec<Mutex<InstanceState>>>>,
-        graph: Arc<RwLock<HashMap<StateLabel, Computations>>>,
+        graph: Arc<RwLock<Graph>>,
         instance_id: InstanceId,
         op: impl Operation + Send + Sync + 'static,
+        timings: AutoProgressTimings,
         rx: &mut Receiver<()>,
     ) -> Option<OpOut> {
         let op = Arc::new(op);
@@ -401,16 +1053,21 @@ impl ApiState {
                 graph.clone(),
                 op.clone(),
                 instance_id,
-                AUTO_PROGRESS_OPERATION_TIMEOUT,
+                timings.operation_timeout,
+                0,
             )
             .await
             .unwrap()
             {
                 UpdateReply::Started { state_label, op_id } => {
                     break loop {
-                        sleep(READ_GRAPH_DELAY).await;
-                        if let Some((_, op_out)) =
-                            Self::read_result(graph.clone(), &state_label, &op_id)
+                        if let Some((_, op_out)) = Self::await_result(
+                            graph.clone(),
+                            &state_label,
+                            &op_id,
+                            timings.read_graph_delay,
+                        )
+                        .await
                         {
                             break Some(op_out);
                         }
@@ -432,23 +1089,54 @@ impl ApiState {
     /// The client lib dispatches a long running operation and gets a Started {state_label, op_id}.
     /// It then polls on that via this state tree api function.
     pub fn read_result(
-        graph: Arc<RwLock<HashMap<StateLabel, Computations>>>,
+        graph: Arc<RwLock<Graph>>,
         state_label: &StateLabel,
         op_id: &OpId,
     ) -> Option<(StateLabel, OpOut)> {
-        if let Some((new_state_label, op_out)) = graph.try_read().ok()?.get(state_label)?.get(op_id)
-        {
-            Some((new_state_label.clone(), op_out.clone()))
-        } else {
-            None
+        graph.try_read().ok()?.get(state_label, op_id)
+    }
+
+    /// Same as [Self::read_result] except that the wall-clock time it took
+    /// to compute the result is returned, too.
+    pub fn read_result_with_timing(
+        graph: Arc<RwLock<Graph>>,
+        state_label: &StateLabel,
+        op_id: &OpId,
+    ) -> Option<(StateLabel, OpOut, Duration)> {
+        graph.try_read().ok()?.get_with_timing(state_label, op_id)
+    }
+
+    /// For long-polling:
+    /// Same as [Self::read_result] except that the caller is parked until the result
+    /// of the operation is added to the graph or `max_wait` elapses.
+    pub async fn await_result(
+        graph: Arc<RwLock<Graph>>,
+        state_label: &StateLabel,
+        op_id: &OpId,
+        max_wait: Duration,
+    ) -> Option<(StateLabel, OpOut)> {
+        let mut graph_guard = graph.write().await;
+        if let Some(result) = graph_guard.get(state_label, op_id) {
+            return Some(result);
         }
+        let notify = graph_guard.waiter(state_label, op_id);
+        // The future is registered for notifications as soon as it is created,
+        // i.e., before the graph lock is released and the result can be added.
+        let notified = notify.notified();
+        drop(graph_guard);
+        let _ = time::timeout(max_wait, notified).await;
+        graph.read().await.get(state_label, op_id)
     }
 
-    pub fn get_graph(&self) -> Arc<RwLock<HashMap<StateLabel, Computations>>> {
+    pub fn get_graph(&self) -> Arc<RwLock<Graph>> {
         self.graph.clone()
     }
 
-    pub async fn add_instance(&self, instance: PocketIc) -> InstanceId {
+    pub async fn add_instance(&self, mut instance: PocketIc) -> InstanceId {
+        instance.set_cpu_budget(self.instance_cpu_budget);
+        if self.record_cycle_history {
+            instance.enable_cycle_history();
+        }
         let mut instances = self.instances.write().await;
         let mut progress_threads = self.progress_threads.write().await;
         instances.push(Mutex::new(InstanceState::Available(instance)));
@@ -456,6 +1144,22 @@ impl ApiState {
         instances.len() - 1
     }
 
+    /// Builds a new instance with the subnets given by `spec` and makes it available.
+    /// Returns the id of the new instance and its topology.
+    pub async fn create_instance_from_spec(
+        &self,
+        runtime: Arc<Runtime>,
+        spec: ExtendedSubnetConfigSet,
+    ) -> Result<(InstanceId, Topology), String> {
+        spec.validate()?;
+        let pocket_ic = spawn_blocking(move || PocketIc::new(runtime, spec, None, false))
+            .await
+            .map_err(|e| format!("Failed to launch PocketIC: {}", e))?;
+        let topology = pocket_ic.topology();
+        let instance_id = self.add_instance(pocket_ic).await;
+        Ok((instance_id, topology))
+    }
+
     pub async fn delete_instance(&self, instance_id: InstanceId) {
         self.stop_progress(instance_id).await;
         let instances = self.instances.read().await;
@@ -467,10 +1171,26 @@ impl ApiState {
         }
     }
 
+    /// Creates a new HTTP gateway and returns its id and port once it is ready to serve requests,
+    /// i.e., once it has fetched the root key of its backend and set up TLS (if configured).
+    /// If the HTTP gateway fails to become ready within [HTTP_GATEWAY_READY_TIMEOUT],
+    /// it is stopped and an error is returned.
+    ///
+    /// The HTTP gateway forwards the following API endpoints to its backend
+    /// (all other requests are served as HTTP requests to canisters):
+    /// - `/api/v2/status`,
+    /// - `/api/v2/canister/<ecid>/call` (asynchronous call),
+    /// - `/api/v2/canister/<ecid>/query` and `/api/v3/canister/<ecid>/query`,
+    /// - `/api/v2/canister/<ecid>/read_state` and `/api/v3/canister/<ecid>/read_state`,
+    /// - `/api/v3/canister/<ecid>/call` (synchronous call returning a certificate).
+    ///
+    /// Request headers on the allowlist of the config (or a default allowlist including
+    /// tracing and idempotency headers) are forwarded to the backend on these endpoints
+    /// except for hop-by-hop headers.
     pub async fn create_http_gateway(
         &self,
         http_gateway_config: HttpGatewayConfig,
-    ) -> (InstanceId, u16) {
+    ) -> Result<(InstanceId, u16), String> {
         use crate::state_api::routes::verify_cbor_content_header;
         use axum::extract::{DefaultBodyLimit, Path, Request as AxumRequest, State};
         use axum::handler::Handler;
@@ -480,108 +1200,202 @@ impl ApiState {
         use axum::Router;
         use http_body_util::Full;
         use hyper::body::{Bytes, Incoming};
-        use hyper::header::CONTENT_TYPE;
+        use hyper::header::{HeaderMap, HeaderName, CONNECTION, CONTENT_TYPE};
+        use hyper::http::request::Builder;
         use hyper::{Method, Request, Response, StatusCode, Uri};
         use hyper_util::client::legacy::{connect::HttpConnector, Client};
         use icx_proxy::{agent_handler, AppState, DnsCanisterConfig, ResolverState, Validator};
         use std::str::FromStr;
+        use tower_http::compression::{predicate::SizeAbove, CompressionLayer};
+        use tower_http::decompression::RequestDecompressionLayer;
 
-        async fn handler_status(
-            State(replica_url): State<String>,
+        #[derive(Clone)]
+        struct Backend {
+            replica_url: String,
+            // request headers forwarded to the backend (none of them is hop-by-hop)
+            forward_headers: Arc<Vec<HeaderName>>,
+        }
+
+        // copies the allowlisted request headers except for those
+        // declared hop-by-hop by the `Connection` header
+        fn with_forwarded_headers(
+            mut builder: Builder,
+            backend: &Backend,
+            headers: &HeaderMap,
+        ) -> Builder {
+            let connection_headers: Vec<String> = headers
+                .get_all(CONNECTION)
+                .iter()
+                .filter_map(|v| v.to_str().ok())
+                .flat_map(|v| v.split(','))
+                .map(|name| name.trim().to_ascii_lowercase())
+                .collect();
+            for name in backend.forward_headers.iter() {
+                if connection_headers.iter().any(|h| h == name.as_str()) {
+                    continue;
+                }
+                for value in headers.get_all(name) {
+                    builder = builder.header(name, value);
+                }
+            }
+            builder
+        }
+
+        // a response of the backend or the status code and message
+        // of an error that occurred while forwarding the request to the backend
+        type BackendResponse = Result<(StatusCode, Response<Incoming>), (StatusCode, String)>;
+
+        async fn forward_to_backend(
+            builder: Builder,
+            url: String,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
+        ) -> BackendResponse {
             let client =
                 Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
-            let url = format!("{}/api/v2/status", replica_url);
-            let req = Request::builder()
-                .uri(url)
+            let req = builder
+                .uri(url.as_str())
                 .header(CONTENT_TYPE, "application/cbor")
                 .body(Full::<Bytes>::new(bytes))
-                .unwrap();
-            let resp = client.request(req).await.unwrap();
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to build request to {}: {}", url, e),
+                    )
+                })?;
+            let resp = client.request(req).await.map_err(|e| {
+                (
+                    StatusCode::BAD_GATEWAY,
+                    format!("Failed to forward request to {}: {}", url, e),
+                )
+            })?;
+
+            Ok((resp.status(), resp))
+        }
 
-            (resp.status(), resp)
+        async fn handler_status(
+            State(backend): State<Backend>,
+            headers: HeaderMap,
+            bytes: Bytes,
+        ) -> BackendResponse {
+            let url = format!("{}/api/v2/status", backend.replica_url);
+            let builder = with_forwarded_headers(Request::builder(), &backend, &headers);
+            forward_to_backend(builder, url, bytes).await
         }
 
         async fn handler_api_canister(
             api_version: ApiVersion,
-            replica_url: String,
+            backend: Backend,
             effective_canister_id: CanisterId,
             endpoint: &str,
+            headers: HeaderMap,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
-            let client =
-                Client::builder(hyper_util::rt::TokioExecutor::new()).build(HttpConnector::new());
+        ) -> BackendResponse {
             let url = format!(
                 "{}/api/{}/canister/{}/{}",
-                replica_url, api_version, effective_canister_id, endpoint
+                backend.replica_url, api_version, effective_canister_id, endpoint
             );
-            let req = Request::builder()
-                .method(Method::POST)
-                .uri(url)
-                .header(CONTENT_TYPE, "application/cbor")
-                .body(Full::<Bytes>::new(bytes))
-                .unwrap();
-            let resp = client.request(req).await.unwrap();
-
-            (resp.status(), resp)
+            let builder =
+                with_forwarded_headers(Request::builder(), &backend, &headers).method(Method::POST);
+            forward_to_backend(builder, url, bytes).await
         }
 
         async fn handler_call_v2(
-            State(replica_url): State<String>,
+            State(backend): State<Backend>,
             Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
+        ) -> BackendResponse {
             handler_api_canister(
                 ApiVersion::V2,
-                replica_url,
+                backend,
                 effective_canister_id,
                 "call",
+                headers,
                 bytes,
             )
             .await
         }
 
         async fn handler_call_v3(
-            State(replica_url): State<String>,
+            State(backend): State<Backend>,
             Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
+        ) -> BackendResponse {
             handler_api_canister(
                 ApiVersion::V3,
-                replica_url,
+                backend,
                 effective_canister_id,
                 "call",
+                headers,
                 bytes,
             )
             .await
         }
 
-        async fn handler_query(
-            State(replica_url): State<String>,
+        async fn handler_query_v2(
+            State(backend): State<Backend>,
             Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
+        ) -> BackendResponse {
             handler_api_canister(
                 ApiVersion::V2,
-                replica_url,
+                backend,
+                effective_canister_id,
+                "query",
+                headers,
+                bytes,
+            )
+            .await
+        }
+
+        async fn handler_query_v3(
+            State(backend): State<Backend>,
+            Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
+            bytes: Bytes,
+        ) -> BackendResponse {
+            handler_api_canister(
+                ApiVersion::V3,
+                backend,
                 effective_canister_id,
                 "query",
+                headers,
                 bytes,
             )
             .await
         }
 
-        async fn handler_read_state(
-            State(replica_url): State<String>,
+        async fn handler_read_state_v2(
+            State(backend): State<Backend>,
             Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
             bytes: Bytes,
-        ) -> (StatusCode, Response<Incoming>) {
+        ) -> BackendResponse {
             handler_api_canister(
                 ApiVersion::V2,
-                replica_url,
+                backend,
+                effective_canister_id,
+                "read_state",
+                headers,
+                bytes,
+            )
+            .await
+        }
+
+        async fn handler_read_state_v3(
+            State(backend): State<Backend>,
+            Path(effective_canister_id): Path<CanisterId>,
+            headers: HeaderMap,
+            bytes: Bytes,
+        ) -> BackendResponse {
+            handler_api_canister(
+                ApiVersion::V3,
+                backend,
                 effective_canister_id,
                 "read_state",
+                headers,
                 bytes,
             )
             .await
@@ -611,160 +1425,339 @@ impl ApiState {
         }
 
         let port = http_gateway_config.listen_at.unwrap_or_default();
-        let addr = format!("[::]:{}", port);
-        let listener = std::net::TcpListener::bind(&addr)
-            .unwrap_or_else(|_| panic!("Failed to start HTTP gateway on port {}", port));
-        let real_port = listener.local_addr().unwrap().port();
-
+        let ip = http_gateway_config
+            .listen_addr
+            .unwrap_or(IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+        let addr = SocketAddr::new(ip, port);
+        let bind_result = std::net::TcpListener::bind(addr)
+            .and_then(|listener| {
+                let real_port = listener.local_addr()?.port();
+                Ok((listener, real_port))
+            })
+            .map_err(|e| format!("Failed to start HTTP gateway on {}: {}", addr, e));
+        let (real_port, status) = match &bind_result {
+            Ok((_, real_port)) => (*real_port, GatewayStatus::Running),
+            Err(e) => (port, GatewayStatus::Failed(e.clone())),
+        };
+        let gateway_domains = http_gateway_config
+            .domains
+            .clone()
+            .unwrap_or(vec!["localhost".to_string()]);
+
+        let shutdown_grace_period = http_gateway_config
+            .shutdown_grace_period
+            .unwrap_or(DEFAULT_HTTP_GATEWAY_SHUTDOWN_GRACE_PERIOD);
+        let shutdown = Arc::new(Notify::new());
+        let (terminated_sender, terminated) = watch::channel(false);
+        let mut terminated_for_shutdown = terminated.clone();
         let mut http_gateways = self.http_gateways.write().await;
-        http_gateways.push(true);
-        let instance_id = http_gateways.len() - 1;
+        let instance_id = http_gateways.len();
+        http_gateways.push(HttpGateway {
+            details: HttpGatewayDetails {
+                gateway_id: instance_id,
+                port: real_port,
+                forward_to: http_gateway_config.forward_to.clone(),
+                domains: gateway_domains.clone(),
+                https: http_gateway_config.https_config.is_some(),
+                status,
+            },
+            shutdown_grace_period,
+            shutdown: shutdown.clone(),
+            terminated,
+        });
         drop(http_gateways);
+        // A gateway that failed to bind is kept (as failed) so that its error can be queried.
+        let listener = match bind_result {
+            Ok((listener, _)) => listener,
+            Err(e) => {
+                error!("HTTP gateway {} failed: {}", instance_id, e);
+                let _ = terminated_sender.send(true);
+                return Err(e);
+            }
+        };
 
         let http_gateways = self.http_gateways.clone();
         let pocket_ic_server_port = self.port.unwrap();
+        let (ready_sender, ready_receiver) = oneshot::channel::<Result<(), String>>();
         spawn(async move {
-            let replica_url = match http_gateway_config.forward_to {
-                HttpGatewayBackend::Replica(replica_url) => replica_url,
-                HttpGatewayBackend::PocketIcInstance(instance_id) => {
-                    format!(
-                        "http://localhost:{}/instances/{}/",
-                        pocket_ic_server_port, instance_id
-                    )
-                }
-            };
-            let agent = ic_agent::Agent::builder()
-                .with_url(replica_url.clone())
-                .build()
-                .unwrap();
-            agent.fetch_root_key().await.unwrap();
-            let replica_uri = Uri::from_str(&replica_url).unwrap();
-            let replicas = vec![(agent, replica_uri)];
-            let gateway_domains = http_gateway_config
-                .domains
-                .unwrap_or(vec!["localhost".to_string()]);
-            let aliases: Vec<String> = vec![];
-            let suffixes: Vec<String> = gateway_domains;
-            let resolver = ResolverState {
-                dns: DnsCanisterConfig::new(aliases, suffixes).unwrap(),
-            };
-            let validator = Validator::default();
-            let app_state = AppState::new_for_testing(replicas, resolver, validator);
-            let fallback_handler = agent_handler.with_state(app_state);
-
-            let router = Router::new()
-                .route("/api/v2/status", get(handler_status))
-                .route(
-                    "/api/v2/canister/:ecid/call",
-                    post(handler_call_v2)
-                        .layer(axum::middleware::from_fn(verify_cbor_content_header)),
-                )
-                .route(
-                    "/api/v3/canister/:ecid/call",
-                    post(handler_call_v3)
-                        .layer(axum::middleware::from_fn(verify_cbor_content_header)),
-                )
-                .route(
-                    "/api/v2/canister/:ecid/query",
-                    post(handler_query)
-                        .layer(axum::middleware::from_fn(verify_cbor_content_header)),
-                )
-                .route(
-                    "/api/v2/canister/:ecid/read_state",
-                    post(handler_read_state)
-                        .layer(axum::middleware::from_fn(verify_cbor_content_header)),
-                )
-                .fallback_service(fallback_handler)
-                .layer(DefaultBodyLimit::disable())
-                .layer(cors_layer())
-                .layer(middleware::from_fn(http2_middleware))
-                .with_state(replica_url.trim_end_matches('/').to_string())
-                .into_make_service();
-
-            let handle = Handle::new();
-            let shutdown_handle = handle.clone();
-            let http_gateways_for_shutdown = http_gateways.clone();
-            tokio::spawn(async move {
-                loop {
-                    let guard = http_gateways_for_shutdown.read().await;
-                    if !guard[instance_id] {
-                        shutdown_handle.shutdown();
-                        break;
+            let mut ready_sender = Some(ready_sender);
+            let res: Result<(), String> = async {
+                let replica_url = match http_gateway_config.forward_to {
+                    HttpGatewayBackend::Replica(replica_url) => replica_url,
+                    HttpGatewayBackend::PocketIcInstance(instance_id) => {
+                        format!(
+                            "http://localhost:{}/instances/{}/",
+                            pocket_ic_server_port, instance_id
+                        )
                     }
-                    drop(guard);
-                    tokio::time::sleep(Duration::from_secs(1)).await;
+                };
+                let agent = ic_agent::Agent::builder()
+                    .with_url(replica_url.clone())
+                    .build()
+                    .map_err(|e| format!("Failed to create agent: {}", e))?;
+                agent
+                    .fetch_root_key()
+                    .await
+                    .map_err(|e| format!("Failed to fetch root key from {}: {}", replica_url, e))?;
+                let replica_uri = Uri::from_str(&replica_url)
+                    .map_err(|e| format!("Invalid replica URL {}: {}", replica_url, e))?;
+                let forward_headers = match http_gateway_config.forward_headers {
+                    Some(forward_headers) => forward_headers,
+                    None => DEFAULT_HTTP_GATEWAY_FORWARDED_HEADERS
+                        .iter()
+                        .map(|h| h.to_string())
+                        .collect(),
                 }
-            });
-            if let Some(https_config) = http_gateway_config.https_config {
-                let config = RustlsConfig::from_pem_file(
-                    PathBuf::from(https_config.cert_path),
-                    PathBuf::from(https_config.key_path),
-                )
-                .await;
-                match config {
-                    Ok(config) => {
-                        axum_server::from_tcp_rustls(listener, config)
-                            .handle(handle)
-                            .serve(router)
-                            .await
-                            .unwrap();
+                .into_iter()
+                .map(|h| {
+                    HeaderName::from_str(&h).map_err(|e| format!("Invalid header {}: {}", h, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+                .into_iter()
+                .filter(|h| !HTTP_GATEWAY_NON_FORWARDABLE_HEADERS.contains(&h.as_str()))
+                .collect();
+                let backend = Backend {
+                    replica_url: replica_url.trim_end_matches('/').to_string(),
+                    forward_headers: Arc::new(forward_headers),
+                };
+                let replicas = vec![(agent, replica_uri)];
+                let aliases: Vec<String> = vec![];
+                let suffixes: Vec<String> = gateway_domains;
+                let resolver = ResolverState {
+                    dns: DnsCanisterConfig::new(aliases, suffixes)
+                        .map_err(|e| format!("Invalid domains: {}", e))?,
+                };
+                let validator = Validator::default();
+                let app_state = AppState::new_for_testing(replicas, resolver, validator);
+                let fallback_handler = agent_handler.with_state(app_state);
+
+                // Requests to the API routes can be gzip-encoded and their responses are
+                // gzip-encoded if the client sends `Accept-Encoding: gzip`, as on a replica.
+                let router = Router::new()
+                    .route("/api/v2/status", get(handler_status))
+                    .route(
+                        "/api/v2/canister/:ecid/call",
+                        post(handler_call_v2)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .route(
+                        "/api/v3/canister/:ecid/call",
+                        post(handler_call_v3)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .route(
+                        "/api/v2/canister/:ecid/query",
+                        post(handler_query_v2)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .route(
+                        "/api/v3/canister/:ecid/query",
+                        post(handler_query_v3)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .route(
+                        "/api/v2/canister/:ecid/read_state",
+                        post(handler_read_state_v2)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .route(
+                        "/api/v3/canister/:ecid/read_state",
+                        post(handler_read_state_v3)
+                            .layer(axum::middleware::from_fn(verify_cbor_content_header)),
+                    )
+                    .layer(RequestDecompressionLayer::new())
+                    .layer(
+                        CompressionLayer::new()
+                            .no_br()
+                            .no_deflate()
+                            .no_zstd()
+                            .compress_when(SizeAbove::new(
+                                HTTP_GATEWAY_COMPRESSION_THRESHOLD_BYTES,
+                            )),
+                    )
+                    .fallback_service(fallback_handler)
+                    .layer(DefaultBodyLimit::disable())
+                    .layer(cors_layer())
+                    .layer(middleware::from_fn(http2_middleware))
+                    .with_state(backend)
+                    .into_make_service();
+
+                let handle = Handle::new();
+                let shutdown_handle = handle.clone();
+                tokio::spawn(async move {
+                    tokio::select! {
+                        // A shutdown requested before this point is not lost
+                        // since `Notify` stores a permit.
+                        _ = shutdown.notified() => {
+                            // Stop accepting new connections and let in-flight requests
+                            // complete within the grace period (connections still open
+                            // afterwards are closed forcibly).
+                            shutdown_handle.graceful_shutdown(Some(shutdown_grace_period));
+                        }
+                        // The HTTP gateway terminated on its own (e.g., because it failed).
+                        _ = terminated_for_shutdown.wait_for(|terminated| *terminated) => {}
+                    }
+                });
+                if let Some(https_config) = http_gateway_config.https_config {
+                    let config = RustlsConfig::from_pem_file(
+                        PathBuf::from(https_config.cert_path),
+                        PathBuf::from(https_config.key_path),
+                    )
+                    .await
+                    .map_err(|e| format!("TLS config could not be created: {:?}", e))?;
+                    if let Some(ready_sender) = ready_sender.take() {
+                        let _ = ready_sender.send(Ok(()));
                     }
-                    Err(e) => {
-                        error!("TLS config could not be created: {:?}", e);
-                        let mut guard = http_gateways.write().await;
-                        guard[instance_id] = false;
-                        return;
+                    axum_server::from_tcp_rustls(listener, config)
+                        .handle(handle)
+                        .serve(router)
+                        .await
+                        .map_err(|e| format!("Failed to serve HTTPS: {}", e))
+                } else {
+                    if let Some(ready_sender) = ready_sender.take() {
+                        let _ = ready_sender.send(Ok(()));
                     }
+                    axum_server::from_tcp(listener)
+                        .handle(handle)
+                        .serve(router)
+                        .await
+                        .map_err(|e| format!("Failed to serve HTTP: {}", e))
                 }
-            } else {
-                axum_server::from_tcp(listener)
-                    .handle(handle)
-                    .serve(router)
-                    .await
-                    .unwrap();
             }
-
-            info!("Terminating HTTP gateway.");
+            .await;
+
+            match res {
+                Ok(()) => info!("Terminating HTTP gateway."),
+                Err(message) => {
+                    error!("HTTP gateway {} failed: {}", instance_id, message);
+                    http_gateways.write().await[instance_id].details.status =
+                        GatewayStatus::Failed(message.clone());
+                    if let Some(ready_sender) = ready_sender.take() {
+                        let _ = ready_sender.send(Err(message));
+                    }
+                }
+            }
+            // The listener has been dropped by now and thus the port is released.
+            let _ = terminated_sender.send(true);
         });
-        (instance_id, real_port)
+
+        let res = match time::timeout(HTTP_GATEWAY_READY_TIMEOUT, ready_receiver).await {
+            Ok(Ok(res)) => res,
+            Ok(Err(_)) => Err("HTTP gateway terminated unexpectedly".to_string()),
+            Err(_) => Err(format!(
+                "HTTP gateway did not become ready within {:?}",
+                HTTP_GATEWAY_READY_TIMEOUT
+            )),
+        };
+        if let Err(e) = res {
+            // Mark the HTTP gateway as failed and make its (possibly still running) task terminate.
+            let mut http_gateways = self.http_gateways.write().await;
+            let http_gateway = &mut http_gateways[instance_id];
+            http_gateway.details.status = GatewayStatus::Failed(e.clone());
+            http_gateway.shutdown.notify_one();
+            return Err(e);
+        }
+        Ok((instance_id, real_port))
     }
 
+    /// Stops an HTTP gateway: in-flight requests can complete within the gateway's
+    /// shutdown grace period. Returns once the HTTP gateway has released its port
+    /// (or the grace period and an additional margin elapsed).
     pub async fn stop_http_gateway(&self, instance_id: InstanceId) {
         let mut http_gateways = self.http_gateways.write().await;
-        if instance_id < http_gateways.len() {
-            http_gateways[instance_id] = false;
+        let Some(http_gateway) = http_gateways.get_mut(instance_id) else {
+            return;
+        };
+        if http_gateway.details.status == GatewayStatus::Running {
+            http_gateway.details.status = GatewayStatus::Stopped;
         }
+        http_gateway.shutdown.notify_one();
+        let mut terminated = http_gateway.terminated.clone();
+        let timeout = http_gateway.shutdown_grace_period + HTTP_GATEWAY_SHUTDOWN_TIMEOUT_MARGIN;
+        drop(http_gateways);
+        // An error means that the HTTP gateway's task (and thus its listener) is gone.
+        if time::timeout(timeout, terminated.wait_for(|terminated| *terminated))
+            .await
+            .is_err()
+        {
+            error!(
+                "HTTP gateway {} did not terminate within {:?}",
+                instance_id, timeout
+            );
+        }
+    }
+
+    /// Lists all HTTP gateways (including stopped and failed ones) ordered by their ids.
+    pub async fn list_http_gateways(&self) -> Vec<HttpGatewayDetails> {
+        self.http_gateways
+            .read()
+            .await
+            .iter()
+            .map(|http_gateway| http_gateway.details.clone())
+            .collect()
+    }
+
+    /// Returns the details of the given HTTP gateway or `None` if no such HTTP gateway exists.
+    pub async fn get_http_gateway(&self, instance_id: InstanceId) -> Option<HttpGatewayDetails> {
+        self.http_gateways
+            .read()
+            .await
+            .get(instance_id)
+            .map(|http_gateway| http_gateway.details.clone())
+    }
+
+    /// Returns the status of the given HTTP gateway or `None` if no such HTTP gateway exists.
+    /// A failed HTTP gateway retains the error that made it fail.
+    pub async fn http_gateway_status(&self, instance_id: InstanceId) -> Option<GatewayStatus> {
+        self.get_http_gateway(instance_id)
+            .await
+            .map(|http_gateway| http_gateway.status)
     }
 
-    async fn make_http_request(
+    pub(crate) async fn make_http_request(
         canister_http_request: CanisterHttpRequest,
+        socks_proxy: Option<String>,
+        client_identity: Option<Arc<CanisterHttpClientIdentity>>,
+        canister_http_policy: CanisterHttpPolicy,
     ) -> Result<CanisterHttpReply, (RejectCode, String)> {
+        // TLS setup
+        // The native root certificates are trusted in any case and the client identity
+        // (if configured on the instance) is presented to servers requiring mutual TLS.
+        let tls_config = client_identity
+            .map(|client_identity| client_identity.tls_config())
+            .transpose()
+            .map_err(|e| (RejectCode::SysFatal, e))?;
+        let https_connector_builder = || match tls_config.clone() {
+            Some(tls_config) => HttpsConnectorBuilder::new().with_tls_config(tls_config),
+            None => HttpsConnectorBuilder::new().with_native_roots(),
+        };
+
         // Socks client setup
-        // We don't really use the Socks client in PocketIC as we set `socks_proxy_allowed: false` in the request,
-        // but we still have to provide one when constructing the production `CanisterHttp` object
-        // and thus we use a reserved (and invalid) proxy IP address.
+        // The Socks client is only used if a SOCKS proxy is configured on the instance
+        // (we set `socks_proxy_allowed` in the request accordingly), but we still have to provide one
+        // when constructing the production `CanisterHttp` object and thus we use a reserved
+        // (and invalid) proxy IP address by default.
+        let socks_proxy_allowed = socks_proxy.is_some();
         let mut http_connector = HttpConnector::new();
         http_connector.enforce_http(false);
         http_connector.set_connect_timeout(Some(Duration::from_secs(2)));
         let proxy_connector = SocksConnector {
-            proxy_addr: "http://240.0.0.0:8080"
+            proxy_addr: socks_proxy
+                .as_deref()
+                .unwrap_or("http://240.0.0.0:8080")
                 .parse::<tonic::transport::Uri>()
                 .expect("Failed to parse socks url."),
             auth: None,
             connector: http_connector.clone(),
         };
-        let https_connector = HttpsConnectorBuilder::new()
-            .with_native_roots()
+        let https_connector = https_connector_builder()
             .https_only()
             .enable_http1()
             .wrap_connector(proxy_connector);
         let socks_client = Client::builder().build::<_, hyper_legacy::Body>(https_connector);
 
         // Https client setup.
-        let builder = HttpsConnectorBuilder::new()
-            .with_native_roots()
-            .https_or_http()
-            .enable_http1();
+        let builder = https_connector_builder().https_or_http().enable_http1();
         let https_client = Client::builder()
             .build::<_, hyper_legacy::Body>(builder.wrap_connector(http_connector));
 
@@ -774,6 +1767,14 @@ impl ApiState {
             no_op_logger(),
             &MetricsRegistry::default(),
         );
+        // Only idempotent requests are retried.
+        let max_retries = match canister_http_request.http_method {
+            CanisterHttpMethod::GET | CanisterHttpMethod::HEAD => canister_http_policy.max_retries,
+            CanisterHttpMethod::POST => 0,
+        };
+        let max_response_bytes = canister_http_request
+            .max_response_bytes
+            .unwrap_or(MAX_CANISTER_HTTP_RESPONSE_BYTES);
         let canister_http_request = CanisterHttpSendRequest {
             url: canister_http_request.url,
             method: match canister_http_request.http_method {
@@ -781,9 +1782,7 @@ impl ApiState {
                 CanisterHttpMethod::POST => HttpMethod::Post.into(),
                 CanisterHttpMethod::HEAD => HttpMethod::Head.into(),
             },
-            max_response_size_bytes: canister_http_request
-                .max_response_bytes
-                .unwrap_or(MAX_CANISTER_HTTP_RESPONSE_BYTES),
+            max_response_size_bytes: max_response_bytes,
             headers: canister_http_request
                 .headers
                 .into_iter()
@@ -793,12 +1792,38 @@ impl ApiState {
                 })
                 .collect(),
             body: canister_http_request.body,
-            socks_proxy_allowed: false,
+            socks_proxy_allowed,
         };
-        let request = Request::new(canister_http_request);
-        canister_http
-            .canister_http_send(request)
-            .await
+        let send_with_retries = async {
+            let mut retries = 0;
+            loop {
+                let request = Request::new(canister_http_request.clone());
+                match canister_http.canister_http_send(request).await {
+                    Err(grpc_status) if retries < max_retries => {
+                        retries += 1;
+                        debug!(
+                            "Retrying canister HTTP request ({}/{}) after error: {}",
+                            retries,
+                            max_retries,
+                            grpc_status.message()
+                        );
+                    }
+                    res => break res,
+                }
+            }
+        };
+        let res = match canister_http_policy.total_timeout {
+            Some(total_timeout) => time::timeout(total_timeout, send_with_retries)
+                .await
+                .map_err(|_| {
+                    (
+                        RejectCode::SysTransient,
+                        format!("Canister HTTP request timed out after {:?}", total_timeout),
+                    )
+                })?,
+            None => send_with_retries.await,
+        };
+        let reply = res
             .map(|adapter_response| {
                 let CanisterHttpSendResponse {
                     status,
@@ -819,21 +1844,82 @@ impl ApiState {
                     grpc_status_code_to_reject(grpc_status.code()),
                     grpc_status.message().to_string(),
                 )
+            })?;
+        // The adapter is supposed to enforce the limit already, but we double-check
+        // the reassembled body so that an oversized response is rejected as on a replica.
+        if reply.body.len() as u64 > max_response_bytes {
+            return Err((
+                RejectCode::SysFatal,
+                format!(
+                    "Http body exceeds size limit of {} bytes.",
+                    max_response_bytes
+                ),
+            ));
+        }
+        Ok(reply)
+    }
+
+    /// Makes the given canister HTTP outcalls concurrently (up to the configured cap)
+    /// and returns the responses in the order of the outcalls.
+    pub(crate) async fn make_http_requests(
+        canister_http_requests: Vec<CanisterHttpRequest>,
+        socks_proxy: Option<String>,
+        client_identity: Option<Arc<CanisterHttpClientIdentity>>,
+        canister_http_policy: CanisterHttpPolicy,
+    ) -> Vec<MockCanisterHttpResponse> {
+        // The responses are collected (and thus applied to the instance) in the order
+        // of the requests so that the resulting state is deterministic.
+        stream::iter(canister_http_requests)
+            .map(|canister_http_request| {
+                let socks_proxy = socks_proxy.clone();
+                let client_identity = client_identity.clone();
+                async move {
+                    let subnet_id = canister_http_request.subnet_id;
+                    let request_id = canister_http_request.request_id;
+                    let response = match Self::make_http_request(
+                        canister_http_request,
+                        socks_proxy,
+                        client_identity,
+                        canister_http_policy,
+                    )
+                    .await
+                    {
+                        Ok(reply) => CanisterHttpResponse::CanisterHttpReply(reply),
+                        Err((reject_code, e)) => {
+                            CanisterHttpResponse::CanisterHttpReject(CanisterHttpReject {
+                                reject_code: reject_code as u64,
+                                message: e,
+                            })
+                        }
+                    };
+                    MockCanisterHttpResponse {
+                        subnet_id,
+                        request_id,
+                        response,
+                        apply_after: None,
+                    }
+                }
             })
+            .buffered(canister_http_policy.max_concurrency.max(1))
+            .collect()
+            .await
     }
 
     async fn process_canister_http_requests(
         instances: Arc<RwLock<Vec<Mutex<InstanceState>>>>,
-        graph: Arc<RwLock<HashMap<StateLabel, Computations>>>,
+        graph: Arc<RwLock<Graph>>,
         instance_id: InstanceId,
+        canister_http_policy: CanisterHttpPolicy,
+        timings: AutoProgressTimings,
         rx: &mut Receiver<()>,
-    ) -> Option<()> {
+    ) -> Option<usize> {
         let get_canister_http_op = GetCanisterHttp;
         let canister_http_requests = match Self::execute_operation(
             instances.clone(),
             graph.clone(),
             instance_id,
             get_canister_http_op,
+            timings,
             rx,
         )
         .await?
@@ -841,26 +1927,43 @@ impl ApiState {
             OpOut::CanisterHttp(canister_http) => canister_http,
             out => panic!("Unexpected OpOut: {:?}", out),
         };
-        let mut mock_canister_http_responses = vec![];
-        for canister_http_request in canister_http_requests {
-            let subnet_id = canister_http_request.subnet_id;
-            let request_id = canister_http_request.request_id;
-            let response = match Self::make_http_request(canister_http_request).await {
-                Ok(reply) => CanisterHttpResponse::CanisterHttpReply(reply),
-                Err((reject_code, e)) => {
-                    CanisterHttpResponse::CanisterHttpReject(CanisterHttpReject {
-                        reject_code: reject_code as u64,
-                        message: e,
-                    })
-                }
-            };
-            let mock_canister_http_response = MockCanisterHttpResponse {
-                subnet_id,
-                request_id,
-                response,
-            };
-            mock_canister_http_responses.push(mock_canister_http_response);
+        if canister_http_requests.is_empty() {
+            return Some(0);
         }
+        let socks_proxy = match Self::execute_operation(
+            instances.clone(),
+            graph.clone(),
+            instance_id,
+            GetCanisterHttpSocksProxy,
+            timings,
+            rx,
+        )
+        .await?
+        {
+            OpOut::CanisterHttpSocksProxy(socks_proxy) => socks_proxy,
+            out => panic!("Unexpected OpOut: {:?}", out),
+        };
+        let client_identity = match Self::execute_operation(
+            instances.clone(),
+            graph.clone(),
+            instance_id,
+            GetCanisterHttpClientIdentity,
+            timings,
+            rx,
+        )
+        .await?
+        {
+            OpOut::CanisterHttpClientIdentity(client_identity) => client_identity,
+            out => panic!("Unexpected OpOut: {:?}", out),
+        };
+        let num_canister_http_requests = canister_http_requests.len();
+        let mock_canister_http_responses = Self::make_http_requests(
+            canister_http_requests,
+            socks_proxy,
+            client_identity,
+            canister_http_policy,
+        )
+        .await;
         for mock_canister_http_response in mock_canister_http_responses {
             let mock_canister_http_op = MockCanisterHttp {
                 mock_canister_http_response,
@@ -870,57 +1973,172 @@ impl ApiState {
                 graph.clone(),
                 instance_id,
                 mock_canister_http_op,
+                timings,
                 rx,
             )
             .await?;
         }
-        Some(())
+        Some(num_canister_http_requests)
     }
 
     pub async fn auto_progress(&self, instance_id: InstanceId) {
+        self.auto_progress_with_config(instance_id, AutoProgressConfig::default())
+            .await
+    }
+
+    /// Makes the given instance progress automatically: on every tick, the time of the instance
+    /// is advanced, a round is executed on all subnets, and canister HTTP outcalls are made.
+    /// The tick interval and the time by which the instance is advanced on every tick can be
+    /// pinned by the given config, e.g., for reproducible time progression. If the instance
+    /// already makes progress automatically with a different config, the thread making it
+    /// progress is restarted with the given config.
+    pub async fn auto_progress_with_config(
+        &self,
+        instance_id: InstanceId,
+        config: AutoProgressConfig,
+    ) {
         let progress_threads = self.progress_threads.read().await;
         let mut progress_thread = progress_threads[instance_id].lock().await;
         let instances = self.instances.clone();
         let graph = self.graph.clone();
-        if progress_thread.is_none() {
-            let (tx, mut rx) = mpsc::channel::<()>(1);
-            let handle = spawn(async move {
-                let mut now = Instant::now();
-                loop {
-                    let start = Instant::now();
-                    let old = std::mem::replace(&mut now, Instant::now());
-                    let op = AdvanceTimeAndTick(now.duration_since(old));
-                    if Self::execute_operation(
-                        instances.clone(),
-                        graph.clone(),
-                        instance_id,
-                        op,
-                        &mut rx,
-                    )
-                    .await
-                    .is_none()
-                    {
-                        return;
-                    }
-                    if Self::process_canister_http_requests(
-                        instances.clone(),
-                        graph.clone(),
-                        instance_id,
-                        &mut rx,
-                    )
-                    .await
-                    .is_none()
-                    {
-                        return;
-                    }
-                    let duration = start.elapsed();
-                    sleep(std::cmp::max(duration, MIN_OPERATION_DELAY)).await;
-                    if received_stop_signal(&mut rx) {
-                        return;
+        let canister_http_policy = self.canister_http_policy;
+        let timings = self.auto_progress_timings;
+        if let Some(t) = progress_thread.as_ref() {
+            if t.config == config {
+                return;
+            }
+        }
+        // A thread running with a different config is restarted with the new config
+        // (and keeps being paused if it was paused).
+        let was_paused = match progress_thread.take() {
+            Some(t) => {
+                t.sender.send(()).await.unwrap();
+                t.handle.await.unwrap();
+                *t.paused.borrow()
+            }
+            None => false,
+        };
+        let (tx, mut rx) = mpsc::channel::<()>(1);
+        let status = Arc::new(std::sync::Mutex::new(ProgressStatus {
+            last_operation: ProgressOperationKind::AdvanceTimeAndTick,
+            last_operation_started_at: SystemTime::now(),
+            canister_http_requests: 0,
+        }));
+        let thread_status = status.clone();
+        let (paused, mut paused_rx) = watch::channel(was_paused);
+        let handle = spawn(async move {
+            let mut now = Instant::now();
+            loop {
+                if *paused_rx.borrow() {
+                    set_progress_operation(&thread_status, ProgressOperationKind::Paused);
+                    let paused_at = Instant::now();
+                    tokio::select! {
+                        _ = paused_rx.wait_for(|paused| !*paused) => {}
+                        _ = rx.recv() => return,
                     }
+                    // The time spent paused does not count as elapsed time
+                    // so that resuming does not make the time of the instance jump.
+                    now += paused_at.elapsed();
+                }
+                set_progress_operation(&thread_status, ProgressOperationKind::AdvanceTimeAndTick);
+                let start = Instant::now();
+                let old = std::mem::replace(&mut now, Instant::now());
+                let advance_by = config.advance_by.unwrap_or_else(|| now.duration_since(old));
+                let op = AdvanceTimeAndTick(advance_by);
+                if Self::execute_operation(
+                    instances.clone(),
+                    graph.clone(),
+                    instance_id,
+                    op,
+                    timings,
+                    &mut rx,
+                )
+                .await
+                .is_none()
+                {
+                    return;
+                }
+                set_progress_operation(
+                    &thread_status,
+                    ProgressOperationKind::ProcessCanisterHttpRequests,
+                );
+                let Some(num_canister_http_requests) = Self::process_canister_http_requests(
+                    instances.clone(),
+                    graph.clone(),
+                    instance_id,
+                    canister_http_policy,
+                    timings,
+                    &mut rx,
+                )
+                .await
+                else {
+                    return;
+                };
+                thread_status.lock().unwrap().canister_http_requests = num_canister_http_requests;
+                set_progress_operation(&thread_status, ProgressOperationKind::Sleep);
+                let duration = start.elapsed();
+                let tick_interval = config.tick_interval.unwrap_or(timings.min_operation_delay);
+                sleep(std::cmp::max(duration, tick_interval)).await;
+                if received_stop_signal(&mut rx) {
+                    return;
                 }
-            });
-            *progress_thread = Some(ProgressThread { handle, sender: tx });
+            }
+        });
+        *progress_thread = Some(ProgressThread {
+            handle,
+            sender: tx,
+            paused,
+            status,
+            config,
+        });
+    }
+
+    /// Returns the status of the thread making the given instance progress automatically
+    /// or `None` if the instance does not make progress automatically.
+    pub async fn progress_thread_status(&self, instance_id: InstanceId) -> Option<ProgressStatus> {
+        let progress_threads = self.progress_threads.read().await;
+        let progress_thread = progress_threads.get(instance_id)?.lock().await;
+        progress_thread
+            .as_ref()
+            .map(|t| t.status.lock().unwrap().clone())
+    }
+
+    /// Returns the slowest operations computed on the given instance (ordered by decreasing duration)
+    /// or `None` if no such instance exists.
+    pub async fn op_timings(&self, instance_id: InstanceId) -> Option<Vec<OpTiming>> {
+        let instances = self.instances.read().await;
+        if instance_id >= instances.len() {
+            return None;
+        }
+        let op_timings = self
+            .graph
+            .read()
+            .await
+            .op_timings
+            .get(&instance_id)
+            .cloned();
+        Some(op_timings.unwrap_or_default())
+    }
+
+    /// Pauses automatic progress of the given instance without terminating the thread
+    /// making the instance progress automatically: the thread parks after its current iteration
+    /// until automatic progress is resumed. Does nothing if the instance does not make progress
+    /// automatically.
+    pub async fn pause_progress(&self, instance_id: InstanceId) {
+        self.set_progress_paused(instance_id, true).await
+    }
+
+    /// Resumes automatic progress of the given instance paused by `pause_progress`.
+    /// The wall-clock time elapsed while paused is not added to the time of the instance.
+    pub async fn resume_progress(&self, instance_id: InstanceId) {
+        self.set_progress_paused(instance_id, false).await
+    }
+
+    async fn set_progress_paused(&self, instance_id: InstanceId, paused: bool) {
+        let progress_threads = self.progress_threads.read().await;
+        let progress_thread = progress_threads[instance_id].lock().await;
+        if let Some(t) = progress_thread.as_ref() {
+            t.paused.send_replace(paused);
         }
     }
 
@@ -933,6 +2151,100 @@ impl ApiState {
         }
     }
 
+    /// Resets the wall-clock time spent computing operations on the given instance
+    /// so that operations refused due to an exhausted CPU budget are accepted again.
+    pub async fn reset_cpu_budget(&self, instance_id: InstanceId) -> Result<(), UpdateError> {
+        let instances = self.instances.read().await;
+        let Some(instance_mutex) = instances.get(instance_id) else {
+            return Err(UpdateError::InstanceNotFound);
+        };
+        match &mut *instance_mutex.lock().await {
+            InstanceState::Available(pocket_ic) => {
+                pocket_ic.reset_cpu_time_used();
+                Ok(())
+            }
+            InstanceState::Busy { .. } => Err(UpdateError::InstanceBusy),
+            InstanceState::Deleted => Err(UpdateError::InstanceDeleted),
+        }
+    }
+
+    /// Saves a snapshot of the given instance to the given directory which must not exist yet
+    /// or be empty. The snapshot can be loaded by [PocketIcApiStateBuilder::add_instance_from_dir]
+    /// and the loaded instance has the same state label as the given instance after saving it.
+    pub async fn save_instance(
+        &self,
+        instance_id: InstanceId,
+        path: PathBuf,
+    ) -> Result<(), UpdateError> {
+        let op = Arc::new(SaveInstance { path });
+        match self
+            .update_with_timeout(op, instance_id, Some(SAVE_INSTANCE_TIMEOUT))
+            .await?
+        {
+            UpdateReply::Output(OpOut::NoOutput) => Ok(()),
+            UpdateReply::Output(OpOut::Error(PocketIcError::InstanceSnapshotError(message))) => {
+                Err(UpdateError::Internal(message))
+            }
+            UpdateReply::Output(out) => Err(UpdateError::Internal(format!(
+                "Failed to save instance: {:?}",
+                out
+            ))),
+            UpdateReply::Busy { .. } => Err(UpdateError::InstanceBusy),
+            UpdateReply::Started { .. } => Err(UpdateError::Internal(
+                "Saving the instance timed out".to_string(),
+            )),
+        }
+    }
+
+    /// Cancels an operation on the given instance and marks its result in the graph as
+    /// [OpOut::Cancelled] so that clients polling for the result stop waiting.
+    ///
+    /// * An operation queued up on a busy instance is removed from the queue and never executed.
+    ///
+    /// * An operation that is already running cannot be interrupted in general: it runs to
+    /// completion (and its effects on the instance persist), but its result is discarded.
+    /// Only operations consisting of several steps check for cancellation between the steps
+    /// and return early; currently, this only applies to awaiting an ingress message.
+    ///
+    /// The instance becomes available again once the running operation returns
+    /// and the queue is drained.
+    pub async fn cancel_operation(
+        &self,
+        instance_id: InstanceId,
+        op_id: OpId,
+    ) -> Result<(), UpdateError> {
+        let instances = self.instances.read().await;
+        let Some(instance_mutex) = instances.get(instance_id) else {
+            return Err(UpdateError::InstanceNotFound);
+        };
+        let mut instance_state = instance_mutex.lock().await;
+        let InstanceState::Busy {
+            state_label,
+            op_id: running_op_id,
+            cancelled,
+            queue,
+            ..
+        } = &mut *instance_state
+        else {
+            return Err(UpdateError::OperationNotFound);
+        };
+        let state_label = if *running_op_id == op_id {
+            cancelled.store(true, Ordering::Relaxed);
+            state_label.clone()
+        } else if let Some(pos) = queue.iter().position(|queued_op| queued_op.op_id == op_id) {
+            queue.remove(pos).unwrap().state_label
+        } else {
+            return Err(UpdateError::OperationNotFound);
+        };
+        let mut graph_guard = self.graph.write().await;
+        graph_guard.insert(
+            &state_label,
+            op_id,
+            (state_label.clone(), OpOut::Cancelled, Duration::ZERO),
+        );
+        Ok(())
+    }
+
     pub async fn list_instance_states(&self) -> Vec<String> {
         let instances = self.instances.read().await;
         let mut res = vec![];
@@ -940,10 +2252,59 @@ impl ApiState {
         for instance_state in &*instances {
             let instance_state = &*instance_state.lock().await;
             match instance_state {
-                InstanceState::Busy { state_label, op_id } => {
-                    res.push(format!("Busy({:?}, {:?})", state_label, op_id))
+                InstanceState::Busy {
+                    state_label,
+                    op_id,
+                    queue,
+                    ..
+                } => res.push(format!(
+                    "Busy({:?}, {:?}, queued: {})",
+                    state_label,
+                    op_id,
+                    queue.len()
+                )),
+                InstanceState::Available(pocket_ic) => {
+                    let mut details = vec![];
+                    let held = pocket_ic.held_canister_http_response_ids();
+                    if !held.is_empty() {
+                        let held: Vec<_> = held
+                            .into_iter()
+                            .map(|(subnet_id, request_id, due_time)| {
+                                format!(
+                                    "{}:{} at {}",
+                                    subnet_id,
+                                    request_id,
+                                    systemtime_to_unix_epoch_nanos(due_time)
+                                )
+                            })
+                            .collect();
+                        details.push(format!(
+                            "held canister HTTP responses: [{}]",
+                            held.join(", ")
+                        ));
+                    }
+                    let injected_faults = pocket_ic.injected_faults();
+                    if !injected_faults.is_empty() {
+                        let injected_faults: Vec<_> = injected_faults
+                            .into_iter()
+                            .map(|fault| {
+                                format!(
+                                    "{}:{} -> {:?}{}",
+                                    fault.canister_id,
+                                    fault.method,
+                                    fault.error.code,
+                                    if fault.repeat { " (repeated)" } else { "" }
+                                )
+                            })
+                            .collect();
+                        details.push(format!("injected faults: [{}]", injected_faults.join(", ")));
+                    }
+                    if details.is_empty() {
+                        res.push("Available".to_string())
+                    } else {
+                        res.push(format!("Available({})", details.join("; ")))
+                    }
                 }
-                InstanceState::Available(_) => res.push("Available".to_string()),
                 InstanceState::Deleted => res.push("Deleted".to_string()),
             }
         }
@@ -963,9 +2324,14 @@ impl ApiState {
     /// containing the result.
     ///
     /// Operations are _not_ queued by default. Thus, if the instance is busy with an existing operation,
-    /// the client has to retry until the operation is done. Some operations for which the client
+    /// the client has to retry until the operation is done. If a maximum queue depth is configured,
+    /// an operation submitted against a busy instance is queued up instead and [UpdateReply::Started]
+    /// is returned; [UpdateReply::Busy] is only returned if the queue is full. Some operations for which the client
     /// might be unable to retry are exceptions to this rule and they are queued up implicitly
-    /// by a retry mechanism inside PocketIc.
+    /// by a retry mechanism inside PocketIc. Read-only operations (see [Operation::is_read_only])
+    /// on a busy instance are neither rejected nor queued up, but computed on a read-only view
+    /// of the instance concurrently with the running operation. They are not isolated from
+    /// the running operation and might observe the effects of some of the rounds it executes.
     pub async fn update<O>(&self, op: Arc<O>, instance_id: InstanceId) -> UpdateResult
     where
         O: Operation + Send + Sync + 'static,
@@ -991,18 +2357,159 @@ impl ApiState {
             op,
             instance_id,
             sync_wait_time,
+            self.max_queue_depth,
         )
         .await
     }
 
+    /// Computes an operation on an instance marked as busy and stores the result in the graph.
+    /// Afterwards, the next queued operation (if any) is started in a new background task,
+    /// or the instance is made available again.
+    fn compute_operation(
+        instances: Arc<RwLock<Vec<Mutex<InstanceState>>>>,
+        graph: Arc<RwLock<Graph>>,
+        instance_id: InstanceId,
+        mut pocket_ic: PocketIc,
+        op: QueuedOperation,
+    ) -> (OpOut, StateLabel) {
+        let old_state_label = pocket_ic.get_state_label();
+        let op_id = op.op_id;
+        trace!(
+            "bg_task::start instance_id={} state_label={:?} op_id={}",
+            instance_id,
+            old_state_label,
+            op_id.0,
+        );
+        pocket_ic.set_operation_cancelled_flag(op.cancelled.clone());
+        // The budget of the instance might have been exhausted by an operation queued up earlier.
+        let (result, duration) = if let Some(cpu_time_used) = pocket_ic.cpu_budget_exhausted() {
+            (
+                OpOut::Error(PocketIcError::CpuBudgetExhausted(cpu_time_used)),
+                Duration::ZERO,
+            )
+        } else {
+            let start = Instant::now();
+            let result = (op.compute)(&mut pocket_ic);
+            let duration = start.elapsed();
+            pocket_ic.add_cpu_time_used(duration);
+            (result, duration)
+        };
+        // The result of a cancelled operation is discarded.
+        let result = if op.cancelled.load(Ordering::Relaxed) {
+            OpOut::Cancelled
+        } else {
+            result
+        };
+        let new_state_label = pocket_ic.get_state_label();
+        // add result to graph, but grab instance lock first!
+        let instances_locked = instances.blocking_read();
+        let mut graph_guard = graph.blocking_write();
+        graph_guard.insert(
+            &old_state_label,
+            op_id.clone(),
+            (new_state_label.clone(), result.clone(), duration),
+        );
+        // A queued operation is polled for using the state label returned when it was queued,
+        // which differs from the state label it was eventually computed on.
+        if op.state_label != old_state_label {
+            graph_guard.insert(
+                &op.state_label,
+                op_id.clone(),
+                (new_state_label, result.clone(), duration),
+            );
+        }
+        graph_guard.record_op_timing(instance_id, &op_id, duration);
+        drop(graph_guard);
+        let mut instance_state = instances_locked[instance_id].blocking_lock();
+        if let InstanceState::Busy {
+            state_label,
+            op_id: busy_op_id,
+            cancelled,
+            queue,
+            read_only_view,
+        } = &mut *instance_state
+        {
+            match queue.pop_front() {
+                Some(next_op) => {
+                    *state_label = pocket_ic.get_state_label();
+                    *busy_op_id = next_op.op_id.clone();
+                    *cancelled = next_op.cancelled.clone();
+                    *read_only_view = Arc::new(std::sync::Mutex::new(pocket_ic.read_only_view()));
+                    let instances = instances.clone();
+                    let graph = graph.clone();
+                    spawn_blocking(move || {
+                        Self::compute_operation(instances, graph, instance_id, pocket_ic, next_op)
+                    });
+                }
+                None => *instance_state = InstanceState::Available(pocket_ic),
+            }
+        } else {
+            // the instance was deleted in the meantime
+            std::mem::drop(pocket_ic);
+        }
+        drop(instance_state);
+        Self::evict_graph(&instances_locked, &graph);
+        trace!("bg_task::end instance_id={} op_id={}", instance_id, op_id.0);
+        // also return old_state_label so we can prune graph if we return quickly
+        (result, old_state_label)
+    }
+
+    /// Computes a read-only operation on the read-only view of a busy instance and stores the result
+    /// in the graph under the state label of the busy instance (which the operation does not change).
+    fn compute_read_only_operation(
+        graph: Arc<RwLock<Graph>>,
+        instance_id: InstanceId,
+        read_only_view: Arc<std::sync::Mutex<PocketIc>>,
+        op: QueuedOperation,
+    ) -> (OpOut, StateLabel) {
+        let start = Instant::now();
+        let result = (op.compute)(&mut read_only_view.lock().unwrap());
+        let duration = start.elapsed();
+        let mut graph_guard = graph.blocking_write();
+        graph_guard.insert(
+            &op.state_label,
+            op.op_id.clone(),
+            (op.state_label.clone(), result.clone(), duration),
+        );
+        graph_guard.record_op_timing(instance_id, &op.op_id, duration);
+        (result, op.state_label)
+    }
+
+    /// Evicts state labels from the graph if its capacity is exceeded.
+    /// The state labels of running and queued operations are kept so that
+    /// clients polling for their results do not observe them as expired.
+    fn evict_graph(instances_locked: &[Mutex<InstanceState>], graph: &RwLock<Graph>) {
+        if !graph.blocking_read().needs_eviction() {
+            return;
+        }
+        // The instance locks are held until the eviction is done
+        // so that no operation is started on an evicted state label.
+        let instance_states: Vec<_> = instances_locked
+            .iter()
+            .map(|instance_state| instance_state.blocking_lock())
+            .collect();
+        let mut pinned = HashSet::new();
+        for instance_state in &instance_states {
+            if let InstanceState::Busy {
+                state_label, queue, ..
+            } = &**instance_state
+            {
+                pinned.insert(state_label.clone());
+                pinned.extend(queue.iter().map(|op| op.state_label.clone()));
+            }
+        }
+        graph.blocking_write().evict(&pinned);
+    }
+
     /// Same as [Self::update] except that the timeout can be specified manually. This is useful in
     /// cases when clients want to enforce a long-running blocking call.
     async fn update_instances_with_timeout<O>(
         instances: Arc<RwLock<Vec<Mutex<InstanceState>>>>,
-        graph: Arc<RwLock<HashMap<StateLabel, Computations>>>,
+        graph: Arc<RwLock<Graph>>,
         op: Arc<O>,
         instance_id: InstanceId,
         sync_wait_time: Duration,
+        max_queue_depth: usize,
     ) -> UpdateResult
     where
         O: Operation + Send + Sync + 'static,
@@ -1015,84 +2522,118 @@ impl ApiState {
         );
         let instances_cloned = instances.clone();
         let instances_locked = instances_cloned.read().await;
-        let (bg_task, busy_outcome) = if let Some(instance_mutex) =
+        let (bg_task, pinned_state_label, busy_outcome) = if let Some(instance_mutex) =
             instances_locked.get(instance_id)
         {
             let mut instance_state = instance_mutex.lock().await;
-            // If this instance is busy, return the running op and initial state
-            match &*instance_state {
+            // If this instance is busy, queue up the op or return the running op and initial state
+            match &mut *instance_state {
                 InstanceState::Deleted => {
-                    return Err(UpdateError {
-                        message: "Instance was deleted".to_string(),
-                    });
+                    return Err(UpdateError::InstanceDeleted);
                 }
                 // TODO: cache lookup possible with this state_label and our own op_id
-                InstanceState::Busy { state_label, op_id } => {
-                    return Ok(UpdateReply::Busy {
-                        state_label: state_label.clone(),
-                        op_id: op_id.clone(),
-                    });
+                InstanceState::Busy {
+                    state_label,
+                    op_id,
+                    queue,
+                    read_only_view,
+                    ..
+                } => {
+                    // Read-only operations do not need to wait for the running operation.
+                    if op.is_read_only() {
+                        let op_id = op.id();
+                        let op = QueuedOperation::new(state_label.clone(), op);
+                        let read_only_view = read_only_view.clone();
+                        let graph = graph.clone();
+                        let bg_task: BackgroundTask = Box::new(move || {
+                            Self::compute_read_only_operation(
+                                graph,
+                                instance_id,
+                                read_only_view,
+                                op,
+                            )
+                        });
+                        (
+                            bg_task,
+                            state_label.clone(),
+                            UpdateReply::Started {
+                                state_label: state_label.clone(),
+                                op_id,
+                            },
+                        )
+                    } else if queue.len() < max_queue_depth {
+                        let queued_op = QueuedOperation::new(state_label.clone(), op);
+                        let queued_op_id = queued_op.op_id.clone();
+                        queue.push_back(queued_op);
+                        trace!(
+                            "update_with_timeout::queued instance_id={} op_id={} queue_len={}",
+                            instance_id,
+                            queued_op_id.0,
+                            queue.len(),
+                        );
+                        return Ok(UpdateReply::Started {
+                            state_label: state_label.clone(),
+                            op_id: queued_op_id,
+                        });
+                    } else if max_queue_depth > 0 {
+                        return Err(UpdateError::QueueFull);
+                    } else {
+                        return Ok(UpdateReply::Busy {
+                            state_label: state_label.clone(),
+                            op_id: op_id.clone(),
+                        });
+                    }
                 }
                 InstanceState::Available(pocket_ic) => {
+                    if let Some(cpu_time_used) = pocket_ic.cpu_budget_exhausted() {
+                        return Ok(UpdateReply::Output(OpOut::Error(
+                            PocketIcError::CpuBudgetExhausted(cpu_time_used),
+                        )));
+                    }
+
                     // move pocket_ic out
 
                     let state_label = pocket_ic.get_state_label();
                     let op_id = op.id();
+                    let op = QueuedOperation::new(state_label.clone(), op);
                     let busy = InstanceState::Busy {
                         state_label: state_label.clone(),
                         op_id: op_id.clone(),
+                        cancelled: op.cancelled.clone(),
+                        queue: VecDeque::new(),
+                        read_only_view: Arc::new(std::sync::Mutex::new(pocket_ic.read_only_view())),
                     };
-                    let InstanceState::Available(mut pocket_ic) =
+                    let InstanceState::Available(pocket_ic) =
                         std::mem::replace(&mut *instance_state, busy)
                     else {
                         unreachable!()
                     };
 
-                    let bg_task = {
-                        let old_state_label = state_label.clone();
-                        let op_id = op_id.clone();
+                    let bg_task: BackgroundTask = {
                         let graph = graph.clone();
-                        move || {
-                            trace!(
-                                "bg_task::start instance_id={} state_label={:?} op_id={}",
-                                instance_id,
-                                old_state_label,
-                                op_id.0,
-                            );
-                            let result = op.compute(&mut pocket_ic);
-                            let new_state_label = pocket_ic.get_state_label();
-                            // add result to graph, but grab instance lock first!
-                            let instances = instances.blocking_read();
-                            let mut graph_guard = graph.blocking_write();
-                            let cached_computations =
-                                graph_guard.entry(old_state_label.clone()).or_default();
-                            cached_computations
-                                .insert(op_id.clone(), (new_state_label, result.clone()));
-                            drop(graph_guard);
-                            let mut instance_state = instances[instance_id].blocking_lock();
-                            if let InstanceState::Deleted = &*instance_state {
-                                std::mem::drop(pocket_ic);
-                            } else {
-                                *instance_state = InstanceState::Available(pocket_ic);
-                            }
-                            trace!("bg_task::end instance_id={} op_id={}", instance_id, op_id.0);
-                            // also return old_state_label so we can prune graph if we return quickly
-                            (result, old_state_label)
-                        }
+                        Box::new(move || {
+                            Self::compute_operation(instances, graph, instance_id, pocket_ic, op)
+                        })
                     };
 
                     // cache miss: replace pocket_ic instance in the vector with Busy
-                    (bg_task, UpdateReply::Started { state_label, op_id })
+                    (
+                        bg_task,
+                        state_label.clone(),
+                        UpdateReply::Started { state_label, op_id },
+                    )
                 }
             }
         } else {
-            return Err(UpdateError {
-                message: "Instance not found".to_string(),
-            });
+            return Err(UpdateError::InstanceNotFound);
         };
         // drop lock, otherwise we end up with a deadlock
         std::mem::drop(instances_locked);
 
+        // The state label is pinned until the synchronous wait below is over
+        // so that the result is not evicted before it is returned to the client.
+        graph.write().await.pin(&pinned_state_label);
+
         // We schedule a blocking background task on the tokio runtime. Note that if all
         // blocking workers are busy, the task is put on a queue (which is what we want).
         //
@@ -1110,21 +2651,22 @@ impl ApiState {
         // note: this assumes that cancelling the JoinHandle does not stop the execution of the
         // background task. This only works because the background thread, in this case, is a
         // kernel thread.
-        if let Ok(Ok((op_out, old_state_label))) = time::timeout(sync_wait_time, bg_handle).await {
+        let res = time::timeout(sync_wait_time, bg_handle).await;
+        let mut graph_guard = graph.write().await;
+        graph_guard.unpin(&pinned_state_label);
+        if let Ok(Ok((op_out, old_state_label))) = res {
             trace!(
                 "update_with_timeout::synchronous instance_id={} op_id={}",
                 instance_id,
                 op_id,
             );
             // prune this sync computation from graph, but only the value
-            let mut graph_guard = graph.write().await;
-            let cached_computations = graph_guard.entry(old_state_label.clone()).or_default();
-            let (new_state_label, _) = cached_computations.get(&OpId(op_id.clone())).unwrap();
-            cached_computations.insert(OpId(op_id), (new_state_label.clone(), OpOut::Pruned));
+            graph_guard.prune(&old_state_label, &OpId(op_id));
             drop(graph_guard);
 
             return Ok(UpdateReply::Output(op_out));
         }
+        drop(graph_guard);
 
         trace!(
             "update_with_timeout::timeout instance_id={} op_id={}",
@@ -1138,9 +2680,16 @@ impl ApiState {
 impl std::fmt::Debug for InstanceState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Busy { state_label, op_id } => {
-                write!(f, "Busy {{ {state_label:?}, {op_id:?} }}")?
-            }
+            Self::Busy {
+                state_label,
+                op_id,
+                queue,
+                ..
+            } => write!(
+                f,
+                "Busy {{ {state_label:?}, {op_id:?}, queued: {} }}",
+                queue.len()
+            )?,
             Self::Available(pic) => write!(f, "Available({:?})", pic.get_state_label())?,
             Self::Deleted => write!(f, "Deleted")?,
         }
@@ -6,17 +6,23 @@ use ic_agent::agent::http_transport::ReqwestTransport;
 use ic_management_canister_types::ProvisionalCreateCanisterWithCyclesArgs;
 use ic_registry_proto_data_provider::ProtoRegistryDataProvider;
 use ic_utils::interfaces::ManagementCanister;
-use pocket_ic::common::rest::{HttpsConfig, InstanceConfig, SubnetConfigSet};
+use pocket_ic::common::rest::{
+    CreateHttpGatewayResponse, GatewayStatus, HttpGatewayBackend, HttpGatewayConfig,
+    HttpGatewayDetails, HttpsConfig, InstanceConfig, SubnetConfigSet,
+};
 use pocket_ic::{PocketIc, PocketIcBuilder, WasmResult};
 use rcgen::{CertificateParams, KeyPair};
 use reqwest::blocking::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use reqwest::Client as NonblockingClient;
-use reqwest::{StatusCode, Url};
+use reqwest::{Method, StatusCode, Url};
 use std::io::Read;
 use std::io::Write;
-use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
 use std::path::PathBuf;
 use std::process::{Child, Command};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tempfile::{NamedTempFile, TempDir};
 
@@ -382,6 +388,600 @@ async fn test_https_gateway() {
     test_gateway(true).await;
 }
 
+#[test]
+fn test_http_gateway_listen_addr() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+    let pic = PocketIc::from_config_and_server_url(
+        SubnetConfigSet {
+            application: 1,
+            ..Default::default()
+        },
+        server_url.clone(),
+    );
+
+    // create an HTTP gateway listening on the IPv4 loopback interface only
+    let http_gateway_config = HttpGatewayConfig {
+        listen_addr: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        listen_at: Some(0),
+        forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+        domains: None,
+        https_config: None,
+        shutdown_grace_period: None,
+        forward_headers: None,
+    };
+    let res = Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap();
+    let port = match res {
+        CreateHttpGatewayResponse::Created(info) => info.port,
+        CreateHttpGatewayResponse::Error { message } => {
+            panic!("Failed to create HTTP gateway: {}", message)
+        }
+    };
+    assert_ne!(port, 0);
+
+    let http_gateway_status = || {
+        Client::new()
+            .get(server_url.join("http_gateway/0/status").unwrap())
+            .send()
+            .unwrap()
+            .json::<GatewayStatus>()
+            .unwrap()
+    };
+    assert_eq!(http_gateway_status(), GatewayStatus::Running);
+
+    // the HTTP gateway is reachable over IPv4, but not over IPv6
+    let timeout = Duration::from_secs(1);
+    TcpStream::connect_timeout(
+        &SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), port),
+        timeout,
+    )
+    .unwrap();
+    assert!(TcpStream::connect_timeout(
+        &SocketAddr::new(IpAddr::V6(Ipv6Addr::LOCALHOST), port),
+        timeout
+    )
+    .is_err());
+
+    // stopping the HTTP gateway is reflected in its status
+    Client::new()
+        .post(server_url.join("http_gateway/0/stop").unwrap())
+        .send()
+        .unwrap();
+    assert_eq!(http_gateway_status(), GatewayStatus::Stopped);
+
+    // the port is released once stopping the HTTP gateway returned
+    // and thus a new HTTP gateway can be started on the same port right away
+    let http_gateway_config = HttpGatewayConfig {
+        listen_at: Some(port),
+        ..http_gateway_config
+    };
+    let res = Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap();
+    match res {
+        CreateHttpGatewayResponse::Created(info) => assert_eq!(info.port, port),
+        CreateHttpGatewayResponse::Error { message } => {
+            panic!("Failed to restart HTTP gateway: {}", message)
+        }
+    }
+}
+
+#[test]
+fn test_http_gateway_port_in_use() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+    let pic = PocketIc::from_config_and_server_url(
+        SubnetConfigSet {
+            application: 1,
+            ..Default::default()
+        },
+        server_url.clone(),
+    );
+
+    // occupy a port so that the HTTP gateway cannot bind to it
+    let listener = std::net::TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let http_gateway_config = HttpGatewayConfig {
+        listen_addr: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        listen_at: Some(port),
+        forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+        domains: None,
+        https_config: None,
+        shutdown_grace_period: None,
+        forward_headers: None,
+    };
+    let res = Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap();
+    let message = match res {
+        CreateHttpGatewayResponse::Created(info) => {
+            panic!("Unexpectedly created HTTP gateway: {:?}", info)
+        }
+        CreateHttpGatewayResponse::Error { message } => message,
+    };
+    assert!(message.contains("Failed to start HTTP gateway"));
+
+    // the failed HTTP gateway retains its error
+    let status = Client::new()
+        .get(server_url.join("http_gateway/0/status").unwrap())
+        .send()
+        .unwrap()
+        .json::<GatewayStatus>()
+        .unwrap();
+    assert_eq!(status, GatewayStatus::Failed(message));
+
+    // the server keeps serving HTTP gateways
+    drop(listener);
+    let res = Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap();
+    match res {
+        CreateHttpGatewayResponse::Created(info) => assert_eq!(info.port, port),
+        CreateHttpGatewayResponse::Error { message } => {
+            panic!("Failed to create HTTP gateway: {}", message)
+        }
+    }
+}
+
+#[test]
+fn test_multiple_http_gateways() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+    let pic = PocketIc::from_config_and_server_url(
+        SubnetConfigSet {
+            application: 1,
+            ..Default::default()
+        },
+        server_url.clone(),
+    );
+
+    // create two HTTP gateways with distinct domains for the same instance
+    let create_http_gateway = |domain: &str| {
+        let http_gateway_config = HttpGatewayConfig {
+            listen_addr: None,
+            listen_at: None,
+            forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+            domains: Some(vec![domain.to_string()]),
+            https_config: None,
+            shutdown_grace_period: None,
+            forward_headers: None,
+        };
+        let res = Client::new()
+            .post(server_url.join("http_gateway").unwrap())
+            .json(&http_gateway_config)
+            .send()
+            .unwrap()
+            .json::<CreateHttpGatewayResponse>()
+            .unwrap();
+        match res {
+            CreateHttpGatewayResponse::Created(info) => info,
+            CreateHttpGatewayResponse::Error { message } => {
+                panic!("Failed to create HTTP gateway: {}", message)
+            }
+        }
+    };
+    let first = create_http_gateway("foo.localhost");
+    let second = create_http_gateway("bar.localhost");
+    assert_ne!(first.port, second.port);
+
+    let list_http_gateways = || {
+        Client::new()
+            .get(server_url.join("http_gateway").unwrap())
+            .send()
+            .unwrap()
+            .json::<Vec<HttpGatewayDetails>>()
+            .unwrap()
+    };
+    let http_gateways = list_http_gateways();
+    assert_eq!(
+        http_gateways,
+        vec![
+            HttpGatewayDetails {
+                gateway_id: first.instance_id,
+                port: first.port,
+                forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+                domains: vec!["foo.localhost".to_string()],
+                https: false,
+                status: GatewayStatus::Running,
+            },
+            HttpGatewayDetails {
+                gateway_id: second.instance_id,
+                port: second.port,
+                forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+                domains: vec!["bar.localhost".to_string()],
+                https: false,
+                status: GatewayStatus::Running,
+            },
+        ]
+    );
+
+    // stopping the first HTTP gateway does not affect the second one
+    Client::new()
+        .post(
+            server_url
+                .join(&format!("http_gateway/{}/stop", first.instance_id))
+                .unwrap(),
+        )
+        .send()
+        .unwrap();
+    let http_gateways = list_http_gateways();
+    assert_eq!(
+        http_gateways[first.instance_id].status,
+        GatewayStatus::Stopped
+    );
+    assert_eq!(
+        http_gateways[second.instance_id].status,
+        GatewayStatus::Running
+    );
+    assert_eq!(http_gateways[second.instance_id].port, second.port);
+
+    // a single HTTP gateway can be retrieved by its id
+    let get_http_gateway = |id: usize| {
+        Client::new()
+            .get(server_url.join(&format!("http_gateway/{}", id)).unwrap())
+            .send()
+            .unwrap()
+    };
+    let http_gateway = get_http_gateway(second.instance_id)
+        .json::<HttpGatewayDetails>()
+        .unwrap();
+    assert_eq!(http_gateway, http_gateways[second.instance_id]);
+    assert_eq!(get_http_gateway(42).status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_http_gateway_unreachable_backend() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+
+    // bind (and immediately release) a port so that no backend is listening on it
+    let backend_port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let http_gateway_config = HttpGatewayConfig {
+        listen_addr: None,
+        listen_at: None,
+        forward_to: HttpGatewayBackend::Replica(format!("http://127.0.0.1:{}/", backend_port)),
+        domains: None,
+        https_config: None,
+        shutdown_grace_period: None,
+        forward_headers: None,
+    };
+    let res = Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap();
+
+    // the HTTP gateway cannot fetch the root key and thus never becomes ready
+    match res {
+        CreateHttpGatewayResponse::Error { message } => {
+            assert!(message.contains("Failed to fetch root key"));
+        }
+        CreateHttpGatewayResponse::Created(info) => {
+            panic!("Unexpectedly created HTTP gateway: {:?}", info)
+        }
+    }
+
+    // the failure is retained in the status of the HTTP gateway
+    let status = Client::new()
+        .get(server_url.join("http_gateway/0/status").unwrap())
+        .send()
+        .unwrap()
+        .json::<GatewayStatus>()
+        .unwrap();
+    match status {
+        GatewayStatus::Failed(message) => assert!(message.contains("Failed to fetch root key")),
+        _ => panic!("Unexpected HTTP gateway status: {:?}", status),
+    }
+}
+
+/// Starts a minimal HTTP/1.1 proxy forwarding all requests to the given URL
+/// and recording the path and headers of every request it receives.
+fn start_recording_proxy(target: Url) -> (u16, Arc<Mutex<Vec<(String, HeaderMap)>>>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let requests = Arc::new(Mutex::new(vec![]));
+    let recorded_requests = requests.clone();
+    std::thread::spawn(move || {
+        let client = Client::new();
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let method = Method::from_bytes(parts.next().unwrap().as_bytes()).unwrap();
+            let path = parts.next().unwrap().to_string();
+            let mut headers = HeaderMap::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let Some((name, value)) = line.trim_end().split_once(':') else {
+                    break;
+                };
+                headers.append(
+                    HeaderName::from_bytes(name.trim().as_bytes()).unwrap(),
+                    HeaderValue::from_str(value.trim()).unwrap(),
+                );
+            }
+            let content_length = headers
+                .get(reqwest::header::CONTENT_LENGTH)
+                .map(|v| v.to_str().unwrap().parse().unwrap())
+                .unwrap_or(0);
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let response = client
+                .request(method, target.join(path.trim_start_matches('/')).unwrap())
+                .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+                .body(body)
+                .send()
+                .unwrap();
+            recorded_requests.lock().unwrap().push((path, headers));
+            let status = response.status();
+            let body = response.bytes().unwrap();
+            write!(
+                stream,
+                "HTTP/1.1 {}\r\ncontent-type: application/cbor\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                status,
+                body.len()
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+    (port, requests)
+}
+
+#[test]
+fn test_http_gateway_forwards_headers() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+    let pic = PocketIc::from_config_and_server_url(
+        SubnetConfigSet {
+            application: 1,
+            ..Default::default()
+        },
+        server_url.clone(),
+    );
+    let instance_url = server_url
+        .join(&format!("instances/{}/", pic.instance_id()))
+        .unwrap();
+    let (backend_port, requests) = start_recording_proxy(instance_url);
+
+    let http_gateway_config = HttpGatewayConfig {
+        listen_addr: None,
+        listen_at: None,
+        forward_to: HttpGatewayBackend::Replica(format!("http://127.0.0.1:{}/", backend_port)),
+        domains: None,
+        https_config: None,
+        shutdown_grace_period: None,
+        forward_headers: None,
+    };
+    let port = match Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap()
+    {
+        CreateHttpGatewayResponse::Created(info) => info.port,
+        CreateHttpGatewayResponse::Error { message } => {
+            panic!("Failed to create HTTP gateway: {}", message)
+        }
+    };
+
+    // the (invalid) request is forwarded to the backend regardless of its content
+    Client::new()
+        .post(format!(
+            "http://[::1]:{}/api/v2/canister/{}/query",
+            port,
+            Principal::management_canister()
+        ))
+        .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+        .header("Idempotency-Key", "42")
+        .header(
+            "traceparent",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        )
+        .header("x-custom-header", "not on the allowlist")
+        .body(vec![])
+        .send()
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    let (_, headers) = requests
+        .iter()
+        .find(|(path, _)| path.ends_with("/query"))
+        .expect("query was not forwarded to the backend");
+    assert_eq!(headers.get("idempotency-key").unwrap(), "42");
+    assert_eq!(
+        headers.get("traceparent").unwrap(),
+        "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+    );
+    assert!(headers.get("x-custom-header").is_none());
+}
+
+const LARGE_REPLY_WAT: &str = r#"
+    (module
+        (import "ic0" "msg_reply" (func $msg_reply))
+        (import "ic0" "msg_reply_data_append"
+            (func $msg_reply_data_append (param i32 i32)))
+        (func $read
+            (call $msg_reply_data_append (i32.const 0) (i32.const 65536))
+            (call $msg_reply))
+        (memory $memory 1)
+        (export "canister_query read" (func $read))
+    )
+"#;
+
+#[test]
+fn test_http_gateway_gzip() {
+    let (server_url, _) = start_server_helper(None, Some(5), false);
+    let pic = PocketIc::from_config_and_server_url(
+        SubnetConfigSet {
+            application: 1,
+            ..Default::default()
+        },
+        server_url.clone(),
+    );
+    let canister_id = pic.create_canister();
+    pic.add_cycles(canister_id, 2_000_000_000_000);
+    pic.install_canister(
+        canister_id,
+        wat::parse_str(LARGE_REPLY_WAT).unwrap(),
+        vec![],
+        None,
+    );
+
+    let http_gateway_config = HttpGatewayConfig {
+        listen_addr: None,
+        listen_at: None,
+        forward_to: HttpGatewayBackend::PocketIcInstance(pic.instance_id()),
+        domains: None,
+        https_config: None,
+        shutdown_grace_period: None,
+        forward_headers: None,
+    };
+    let port = match Client::new()
+        .post(server_url.join("http_gateway").unwrap())
+        .json(&http_gateway_config)
+        .send()
+        .unwrap()
+        .json::<CreateHttpGatewayResponse>()
+        .unwrap()
+    {
+        CreateHttpGatewayResponse::Created(info) => info.port,
+        CreateHttpGatewayResponse::Error { message } => {
+            panic!("Failed to create HTTP gateway: {}", message)
+        }
+    };
+
+    // an anonymous query whose reply (64 KiB) exceeds the compression threshold
+    let ingress_expiry = pic.get_time() + Duration::from_secs(240);
+    let ingress_expiry = ingress_expiry
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let text = |s: &str| serde_cbor::Value::Text(s.to_string());
+    let content = serde_cbor::Value::Map(
+        [
+            (text("request_type"), text("query")),
+            (
+                text("canister_id"),
+                serde_cbor::Value::Bytes(canister_id.as_slice().to_vec()),
+            ),
+            (text("method_name"), text("read")),
+            (text("arg"), serde_cbor::Value::Bytes(vec![])),
+            (
+                text("sender"),
+                serde_cbor::Value::Bytes(Principal::anonymous().as_slice().to_vec()),
+            ),
+            (
+                text("ingress_expiry"),
+                serde_cbor::Value::Integer(ingress_expiry.into()),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    let envelope = serde_cbor::Value::Map([(text("content"), content)].into_iter().collect());
+    let envelope = serde_cbor::to_vec(&envelope).unwrap();
+    let query_url = |api_version: &str| {
+        format!(
+            "http://[::1]:{}/api/{}/canister/{}/query",
+            port, api_version, canister_id
+        )
+    };
+    let reply_len = |body: &[u8]| {
+        let response: serde_cbor::Value = serde_cbor::from_slice(body).unwrap();
+        let serde_cbor::Value::Map(response) = response else {
+            panic!("Unexpected query response: {:?}", response);
+        };
+        assert_eq!(response.get(&text("status")), Some(&text("replied")));
+        let Some(serde_cbor::Value::Map(reply)) = response.get(&text("reply")) else {
+            panic!("Unexpected query response: {:?}", response);
+        };
+        match reply.get(&text("arg")) {
+            Some(serde_cbor::Value::Bytes(arg)) => arg.len(),
+            arg => panic!("Unexpected reply: {:?}", arg),
+        }
+    };
+
+    // without `Accept-Encoding: gzip`, the response is not compressed
+    let response = Client::new()
+        .post(query_url("v2"))
+        .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+        .body(envelope.clone())
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get(reqwest::header::CONTENT_ENCODING)
+        .is_none());
+    assert_eq!(reply_len(&response.bytes().unwrap()), 65536);
+
+    // the query endpoint is also available under v3
+    let response = Client::new()
+        .post(query_url("v3"))
+        .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+        .body(envelope.clone())
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(reply_len(&response.bytes().unwrap()), 65536);
+
+    // a gzip-encoded request is decompressed and, with `Accept-Encoding: gzip`,
+    // the response is compressed
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(&envelope).unwrap();
+    let gzipped_envelope = encoder.finish().unwrap();
+    let response = Client::new()
+        .post(query_url("v2"))
+        .header(reqwest::header::CONTENT_TYPE, "application/cbor")
+        .header(reqwest::header::CONTENT_ENCODING, "gzip")
+        .header(reqwest::header::ACCEPT_ENCODING, "gzip")
+        .body(gzipped_envelope)
+        .send()
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response
+            .headers()
+            .get(reqwest::header::CONTENT_ENCODING)
+            .unwrap()
+            .as_bytes(),
+        b"gzip"
+    );
+    let compressed = response.bytes().unwrap();
+    assert!(compressed.len() < 65536);
+    let mut body = vec![];
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_end(&mut body)
+        .unwrap();
+    assert_eq!(reply_len(&body), 65536);
+}
+
 #[test]
 fn test_specified_id() {
     use ic_utils::interfaces::ManagementCanister;
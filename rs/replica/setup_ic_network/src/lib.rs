@@ -460,6 +460,7 @@ fn start_consensus(
             subnet_id,
             Arc::clone(&consensus_block_cache),
             Arc::clone(&state_reader),
+            idkg::DEFAULT_LOOK_AHEAD,
             metrics_registry.clone(),
         ));
 
@@ -470,6 +471,9 @@ fn start_consensus(
                 Arc::clone(&consensus_block_cache),
                 Arc::clone(&consensus_crypto),
                 Arc::clone(&state_reader),
+                idkg::ThresholdSignerMode::Full,
+                idkg::INACTIVE_TRANSCRIPT_PURGE_SECS,
+                None,
                 metrics_registry.clone(),
                 log.clone(),
                 malicious_flags,
@@ -8,7 +8,9 @@ use ic_config::{
 use ic_consensus::consensus::payload_builder::PayloadBuilderImpl;
 use ic_consensus::dkg::{make_registry_cup, make_registry_cup_from_cup_contents};
 use ic_consensus_utils::crypto::SignVerify;
-use ic_constants::{MAX_INGRESS_TTL, PERMITTED_DRIFT, SMALL_APP_SUBNET_MAX_SIZE};
+use ic_constants::{
+    MAX_INGRESS_TTL, PERMITTED_DRIFT, PERMITTED_DRIFT_AT_VALIDATOR, SMALL_APP_SUBNET_MAX_SIZE,
+};
 use ic_crypto_ecdsa_secp256k1::{PrivateKey, PublicKey};
 use ic_crypto_extended_bip32::{DerivationIndex, DerivationPath};
 use ic_crypto_test_utils_ni_dkg::{
@@ -125,7 +127,8 @@ use ic_types::{
     signature::ThresholdSignature,
     time::GENESIS,
     xnet::{CertifiedStreamSlice, StreamIndex},
-    CanisterLog, CountBytes, CryptoHashOfPartialState, Height, NodeId, Randomness, RegistryVersion,
+    CanisterLog, CountBytes, CryptoHashOfPartialState, Height, NodeId, NumSeconds, Randomness,
+    RegistryVersion,
 };
 pub use ic_types::{
     canister_http::{
@@ -507,6 +510,67 @@ impl PocketIngressPool {
     }
 }
 
+/// Policy applied to XNet messages from a remote subnet to a `StateMachine`
+/// to simulate slow or unreliable cross-subnet messaging.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum XNetPolicy {
+    /// A stream slice is only inducted after it has been withheld
+    /// for the given number of attempts to fetch it (i.e., rounds).
+    Delay { ticks: u64 },
+    /// Every attempt to fetch a stream slice fails with the given probability (in percent)
+    /// drawn from a PRNG with the given seed. Since XNet streams are reliable, a dropped
+    /// slice is fetched again in a later round.
+    Drop { probability_percent: u8, seed: u64 },
+}
+
+/// An `XNetPolicy` together with the state required to apply it.
+struct XNetPolicyState {
+    policy: XNetPolicy,
+    rng: StdRng,
+    /// The begin index of the stream slice currently withheld by a `XNetPolicy::Delay`
+    /// and the remaining number of attempts for which it is withheld.
+    delayed: Option<(Option<StreamIndex>, u64)>,
+}
+
+impl XNetPolicyState {
+    fn new(policy: XNetPolicy) -> Self {
+        let seed = match policy {
+            XNetPolicy::Delay { .. } => 0,
+            XNetPolicy::Drop { seed, .. } => seed,
+        };
+        Self {
+            policy,
+            rng: StdRng::seed_from_u64(seed),
+            delayed: None,
+        }
+    }
+
+    /// Returns `true` if a stream slice starting at the given index should be withheld.
+    fn withhold(&mut self, msg_begin: Option<StreamIndex>) -> bool {
+        match self.policy {
+            XNetPolicy::Delay { ticks } => match self.delayed {
+                Some((begin, 0)) if begin == msg_begin => {
+                    self.delayed = None;
+                    false
+                }
+                Some((begin, ref mut remaining)) if begin == msg_begin => {
+                    *remaining -= 1;
+                    true
+                }
+                _ if ticks == 0 => false,
+                _ => {
+                    self.delayed = Some((msg_begin, ticks - 1));
+                    true
+                }
+            },
+            XNetPolicy::Drop {
+                probability_percent,
+                ..
+            } => self.rng.gen_range(0..100) < probability_percent,
+        }
+    }
+}
+
 /// Struct mocking the pool of XNet messages required for
 /// instantiating `XNetPayloadBuilderImpl` in `StateMachine`.
 struct PocketXNetSlicePoolImpl {
@@ -515,16 +579,20 @@ struct PocketXNetSlicePoolImpl {
     subnets: Arc<RwLock<BTreeMap<SubnetId, Arc<StateMachine>>>>,
     /// Subnet ID of the `StateMachine` containing the pool.
     own_subnet_id: SubnetId,
+    /// Policies applied to XNet messages from remote subnets (keyed by their subnet IDs).
+    xnet_policies: Arc<Mutex<BTreeMap<SubnetId, XNetPolicyState>>>,
 }
 
 impl PocketXNetSlicePoolImpl {
     fn new(
         subnets: Arc<RwLock<BTreeMap<SubnetId, Arc<StateMachine>>>>,
         own_subnet_id: SubnetId,
+        xnet_policies: Arc<Mutex<BTreeMap<SubnetId, XNetPolicyState>>>,
     ) -> Self {
         Self {
             subnets,
             own_subnet_id,
+            xnet_policies,
         }
     }
 }
@@ -550,6 +618,20 @@ impl XNetSlicePool for PocketXNetSlicePoolImpl {
             msg_limit,
             byte_limit,
         );
+        // Policies only apply to slices containing messages (and not just signals).
+        let has_messages = sm
+            .get_latest_state()
+            .get_stream(&self.own_subnet_id)
+            .is_some_and(|stream| {
+                stream.messages_end() > msg_begin.unwrap_or(stream.messages_begin())
+            });
+        if has_messages {
+            if let Some(policy) = self.xnet_policies.lock().unwrap().get_mut(&subnet_id) {
+                if policy.withhold(msg_begin) {
+                    return Ok(None);
+                }
+            }
+        }
         Ok(certified_stream
             .map(|certified_stream| {
                 let mut num_bytes = certified_slice_count_bytes(&certified_stream).unwrap();
@@ -643,11 +725,14 @@ pub struct StateMachine {
     idkg_subnet_secret_keys: BTreeMap<MasterPublicKeyId, PrivateKey>,
     replica_logger: ReplicaLogger,
     pub nodes: Vec<StateMachineNode>,
+    // Clock skews (in nanoseconds) of individual nodes (see [StateMachine::set_node_clock_skew]).
+    node_clock_skews: RwLock<BTreeMap<NodeId, i64>>,
     pub batch_summary: Option<BatchSummary>,
     time_source: Arc<FastForwardTimeSource>,
     consensus_pool_cache: Arc<FakeConsensusPoolCache>,
     canister_http_pool: Arc<RwLock<CanisterHttpPoolImpl>>,
     canister_http_payload_builder: Arc<CanisterHttpPayloadBuilderImpl>,
+    xnet_policies: Arc<Mutex<BTreeMap<SubnetId, XNetPolicyState>>>,
     certified_height_tx: watch::Sender<Height>,
     pub ingress_watcher_handle: IngressWatcherHandle,
     /// A drop guard to gracefully cancel the ingress watcher task.
@@ -1024,7 +1109,11 @@ impl StateMachineBuilder {
         // Instantiate a `XNetPayloadBuilderImpl`.
         // We need to use a deterministic PRNG - so we use an arbitrary fixed seed, e.g., 42.
         let rng = Arc::new(Some(Mutex::new(StdRng::seed_from_u64(42))));
-        let xnet_slice_pool_impl = Box::new(PocketXNetSlicePoolImpl::new(subnets, subnet_id));
+        let xnet_slice_pool_impl = Box::new(PocketXNetSlicePoolImpl::new(
+            subnets,
+            subnet_id,
+            sm.xnet_policies.clone(),
+        ));
         let metrics = Arc::new(XNetPayloadBuilderMetrics::new(&sm.metrics_registry));
         let xnet_payload_builder = XNetPayloadBuilderImpl::new_from_components(
             sm.state_manager.clone(),
@@ -1531,11 +1620,13 @@ impl StateMachine {
             idkg_subnet_secret_keys,
             replica_logger: replica_logger.clone(),
             nodes,
+            node_clock_skews: Default::default(),
             batch_summary: None,
             time_source,
             consensus_pool_cache,
             canister_http_pool,
             canister_http_payload_builder,
+            xnet_policies: Default::default(),
         }
     }
 
@@ -1764,6 +1855,19 @@ impl StateMachine {
             )));
         }
 
+        // Validate the ingress expiry against the local clock of the node receiving
+        // the ingress message, which might be skewed (see `Self::set_node_clock_skew`).
+        let node_time = self.get_node_time(self.nodes[0].node_id);
+        let min_allowed_expiry = node_time;
+        let max_allowed_expiry = node_time + MAX_INGRESS_TTL + PERMITTED_DRIFT_AT_VALIDATOR;
+        let expiry = msg.expiry_time();
+        if expiry < min_allowed_expiry || expiry > max_allowed_expiry {
+            return Err(SubmitIngressError::HttpError(format!(
+                "Specified ingress_expiry not within expected range: Minimum allowed expiry: {}, Maximum allowed expiry: {}, Provided expiry: {}",
+                min_allowed_expiry, max_allowed_expiry, expiry
+            )));
+        }
+
         // Run `IngressFilter` on the ingress message.
         let ingress_filter = self.ingress_filter.clone();
         self.runtime
@@ -1790,6 +1894,18 @@ impl StateMachine {
             .push(msg, self.get_time());
     }
 
+    /// Returns the ingress messages in the ingress pool, i.e., the ingress messages
+    /// that have been submitted, but not yet included in a block.
+    pub fn ingress_pool_messages(&self) -> Vec<SignedIngress> {
+        self.ingress_pool
+            .read()
+            .unwrap()
+            .validated
+            .values()
+            .map(|artifact| artifact.msg.signed_ingress.clone())
+            .collect()
+    }
+
     pub fn mock_canister_http_response(
         &self,
         request_id: u64,
@@ -2099,6 +2215,11 @@ impl StateMachine {
             .unwrap_or_else(|_| error!(self.replica_logger, "Time went backwards."));
     }
 
+    /// Returns the path of the state directory of this state machine.
+    pub fn state_dir(&self) -> PathBuf {
+        self.state_dir.path()
+    }
+
     /// Returns the current state machine time.
     pub fn time(&self) -> SystemTime {
         SystemTime::UNIX_EPOCH + Duration::from_nanos(self.time.load(Ordering::Relaxed))
@@ -2134,6 +2255,75 @@ impl StateMachine {
         self.set_time(self.time() + amount);
     }
 
+    /// Sets the policy applied to XNet messages from the given remote subnet
+    /// to this subnet. Without a policy (`None`), XNet messages are inducted
+    /// as soon as possible.
+    pub fn set_xnet_policy(&self, from_subnet_id: SubnetId, policy: Option<XNetPolicy>) {
+        let mut xnet_policies = self.xnet_policies.lock().unwrap();
+        match policy {
+            Some(policy) => {
+                xnet_policies.insert(from_subnet_id, XNetPolicyState::new(policy));
+            }
+            None => {
+                xnet_policies.remove(&from_subnet_id);
+            }
+        }
+    }
+
+    /// Returns the policies applied to XNet messages to this subnet
+    /// keyed by the IDs of the remote subnets they apply to.
+    pub fn xnet_policies(&self) -> BTreeMap<SubnetId, XNetPolicy> {
+        self.xnet_policies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(subnet_id, policy_state)| (*subnet_id, policy_state.policy))
+            .collect()
+    }
+
+    /// Sets the clock skew of the given node in nanoseconds, i.e., the amount by which
+    /// the local clock of the node is ahead of (if positive) or behind (if negative)
+    /// the state machine time. The time used for executing rounds is not affected
+    /// since it is agreed upon by all nodes of the subnet, but the node validates
+    /// the expiry of ingress messages it receives against its local clock
+    /// (see [`StateMachine::submit_signed_ingress`]).
+    ///
+    /// Panics if the node does not belong to this subnet.
+    pub fn set_node_clock_skew(&self, node_id: NodeId, skew_nanos: i64) {
+        assert!(
+            self.nodes.iter().any(|node| node.node_id == node_id),
+            "Node {} does not belong to subnet {}",
+            node_id,
+            self.subnet_id
+        );
+        self.node_clock_skews
+            .write()
+            .unwrap()
+            .insert(node_id, skew_nanos);
+    }
+
+    /// Returns the clock skews (in nanoseconds) of the nodes whose clock skew has been set.
+    pub fn node_clock_skews(&self) -> BTreeMap<NodeId, i64> {
+        self.node_clock_skews.read().unwrap().clone()
+    }
+
+    /// Returns the time reported by the local clock of the given node,
+    /// i.e., the current state machine time adjusted by the clock skew of the node.
+    pub fn get_node_time(&self, node_id: NodeId) -> Time {
+        let skew_nanos = self
+            .node_clock_skews
+            .read()
+            .unwrap()
+            .get(&node_id)
+            .cloned()
+            .unwrap_or_default();
+        Time::from_nanos_since_unix_epoch(
+            self.get_time()
+                .as_nanos_since_unix_epoch()
+                .saturating_add_signed(skew_nanos),
+        )
+    }
+
     /// Returns the root key of the state machine.
     pub fn root_key(&self) -> ThresholdSigPublicKey {
         self.public_key
@@ -2961,6 +3151,24 @@ impl StateMachine {
     ///   * The specified canister does not exist.
     ///   * The specified canister does not have a module installed.
     pub fn stable_memory(&self, canister_id: CanisterId) -> Vec<u8> {
+        self.stable_memory_range(canister_id, 0, usize::MAX).1
+    }
+
+    /// Returns the size of the stable memory of the specified canister in bytes
+    /// and (at most) `length` bytes of the stable memory starting at `offset`.
+    /// No bytes are returned if `offset` is beyond the end of the stable memory.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if:
+    ///   * The specified canister does not exist.
+    ///   * The specified canister does not have a module installed.
+    pub fn stable_memory_range(
+        &self,
+        canister_id: CanisterId,
+        offset: usize,
+        length: usize,
+    ) -> (usize, Vec<u8>) {
         let replicated_state = self.state_manager.get_latest_state().take();
         let memory = &replicated_state
             .canister_state(&canister_id)
@@ -2970,10 +3178,13 @@ impl StateMachine {
             .unwrap_or_else(|| panic!("Canister {} has no module", canister_id))
             .stable_memory;
 
-        let mut dst = vec![0u8; memory.size.get() * WASM_PAGE_SIZE_IN_BYTES];
+        let size = memory.size.get() * WASM_PAGE_SIZE_IN_BYTES;
+        let start = offset.min(size);
+        let end = start.saturating_add(length).min(size);
+        let mut dst = vec![0u8; end - start];
         let buffer = Buffer::new(memory.page_map.clone());
-        buffer.read(&mut dst, 0);
-        dst
+        buffer.read(&mut dst, start);
+        (size, dst)
     }
 
     /// Returns the canister log of the specified canister.
@@ -3096,6 +3307,68 @@ impl StateMachine {
         balance
     }
 
+    /// Replaces the controllers of the specified canister and returns the resulting controllers.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the specified canister does not exist.
+    pub fn set_controllers(
+        &self,
+        canister_id: CanisterId,
+        controllers: Vec<PrincipalId>,
+    ) -> BTreeSet<PrincipalId> {
+        let (height, mut state) = self.state_manager.take_tip();
+        let canister_state = state
+            .canister_state_mut(&canister_id)
+            .unwrap_or_else(|| panic!("Canister {} not found", canister_id));
+        canister_state.system_state.controllers = controllers.into_iter().collect();
+        let controllers = canister_state.system_state.controllers.clone();
+        self.state_manager.commit_and_certify(
+            state,
+            height.increment(),
+            CertificationScope::Metadata,
+            None,
+        );
+        controllers
+    }
+
+    /// Returns the freezing threshold (in seconds) of the specified canister.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the specified canister does not exist.
+    pub fn freezing_threshold(&self, canister_id: CanisterId) -> u64 {
+        let state = self.state_manager.get_latest_state().take();
+        state
+            .canister_state(&canister_id)
+            .unwrap_or_else(|| panic!("Canister {} not found", canister_id))
+            .system_state
+            .freeze_threshold
+            .get()
+    }
+
+    /// Sets the freezing threshold (in seconds) of the specified canister
+    /// and returns the resulting freezing threshold.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the specified canister does not exist.
+    pub fn set_freezing_threshold(&self, canister_id: CanisterId, threshold_secs: u64) -> u64 {
+        let (height, mut state) = self.state_manager.take_tip();
+        let canister_state = state
+            .canister_state_mut(&canister_id)
+            .unwrap_or_else(|| panic!("Canister {} not found", canister_id));
+        canister_state.system_state.freeze_threshold = NumSeconds::from(threshold_secs);
+        let threshold_secs = canister_state.system_state.freeze_threshold.get();
+        self.state_manager.commit_and_certify(
+            state,
+            height.increment(),
+            CertificationScope::Metadata,
+            None,
+        );
+        threshold_secs
+    }
+
     /// Returns `sign_with_ecdsa` contexts from internal subnet call context manager.
     pub fn sign_with_ecdsa_contexts(&self) -> BTreeMap<CallbackId, SignWithThresholdContext> {
         let state = self.state_manager.get_latest_state().take();
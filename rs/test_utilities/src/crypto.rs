@@ -72,6 +72,8 @@ pub struct CryptoReturningOk {
     pub loaded_transcripts: std::sync::RwLock<BTreeSet<NiDkgId>>,
     // Here we keep track of all transcripts ids asked to be retained.
     pub retained_transcripts: std::sync::RwLock<Vec<HashSet<NiDkgId>>>,
+    // Here we keep track of all IDKG transcript ids asked to be retained.
+    pub retained_idkg_transcripts: std::sync::RwLock<Vec<HashSet<IDkgTranscriptId>>>,
 }
 
 impl<T: Signable> BasicSigner<T> for CryptoReturningOk {
@@ -435,8 +437,12 @@ impl IDkgProtocol for CryptoReturningOk {
 
     fn retain_active_transcripts(
         &self,
-        _active_transcripts: &HashSet<IDkgTranscript>,
+        active_transcripts: &HashSet<IDkgTranscript>,
     ) -> Result<(), IDkgRetainKeysError> {
+        self.retained_idkg_transcripts
+            .write()
+            .unwrap()
+            .push(active_transcripts.iter().map(|t| t.transcript_id).collect());
         Ok(())
     }
 }
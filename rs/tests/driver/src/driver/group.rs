@@ -22,7 +22,7 @@ use crate::driver::{
 use crate::driver::{
     log_events,
     pot_dsl::{PotSetupFn, SysTestFn},
-    test_env::{TestEnv, TestEnvAttribute},
+    test_env::{TestEnv, TestEnvAttribute, TestRngSeed, TEST_RNG_SEED_ENV_VAR},
     test_env_api::HasIcDependencies,
     test_setup::{GroupSetup, InfraProvider},
 };
@@ -372,7 +372,17 @@ impl SystemTestSubGroup {
                     let group_ctx = ctx.group_ctx.clone();
                     move || {
                         debug!(logger, ">>> test_fn({})", &task_id);
+                        let test_name = task_id.name();
                         let env = get_or_create_env(group_ctx, task_id).unwrap();
+                        let seed = TestRngSeed::for_test(&test_name);
+                        seed.write_attribute(&env);
+                        info!(
+                            logger,
+                            "Seed of the random number generator of test {}: {} (set {} to override)",
+                            test_name,
+                            seed.0,
+                            TEST_RNG_SEED_ENV_VAR
+                        );
                         // This function will only be called after setup finishes
                         if SetupResult::try_read_attribute(&env).is_err() {
                             panic!("Failed to find SetupResult attribute after setup. Cancelling test function.");
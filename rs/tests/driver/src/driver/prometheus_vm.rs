@@ -39,6 +39,11 @@ use super::boundary_node::BoundaryNodeVm;
 
 const PROMETHEUS_VM_NAME: &str = "prometheus";
 
+/// When this environment variable is set (to any value), `PrometheusVm::start`
+/// skips deploying the Prometheus VM. This shortens local iterations of tests
+/// whose metrics are not inspected.
+pub const SKIP_PROMETHEUS_VM_ENV_VAR: &str = "SYSTEST_SKIP_PROMETHEUS_VM";
+
 /// The SHA-256 hash of the Prometheus VM disk image.
 /// The latest hash can be retrieved by downloading the SHA256SUMS file from:
 /// https://hydra.dfinity.systems/job/dfinity-ci-build/farm/universal-vm.img-prometheus.x86_64-linux/latest
@@ -82,6 +87,7 @@ const BN_NGINX_PROMETHEUS_TARGET: &str = "boundary_nodes_nginx.json";
 pub struct PrometheusVm {
     universal_vm: UniversalVm,
     scrape_interval: Duration,
+    enabled: bool,
 }
 
 impl Default for PrometheusVm {
@@ -106,9 +112,18 @@ impl PrometheusVm {
                     boot_image_minimal_size_gibibytes: Some(ImageSizeGiB::new(100)),
                 }),
             scrape_interval: Duration::from_secs(10),
+            enabled: std::env::var_os(SKIP_PROMETHEUS_VM_ENV_VAR).is_none(),
         }
     }
 
+    /// Enables or disables deploying the Prometheus VM, overriding
+    /// `SKIP_PROMETHEUS_VM_ENV_VAR`. When disabled, `start` is a no-op
+    /// and so is `HasPrometheus::sync_with_prometheus`.
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
     pub fn with_scrape_interval(mut self, scrape_interval: Duration) -> Self {
         self.scrape_interval = scrape_interval;
         self
@@ -137,9 +152,13 @@ impl PrometheusVm {
     }
 
     pub fn start(&self, env: &TestEnv) -> Result<()> {
+        let log = env.logger();
+        if !self.enabled {
+            info!(log, "Skipping deployment of the Prometheus VM.");
+            return Ok(());
+        }
         // Create a config directory containing the prometheus.yml configuration file.
         let vm_name = String::from(PROMETHEUS_VM_NAME);
-        let log = env.logger();
         let config_dir = env
             .single_activate_script_config_dir(
                 &vm_name,
@@ -238,6 +257,8 @@ fi
 pub trait HasPrometheus {
     /// Retrieves a topology snapshot, converts it into p8s scraping target
     /// JSON files and scps them to the prometheus VM.
+    ///
+    /// This is a no-op if no prometheus VM has been deployed.
     fn sync_with_prometheus(&self);
 
     /// Retrieves a topology snapshot by name, converts it into p8s scraping target
@@ -269,6 +290,18 @@ impl HasPrometheus for TestEnv {
         }
 
         let vm_name = PROMETHEUS_VM_NAME.to_string();
+        // Return early without failing if no prometheus VM has been deployed,
+        // e.g. because its deployment was skipped.
+        let deployed_prometheus_vm = match self.get_deployed_universal_vm(&vm_name) {
+            Err(_) => {
+                info!(
+                    self.logger(),
+                    "No prometheus VM deployed, skipping synchronization of scraping targets."
+                );
+                return;
+            }
+            Ok(deployed_prometheus_vm) => deployed_prometheus_vm,
+        };
         // Write the scraping target JSON files to the local prometheus config directory.
         let prometheus_config_dir = self.get_universal_vm_config_dir(&vm_name);
         let group_name = GroupSetup::read_attribute(self).infra_group_name;
@@ -286,7 +319,6 @@ impl HasPrometheus for TestEnv {
         )
         .expect("Failed to synchronize prometheus config with the last deployments of the boundary nodes");
         // Setup an SSH session to the prometheus VM which we'll use to scp the JSON files.
-        let deployed_prometheus_vm = self.get_deployed_universal_vm(&vm_name).unwrap();
         let session = deployed_prometheus_vm
             .block_on_ssh_session()
             .unwrap_or_else(|e| panic!("Failed to setup SSH session to {vm_name} because: {e:?}!"));
@@ -1,10 +1,11 @@
 use anyhow::{Context, Result};
+use ic_crypto_sha2::Sha256;
 use ic_prep_lib::prep_state_directory::IcPrepStateDir;
 use ic_sys::fs::{sync_path, write_atomically};
 use rand::{RngCore, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use slog::{info, o, warn, Drain, Logger};
 use slog_async::OverflowStrategy;
 use std::fs::{self, File};
@@ -242,11 +243,43 @@ pub trait HasDefaultRng {
 }
 
 impl HasDefaultRng for TestEnv {
-    /// Returns a random number generator based on a constant seed. At a later
-    /// point, the seed will be configured through the underlying test
-    /// environment.
+    /// Returns a random number generator seeded with the `TestRngSeed` of the
+    /// running test, so that randomized payloads can be reproduced. Every call
+    /// returns a generator producing the same sequence. Outside of a test
+    /// function (e.g., during setup), no seed is available and a constant seed
+    /// is used instead.
     fn default_rng(&self) -> Box<dyn RngCore> {
-        Box::new(ChaCha8Rng::seed_from_u64(42))
+        let seed = TestRngSeed::try_read_attribute(self).map_or(42, |seed| seed.0);
+        Box::new(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+/// When set to a `u64`, this environment variable overrides the seed of the
+/// per-test random number generator, e.g. to replay a failed run with the seed
+/// logged at its start.
+pub const TEST_RNG_SEED_ENV_VAR: &str = "SYSTEST_RNG_SEED";
+
+/// The seed of the deterministic random number generator of a test function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TestRngSeed(pub u64);
+
+impl TestRngSeed {
+    /// Derives the seed from the name of the test, unless it is overridden
+    /// through `TEST_RNG_SEED_ENV_VAR`.
+    pub fn for_test(test_name: &str) -> Self {
+        if let Ok(seed) = std::env::var(TEST_RNG_SEED_ENV_VAR) {
+            return TestRngSeed(seed.parse().unwrap_or_else(|e| {
+                panic!("invalid value {seed:?} of {TEST_RNG_SEED_ENV_VAR}: {e}")
+            }));
+        }
+        let digest = Sha256::hash(test_name.as_bytes());
+        TestRngSeed(u64::from_be_bytes(digest[..8].try_into().unwrap()))
+    }
+}
+
+impl TestEnvAttribute for TestRngSeed {
+    fn attribute_name() -> String {
+        String::from("test_rng_seed")
     }
 }
 
@@ -381,6 +381,27 @@ impl TopologySnapshot {
         )
     }
 
+    /// Returns the subnets of the given type. The iterator is empty if the
+    /// topology contains no subnet of that type.
+    pub fn subnets_of_type(
+        &self,
+        subnet_type: SubnetType,
+    ) -> Box<dyn Iterator<Item = SubnetSnapshot>> {
+        Box::new(
+            self.subnets()
+                .filter(move |s| s.subnet_type() == subnet_type),
+        )
+    }
+
+    /// Waits until all nodes assigned to a subnet report as healthy, polling them concurrently.
+    /// Fails once `timeout` has elapsed, listing the nodes that are still not healthy.
+    pub fn await_all_nodes_healthy(&self, timeout: Duration) -> Result<()> {
+        await_nodes_healthy(
+            self.subnets().flat_map(|subnet| subnet.nodes()).collect(),
+            timeout,
+        )
+    }
+
     pub fn subnet_canister_ranges(&self, sub: SubnetId) -> Vec<CanisterIdRange> {
         let registry_version = self.local_registry.get_latest_version();
         self.local_registry
@@ -1872,13 +1893,67 @@ impl IcNodeContainer for SubnetSnapshot {
     }
 
     fn await_all_nodes_healthy(&self) -> Result<()> {
-        let mut jhs = vec![];
-        for node in self.nodes() {
-            jhs.push(std::thread::spawn(move || node.await_status_is_healthy()));
+        await_nodes_healthy(self.nodes().collect(), READY_WAIT_TIMEOUT)
+    }
+}
+
+/// Polls the status of all given nodes concurrently until they all report as healthy.
+/// Fails once `timeout` has elapsed, listing the unhealthy nodes with their last observed status.
+pub fn await_nodes_healthy(nodes: Vec<IcNodeSnapshot>, timeout: Duration) -> Result<()> {
+    fn observe_health(node: &IcNodeSnapshot) -> Result<(), String> {
+        match node.status() {
+            Ok(status) => match status.replica_health_status {
+                Some(ReplicaHealthStatus::Healthy) => Ok(()),
+                Some(health_status) => Err(health_status.as_ref().to_string()),
+                None => Err("health status not set in status response".to_string()),
+            },
+            Err(e) => Err(format!("status check failed: {e}")),
         }
-        #[allow(clippy::needless_collect)]
-        let res: Vec<_> = jhs.into_iter().map(|j| j.join().unwrap()).collect();
-        res.into_iter().try_for_each(|x| x)
+    }
+
+    let Some(log) = nodes.first().map(|node| node.test_env().logger()) else {
+        return Ok(());
+    };
+    let start = Instant::now();
+    let mut unhealthy_nodes = nodes;
+    loop {
+        let observed: Vec<_> = std::thread::scope(|s| {
+            let handles: Vec<_> = unhealthy_nodes
+                .iter()
+                .map(|node| s.spawn(move || observe_health(node)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("status polling thread panicked"))
+                .collect()
+        });
+        let still_unhealthy: Vec<_> = unhealthy_nodes
+            .into_iter()
+            .zip(observed)
+            .filter_map(|(node, health)| health.err().map(|status| (node, status)))
+            .collect();
+        if still_unhealthy.is_empty() {
+            return Ok(());
+        }
+        if start.elapsed() > timeout {
+            let report: Vec<_> = still_unhealthy
+                .iter()
+                .map(|(node, status)| format!("{}: {status}", node.node_id))
+                .collect();
+            bail!(
+                "{} node(s) not healthy after {:?}: [{}]",
+                report.len(),
+                timeout,
+                report.join(", ")
+            );
+        }
+        info!(
+            log,
+            "Waiting for {} node(s) to become healthy ...",
+            still_unhealthy.len()
+        );
+        unhealthy_nodes = still_unhealthy.into_iter().map(|(node, _)| node).collect();
+        std::thread::sleep(RETRY_BACKOFF);
     }
 }
 
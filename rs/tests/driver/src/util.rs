@@ -1164,6 +1164,114 @@ pub fn block_on<F: Future>(f: F) -> F::Output {
     }
 }
 
+/// How long and how often to poll until an expected condition holds.
+#[derive(Clone, Copy, Debug)]
+pub struct RetryPolicy {
+    pub timeout: Duration,
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: READY_WAIT_TIMEOUT,
+            backoff: RETRY_BACKOFF,
+        }
+    }
+}
+
+/// Number of attempts to read the stable memory of a canister before giving up on a poll.
+const STABLE_READ_RETRIES: u64 = 10;
+
+/// Polls the stable memory of the universal canister `canister_id`, starting from `offset`,
+/// until it equals `expected`.
+/// Fails once `retry_policy` expires, reporting the bytes that were last read.
+pub async fn assert_canister_stable_eq(
+    agent: &Agent,
+    canister_id: Principal,
+    offset: u32,
+    expected: &[u8],
+    retry_policy: RetryPolicy,
+    log: &slog::Logger,
+) -> anyhow::Result<()> {
+    let ucan = UniversalCanister::from_canister_id(agent, canister_id);
+    let start = Instant::now();
+    loop {
+        let actual = ucan
+            .try_read_stable_with_retries(
+                log,
+                offset,
+                expected.len() as u32,
+                STABLE_READ_RETRIES,
+                retry_policy.backoff,
+            )
+            .await;
+        if actual == expected {
+            return Ok(());
+        }
+        if start.elapsed() > retry_policy.timeout {
+            bail!(
+                "stable memory of canister {canister_id} at offset {offset} did not match after {:?}: expected {:?}, last seen {:?}",
+                retry_policy.timeout,
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(&actual)
+            );
+        }
+        debug!(
+            log,
+            "Stable memory of canister {canister_id} does not match yet, retrying in {:?} ...",
+            retry_policy.backoff
+        );
+        tokio::time::sleep(retry_policy.backoff).await;
+    }
+}
+
+/// Installs a universal canister via each of the given nodes concurrently and returns
+/// the ids of the installed canisters in the order of `nodes`.
+/// Each installation is retried like in [UniversalCanister::new_with_retries].
+pub fn install_universal_canisters_parallel(
+    nodes: &[IcNodeSnapshot],
+    log: &slog::Logger,
+) -> anyhow::Result<Vec<Principal>> {
+    block_on(async {
+        let installs = nodes.iter().map(|node| async move {
+            let effective_canister_id = node.effective_canister_id();
+            let agent = node.build_default_agent_async().await;
+            retry_with_msg_async!(
+                format!(
+                    "install UniversalCanister {} via node {}",
+                    effective_canister_id, node.node_id
+                ),
+                log,
+                READY_WAIT_TIMEOUT,
+                RETRY_BACKOFF,
+                || async {
+                    match UniversalCanister::new_with_params(
+                        &agent,
+                        effective_canister_id,
+                        None,
+                        None,
+                        None,
+                    )
+                    .await
+                    {
+                        Ok(c) => Ok(c.canister_id()),
+                        Err(e) => anyhow::bail!(e),
+                    }
+                }
+            )
+            .await
+            .map_err(|e| {
+                anyhow::anyhow!(
+                    "failed to install universal canister via node {}: {e}",
+                    node.node_id
+                )
+            })
+        });
+        join_all(installs).await.into_iter().collect()
+    })
+}
+
 pub async fn create_canister_via_canister(
     wallet_canister: &UniversalCanister<'_>,
 ) -> Result<Principal, AgentError> {
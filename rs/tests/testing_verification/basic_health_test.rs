@@ -1,7 +1,8 @@
 /* tag::catalog[]
 Title:: Basic system health test
 
-Goal:: Start an IC with 2 subnets, 4 nodes per subnet. Install canisters and
+Goal:: Start an IC with 2 subnets, 4 nodes per subnet (or any other topology
+given to `config`). Install canisters and
 make update and query calls that require cross-subnet communication to
 succeed. While this is happening, ensure the IC finalizes rounds correctly
 across all nodes in each subnet. No real load is generated by this test, it's
@@ -30,7 +31,6 @@ Coverage::
 
 end::catalog[] */
 
-use anyhow::bail;
 use anyhow::Result;
 use ic_registry_subnet_type::SubnetType;
 use ic_system_test_driver::driver::group::SystemTestGroup;
@@ -41,7 +41,7 @@ use ic_system_test_driver::driver::test_env_api::*;
 use ic_system_test_driver::systest;
 use ic_system_test_driver::util::*; // to use the universal canister
 use slog::info;
-use std::time::Duration;
+use std::collections::BTreeSet;
 
 fn main() -> Result<()> {
     SystemTestGroup::new()
@@ -53,28 +53,38 @@ fn main() -> Result<()> {
 }
 
 pub fn setup(env: TestEnv) {
+    config(env, 4, 1, 4);
+}
+
+/// Sets up an IC with one system subnet of `num_system_nodes` nodes and
+/// `num_app_subnets` application subnets of `nodes_per_app_subnet` nodes each.
+pub fn config(
+    env: TestEnv,
+    num_system_nodes: usize,
+    num_app_subnets: usize,
+    nodes_per_app_subnet: usize,
+) {
     PrometheusVm::default()
         .start(&env)
         .expect("failed to start prometheus VM");
-    InternetComputer::new()
-        .add_subnet(
-            Subnet::new(SubnetType::System)
-                .with_random_height()
-                .add_nodes(4),
-        )
-        .add_subnet(
+    let mut ic = InternetComputer::new().add_subnet(
+        Subnet::new(SubnetType::System)
+            .with_random_height()
+            .add_nodes(num_system_nodes),
+    );
+    for _ in 0..num_app_subnets {
+        ic = ic.add_subnet(
             Subnet::new(SubnetType::Application)
                 .with_random_height()
-                .add_nodes(4),
-        )
-        .setup_and_start(&env)
+                .add_nodes(nodes_per_app_subnet),
+        );
+    }
+    ic.setup_and_start(&env)
         .expect("failed to setup IC under test");
     env.sync_with_prometheus();
 }
 
 const MSG: &[u8] = b"this beautiful prose should be persisted for future generations";
-const READ_RETRIES: u64 = 10;
-const RETRY_WAIT: Duration = Duration::from_secs(10);
 
 /// Here we define the test workflow, which should implement the Runbook given
 /// in the test catalog entry at the top of this file.
@@ -88,38 +98,30 @@ pub fn test(env: TestEnv) {
         .collect();
 
     info!(log, "Waiting for the nodes to become healthy ...");
-    nodes
-        .iter()
-        .try_for_each(|n| n.await_status_is_healthy())
-        .unwrap();
+    env.topology_snapshot()
+        .await_all_nodes_healthy(READY_WAIT_TIMEOUT)
+        .expect("nodes did not become healthy");
 
     info!(log, "Installing universal canisters on subnets (via all nodes), reading and storing messages ...");
-    let ucan_ids: Vec<_> = nodes
-        .iter()
-        .map(|node| {
-            let inner_log = log.clone();
-            let effective_canister_id = node.effective_canister_id();
-            node.with_default_agent(move |agent| async move {
-                let ucan =
-                    UniversalCanister::new_with_retries(&agent, effective_canister_id, &inner_log)
-                        .await;
-
-                // send a query call to it
-                assert_eq!(ucan.try_read_stable(0, 0).await, Vec::<u8>::new());
-
-                // send an update call to it
-                ucan.store_to_stable(0, MSG).await;
-
-                // query for mutated data
-                assert_eq!(
-                    ucan.try_read_stable(0, MSG.len() as u32).await,
-                    MSG.to_vec()
-                );
-
-                ucan.canister_id()
-            })
-        })
-        .collect();
+    let ucan_ids = install_universal_canisters_parallel(&nodes, &log)
+        .expect("failed to install universal canisters");
+    for (node, ucan_id) in nodes.iter().zip(ucan_ids.iter().cloned()) {
+        node.with_default_agent(move |agent| async move {
+            let ucan = UniversalCanister::from_canister_id(&agent, ucan_id);
+
+            // send a query call to it
+            assert_eq!(ucan.try_read_stable(0, 0).await, Vec::<u8>::new());
+
+            // send an update call to it
+            ucan.store_to_stable(0, MSG).await;
+
+            // query for mutated data
+            assert_eq!(
+                ucan.try_read_stable(0, MSG.len() as u32).await,
+                MSG.to_vec()
+            );
+        });
+    }
 
     // Match up canisters with each other. The first canister of the pair will
     // send an update to the second canister of the pair (see below).
@@ -130,8 +132,9 @@ pub fn test(env: TestEnv) {
         .collect::<Vec<_>>();
     const XNET_MSG: &[u8] = b"just received a xnet message";
 
-    // We expect to find these contents in stable memory.
-    let expected_memory_values = vec![MSG, XNET_MSG].into_iter().map(|s| s.to_vec());
+    // Canisters that already received a xnet message, which overwrote the
+    // original message in their stable memory.
+    let mut xnet_receivers = BTreeSet::new();
 
     info!(log, "Sending xnet messages ...");
     // Again we execute functions to call each of the canisters on the
@@ -140,7 +143,14 @@ pub fn test(env: TestEnv) {
     // Since interactions with the universal canister are `async`, we must
     // execute these within the context of the Tokio runtime, even though
     // there is no concurrency from this point forward.
-    for ((n, (from, to)), expect) in nodes.iter().zip(canister_info).zip(expected_memory_values) {
+    for (n, (from, to)) in nodes.iter().zip(canister_info) {
+        // We expect to find these contents in stable memory.
+        let expect = if xnet_receivers.contains(&from) {
+            XNET_MSG
+        } else {
+            MSG
+        };
+        xnet_receivers.insert(to);
         let log = log.clone();
         n.with_default_agent(move |agent| async move {
             // Note: `from` is the canister id of the universal canister that was
@@ -169,34 +179,10 @@ pub fn test(env: TestEnv) {
     for (node, ucan_id) in nodes.iter().zip(ucan_ids) {
         let log = log.clone();
         node.with_default_agent(move |agent| async move {
-            let ucan = UniversalCanister::from_canister_id(&agent, ucan_id);
             // NOTE: retries are important here, 1/3 of the nodes might not observe changes immediately.
-            ic_system_test_driver::retry_with_msg_async!(
-                format!(
-                    "check if read message from canister {} is as expected",
-                    ucan_id.to_string()
-                ),
-                &log,
-                READY_WAIT_TIMEOUT,
-                RETRY_BACKOFF,
-                || async {
-                    let current_msg = ucan
-                        .try_read_stable_with_retries(
-                            &log,
-                            0,
-                            XNET_MSG.len() as u32,
-                            READ_RETRIES,
-                            RETRY_WAIT,
-                        )
-                        .await;
-                    if current_msg != XNET_MSG.to_vec() {
-                        bail!("Expected message not found!")
-                    }
-                    Ok(())
-                }
-            )
-            .await
-            .expect("Node not healthy");
+            assert_canister_stable_eq(&agent, ucan_id, 0, XNET_MSG, RetryPolicy::default(), &log)
+                .await
+                .expect("Node not healthy");
         })
     }
 }